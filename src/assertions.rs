@@ -0,0 +1,660 @@
+use anyhow::{Result, anyhow};
+use colored::*;
+use serde_json::Value;
+
+use crate::client::Response;
+
+// Response assertions such as `status == 200`, `header.content-type contains json`
+// or `$.data[0].id exists`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subject {
+    Status,
+    Duration,
+    Size,
+    Body,
+    Header(String),
+    JsonPath(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    NotContains,
+    Matches,
+    Exists,
+}
+
+impl Operator {
+    fn parse(token: &str) -> Option<Self> {
+        match token {
+            "==" => Some(Self::Eq),
+            "!=" => Some(Self::Ne),
+            "<" => Some(Self::Lt),
+            "<=" => Some(Self::Le),
+            ">" => Some(Self::Gt),
+            ">=" => Some(Self::Ge),
+            "contains" => Some(Self::Contains),
+            "!contains" => Some(Self::NotContains),
+            "matches" => Some(Self::Matches),
+            "exists" => Some(Self::Exists),
+            _ => None,
+        }
+    }
+
+    fn token(self) -> &'static str {
+        match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Contains => "contains",
+            Self::NotContains => "!contains",
+            Self::Matches => "matches",
+            Self::Exists => "exists",
+        }
+    }
+}
+
+impl std::fmt::Display for Subject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status => write!(f, "status"),
+            Self::Duration => write!(f, "duration"),
+            Self::Size => write!(f, "size"),
+            Self::Body => write!(f, "body"),
+            Self::Header(name) => write!(f, "header.{}", name),
+            Self::JsonPath(path) => write!(f, "{}", path),
+        }
+    }
+}
+
+// An expression's text is read as JSON when it parses and as a string otherwise; a value
+// from an assertion file already has its type and is compared as it is
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expected {
+    Text(String),
+    Value(Value),
+}
+
+impl Expected {
+    fn text(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            Self::Text(text) | Self::Value(Value::String(text)) => text.into(),
+            Self::Value(value) => value.to_string().into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Assertion {
+    pub source: String,
+    pub subject: Subject,
+    pub operator: Operator,
+    pub expected: Option<Expected>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssertionResult {
+    pub assertion: String,
+    pub passed: bool,
+    pub actual: String,
+}
+
+impl Assertion {
+    pub fn parse(source: &str) -> Result<Self> {
+        let expanded = expand_compact(source.trim());
+        let trimmed = expanded.as_deref().unwrap_or(source.trim());
+        let (subject_token, rest) = trimmed.split_once(char::is_whitespace).ok_or_else(|| {
+            anyhow!(
+                "Invalid assertion '{}': expected '<subject> <op> [value]'",
+                source
+            )
+        })?;
+        let rest = rest.trim_start();
+        let (operator_token, expected) = match rest.split_once(char::is_whitespace) {
+            Some((op, value)) => (op, Some(value.trim())),
+            None => (rest, None),
+        };
+
+        let subject = Self::parse_subject(subject_token)
+            .ok_or_else(|| anyhow!("Unknown assertion subject '{}'", subject_token))?;
+        let operator = Operator::parse(operator_token).ok_or_else(|| {
+            anyhow!(
+                "Unknown assertion operator '{}'. Use ==, !=, <, <=, >, >=, contains, !contains, matches or exists",
+                operator_token
+            )
+        })?;
+
+        let expected = expected
+            .filter(|v| !v.is_empty())
+            .map(|v| Expected::Text(unquote(v).to_string()));
+        Self::checked(trimmed.to_string(), subject, operator, expected)
+    }
+
+    /// Builds a check from its parts, the way an assertion file describes it.
+    pub fn new(subject: Subject, operator: Operator, expected: Option<Value>) -> Result<Self> {
+        let source = match expected {
+            Some(ref value) => format!("{} {} {}", subject, operator.token(), value),
+            None => format!("{} {}", subject, operator.token()),
+        };
+        Self::checked(source, subject, operator, expected.map(Expected::Value))
+    }
+
+    fn checked(
+        source: String,
+        subject: Subject,
+        operator: Operator,
+        expected: Option<Expected>,
+    ) -> Result<Self> {
+        if let Subject::JsonPath(ref path) = subject {
+            crate::utils::parse_json_path(path)?;
+        }
+
+        match (operator, &expected) {
+            (Operator::Exists, Some(_)) => {
+                return Err(anyhow!("'exists' does not take a value in '{}'", source));
+            }
+            (Operator::Exists, None) => {}
+            (_, None) => return Err(anyhow!("Missing expected value in '{}'", source)),
+            (Operator::Matches, Some(pattern)) => {
+                regex::Regex::new(&pattern.text())
+                    .map_err(|e| anyhow!("Invalid regex in '{}': {}", source, e))?;
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            source,
+            subject,
+            operator,
+            expected,
+        })
+    }
+
+    fn parse_subject(token: &str) -> Option<Subject> {
+        match token {
+            "status" => Some(Subject::Status),
+            "duration" => Some(Subject::Duration),
+            "size" => Some(Subject::Size),
+            "body" => Some(Subject::Body),
+            _ if token.starts_with('$') => Some(Subject::JsonPath(token.to_string())),
+            _ => token
+                .strip_prefix("header.")
+                .filter(|name| !name.is_empty())
+                .map(|name| Subject::Header(name.to_lowercase())),
+        }
+    }
+
+    pub fn evaluate(&self, response: &Response) -> AssertionResult {
+        let actual = self.actual_value(response);
+        let passed = match (&actual, self.operator) {
+            (None, Operator::Exists) => false,
+            (Some(_), Operator::Exists) => true,
+            (None, Operator::Ne | Operator::NotContains) => true,
+            (None, _) => false,
+            (Some(value), op) => match self.expected {
+                Some(ref expected) => compare(value, op, expected),
+                None => false,
+            },
+        };
+
+        AssertionResult {
+            assertion: self.source.clone(),
+            passed,
+            actual: actual
+                .map(|v| match v {
+                    Value::String(s) => crate::utils::truncate_string(&s, 80),
+                    other => crate::utils::truncate_string(&other.to_string(), 80),
+                })
+                .unwrap_or_else(|| "<missing>".to_string()),
+        }
+    }
+
+    fn actual_value(&self, response: &Response) -> Option<Value> {
+        match &self.subject {
+            Subject::Status => Some(Value::from(response.status)),
+            Subject::Duration => Some(Value::from(response.duration.as_millis() as u64)),
+            Subject::Size => Some(Value::from(response.size)),
+            Subject::Body => Some(Value::String(response.body.clone())),
+            Subject::Header(name) => response
+                .headers
+                .iter()
+                .find(|(key, _)| key.to_lowercase() == *name)
+                .map(|(_, value)| Value::String(value.clone())),
+            Subject::JsonPath(path) => serde_json::from_str::<Value>(&response.body)
+                .ok()
+                .and_then(|body| crate::utils::json_path(&body, path).cloned()),
+        }
+    }
+}
+
+// `$.status==done` is shorthand for `$.status == done`
+fn expand_compact(source: &str) -> Option<String> {
+    let token = source.split(char::is_whitespace).next()?;
+    let start = token
+        .find(['=', '!', '<', '>'])
+        .filter(|&start| start > 0)?;
+    let operator = ["==", "!=", "<=", ">=", "<", ">"]
+        .into_iter()
+        .find(|op| token[start..].starts_with(op))?;
+    let expected = source[start + operator.len()..].trim();
+    Some(format!("{} {} {}", &token[..start], operator, expected))
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('\'')
+        .and_then(|v| v.strip_suffix('\''))
+        .unwrap_or(value)
+}
+
+fn compare(actual: &Value, operator: Operator, expected: &Expected) -> bool {
+    let actual_text = match actual {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let expected_text = expected.text();
+
+    match operator {
+        Operator::Eq => expected_equal(actual, expected),
+        Operator::Ne => !expected_equal(actual, expected),
+        Operator::Lt | Operator::Le | Operator::Gt | Operator::Ge => {
+            let (Some(a), Ok(b)) = (as_number(actual), expected_text.parse::<f64>()) else {
+                return false;
+            };
+            match operator {
+                Operator::Lt => a < b,
+                Operator::Le => a <= b,
+                Operator::Gt => a > b,
+                _ => a >= b,
+            }
+        }
+        Operator::Contains => actual_text.contains(&*expected_text),
+        Operator::NotContains => !actual_text.contains(&*expected_text),
+        Operator::Matches => regex::Regex::new(&expected_text)
+            .map(|re| re.is_match(&actual_text))
+            .unwrap_or(false),
+        Operator::Exists => true,
+    }
+}
+
+fn expected_equal(actual: &Value, expected: &Expected) -> bool {
+    match expected {
+        Expected::Text(text) => values_equal(actual, text),
+        // Numbers match numerically and headers are text, so `x-version: 2` still matches
+        // the header "2"; a string only ever matches the same string
+        Expected::Value(value @ Value::Number(_)) => as_number(actual) == value.as_f64(),
+        Expected::Value(Value::String(text)) => matches!(actual, Value::String(s) if s == text),
+        Expected::Value(value) => {
+            actual == value || matches!(actual, Value::String(s) if *s == *expected.text())
+        }
+    }
+}
+
+fn values_equal(actual: &Value, expected: &str) -> bool {
+    // Compare as JSON when the expectation parses (numbers, booleans, null, quoted strings)
+    if let Ok(expected_json) = serde_json::from_str::<Value>(expected) {
+        if let (Some(a), Some(b)) = (as_number(actual), expected_json.as_f64()) {
+            return a == b;
+        }
+        if *actual == expected_json {
+            return true;
+        }
+    }
+
+    matches!(actual, Value::String(s) if s == expected)
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// Assertions kept in a YAML (or JSON) file so one set of checks can be reused:
+//
+//   status: 200
+//   headers:
+//     content-type: { contains: json }
+//   checks:
+//     - { jsonpath: $.ok, equals: true }
+//     - duration < 500
+//
+// A bare list is read as `checks`. Values keep their YAML type, and a string check is
+// an ordinary assertion expression.
+pub fn load_file(path: &std::path::Path) -> Result<Vec<Assertion>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let document: serde_yaml::Value = serde_yaml::from_str(&contents)
+        .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    from_document(&document).map_err(|e| anyhow!("In {}: {}", path.display(), e))
+}
+
+fn from_document(document: &serde_yaml::Value) -> Result<Vec<Assertion>> {
+    use serde_yaml::Value as Yaml;
+
+    let mut assertions = Vec::new();
+    let checks = match document {
+        Yaml::Sequence(checks) => checks.as_slice(),
+        Yaml::Mapping(map) => {
+            for (key, value) in map {
+                match key.as_str() {
+                    Some("status") => assertions.extend(expectations(Subject::Status, value)?),
+                    Some("headers") => {
+                        let headers = value
+                            .as_mapping()
+                            .ok_or_else(|| anyhow!("'headers' must map names to values"))?;
+                        for (name, value) in headers {
+                            let name = name
+                                .as_str()
+                                .ok_or_else(|| anyhow!("Header names must be strings"))?;
+                            assertions
+                                .extend(expectations(Subject::Header(name.to_lowercase()), value)?);
+                        }
+                    }
+                    Some("checks") => {}
+                    _ => return Err(anyhow!("Unknown key {}", describe(key))),
+                }
+            }
+            map.get("checks")
+                .map(|checks| {
+                    checks
+                        .as_sequence()
+                        .map(Vec::as_slice)
+                        .ok_or_else(|| anyhow!("'checks' must be a list"))
+                })
+                .transpose()?
+                .unwrap_or_default()
+        }
+        Yaml::Null => &[],
+        _ => return Err(anyhow!("expected a list of checks or a mapping")),
+    };
+
+    for check in checks {
+        match check {
+            Yaml::String(expression) => assertions.push(Assertion::parse(expression)?),
+            Yaml::Mapping(entry) => assertions.extend(from_entry(entry)?),
+            other => return Err(anyhow!("Invalid check {}", describe(other))),
+        }
+    }
+    Ok(assertions)
+}
+
+// `status`, `duration`, `size` and `body` carry their own expectation; `jsonpath` and
+// `header` name what the operator keys beside them apply to
+fn from_entry(entry: &serde_yaml::Mapping) -> Result<Vec<Assertion>> {
+    let mut assertions = Vec::new();
+    let mut target = None;
+    let mut operators = serde_yaml::Mapping::new();
+    for (key, value) in entry {
+        let subject = match key.as_str() {
+            Some("status") => Subject::Status,
+            Some("duration") => Subject::Duration,
+            Some("size") => Subject::Size,
+            Some("body") => Subject::Body,
+            Some(kind @ ("jsonpath" | "header")) => {
+                let name = value
+                    .as_str()
+                    .ok_or_else(|| anyhow!("'{}' must be a string", kind))?;
+                target = Some(match kind {
+                    "header" => Subject::Header(name.to_lowercase()),
+                    _ => Subject::JsonPath(name.to_string()),
+                });
+                continue;
+            }
+            Some(op) if file_operator(op).is_some() => {
+                operators.insert(key.clone(), value.clone());
+                continue;
+            }
+            _ => return Err(anyhow!("Unknown key {}", describe(key))),
+        };
+        assertions.extend(expectations(subject, value)?);
+    }
+
+    match target {
+        Some(subject) if operators.is_empty() => {
+            assertions.push(Assertion::new(subject, Operator::Exists, None)?)
+        }
+        Some(subject) => assertions.extend(expectations(
+            subject,
+            &serde_yaml::Value::Mapping(operators),
+        )?),
+        None if !operators.is_empty() => {
+            return Err(anyhow!(
+                "Operators need a 'jsonpath' or 'header' to apply to"
+            ));
+        }
+        None => {}
+    }
+    Ok(assertions)
+}
+
+// A bare value means equality; a mapping holds one or more operators
+fn expectations(subject: Subject, value: &serde_yaml::Value) -> Result<Vec<Assertion>> {
+    let Some(operators) = value.as_mapping() else {
+        return Ok(vec![Assertion::new(
+            subject,
+            Operator::Eq,
+            Some(scalar(value)?),
+        )?]);
+    };
+
+    let mut assertions = Vec::new();
+    for (key, expected) in operators {
+        let operator = key.as_str().and_then(file_operator).ok_or_else(|| {
+            anyhow!(
+                "Unknown operator {}. Use equals, not_equals, lt, le, gt, ge, contains, not_contains, matches or exists",
+                describe(key)
+            )
+        })?;
+        let expected = match operator {
+            Operator::Exists if expected.as_bool() == Some(true) => None,
+            Operator::Exists => {
+                return Err(anyhow!("'exists' only takes true, for {}", subject));
+            }
+            _ => Some(scalar(expected)?),
+        };
+        assertions.push(Assertion::new(subject.clone(), operator, expected)?);
+    }
+    Ok(assertions)
+}
+
+fn file_operator(key: &str) -> Option<Operator> {
+    match key {
+        "equals" => Some(Operator::Eq),
+        "not_equals" => Some(Operator::Ne),
+        "lt" => Some(Operator::Lt),
+        "le" => Some(Operator::Le),
+        "gt" => Some(Operator::Gt),
+        "ge" => Some(Operator::Ge),
+        "contains" => Some(Operator::Contains),
+        "not_contains" => Some(Operator::NotContains),
+        "matches" => Some(Operator::Matches),
+        "exists" => Some(Operator::Exists),
+        _ => None,
+    }
+}
+
+fn scalar(value: &serde_yaml::Value) -> Result<Value> {
+    use serde_yaml::Value as Yaml;
+    match value {
+        Yaml::String(_) | Yaml::Number(_) | Yaml::Bool(_) | Yaml::Null => {
+            serde_json::to_value(value).map_err(|e| anyhow!("Invalid value: {}", e))
+        }
+        other => Err(anyhow!("Expected a single value, got {}", describe(other))),
+    }
+}
+
+fn describe(value: &serde_yaml::Value) -> String {
+    serde_yaml::to_string(value)
+        .map(|s| format!("'{}'", s.trim_end()))
+        .unwrap_or_else(|_| "value".to_string())
+}
+
+pub fn parse_all(sources: &[String]) -> Result<Vec<Assertion>> {
+    sources.iter().map(|s| Assertion::parse(s)).collect()
+}
+
+pub fn evaluate_all(assertions: &[Assertion], response: &Response) -> Vec<AssertionResult> {
+    assertions.iter().map(|a| a.evaluate(response)).collect()
+}
+
+pub fn print_results(results: &[AssertionResult]) {
+    for result in results {
+        if result.passed {
+            println!("  {} {}", "✓".bright_green(), result.assertion);
+        } else {
+            println!(
+                "  {} {} {}",
+                "✗".bright_red(),
+                result.assertion,
+                format!("(actual: {})", result.actual).bright_black()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::create_test_response;
+
+    fn check(source: &str) -> bool {
+        Assertion::parse(source)
+            .unwrap()
+            .evaluate(&create_test_response())
+            .passed
+    }
+
+    #[test]
+    fn test_status_and_timing_assertions() {
+        assert!(check("status == 200"));
+        assert!(check("status < 400"));
+        assert!(!check("status >= 300"));
+        assert!(check("duration <= 100"));
+        assert!(check("size > 0"));
+    }
+
+    #[test]
+    fn test_body_and_json_path_assertions() {
+        assert!(check("body contains Hello"));
+        assert!(check("$.message == 'Hello, World!'"));
+        assert!(check("$.message matches ^Hello"));
+        assert!(check("$.message exists"));
+        assert!(!check("$.missing exists"));
+        assert!(check("$.missing != 1"));
+        assert!(check("$.message!='Goodbye'"));
+        assert!(check("status==200"));
+    }
+
+    #[test]
+    fn test_header_assertions() {
+        let mut response = create_test_response();
+        response
+            .headers
+            .insert("Content-Type".to_string(), "application/json".to_string());
+
+        let assertion = Assertion::parse("header.content-type contains json").unwrap();
+        assert!(assertion.evaluate(&response).passed);
+    }
+
+    #[test]
+    fn test_assertion_file_documents() {
+        let document = serde_yaml::from_str(
+            r#"
+status: 200
+headers:
+  content-type: { contains: json }
+  x-version: "2"
+checks:
+  - { jsonpath: $.ok, equals: true }
+  - { jsonpath: $.items, exists: true }
+  - header: etag
+  - duration: { lt: 500, ge: 0 }
+  - $.message matches ^Hello
+"#,
+        )
+        .unwrap();
+        let sources = |document: &serde_yaml::Value| -> Vec<String> {
+            from_document(document)
+                .unwrap()
+                .into_iter()
+                .map(|assertion| assertion.source)
+                .collect()
+        };
+        assert_eq!(
+            sources(&document),
+            vec![
+                "status == 200",
+                r#"header.content-type contains "json""#,
+                r#"header.x-version == "2""#,
+                "$.ok == true",
+                "$.items exists",
+                "header.etag exists",
+                "duration < 500",
+                "duration >= 0",
+                "$.message matches ^Hello",
+            ]
+        );
+
+        // A bare list is the checks on their own
+        let list =
+            serde_yaml::from_str(r#"[{ status: 200, jsonpath: "$.ok", equals: true }]"#).unwrap();
+        assert_eq!(sources(&list), vec!["status == 200", "$.ok == true"]);
+
+        for invalid in [
+            "{ stauts: 200 }",
+            "[{ equals: 1 }]",
+            "[{ jsonpath: $.a, approx: 1 }]",
+            "[{ jsonpath: $.a, exists: false }]",
+            "[{ status: [200, 201] }]",
+        ] {
+            let document = serde_yaml::from_str(invalid).unwrap();
+            assert!(from_document(&document).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_assertion_file_values_keep_their_type() {
+        let mut response = create_test_response();
+        response.body = r#"{"first name": "", "code": "007", "flag": true}"#.to_string();
+        response
+            .headers
+            .insert("X-Version".to_string(), "2".to_string());
+
+        let document = serde_yaml::from_str(
+            r#"
+headers:
+  x-version: 2
+checks:
+  - { jsonpath: "$['first name']", equals: "" }
+  - { jsonpath: $.code, equals: "007" }
+  - { jsonpath: $.flag, equals: true }
+  - { jsonpath: $.flag, not_equals: "true" }
+"#,
+        )
+        .unwrap();
+        for assertion in from_document(&document).unwrap() {
+            assert!(assertion.evaluate(&response).passed, "{}", assertion.source);
+        }
+    }
+
+    #[test]
+    fn test_invalid_assertions() {
+        assert!(Assertion::parse("status").is_err());
+        assert!(Assertion::parse("status ~= 200").is_err());
+        assert!(Assertion::parse("latency < 5").is_err());
+        assert!(Assertion::parse("status ==").is_err());
+        assert!(Assertion::parse("$.id exists 1").is_err());
+    }
+}
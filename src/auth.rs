@@ -0,0 +1,551 @@
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::client::TerziClient;
+use crate::config::{Config, StoredToken};
+use crate::request::RequestBuilder;
+
+// OAuth2 token acquisition and refresh for stored tokens
+#[derive(Debug, Clone)]
+pub struct ClientCredentials {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    token_type: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+impl TokenResponse {
+    fn into_stored(self, token_url: &str, client_id: &str, client_secret: &str) -> StoredToken {
+        StoredToken {
+            token_type: self.token_type.unwrap_or_else(|| "Bearer".to_string()),
+            value: self.access_token,
+            expires_at: self
+                .expires_in
+                .map(|seconds| Utc::now() + chrono::Duration::seconds(seconds)),
+            refresh_token: self.refresh_token,
+            scopes: self
+                .scope
+                .map(|s| s.split_whitespace().map(String::from).collect())
+                .unwrap_or_default(),
+            token_url: Some(token_url.to_string()),
+            client_id: Some(client_id.to_string()),
+            client_secret: Some(client_secret.to_string()),
+        }
+    }
+}
+
+pub async fn client_credentials_grant(
+    client: &TerziClient,
+    credentials: &ClientCredentials,
+) -> Result<StoredToken> {
+    let mut form = vec![
+        ("grant_type".to_string(), "client_credentials".to_string()),
+        ("client_id".to_string(), credentials.client_id.clone()),
+        (
+            "client_secret".to_string(),
+            credentials.client_secret.clone(),
+        ),
+    ];
+    if !credentials.scopes.is_empty() {
+        form.push(("scope".to_string(), credentials.scopes.join(" ")));
+    }
+
+    let response = request_token(client, &credentials.token_url, form).await?;
+    let mut token = response.into_stored(
+        &credentials.token_url,
+        &credentials.client_id,
+        &credentials.client_secret,
+    );
+
+    // Servers may omit `scope` when they granted exactly what was asked for
+    if token.scopes.is_empty() {
+        token.scopes = credentials.scopes.clone();
+    }
+    Ok(token)
+}
+
+pub async fn refresh_token(client: &TerziClient, token: &StoredToken) -> Result<StoredToken> {
+    let refresh_token = token
+        .refresh_token
+        .as_ref()
+        .ok_or_else(|| anyhow!("Token has no refresh token"))?;
+    let token_url = token
+        .token_url
+        .as_ref()
+        .ok_or_else(|| anyhow!("Token has no token URL to refresh against"))?;
+    let client_id = token.client_id.clone().unwrap_or_default();
+    let client_secret = token.client_secret.clone().unwrap_or_default();
+
+    let mut form = vec![
+        ("grant_type".to_string(), "refresh_token".to_string()),
+        ("refresh_token".to_string(), refresh_token.clone()),
+    ];
+    if !client_id.is_empty() {
+        form.push(("client_id".to_string(), client_id.clone()));
+    }
+    if !client_secret.is_empty() {
+        form.push(("client_secret".to_string(), client_secret.clone()));
+    }
+
+    let response = request_token(client, token_url, form).await?;
+    let mut refreshed = response.into_stored(token_url, &client_id, &client_secret);
+
+    // Keep the old refresh token and scopes unless the server rotated them
+    if refreshed.refresh_token.is_none() {
+        refreshed.refresh_token = token.refresh_token.clone();
+    }
+    if refreshed.scopes.is_empty() {
+        refreshed.scopes = token.scopes.clone();
+    }
+    Ok(refreshed)
+}
+
+/// Returns the `Authorization` header value for a stored token, refreshing it first if needed.
+pub async fn authorization_header(
+    client: &TerziClient,
+    config: &mut Config,
+    name: &str,
+) -> Result<String> {
+    let token = config.get_token(name).cloned().ok_or_else(|| {
+        anyhow!(
+            "Token '{}' not found. Create it with 'terzi auth oauth2'",
+            name
+        )
+    })?;
+
+    let token = if !token.is_expired() {
+        token
+    } else if config.auth.auto_refresh_tokens && token.refresh_token.is_some() {
+        let refreshed = refresh_token(client, &token)
+            .await
+            .map_err(|e| anyhow!("Failed to refresh token '{}': {}", name, e))?;
+        config.save_token(name, refreshed.clone()).await?;
+        refreshed
+    } else if let Some(command) = config.general.token_refresh_command.clone() {
+        let refreshed = run_refresh_command(&command, name, &token)
+            .await
+            .map_err(|e| anyhow!("Failed to refresh token '{}': {}", name, e))?;
+        config.save_token(name, refreshed.clone()).await?;
+        refreshed
+    } else {
+        return Err(anyhow!(
+            "Token '{}' has expired. Run 'terzi auth oauth2' again to get a new one, \
+             or set general.token_refresh_command",
+            name
+        ));
+    };
+
+    Ok(format!(
+        "{} {}",
+        token_scheme(&token.token_type),
+        token.value
+    ))
+}
+
+// The command prints either the bare token or an OAuth2 token response as JSON. A bare
+// token has no known lifetime, so it keeps the old expiry and the command runs again
+// on the next request.
+async fn run_refresh_command(
+    command: &str,
+    name: &str,
+    token: &StoredToken,
+) -> Result<StoredToken> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let output = shell
+        .arg(command)
+        .env("TERZI_TOKEN_NAME", name)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run '{}': {}", command, e))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "'{}' exited with {}: {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|_| anyhow!("'{}' printed a token that is not UTF-8", command))?;
+    let stdout = stdout.trim();
+    if stdout.is_empty() {
+        return Err(anyhow!("'{}' printed no token", command));
+    }
+
+    let mut refreshed = token.clone();
+    match serde_json::from_str::<TokenResponse>(stdout) {
+        Ok(response) => {
+            refreshed.value = response.access_token;
+            refreshed.token_type = response.token_type.unwrap_or(refreshed.token_type);
+            refreshed.expires_at = response
+                .expires_in
+                .map(|seconds| Utc::now() + chrono::Duration::seconds(seconds));
+            if response.refresh_token.is_some() {
+                refreshed.refresh_token = response.refresh_token;
+            }
+        }
+        Err(_) => refreshed.value = stdout.to_string(),
+    }
+    Ok(refreshed)
+}
+
+/// Reads a bearer token that another tool keeps up to date. The file holds the bare
+/// token, or a full header value such as `Bearer abc` or `Token abc`.
+pub async fn token_file_authorization(path: &std::path::Path) -> Result<String> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| anyhow!("Failed to read token file {}: {}", path.display(), e))?;
+    let token = contents.trim();
+    if token.is_empty() {
+        return Err(anyhow!("Token file {} is empty", path.display()));
+    }
+    if token.contains(char::is_whitespace) {
+        Ok(token.to_string())
+    } else {
+        Ok(format!("Bearer {}", token))
+    }
+}
+
+fn token_scheme(token_type: &str) -> &str {
+    // Servers often return `bearer`, but the scheme is conventionally capitalized
+    if token_type.eq_ignore_ascii_case("bearer") {
+        "Bearer"
+    } else {
+        token_type
+    }
+}
+
+// HTTP Digest authentication (RFC 7616)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl DigestAlgorithm {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_uppercase().as_str() {
+            "MD5" => Some(Self::Md5),
+            "MD5-SESS" => Some(Self::Md5Sess),
+            "SHA-256" => Some(Self::Sha256),
+            "SHA-256-SESS" => Some(Self::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Md5Sess => "MD5-sess",
+            Self::Sha256 => "SHA-256",
+            Self::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn is_session(self) -> bool {
+        matches!(self, Self::Md5Sess | Self::Sha256Sess)
+    }
+
+    fn hash(self, data: &str) -> String {
+        use md5::Digest as _;
+        match self {
+            Self::Md5 | Self::Md5Sess => format!("{:x}", md5::Md5::digest(data.as_bytes())),
+            Self::Sha256 | Self::Sha256Sess => {
+                format!("{:x}", sha2::Sha256::digest(data.as_bytes()))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestChallenge {
+    pub realm: String,
+    pub nonce: String,
+    pub opaque: Option<String>,
+    pub algorithm: DigestAlgorithm,
+    pub qop_auth: bool,
+}
+
+impl DigestChallenge {
+    /// Parses a `WWW-Authenticate: Digest ...` value; returns `None` for other schemes.
+    pub fn parse(header: &str) -> Result<Option<Self>> {
+        let header = header.trim();
+        let Some(params) = header
+            .get(..6)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("digest"))
+            .map(|_| &header[6..])
+        else {
+            return Ok(None);
+        };
+
+        let params = parse_auth_params(params);
+        let get = |key: &str| params.get(key).cloned();
+
+        let nonce = get("nonce").ok_or_else(|| anyhow!("Digest challenge is missing a nonce"))?;
+        let algorithm = match get("algorithm") {
+            Some(name) => DigestAlgorithm::parse(&name)
+                .ok_or_else(|| anyhow!("Unsupported digest algorithm: {}", name))?,
+            None => DigestAlgorithm::Md5,
+        };
+
+        let qop_auth = match get("qop") {
+            Some(qop) => {
+                if !qop
+                    .split(',')
+                    .any(|q| q.trim().eq_ignore_ascii_case("auth"))
+                {
+                    return Err(anyhow!("Unsupported digest qop: {}", qop));
+                }
+                true
+            }
+            None => false,
+        };
+
+        Ok(Some(Self {
+            realm: get("realm").unwrap_or_default(),
+            nonce,
+            opaque: get("opaque"),
+            algorithm,
+            qop_auth,
+        }))
+    }
+
+    /// Builds the `Authorization` header for one request; `nc` is the nonce count.
+    pub fn authorization(
+        &self,
+        username: &str,
+        password: &str,
+        method: &str,
+        uri: &str,
+        nc: u32,
+        cnonce: &str,
+    ) -> String {
+        let algorithm = self.algorithm;
+        let mut ha1 = algorithm.hash(&format!("{}:{}:{}", username, self.realm, password));
+        if algorithm.is_session() {
+            ha1 = algorithm.hash(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = algorithm.hash(&format!("{}:{}", method, uri));
+        let nc = format!("{:08x}", nc);
+
+        let response = if self.qop_auth {
+            algorithm.hash(&format!(
+                "{}:{}:{}:{}:auth:{}",
+                ha1, self.nonce, nc, cnonce, ha2
+            ))
+        } else {
+            algorithm.hash(&format!("{}:{}:{}", ha1, self.nonce, ha2))
+        };
+
+        let mut header = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={}, response="{}""#,
+            username,
+            self.realm,
+            self.nonce,
+            uri,
+            algorithm.name(),
+            response
+        );
+        if self.qop_auth {
+            header.push_str(&format!(r#", qop=auth, nc={}, cnonce="{}""#, nc, cnonce));
+        }
+        if let Some(ref opaque) = self.opaque {
+            header.push_str(&format!(r#", opaque="{}""#, opaque));
+        }
+        header
+    }
+}
+
+// Splits `key=value, key="quoted, value"` auth parameters
+fn parse_auth_params(input: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace() || *c == ',') {
+            chars.next();
+        }
+
+        let key: String = chars
+            .by_ref()
+            .take_while(|c| *c != '=')
+            .collect::<String>()
+            .trim()
+            .to_lowercase();
+        if key.is_empty() {
+            break;
+        }
+
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    _ => value.push(c),
+                }
+            }
+        } else {
+            value = chars
+                .by_ref()
+                .take_while(|c| *c != ',')
+                .collect::<String>()
+                .trim()
+                .to_string();
+        }
+
+        params.insert(key, value);
+    }
+
+    params
+}
+
+async fn request_token(
+    client: &TerziClient,
+    token_url: &str,
+    form: Vec<(String, String)>,
+) -> Result<TokenResponse> {
+    let request = RequestBuilder::new(token_url, "POST")?
+        .header("Accept", "application/json")
+        .form_body(form)?
+        .build();
+
+    let response = client.execute_request(&request).await?;
+    if !response.is_success() {
+        return Err(anyhow!(
+            "Token endpoint returned {}: {}",
+            response.status,
+            crate::utils::truncate_string(&response.body, 200)
+        ));
+    }
+
+    serde_json::from_str(&response.body)
+        .map_err(|e| anyhow!("Invalid token response from {}: {}", token_url, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_response_sets_expiry_and_scopes() {
+        let response: TokenResponse = serde_json::from_str(
+            r#"{"access_token": "abc", "token_type": "bearer", "expires_in": 3600, "scope": "read write"}"#,
+        )
+        .unwrap();
+        let token = response.into_stored("https://auth.example.com/token", "id", "secret");
+
+        assert_eq!(token.value, "abc");
+        assert_eq!(token.scopes, vec!["read", "write"]);
+        assert!(!token.is_expired());
+        assert!(token.expires_at.unwrap() > Utc::now() + chrono::Duration::seconds(3500));
+        assert_eq!(token_scheme(&token.token_type), "Bearer");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_refresh_command_output() {
+        let expired = StoredToken {
+            token_type: "Bearer".to_string(),
+            value: "old".to_string(),
+            expires_at: Some(Utc::now() - chrono::Duration::seconds(60)),
+            refresh_token: None,
+            scopes: Vec::new(),
+            token_url: None,
+            client_id: None,
+            client_secret: None,
+        };
+
+        let plain = run_refresh_command("echo \"$TERZI_TOKEN_NAME-new\"", "ci", &expired)
+            .await
+            .unwrap();
+        assert_eq!(plain.value, "ci-new");
+        assert!(plain.is_expired());
+
+        let json = run_refresh_command(
+            r#"echo '{"access_token": "abc", "expires_in": 3600}'"#,
+            "ci",
+            &expired,
+        )
+        .await
+        .unwrap();
+        assert_eq!(json.value, "abc");
+        assert!(!json.is_expired());
+
+        assert!(run_refresh_command("exit 3", "ci", &expired).await.is_err());
+    }
+
+    #[test]
+    fn test_digest_rfc2617_example() {
+        let challenge = DigestChallenge::parse(
+            r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(challenge.algorithm, DigestAlgorithm::Md5);
+        assert!(challenge.qop_auth);
+
+        let header = challenge.authorization(
+            "Mufasa",
+            "Circle Of Life",
+            "GET",
+            "/dir/index.html",
+            1,
+            "0a4f113b",
+        );
+        assert!(header.contains(r#"response="6629fae49393a05397450978507c4ef1""#));
+        assert!(header.contains("nc=00000001"));
+        assert!(header.contains(r#"opaque="5ccc069c403ebaf9f0171e9517f40e41""#));
+    }
+
+    #[test]
+    fn test_digest_challenge_rejects_other_schemes() {
+        assert!(
+            DigestChallenge::parse(r#"Basic realm="api""#)
+                .unwrap()
+                .is_none()
+        );
+        assert!(DigestChallenge::parse(r#"Digest realm="api""#).is_err());
+        assert!(
+            DigestChallenge::parse(r#"Digest realm="api", nonce="n", qop="auth-int""#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_expired_token() {
+        let response: TokenResponse =
+            serde_json::from_str(r#"{"access_token": "abc", "expires_in": 0}"#).unwrap();
+        let token = response.into_stored("https://auth.example.com/token", "id", "secret");
+
+        assert!(token.is_expired());
+        assert_eq!(token.token_type, "Bearer");
+    }
+}
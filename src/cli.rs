@@ -91,6 +91,39 @@ pub fn print_error(message: &str) {
     eprintln!("{} {}", "❌ Error:".bright_red().bold(), message);
 }
 
+// The whole error chain, then a suggested fix when the failure is a common one
+pub fn print_request_error(error: &anyhow::Error) {
+    print_error(&format!(
+        "Request failed: {}",
+        crate::utils::format_error_chain(error)
+    ));
+    if let Some(hint) = request_error_hint(error) {
+        eprintln!("   {} {}", "💡 Hint:".bright_yellow().bold(), hint);
+    }
+}
+
+fn request_error_hint(error: &anyhow::Error) -> Option<String> {
+    if let Some(e) = error
+        .chain()
+        .find_map(|e| e.downcast_ref::<reqwest::Error>())
+    {
+        return crate::utils::diagnose_error(e);
+    }
+
+    // Limits terzi enforces itself
+    let message = error.to_string();
+    let hint = if message.starts_with("Request timed out") {
+        "Increase the timeout with -t/--timeout"
+    } else if message.starts_with("No data received") {
+        "The server stalled mid-response; raise --read-timeout (or network.read_timeout)"
+    } else if message.starts_with("Response exceeded max size") {
+        "Raise the limit with --max-response-size, or set network.max_response_size to none"
+    } else {
+        return None;
+    };
+    Some(hint.to_string())
+}
+
 pub fn print_success(message: &str) {
     println!("{} {}", "✅".green(), message);
 }
@@ -103,18 +136,59 @@ pub fn print_warning(message: &str) {
     println!("{} {}", "⚠️".yellow(), message);
 }
 
+// For warnings that must not end up in piped response output
+pub fn print_stderr_warning(message: &str) {
+    eprintln!("{} {}", "⚠️".yellow(), message);
+}
+
+// Completion notifications for long-running requests
+#[cfg_attr(not(feature = "notifications"), allow(unused_variables))]
+pub fn notify_completion(summary: &str) {
+    #[cfg(feature = "notifications")]
+    {
+        let shown = notify_rust::Notification::new()
+            .summary("Terzi")
+            .body(summary)
+            .show()
+            .is_ok();
+        if shown {
+            return;
+        }
+    }
+
+    // Fall back to the terminal bell when no desktop notification was shown
+    eprint!("\x07");
+    io::stderr().flush().ok();
+}
+
 // CLI command completions and suggestions
 pub fn suggest_similar_commands(input: &str) -> Vec<&'static str> {
     let commands = vec![
         "interactive",
+        "repl",
         "list",
+        "search",
         "show",
         "delete",
         "edit",
+        "duplicate",
+        "lint",
+        "ping",
         "history",
+        "run",
+        "test",
+        "collection",
+        "auth",
+        "ws",
         "config",
+        "cache",
+        "profile",
         "import",
+        "openapi",
         "export",
+        "export-har",
+        "backup",
+        "completions",
         "help",
         "version",
     ];
@@ -2,11 +2,14 @@ use anyhow::Result;
 use reqwest::{Client, Method, Request, Response as ReqwestResponse, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::time::timeout;
 
-use crate::config::Config;
-use crate::request::SavedRequest;
+use crate::auth::DigestChallenge;
+use crate::config::{Config, ConnectTo};
+use crate::request::{Body, DigestCredentials, SavedRequest};
+use crate::storage::ResponseCache;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Response {
@@ -17,90 +20,464 @@ pub struct Response {
     pub size: usize,
     pub url: String,
     pub method: String,
+    /// The server answered 304 Not Modified and the body is the cached copy
+    #[serde(default)]
+    pub from_cache: bool,
+    /// Correlation ID sent in `network.request_id_header`, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// The request as sent, when `output.capture_request` is on
+    #[serde(skip)]
+    pub wire_request: Option<WireRequest>,
+    /// Bytes that went over the wire for this response, retries and redirects excluded
+    #[serde(skip)]
+    pub transfer: Transfer,
+    /// Every Set-Cookie value; `headers` only keeps the last one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub set_cookies: Vec<String>,
+    /// Redirects followed on the way to this response, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirects: Vec<RedirectHop>,
+}
+
+/// Byte counts of an exchange as sent and received, headers included. Bodies are counted
+/// as they travel, so a compressed body counts its compressed size; a body streamed from
+/// stdin without a Content-Length isn't counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Transfer {
+    pub request_header_bytes: usize,
+    pub request_body_bytes: usize,
+    pub response_header_bytes: usize,
+    pub response_body_bytes: usize,
+}
+
+impl Transfer {
+    pub fn sent(&self) -> usize {
+        self.request_header_bytes + self.request_body_bytes
+    }
+
+    pub fn received(&self) -> usize {
+        self.response_header_bytes + self.response_body_bytes
+    }
+}
+
+impl std::ops::AddAssign for Transfer {
+    fn add_assign(&mut self, other: Self) {
+        self.request_header_bytes += other.request_header_bytes;
+        self.request_body_bytes += other.request_body_bytes;
+        self.response_header_bytes += other.response_header_bytes;
+        self.response_body_bytes += other.response_body_bytes;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RedirectHop {
+    pub url: String,
+    pub status: u16,
+    pub location: String,
+}
+
+// The redirect policy lives on the shared reqwest client, so the limit and the hops of the
+// request being sent are handed to it through a task-local
+#[derive(Clone)]
+struct RedirectTrace {
+    follow: bool,
+    limit: usize,
+    hops: Arc<Mutex<Vec<RedirectHop>>>,
+}
+
+tokio::task_local! {
+    static REDIRECT_TRACE: RedirectTrace;
+}
+
+#[derive(Debug)]
+struct TooManyRedirects {
+    limit: usize,
+    chain: Vec<String>,
+}
+
+impl std::fmt::Display for TooManyRedirects {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stopped after {} redirects: {}",
+            self.limit,
+            self.chain.join(" → ")
+        )
+    }
+}
+
+impl std::error::Error for TooManyRedirects {}
+
+fn redirect_policy(default_limit: usize) -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(move |attempt| {
+        let hop = RedirectHop {
+            url: attempt
+                .previous()
+                .last()
+                .map(|url| url.to_string())
+                .unwrap_or_default(),
+            status: attempt.status().as_u16(),
+            location: attempt.url().to_string(),
+        };
+        let (follow, limit) = REDIRECT_TRACE
+            .try_with(|trace| {
+                if trace.follow {
+                    trace.hops.lock().unwrap().push(hop);
+                }
+                (trace.follow, trace.limit)
+            })
+            .unwrap_or((true, default_limit));
+
+        if !follow {
+            return attempt.stop();
+        }
+        // `previous` holds every URL requested so far, including the one redirecting now
+        if attempt.previous().len() > limit {
+            let mut chain: Vec<String> = attempt
+                .previous()
+                .iter()
+                .map(|url| url.to_string())
+                .collect();
+            chain.push(attempt.url().to_string());
+            attempt.error(TooManyRedirects { limit, chain })
+        } else {
+            attempt.follow()
+        }
+    })
 }
 
 pub struct TerziClient {
     client: Client,
+    // One client per --connect-to entry that moves its host to another address, since the
+    // override is per host and must not catch the host's other ports
+    pinned_clients: Vec<(ConnectTo, Client)>,
     config: Config,
+    // Last digest challenge per origin, reused with an increasing nonce count
+    digest_sessions: Mutex<HashMap<String, DigestSession>>,
+    cache: Option<ResponseCache>,
+    #[cfg(unix)]
+    unix_socket: Option<crate::unix_socket::UnixSocketClient>,
+}
+
+// How --paginate finds the next page: the Link header first, then a cursor in the body
+#[derive(Debug, Clone)]
+pub struct Pagination {
+    pub max_pages: usize,
+    pub cursor_path: Option<String>,
+    pub cursor_param: String,
+}
+
+// Smaller transfers finish before a bar would be worth drawing
+const PROGRESS_THRESHOLD: u64 = 1024 * 1024;
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+// One `terzi ping` probe
+#[derive(Debug, Clone)]
+pub struct Connectivity {
+    pub status: u16,
+    pub duration: Duration,
+    pub remote_addr: Option<std::net::SocketAddr>,
+    /// Fingerprint of the server's leaf certificate, for https:// URLs
+    pub certificate_sha256: Option<String>,
+}
+
+struct DigestSession {
+    challenge: DigestChallenge,
+    nonce_count: u32,
 }
 
 impl TerziClient {
     pub fn new(config: &Config) -> Result<Self> {
-        let mut client_builder = Client::builder()
-            .user_agent(&config.network.user_agent)
-            .cookie_store(true)
-            .timeout(Duration::from_secs(config.network.read_timeout))
-            .connect_timeout(Duration::from_secs(config.network.connection_timeout))
-            .tcp_keepalive(if config.network.keep_alive {
-                Some(Duration::from_secs(60))
-            } else {
-                None
-            })
-            // Note: compression is enabled by default in reqwest
-            .redirect(reqwest::redirect::Policy::limited(
-                config.network.max_redirects as usize,
-            ));
-
-        // Set proxy if configured
-        if let Some(ref proxy_url) = config.network.proxy_url {
-            let proxy = reqwest::Proxy::all(proxy_url)?;
-            client_builder = client_builder.proxy(proxy);
+        if !config.network.verify_ssl {
+            crate::cli::print_stderr_warning(
+                "TLS certificate verification is disabled; responses may come from anyone",
+            );
         }
 
-        // Set SSL verification
-        if !config.network.verify_ssl {
-            client_builder = client_builder.danger_accept_invalid_certs(true);
+        #[cfg(not(unix))]
+        if config.network.unix_socket.is_some() {
+            return Err(anyhow::anyhow!(
+                "--unix-socket is only supported on Unix platforms"
+            ));
         }
 
-        let client = client_builder.build()?;
+        let connect_timeout = Duration::from_secs(config.network.connection_timeout);
+        let client = build_http_client(config, connect_timeout, None)?;
+        // A --resolve entry is a --connect-to that keeps the port, so it only pins that port
+        let resolved = config.network.resolve.iter().map(|(host, addr)| ConnectTo {
+            host: host.clone(),
+            port: addr.port(),
+            target_host: addr.ip().to_string(),
+            target_port: addr.port(),
+        });
+        let pinned_clients = config
+            .network
+            .connect_to
+            .iter()
+            .filter(|entry| entry.target_host != entry.host)
+            .cloned()
+            .chain(resolved)
+            .map(|entry| {
+                let client = build_http_client(config, connect_timeout, Some(&entry))?;
+                Ok((entry, client))
+            })
+            .collect::<Result<_>>()?;
 
         Ok(Self {
             client,
+            pinned_clients,
             config: config.clone(),
+            digest_sessions: Mutex::new(HashMap::new()),
+            cache: config.network.enable_cache.then(ResponseCache::new),
+            #[cfg(unix)]
+            unix_socket: config
+                .network
+                .unix_socket
+                .as_ref()
+                .map(crate::unix_socket::UnixSocketClient::new),
         })
     }
 
     pub async fn execute_request(&self, saved_request: &SavedRequest) -> Result<Response> {
-        let start_time = Instant::now();
+        // Default headers are added per send and never stored; the request's own headers win
+        let mut request = saved_request.clone();
+        request.headers = crate::utils::merge_headers(
+            &self.config.network.default_headers,
+            &saved_request.headers,
+        );
+        // Template functions are evaluated per send, so repeated requests get fresh values
+        let saved_request = &request.resolve_secrets()?.render_dynamic()?;
 
-        // Build the request
-        let method = Method::from_bytes(saved_request.method.as_bytes())?;
-        let url = reqwest::Url::parse(&saved_request.url)?;
+        let mut response = match self.cache {
+            Some(ref cache)
+                if ["GET", "HEAD"]
+                    .iter()
+                    .any(|m| saved_request.method.eq_ignore_ascii_case(m)) =>
+            {
+                self.execute_cached(cache, saved_request).await
+            }
+            _ => self.send(saved_request).await,
+        }?;
+        response.request_id = self.request_id(saved_request).map(|(_, id)| id.clone());
+        Ok(response)
+    }
 
-        let mut request_builder = self.client.request(method.clone(), url.clone());
+    fn request_id<'a>(&self, request: &'a SavedRequest) -> Option<(&'a String, &'a String)> {
+        request
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&self.config.network.request_id_header))
+    }
 
-        // Add headers
-        for (key, value) in &saved_request.headers {
-            request_builder = request_builder.header(key, value);
+    // Sends the validators of a cached copy and substitutes it when the server answers 304
+    async fn execute_cached(
+        &self,
+        cache: &ResponseCache,
+        saved_request: &SavedRequest,
+    ) -> Result<Response> {
+        // A fresh request ID every time must not make every request a cache miss
+        let key = match self.request_id(saved_request) {
+            Some((name, _)) => {
+                let mut keyed = saved_request.clone();
+                keyed.headers.remove(name);
+                ResponseCache::key(&keyed)
+            }
+            None => ResponseCache::key(saved_request),
+        };
+        let cached = cache.get(&key).await;
+
+        let mut request = saved_request.clone();
+        if let Some(ref entry) = cached {
+            let has_header = |name: &str| {
+                saved_request
+                    .headers
+                    .keys()
+                    .any(|key| key.eq_ignore_ascii_case(name))
+            };
+            if let Some(ref etag) = entry.etag
+                && !has_header("if-none-match")
+            {
+                request
+                    .headers
+                    .insert("If-None-Match".to_string(), etag.clone());
+            }
+            if let Some(ref last_modified) = entry.last_modified
+                && !has_header("if-modified-since")
+            {
+                request
+                    .headers
+                    .insert("If-Modified-Since".to_string(), last_modified.clone());
+            }
         }
 
-        // Add body if present
-        if let Some(body) = &saved_request.body {
-            request_builder = request_builder.body(body.clone());
+        let mut response = self.send(&request).await?;
+        match cached {
+            Some(entry) if response.status == 304 => {
+                // Headers sent with the 304 update the stored ones
+                let mut headers = entry.headers;
+                headers.extend(response.headers);
+                response.headers = headers;
+                response.status = entry.status;
+                response.size = entry.body.len();
+                response.body = entry.body;
+                response.from_cache = true;
+            }
+            _ if response.status == 200 => {
+                if let Err(e) = cache.put(&key, &response).await {
+                    crate::cli::print_stderr_warning(&format!(
+                        "Could not cache the response: {}",
+                        e
+                    ));
+                }
+            }
+            _ => {}
         }
+        Ok(response)
+    }
 
-        // Set timeout - use request timeout if specified, otherwise use config default
-        let request_timeout = Duration::from_secs(
+    async fn send(&self, saved_request: &SavedRequest) -> Result<Response> {
+        let start_time = Instant::now();
+
+        // Build the request
+        let method = Method::from_bytes(saved_request.method.as_bytes())?;
+        let url = reqwest::Url::parse(&saved_request.url)?;
+
+        // Set timeout - use request timeout if specified, otherwise use config default.
+        // It covers the whole exchange, including downloading the body.
+        let total_secs = saved_request
+            .timeout
+            .unwrap_or(self.config.general.default_timeout);
+        let read_timeout = Duration::from_secs(
             saved_request
-                .timeout
-                .unwrap_or(self.config.general.default_timeout),
+                .read_timeout
+                .unwrap_or(self.config.network.read_timeout),
         );
 
-        // Execute request with timeout
-        let response = timeout(request_timeout, request_builder.send()).await??;
+        // The connect timeout lives on the reqwest client, so a per-request value needs its own
+        let pinned = self
+            .pinned_clients
+            .iter()
+            .find(|(entry, _)| connects(entry, &url));
+        let custom_client;
+        let client = match saved_request.connect_timeout {
+            Some(secs) if secs != self.config.network.connection_timeout => {
+                custom_client = build_http_client(
+                    &self.config,
+                    Duration::from_secs(secs),
+                    pinned.map(|(entry, _)| entry),
+                )?;
+                &custom_client
+            }
+            _ => pinned.map_or(&self.client, |(_, client)| client),
+        };
 
-        let duration = start_time.elapsed();
+        // Bytes are read once up front so a digest retry resends exactly the same body;
+        // a stdin body is streamed by build_request instead
+        let streamed = matches!(saved_request.body, Some(Body::Stdin));
+        let body = match saved_request.body {
+            Some(Body::Stdin) | None => None,
+            Some(ref body) => Some(body.read().await?),
+        };
 
-        // Extract response data
-        let status = response.status();
-        let headers = response
-            .headers()
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-            .collect();
+        // Read per send, so a token rotated by another tool is picked up between requests
+        let token_authorization = match self.config.auth.token_file {
+            Some(ref path) => Some(crate::auth::token_file_authorization(path).await?),
+            None => None,
+        };
+
+        let exchange = async {
+            // Reuse a known digest challenge so repeat requests skip the 401 round trip
+            let digest = saved_request.digest_auth.as_ref();
+            let authorization = digest
+                .and_then(|credentials| self.digest_authorization(credentials, &method, &url, None))
+                .or_else(|| token_authorization.clone());
+
+            let mut transfer = Transfer::default();
+            let (mut response, mut wire_request) = self
+                .dispatch(self.build_request(
+                    client,
+                    saved_request,
+                    &method,
+                    &url,
+                    authorization.as_deref(),
+                    body.as_deref(),
+                ))
+                .await?;
+
+            if let Some(credentials) = digest
+                && response.status() == StatusCode::UNAUTHORIZED
+                && let Some(challenge) = digest_challenge(&response)?
+            {
+                // The challenge round trip used the wire too; its body is never read
+                transfer.request_header_bytes += wire_request.head_size();
+                transfer.request_body_bytes += wire_request.body_size;
+                transfer.response_header_bytes += response_head_size(&response);
+                if streamed {
+                    return Err(anyhow::anyhow!(
+                        "The server asked for digest authentication, which needs the body sent twice; it can't be used with --stream-body"
+                    ));
+                }
+                let authorization =
+                    self.digest_authorization(credentials, &method, &url, Some(challenge));
+                (response, wire_request) = self
+                    .dispatch(self.build_request(
+                        client,
+                        saved_request,
+                        &method,
+                        &url,
+                        authorization.as_deref(),
+                        body.as_deref(),
+                    ))
+                    .await?;
+            }
 
-        let body = response.text().await?;
-        let size = body.len();
+            transfer.request_header_bytes += wire_request.head_size();
+            transfer.request_body_bytes += wire_request.body_size;
+            transfer.response_header_bytes += response_head_size(&response);
+
+            let set_cookies: Vec<String> = response
+                .headers()
+                .get_all(reqwest::header::SET_COOKIE)
+                .iter()
+                .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                .collect();
+            let status = response.status();
+            let headers: HashMap<String, String> = response
+                .headers()
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+                .collect();
+            let (body, body_bytes) = read_body(
+                response,
+                read_timeout,
+                self.config.network.max_response_size,
+                self.show_progress(),
+            )
+            .await?;
+            transfer.response_body_bytes += body_bytes;
+
+            let wire_request = self.config.output.capture_request.then_some(wire_request);
+            Ok::<_, anyhow::Error>((status, headers, set_cookies, body, wire_request, transfer))
+        };
+
+        let trace = RedirectTrace {
+            follow: saved_request.follow_redirects != Some(false),
+            limit: saved_request
+                .max_redirects
+                .unwrap_or(self.config.network.max_redirects) as usize,
+            hops: Arc::default(),
+        };
+        let (status, headers, set_cookies, body, wire_request, transfer) = timeout(
+            Duration::from_secs(total_secs),
+            REDIRECT_TRACE.scope(trace.clone(), exchange),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Request timed out after {}s", total_secs))??;
+        let redirects = std::mem::take(&mut *trace.hops.lock().unwrap());
+
+        let duration = start_time.elapsed();
+        let size = transfer.response_body_bytes;
 
         Ok(Response {
             status: status.as_u16(),
@@ -110,12 +487,232 @@ impl TerziClient {
             size,
             url: url.to_string(),
             method: method.to_string(),
+            from_cache: false,
+            request_id: None,
+            wire_request,
+            transfer,
+            set_cookies,
+            redirects,
         })
     }
 
-    pub async fn test_connection(&self, url: &str) -> Result<bool> {
-        let response = self.client.head(url).send().await;
-        Ok(response.is_ok())
+    // Follows next links and merges every page's items into one JSON array response
+    pub async fn execute_paginated(
+        &self,
+        saved_request: &SavedRequest,
+        pagination: &Pagination,
+    ) -> Result<Response> {
+        let mut request = saved_request.clone();
+        let mut visited = std::collections::HashSet::new();
+        let mut items = Vec::new();
+        let mut combined: Option<Response> = None;
+
+        for page in 1..=pagination.max_pages {
+            visited.insert(request.url.clone());
+            let response = self.execute_request(&request).await?;
+            // A failed page is shown as-is rather than hidden in a partial result
+            if !response.is_success() {
+                return Ok(response);
+            }
+
+            let body: serde_json::Value = serde_json::from_str(&response.body).map_err(|e| {
+                anyhow::anyhow!(
+                    "Page {} is not JSON, so it cannot be paginated: {}",
+                    page,
+                    e
+                )
+            })?;
+            let next = next_page_url(&response, &body, pagination)?;
+            items.extend(page_items(body));
+
+            combined = Some(match combined {
+                Some(mut combined) => {
+                    combined.status = response.status;
+                    combined.headers = response.headers;
+                    combined.duration += response.duration;
+                    combined.transfer += response.transfer;
+                    combined
+                }
+                None => response,
+            });
+
+            match next {
+                Some(url) if !visited.contains(&url) => request.url = url,
+                _ => break,
+            }
+            if page == pagination.max_pages {
+                crate::cli::print_stderr_warning(&format!(
+                    "Stopped after {} pages; raise --max-pages to fetch the rest",
+                    page
+                ));
+            }
+        }
+
+        let mut response = combined.ok_or_else(|| anyhow::anyhow!("No pages were fetched"))?;
+        response.body = serde_json::to_string(&items)?;
+        response.size = response.body.len();
+        response
+            .headers
+            .insert("content-type".to_string(), "application/json".to_string());
+        Ok(response)
+    }
+
+    // Bars go to stderr, and only when someone is watching it
+    fn show_progress(&self) -> bool {
+        use std::io::IsTerminal;
+        self.config.output.show_progress && std::io::stderr().is_terminal()
+    }
+
+    fn upload_progress(&self, len: usize) -> Option<indicatif::ProgressBar> {
+        // The Unix socket client needs the body in one piece
+        #[cfg(unix)]
+        if self.unix_socket.is_some() {
+            return None;
+        }
+        (self.show_progress() && len as u64 >= PROGRESS_THRESHOLD).then(|| {
+            let progress = crate::utils::create_progress_bar(len as u64);
+            progress.set_message("uploading");
+            progress
+        })
+    }
+
+    // Goes over the --unix-socket connection when one is configured, TCP otherwise
+    async fn dispatch(
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<(ReqwestResponse, WireRequest)> {
+        let (client, request) = request_builder.build_split();
+        let request = request?;
+        // Always captured, since history counts the bytes of what was sent
+        let wire = WireRequest::capture(&request, &self.config.network.user_agent);
+        if self.config.output.dump_request {
+            eprint!("{}", wire.dump());
+        }
+
+        #[cfg(unix)]
+        if let Some(ref socket) = self.unix_socket {
+            let response = socket
+                .send(request, &self.config.network.user_agent)
+                .await?;
+            return Ok((response, wire));
+        }
+        Ok((client.execute(request).await?, wire))
+    }
+
+    fn build_request(
+        &self,
+        client: &Client,
+        saved_request: &SavedRequest,
+        method: &Method,
+        url: &reqwest::Url,
+        authorization: Option<&str>,
+        body: Option<&[u8]>,
+    ) -> reqwest::RequestBuilder {
+        let (url, host) = connect_target(&self.config.network.connect_to, url);
+        let mut request_builder = client.request(method.clone(), url);
+
+        // Add headers; a computed Authorization replaces a saved one
+        for (key, value) in &saved_request.headers {
+            if authorization.is_some() && key.eq_ignore_ascii_case("authorization") {
+                continue;
+            }
+            request_builder = request_builder.header(key, value);
+        }
+        // A Host header the request sets itself wins over the one --connect-to keeps
+        if let Some(host) = host
+            && !saved_request
+                .headers
+                .keys()
+                .any(|key| key.eq_ignore_ascii_case("host"))
+        {
+            request_builder = request_builder.header(reqwest::header::HOST, host);
+        }
+        if let Some(authorization) = authorization {
+            request_builder = request_builder.header(reqwest::header::AUTHORIZATION, authorization);
+        }
+        if let Some(ref hmac) = saved_request.hmac {
+            request_builder = request_builder.header(&hmac.header, hmac.sign(body.unwrap_or(&[])));
+        }
+
+        // Add body if present
+        if let Some(body) = body {
+            request_builder = match self.upload_progress(body.len()) {
+                Some(progress) => request_builder
+                    .header(reqwest::header::CONTENT_LENGTH, body.len())
+                    .body(reqwest::Body::wrap_stream(upload_stream(body, progress))),
+                None => request_builder.body(body.to_vec()),
+            };
+        } else if let Some(Body::Stdin) = saved_request.body {
+            // No Content-Length, so hyper sends it with Transfer-Encoding: chunked
+            request_builder = request_builder.body(reqwest::Body::wrap_stream(stdin_stream()));
+        }
+
+        request_builder
+    }
+
+    // Stores a fresh challenge if given, then signs the request with the origin's current one
+    fn digest_authorization(
+        &self,
+        credentials: &DigestCredentials,
+        method: &Method,
+        url: &reqwest::Url,
+        challenge: Option<DigestChallenge>,
+    ) -> Option<String> {
+        let origin = url.origin().ascii_serialization();
+        let mut sessions = self.digest_sessions.lock().ok()?;
+        if let Some(challenge) = challenge {
+            sessions.insert(
+                origin.clone(),
+                DigestSession {
+                    challenge,
+                    nonce_count: 0,
+                },
+            );
+        }
+
+        let session = sessions.get_mut(&origin)?;
+        session.nonce_count += 1;
+
+        let uri = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let cnonce = uuid::Uuid::new_v4().simple().to_string();
+        Some(session.challenge.authorization(
+            &credentials.username,
+            &credentials.password,
+            method.as_str(),
+            &uri,
+            session.nonce_count,
+            &cnonce[..16],
+        ))
+    }
+
+    /// Sends a HEAD request; any HTTP response counts as reachable, whatever its status.
+    pub async fn test_connection(&self, url: &str) -> Result<Connectivity> {
+        let start = Instant::now();
+        let response = self.client.head(url).send().await?;
+        let duration = start.elapsed();
+
+        let certificate_sha256 = response
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .map(|der| {
+                use sha2::Digest;
+                sha2::Sha256::digest(der)
+                    .iter()
+                    .map(|byte| format!("{:02X}", byte))
+                    .collect::<Vec<_>>()
+                    .join(":")
+            });
+
+        Ok(Connectivity {
+            status: response.status().as_u16(),
+            duration,
+            remote_addr: response.remote_addr(),
+            certificate_sha256,
+        })
     }
 
     pub async fn get_response_preview(&self, url: &str, limit: usize) -> Result<String> {
@@ -130,6 +727,490 @@ impl TerziClient {
     }
 }
 
+// A request roughly as it went on the wire, for --verbose-request and `-o http`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WireRequest {
+    /// The request line, then each header as `name: value`
+    pub head: Vec<String>,
+    pub body: Option<String>,
+    /// Length of the body in bytes; a stream only counts if it declares a Content-Length
+    pub body_size: usize,
+}
+
+impl WireRequest {
+    // Host, Content-Length and the client defaults are only added while sending, so
+    // they are filled in here
+    fn capture(request: &Request, user_agent: &str) -> Self {
+        let url = request.url();
+        let target = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let mut head = vec![
+            format!("{} {} {:?}", request.method(), target, request.version()),
+            format!(
+                "host: {}",
+                &url[url::Position::BeforeHost..url::Position::AfterPort]
+            ),
+        ];
+
+        let headers = request.headers();
+        for (name, value) in headers {
+            head.push(format!(
+                "{}: {}",
+                name,
+                String::from_utf8_lossy(value.as_bytes())
+            ));
+        }
+        if !headers.contains_key(reqwest::header::USER_AGENT) {
+            head.push(format!("user-agent: {}", user_agent));
+        }
+        if !headers.contains_key(reqwest::header::ACCEPT) {
+            head.push("accept: */*".to_string());
+        }
+        // No accept-encoding line: reqwest is built without gzip/brotli, so it adds none
+        let bytes = request.body().and_then(|body| body.as_bytes());
+        if let Some(bytes) = bytes
+            && !headers.contains_key(reqwest::header::CONTENT_LENGTH)
+        {
+            head.push(format!("content-length: {}", bytes.len()));
+        }
+        if request.body().is_some()
+            && bytes.is_none()
+            && !headers.contains_key(reqwest::header::CONTENT_LENGTH)
+            && !headers.contains_key(reqwest::header::TRANSFER_ENCODING)
+        {
+            head.push("transfer-encoding: chunked".to_string());
+        }
+
+        let body_size = match bytes {
+            Some(bytes) => bytes.len(),
+            None => headers
+                .get(reqwest::header::CONTENT_LENGTH)
+                .and_then(|length| length.to_str().ok()?.parse().ok())
+                .unwrap_or(0),
+        };
+        let body = match (request.body(), bytes) {
+            (_, Some([])) => None,
+            (_, Some(bytes)) => Some(match std::str::from_utf8(bytes) {
+                Ok(text) => text.to_string(),
+                Err(_) => format!(
+                    "[{} of binary data]",
+                    crate::utils::format_bytes(bytes.len())
+                ),
+            }),
+            (Some(_), None) => Some("[streamed body]".to_string()),
+            (None, None) => None,
+        };
+        Self {
+            head,
+            body,
+            body_size,
+        }
+    }
+
+    /// curl -v style, with `>` before the request line and headers.
+    pub fn dump(&self) -> String {
+        let mut dump: String = self
+            .head
+            .iter()
+            .map(|line| format!("> {}\n", line))
+            .collect();
+        dump.push_str(">\n");
+        if let Some(ref body) = self.body {
+            dump.push_str(body);
+            if !body.ends_with('\n') {
+                dump.push('\n');
+            }
+        }
+        dump
+    }
+
+    /// Bytes of the request line and headers, with their CRLFs and the blank line after them.
+    pub fn head_size(&self) -> usize {
+        self.head.iter().map(|line| line.len() + 2).sum::<usize>() + 2
+    }
+
+    /// The HTTP version from the request line, e.g. `HTTP/1.1`.
+    pub fn version(&self) -> &str {
+        self.head
+            .first()
+            .and_then(|line| line.rsplit(' ').next())
+            .unwrap_or("HTTP/1.1")
+    }
+}
+
+fn next_page_url(
+    response: &Response,
+    body: &serde_json::Value,
+    pagination: &Pagination,
+) -> Result<Option<String>> {
+    let current = reqwest::Url::parse(&response.url)?;
+    if let Some(link) = response.headers.get("link").and_then(|l| next_link(l)) {
+        return Ok(Some(current.join(&link)?.to_string()));
+    }
+
+    let Some(ref path) = pagination.cursor_path else {
+        return Ok(None);
+    };
+    let cursor = match crate::utils::json_path(body, path) {
+        Some(serde_json::Value::String(cursor)) if !cursor.is_empty() => cursor.clone(),
+        Some(serde_json::Value::Number(cursor)) => cursor.to_string(),
+        _ => return Ok(None),
+    };
+
+    // Some APIs return the next URL itself, others an opaque cursor for the query string
+    if cursor.contains("://") || cursor.starts_with('/') || cursor.starts_with('?') {
+        return Ok(Some(current.join(&cursor)?.to_string()));
+    }
+    let mut next = current.clone();
+    let pairs: Vec<(String, String)> = current
+        .query_pairs()
+        .filter(|(name, _)| name != pagination.cursor_param.as_str())
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    next.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(&pagination.cursor_param, &cursor);
+    Ok(Some(next.to_string()))
+}
+
+// RFC 8288 Link header, e.g. `<https://api.example.com/items?page=2>; rel="next"`
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        params
+            .split(';')
+            .filter_map(|param| param.trim().strip_prefix("rel="))
+            .any(|rel| {
+                rel.trim_matches('"')
+                    .split_whitespace()
+                    .any(|r| r == "next")
+            })
+            .then(|| target.to_string())
+    })
+}
+
+// Wrapper fields that hold a page's list, checked before any other array field
+const PAGE_ITEM_FIELDS: &[&str] = &["data", "items", "results"];
+
+// The page's list: the body itself, a well-known field of a wrapper object, or its only
+// array field. Anything else is kept whole rather than guessing between several arrays.
+fn page_items(body: serde_json::Value) -> Vec<serde_json::Value> {
+    match body {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut obj) => {
+            let mut arrays = obj.iter().filter(|(_, value)| value.is_array());
+            let key = match PAGE_ITEM_FIELDS
+                .iter()
+                .find(|field| obj.get(**field).is_some_and(serde_json::Value::is_array))
+            {
+                Some(field) => Some(field.to_string()),
+                None => match (arrays.next(), arrays.next()) {
+                    (Some((key, _)), None) => Some(key.clone()),
+                    _ => None,
+                },
+            };
+            match key.and_then(|key| obj.remove(&key)) {
+                Some(serde_json::Value::Array(items)) => items,
+                _ => vec![serde_json::Value::Object(obj)],
+            }
+        }
+        other => vec![other],
+    }
+}
+
+// `pinned` is the --connect-to or --resolve entry whose host this client sends to the
+// entry's target
+fn build_http_client(
+    config: &Config,
+    connect_timeout: Duration,
+    pinned: Option<&ConnectTo>,
+) -> Result<Client> {
+    let mut client_builder = Client::builder()
+        .user_agent(&config.network.user_agent)
+        .cookie_store(true)
+        // Keeps the peer certificate on the response for `terzi ping`
+        .tls_info(true)
+        .connect_timeout(connect_timeout)
+        .tcp_keepalive(if config.network.keep_alive {
+            Some(Duration::from_secs(60))
+        } else {
+            None
+        })
+        .redirect(redirect_policy(config.network.max_redirects as usize));
+
+    // Set proxy if configured; socks5:// and socks5h:// URLs are supported too
+    if config.network.bypass_proxy {
+        client_builder = client_builder.no_proxy();
+    } else if let Some(ref proxy_url) = config.network.proxy_url {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| anyhow::anyhow!("Invalid proxy URL '{}': {}", proxy_url, e))?;
+        client_builder = client_builder.proxy(proxy);
+    }
+
+    // The pin covers every port of the host, so the client is only used for the entry's port;
+    // a --connect-to target port is swapped in per request
+    if let Some(entry) = pinned {
+        client_builder = client_builder.resolve(&entry.host, resolve_target(entry)?);
+    }
+    if let Some(address) = config.network.local_address {
+        client_builder = client_builder.local_address(address);
+    }
+
+    // Set SSL verification
+    if !config.network.verify_ssl {
+        client_builder = client_builder.danger_accept_invalid_certs(true);
+    }
+
+    // Extra trusted roots, e.g. a corporate CA
+    if let Some(ref ca_path) = config.network.ca_cert_path {
+        for certificate in load_ca_bundle(ca_path)? {
+            client_builder = client_builder.add_root_certificate(certificate);
+        }
+    }
+
+    // Client certificate for mutual TLS
+    if let Some(ref cert_path) = config.network.client_cert_path {
+        client_builder = client_builder.identity(load_identity(
+            cert_path,
+            config.network.client_key_path.as_deref(),
+        )?);
+    }
+
+    Ok(client_builder.build()?)
+}
+
+fn load_ca_bundle(path: &str) -> Result<Vec<reqwest::Certificate>> {
+    let pem = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read CA certificate '{}': {}", path, e))?;
+    let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+        .map_err(|e| anyhow::anyhow!("Invalid CA certificate '{}': {}", path, e))?;
+    if certificates.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No PEM certificates found in CA file '{}'",
+            path
+        ));
+    }
+    Ok(certificates)
+}
+
+// The key may live in the certificate file itself when no separate key path is given
+fn load_identity(cert_path: &str, key_path: Option<&str>) -> Result<reqwest::Identity> {
+    let cert = std::fs::read(cert_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read client certificate '{}': {}", cert_path, e))?;
+    let key = match key_path {
+        Some(key_path) => std::fs::read(key_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read client key '{}': {}", key_path, e))?,
+        None => cert.clone(),
+    };
+
+    reqwest::Identity::from_pkcs8_pem(&cert, &key).map_err(|e| {
+        anyhow::anyhow!(
+            "Invalid client certificate '{}': expected a PEM certificate and a PKCS#8 PEM private key ({})",
+            cert_path,
+            e
+        )
+    })
+}
+
+// Streams the body in chunks so the bar moves as hyper takes them off the wire
+fn upload_stream(
+    body: &[u8],
+    progress: indicatif::ProgressBar,
+) -> impl futures::Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static {
+    let chunks: Vec<Vec<u8>> = body
+        .chunks(UPLOAD_CHUNK_SIZE)
+        .map(|chunk| chunk.to_vec())
+        .collect();
+    futures::stream::iter(chunks.into_iter().map(move |chunk| {
+        progress.inc(chunk.len() as u64);
+        if progress.position() >= progress.length().unwrap_or(0) {
+            progress.finish_and_clear();
+        }
+        Ok(chunk)
+    }))
+}
+
+// Reads stdin as it's sent, one chunk at a time, so a large pipe is never held in memory
+fn stdin_stream() -> impl futures::Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static {
+    futures::stream::try_unfold(tokio::io::stdin(), |mut stdin| async move {
+        let mut chunk = vec![0; UPLOAD_CHUNK_SIZE];
+        let read = tokio::io::AsyncReadExt::read(&mut stdin, &mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        chunk.truncate(read);
+        Ok(Some((chunk, stdin)))
+    })
+}
+
+// Reads the body chunk by chunk, failing if the server goes quiet for longer than `read_timeout`
+async fn read_body(
+    mut response: ReqwestResponse,
+    read_timeout: Duration,
+    max_size: Option<u64>,
+    show_progress: bool,
+) -> Result<(String, usize)> {
+    // Refuse up front when the server announces the size, before downloading anything
+    if let (Some(limit), Some(length)) = (max_size, response.content_length())
+        && length > limit
+    {
+        return Err(too_large(limit));
+    }
+
+    let progress = match response.content_length() {
+        Some(length) if show_progress && length >= PROGRESS_THRESHOLD => {
+            let progress = crate::utils::create_progress_bar(length);
+            progress.set_message("downloading");
+            Some(progress)
+        }
+        _ => None,
+    };
+    let result = read_chunks(&mut response, read_timeout, max_size, progress.as_ref()).await;
+    if let Some(progress) = progress {
+        progress.finish_and_clear();
+    }
+    result
+}
+
+// The download loop of `read_body`, advancing the bar if there is one
+async fn read_chunks(
+    response: &mut ReqwestResponse,
+    read_timeout: Duration,
+    max_size: Option<u64>,
+    progress: Option<&indicatif::ProgressBar>,
+) -> Result<(String, usize)> {
+    let mut bytes = Vec::new();
+    loop {
+        let chunk = timeout(read_timeout, response.chunk())
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "No data received for {}s while reading the response",
+                    read_timeout.as_secs()
+                )
+            })??;
+        match chunk {
+            Some(chunk) => {
+                bytes.extend_from_slice(&chunk);
+                if let Some(progress) = progress {
+                    progress.inc(chunk.len() as u64);
+                }
+                if let Some(limit) = max_size
+                    && bytes.len() as u64 > limit
+                {
+                    return Err(too_large(limit));
+                }
+            }
+            None => break,
+        }
+    }
+
+    Ok((String::from_utf8_lossy(&bytes).into_owned(), bytes.len()))
+}
+
+// The status line and headers as they arrived, with their CRLFs and the blank line after them
+fn response_head_size(response: &ReqwestResponse) -> usize {
+    let status_line = format!("{:?} {}", response.version(), response.status()).len() + 2;
+    let headers: usize = response
+        .headers()
+        .iter()
+        .map(|(name, value)| name.as_str().len() + 2 + value.len() + 2)
+        .sum();
+    status_line + headers + 2
+}
+
+fn too_large(limit: u64) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Response exceeded max size of {} ({} bytes)",
+        crate::utils::format_bytes(limit as usize),
+        limit
+    )
+}
+
+fn resolve_target(entry: &ConnectTo) -> Result<std::net::SocketAddr> {
+    use std::net::ToSocketAddrs;
+
+    (entry.target_host.as_str(), entry.target_port)
+        .to_socket_addrs()
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Could not resolve --connect-to target '{}': {}",
+                entry.target_host,
+                e
+            )
+        })?
+        .next()
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "--connect-to target '{}' has no addresses",
+                entry.target_host
+            )
+        })
+}
+
+fn connects(entry: &ConnectTo, url: &reqwest::Url) -> bool {
+    url.host_str()
+        .is_some_and(|host| host.eq_ignore_ascii_case(&entry.host))
+        && url.port_or_known_default() == Some(entry.port)
+}
+
+// The URL to dial for a --connect-to match: same host, so TLS and cookies still see it,
+// but the target's port. Also returns the Host header that keeps the original port.
+fn connect_target(entries: &[ConnectTo], url: &reqwest::Url) -> (reqwest::Url, Option<String>) {
+    let host = url.host_str().unwrap_or("").to_lowercase();
+    let port = url.port_or_known_default();
+    let entry = entries.iter().find(|entry| connects(entry, url));
+
+    match entry {
+        Some(entry) if Some(entry.target_port) != port => {
+            let mut target = url.clone();
+            let _ = target.set_port(Some(entry.target_port));
+            let authority = match url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host,
+            };
+            (target, Some(authority))
+        }
+        _ => (url.clone(), None),
+    }
+}
+
+// Picks the strongest digest challenge the server offered. Ones we can't use are skipped,
+// and the first error is only reported when no Digest challenge is left to answer.
+fn digest_challenge(response: &ReqwestResponse) -> Result<Option<DigestChallenge>> {
+    let mut challenges = Vec::new();
+    let mut first_error = None;
+    for value in response
+        .headers()
+        .get_all(reqwest::header::WWW_AUTHENTICATE)
+    {
+        match DigestChallenge::parse(value.to_str().unwrap_or("")) {
+            Ok(Some(challenge)) => challenges.push(challenge),
+            Ok(None) => {}
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if challenges.is_empty()
+        && let Some(e) = first_error
+    {
+        return Err(e);
+    }
+
+    challenges.sort_by_key(|c| {
+        !matches!(
+            c.algorithm,
+            crate::auth::DigestAlgorithm::Sha256 | crate::auth::DigestAlgorithm::Sha256Sess
+        )
+    });
+    Ok(challenges.into_iter().next())
+}
+
 impl Response {
     pub fn is_success(&self) -> bool {
         self.status >= 200 && self.status < 300
@@ -147,6 +1228,15 @@ impl Response {
         self.headers.get("content-type")
     }
 
+    /// Where a 3xx response points
+    pub fn redirect_location(&self) -> Option<&String> {
+        if (300..400).contains(&self.status) {
+            self.headers.get("location")
+        } else {
+            None
+        }
+    }
+
     pub fn is_json(&self) -> bool {
         self.content_type()
             .map(|ct| ct.contains("application/json"))
@@ -159,6 +1249,12 @@ impl Response {
             .unwrap_or(false)
     }
 
+    pub fn is_form_urlencoded(&self) -> bool {
+        self.content_type()
+            .map(|ct| ct.contains("application/x-www-form-urlencoded"))
+            .unwrap_or(false)
+    }
+
     pub fn is_html(&self) -> bool {
         self.content_type()
             .map(|ct| ct.contains("text/html"))
@@ -183,3 +1279,101 @@ impl Response {
         crate::utils::format_duration(self.duration)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_request_adds_client_headers() {
+        let request = Client::new()
+            .post("http://example.com:8080/items?page=2")
+            .header("X-Trace", "1")
+            .body(r#"{"a":1}"#)
+            .build()
+            .unwrap();
+        let wire = WireRequest::capture(&request, "terzi/test");
+
+        assert_eq!(wire.version(), "HTTP/1.1");
+        assert_eq!(
+            wire.dump(),
+            concat!(
+                "> POST /items?page=2 HTTP/1.1\n",
+                "> host: example.com:8080\n",
+                "> x-trace: 1\n",
+                "> user-agent: terzi/test\n",
+                "> accept: */*\n",
+                "> content-length: 7\n",
+                ">\n",
+                "{\"a\":1}\n",
+            )
+        );
+        assert_eq!(wire.body_size, 7);
+        // Each head line plus CRLF, then the blank line
+        let head = "POST /items?page=2 HTTP/1.1\r\nhost: example.com:8080\r\nx-trace: 1\r\nuser-agent: terzi/test\r\naccept: */*\r\ncontent-length: 7\r\n\r\n";
+        assert_eq!(wire.head_size(), head.len());
+    }
+
+    #[tokio::test]
+    async fn test_upload_stream_advances_progress() {
+        use futures::StreamExt;
+
+        let body = vec![7u8; UPLOAD_CHUNK_SIZE * 2 + 10];
+        let progress = indicatif::ProgressBar::hidden();
+        progress.set_length(body.len() as u64);
+
+        let chunks: Vec<Vec<u8>> = upload_stream(&body, progress.clone())
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), body);
+        assert_eq!(progress.position(), body.len() as u64);
+        assert!(progress.is_finished());
+    }
+
+    #[test]
+    fn test_next_link_and_page_items() {
+        let header = r#"<https://api.example.com/items?page=1>; rel="prev", <https://api.example.com/items?page=3>; rel="next last""#;
+        assert_eq!(
+            next_link(header).as_deref(),
+            Some("https://api.example.com/items?page=3")
+        );
+        assert!(next_link(r#"<https://api.example.com/items?page=1>; rel="prev""#).is_none());
+
+        let items = page_items(serde_json::json!({"next_cursor": "abc", "data": [1, 2]}));
+        assert_eq!(items, vec![serde_json::json!(1), serde_json::json!(2)]);
+        let items = page_items(serde_json::json!({"errors": [], "results": [4]}));
+        assert_eq!(items, vec![serde_json::json!(4)]);
+        let items = page_items(serde_json::json!({"events": [5], "next": null}));
+        assert_eq!(items, vec![serde_json::json!(5)]);
+        // Two arrays and no well-known field: the page is kept whole
+        let page = serde_json::json!({"tags": ["a"], "users": [6]});
+        assert_eq!(page_items(page.clone()), vec![page]);
+        assert_eq!(
+            page_items(serde_json::json!([3])),
+            vec![serde_json::json!(3)]
+        );
+    }
+
+    #[test]
+    fn test_connect_target_swaps_port_and_keeps_host() {
+        let entries = vec![ConnectTo {
+            host: "api.example.com".to_string(),
+            port: 443,
+            target_host: "backend-2.internal".to_string(),
+            target_port: 8443,
+        }];
+
+        let url = reqwest::Url::parse("https://API.example.com/health").unwrap();
+        let (target, host) = connect_target(&entries, &url);
+        assert_eq!(target.as_str(), "https://api.example.com:8443/health");
+        assert_eq!(host.as_deref(), Some("api.example.com"));
+
+        // Other ports of the host are left alone
+        let url = reqwest::Url::parse("http://api.example.com/health").unwrap();
+        let (target, host) = connect_target(&entries, &url);
+        assert_eq!(target, url);
+        assert!(host.is_none());
+    }
+}
@@ -0,0 +1,206 @@
+use clap_complete::Shell;
+use std::io::Write;
+
+use crate::config::Config;
+use crate::storage::Storage;
+
+// clap emits static scripts; these additions call `terzi __complete <kind>` for
+// values that live in storage or config
+
+/// Prints one candidate per line. Runs on every Tab press, so it only reads
+/// and prints nothing rather than failing.
+pub async fn print_candidates(kind: Option<&str>) {
+    let candidates: Vec<String> = match kind {
+        Some("requests") => match Storage::new().await {
+            Ok(storage) => storage
+                .list_requests(None)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|request| request.name)
+                .collect(),
+            Err(_) => Vec::new(),
+        },
+        Some("collections") => match Storage::new().await {
+            Ok(storage) => storage
+                .list_collections()
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|collection| collection.name)
+                .collect(),
+            Err(_) => Vec::new(),
+        },
+        Some("environments") => match Storage::new().await {
+            Ok(storage) => storage.list_environments().await.unwrap_or_default(),
+            Err(_) => Vec::new(),
+        },
+        Some("config-keys") => Config::list_all_keys()
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut out = std::io::stdout().lock();
+    for candidate in candidates {
+        let _ = writeln!(out, "{}", candidate);
+    }
+}
+
+pub fn generate(shell: Shell, mut command: clap::Command, out: &mut dyn Write) {
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, "terzi", &mut script);
+    let script = String::from_utf8_lossy(&script);
+
+    let script = match shell {
+        Shell::Bash => format!("{}{}", script, BASH_DYNAMIC),
+        Shell::Fish => format!("{}{}", script, FISH_DYNAMIC),
+        Shell::Zsh => zsh_dynamic(&script),
+        // PowerShell and Elvish only get the static script
+        _ => script.into_owned(),
+    };
+    let _ = out.write_all(script.as_bytes());
+}
+
+const BASH_DYNAMIC: &str = r#"
+_terzi_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local kind=""
+
+    if [[ "${prev}" == "--load" ]]; then
+        kind="requests"
+    elif [[ "${prev}" == "--env" || "${prev}" == "-e" ]]; then
+        kind="environments"
+    elif [[ ${COMP_CWORD} -eq 2 ]]; then
+        case "${COMP_WORDS[1]}" in
+            show|delete|edit|duplicate|lint) kind="requests" ;;
+        esac
+    elif [[ ${COMP_CWORD} -eq 3 && "${COMP_WORDS[1]}" == "config" ]]; then
+        case "${COMP_WORDS[2]}" in
+            get|set|unset) kind="config-keys" ;;
+        esac
+    elif [[ ${COMP_CWORD} -eq 3 && "${COMP_WORDS[1]}" == "collection" ]]; then
+        case "${COMP_WORDS[2]}" in
+            add|show|run|delete) kind="collections" ;;
+        esac
+    elif [[ ${COMP_CWORD} -eq 4 && "${COMP_WORDS[1]}" == "collection" && "${COMP_WORDS[2]}" == "add" ]]; then
+        kind="requests"
+    elif [[ ${COMP_CWORD} -eq 3 && "${COMP_WORDS[1]}" == "env" ]]; then
+        case "${COMP_WORDS[2]}" in
+            show|set|delete) kind="environments" ;;
+        esac
+    fi
+    if [[ ${COMP_CWORD} -ge 2 && "${COMP_WORDS[1]}" == "run" ]]; then
+        kind="requests"
+    fi
+
+    if [[ -n "${kind}" && "${cur}" != -* ]]; then
+        local IFS=$'\n'
+        COMPREPLY=( $(compgen -W "$(terzi __complete "${kind}" 2>/dev/null)" -- "${cur}") )
+        return 0
+    fi
+    _terzi "$@"
+}
+
+complete -F _terzi_dynamic -o bashdefault -o default terzi
+"#;
+
+const FISH_DYNAMIC: &str = r#"
+complete -c terzi -n "__fish_terzi_needs_command" -l load -x -a "(terzi __complete requests 2>/dev/null)"
+complete -c terzi -n "__fish_terzi_using_subcommand show delete edit duplicate lint run" -f -a "(terzi __complete requests 2>/dev/null)"
+complete -c terzi -n "__fish_terzi_using_subcommand collection; and __fish_seen_subcommand_from add show run delete" -f -a "(terzi __complete collections 2>/dev/null)"
+complete -c terzi -l env -x -a "(terzi __complete environments 2>/dev/null)"
+complete -c terzi -n "__fish_terzi_using_subcommand env; and __fish_seen_subcommand_from show set delete" -f -a "(terzi __complete environments 2>/dev/null)"
+complete -c terzi -n "__fish_terzi_using_subcommand config; and __fish_seen_subcommand_from get set unset" -f -a "(terzi __complete config-keys 2>/dev/null)"
+"#;
+
+const ZSH_HELPERS: &str = r#"(( $+functions[_terzi_saved_requests] )) ||
+_terzi_saved_requests() {
+    local -a requests
+    requests=(${(f)"$(terzi __complete requests 2>/dev/null)"})
+    compadd -a requests
+}
+(( $+functions[_terzi_collections] )) ||
+_terzi_collections() {
+    local -a collections
+    collections=(${(f)"$(terzi __complete collections 2>/dev/null)"})
+    compadd -a collections
+}
+(( $+functions[_terzi_environments] )) ||
+_terzi_environments() {
+    local -a environments
+    environments=(${(f)"$(terzi __complete environments 2>/dev/null)"})
+    compadd -a environments
+}
+(( $+functions[_terzi_config_keys] )) ||
+_terzi_config_keys() {
+    local -a keys
+    keys=(${(f)"$(terzi __complete config-keys 2>/dev/null)"})
+    compadd -a keys
+}
+
+"#;
+
+// Swaps `_default` for the helpers on the arguments that take a request name or config key
+fn zsh_dynamic(script: &str) -> String {
+    let mut result = String::with_capacity(script.len() + ZSH_HELPERS.len());
+    for line in script.split_inclusive('\n') {
+        let helper = if line.contains("--load=[")
+            || line.contains(":name -- Name of the saved request")
+            || line.contains(":names -- Names of the saved requests")
+            || line.contains(":request -- Name of the saved request")
+        {
+            Some("_terzi_saved_requests")
+        } else if line.contains(":name -- Collection name")
+            || line.contains(":collection -- Collection name")
+        {
+            Some("_terzi_collections")
+        } else if line.contains("--env=[") || line.contains(":name -- Environment name") {
+            Some("_terzi_environments")
+        } else if line.contains(":key -- Configuration key") {
+            Some("_terzi_config_keys")
+        } else {
+            None
+        };
+
+        match helper {
+            Some(helper) if line.contains(":_default'") => {
+                result.push_str(&line.replace(":_default'", &format!(":{}'", helper)));
+            }
+            _ => {
+                if line.starts_with("if [ \"$funcstack[1]\" = \"_terzi\" ]") {
+                    result.push_str(ZSH_HELPERS);
+                }
+                result.push_str(line);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zsh_arguments_use_dynamic_helpers() {
+        let script = concat!(
+            "'--load=[Load saved request]:LOAD:_default' \\\n",
+            "':name -- Name of the saved request to delete:_default' \\\n",
+            "':key -- Configuration key:_default' \\\n",
+            "':collection -- Collection name:_default' \\\n",
+            "':name -- Profile name:_default' \\\n",
+            "if [ \"$funcstack[1]\" = \"_terzi\" ]; then\n",
+        );
+        let result = zsh_dynamic(script);
+
+        assert!(result.contains("'--load=[Load saved request]:LOAD:_terzi_saved_requests'"));
+        assert!(result.contains("to delete:_terzi_saved_requests'"));
+        assert!(result.contains("Configuration key:_terzi_config_keys'"));
+        assert!(result.contains("Collection name:_terzi_collections'"));
+        assert!(result.contains("':name -- Profile name:_default'"));
+        assert!(result.find("_terzi_saved_requests() {").unwrap() < result.find("if [").unwrap());
+    }
+}
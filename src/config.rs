@@ -2,8 +2,121 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 use tokio::fs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncReadExt;
+
+pub const OUTPUT_FORMATS: &[&str] = &[
+    "auto", "json", "yaml", "xml", "form", "table", "raw", "http",
+];
+
+// Set once at startup from `--config-dir`; takes precedence over `TERZI_CONFIG_DIR`
+static CONFIG_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn set_config_dir(dir: PathBuf) {
+    let _ = CONFIG_DIR_OVERRIDE.set(dir);
+}
+
+/// Directory holding `config.toml` and `data.json`.
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = CONFIG_DIR_OVERRIDE.get() {
+        return dir.clone();
+    }
+
+    if let Some(dir) = std::env::var_os("TERZI_CONFIG_DIR").filter(|d| !d.is_empty()) {
+        PathBuf::from(dir)
+    } else if let Some(config_dir) = dirs::config_dir() {
+        config_dir.join("terzi")
+    } else if let Some(home_dir) = dirs::home_dir() {
+        home_dir.join(".terzi")
+    } else {
+        PathBuf::from(".terzi")
+    }
+}
+
+// Set once at startup from `--profile`; takes precedence over `TERZI_PROFILE`
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+pub fn set_profile(name: String) {
+    let _ = PROFILE_OVERRIDE.set(name);
+}
+
+/// The selected profile, or `None` for the default files.
+pub fn active_profile() -> Option<String> {
+    PROFILE_OVERRIDE
+        .get()
+        .cloned()
+        .or_else(|| std::env::var("TERZI_PROFILE").ok())
+        .filter(|name| !name.is_empty() && name != "default")
+}
+
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid profile name '{}'. Use letters, digits, '-' or '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// File name for `stem.ext` in the given profile, e.g. `config.work.toml`.
+pub fn profile_file_name(stem: &str, ext: &str, profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) => format!("{}.{}.{}", stem, profile, ext),
+        None => format!("{}.{}", stem, ext),
+    }
+}
+
+/// Names of the profiles that have a config file, sorted.
+pub fn list_profiles() -> Result<Vec<String>> {
+    let dir = config_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut profiles: Vec<String> = std::fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix("config.")
+                .and_then(|rest| rest.strip_suffix(".toml"))
+                .filter(|profile| validate_profile_name(profile).is_ok())
+                .map(String::from)
+        })
+        .collect();
+    profiles.sort();
+    Ok(profiles)
+}
+
+// Empty or "none" clears an optional path setting
+fn optional_path(value: &str) -> Option<String> {
+    if value.is_empty() || value == "none" {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_auto_backups() -> usize {
+    10
+}
+
+fn default_warn_request_size() -> Option<u64> {
+    Some(10 * 1024 * 1024)
+}
+
+fn default_request_id_header() -> String {
+    "X-Request-Id".to_string()
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -22,6 +135,23 @@ pub struct GeneralConfig {
     pub max_history_entries: usize,
     pub auto_save_requests: bool,
     pub check_updates: bool,
+    /// Append a JSONL record of every request to this file
+    #[serde(default)]
+    pub log_file: Option<String>,
+    #[serde(default = "default_true")]
+    pub log_mask_secrets: bool,
+    /// Store secrets as `${TERZI_...}` placeholders and mask them in history
+    #[serde(default)]
+    pub redact_secrets: bool,
+    /// Back up saved data before commands that delete or replace it
+    #[serde(default = "default_true")]
+    pub auto_backup: bool,
+    /// Automatic backups kept; older ones are pruned
+    #[serde(default = "default_max_auto_backups")]
+    pub max_auto_backups: usize,
+    /// Shell command whose stdout replaces an expired stored token without a refresh token
+    #[serde(default)]
+    pub token_refresh_command: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +164,15 @@ pub struct OutputConfig {
     pub syntax_highlighting: bool,
     pub color_scheme: String,
     pub max_body_length: Option<usize>,
+    /// Progress bar on stderr for large uploads and downloads
+    #[serde(default = "default_true")]
+    pub show_progress: bool,
+    /// Print each request to stderr as it goes on the wire (set by `--verbose-request`)
+    #[serde(skip)]
+    pub dump_request: bool,
+    /// Keep each request as sent with its response, for `-o http`
+    #[serde(skip)]
+    pub capture_request: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +185,53 @@ pub struct NetworkConfig {
     pub max_redirects: u8,
     pub keep_alive: bool,
     pub compression: bool,
+    #[serde(default)]
+    pub client_cert_path: Option<String>,
+    #[serde(default)]
+    pub client_key_path: Option<String>,
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
+    /// Abort any response larger than this many bytes
+    #[serde(default)]
+    pub max_response_size: Option<u64>,
+    /// Warn before sending a request body larger than this many bytes
+    #[serde(default = "default_warn_request_size")]
+    pub warn_request_size: Option<u64>,
+    /// Revalidate repeated GET/HEAD requests with If-None-Match / If-Modified-Since
+    #[serde(default)]
+    pub enable_cache: bool,
+    /// Sent with every request unless it sets the same header or uses `--no-default-headers`
+    #[serde(default)]
+    pub default_headers: HashMap<String, String>,
+    /// Add a generated correlation ID to every request sent from the command line
+    #[serde(default)]
+    pub inject_request_id: bool,
+    /// Header that carries the request ID
+    #[serde(default = "default_request_id_header")]
+    pub request_id_header: String,
+    /// Ignore both `proxy_url` and the proxy environment variables (set by `--no-proxy`)
+    #[serde(skip)]
+    pub bypass_proxy: bool,
+    /// Route requests over this Unix domain socket (set by `--unix-socket`)
+    #[serde(skip)]
+    pub unix_socket: Option<String>,
+    /// Fixed addresses per host name and port (set by `--resolve`)
+    #[serde(skip)]
+    pub resolve: Vec<(String, std::net::SocketAddr)>,
+    /// Requests for one host and port connect to another (set by `--connect-to`)
+    #[serde(skip)]
+    pub connect_to: Vec<ConnectTo>,
+    /// Local address to connect from; its family decides IPv4 or IPv6 (set by `--ipv4`/`--ipv6`)
+    #[serde(skip)]
+    pub local_address: Option<std::net::IpAddr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectTo {
+    pub host: String,
+    pub port: u16,
+    pub target_host: String,
+    pub target_port: u16,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +239,9 @@ pub struct AuthConfig {
     pub default_auth_type: Option<String>,
     pub stored_tokens: HashMap<String, StoredToken>,
     pub auto_refresh_tokens: bool,
+    /// Bearer token file read on every send (set by `--auth-token-file`)
+    #[serde(skip)]
+    pub token_file: Option<std::path::PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +251,22 @@ pub struct StoredToken {
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
     pub refresh_token: Option<String>,
     pub scopes: Vec<String>,
+    // Where and how the token was obtained, so it can be refreshed
+    #[serde(default)]
+    pub token_url: Option<String>,
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+}
+
+impl StoredToken {
+    pub fn is_expired(&self) -> bool {
+        // Leave a little headroom so the token doesn't expire mid-request
+        let cutoff = chrono::Utc::now() + chrono::Duration::seconds(30);
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= cutoff)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,6 +290,12 @@ impl Default for Config {
                 max_history_entries: 1000,
                 auto_save_requests: false,
                 check_updates: true,
+                log_file: None,
+                log_mask_secrets: true,
+                redact_secrets: false,
+                auto_backup: true,
+                max_auto_backups: default_max_auto_backups(),
+                token_refresh_command: None,
             },
             output: OutputConfig {
                 default_format: "auto".to_string(),
@@ -95,6 +306,9 @@ impl Default for Config {
                 syntax_highlighting: true,
                 color_scheme: "dark".to_string(),
                 max_body_length: Some(10_000),
+                show_progress: true,
+                dump_request: false,
+                capture_request: false,
             },
             network: NetworkConfig {
                 user_agent: format!("terzi/{}", env!("CARGO_PKG_VERSION")),
@@ -105,11 +319,26 @@ impl Default for Config {
                 max_redirects: 10,
                 keep_alive: true,
                 compression: true,
+                client_cert_path: None,
+                client_key_path: None,
+                ca_cert_path: None,
+                max_response_size: None,
+                warn_request_size: default_warn_request_size(),
+                enable_cache: false,
+                default_headers: HashMap::new(),
+                inject_request_id: false,
+                request_id_header: default_request_id_header(),
+                bypass_proxy: false,
+                unix_socket: None,
+                resolve: Vec::new(),
+                connect_to: Vec::new(),
+                local_address: None,
             },
             auth: AuthConfig {
                 default_auth_type: None,
                 stored_tokens: HashMap::new(),
                 auto_refresh_tokens: true,
+                token_file: None,
             },
             ui: UiConfig {
                 theme: "default".to_string(),
@@ -128,14 +357,39 @@ impl Config {
     pub async fn load() -> Result<Self> {
         let config_path = Self::get_config_path()?;
 
+        if let Some(profile) = active_profile() {
+            validate_profile_name(&profile)?;
+            if !config_path.exists() {
+                return Err(anyhow::anyhow!(
+                    "Profile '{}' does not exist. Create it with 'terzi profile create {}'",
+                    profile,
+                    profile
+                ));
+            }
+        }
+
         if config_path.exists() {
             let mut file = fs::File::open(&config_path).await?;
             let mut contents = String::new();
             file.read_to_string(&mut contents).await?;
 
-            let config: Config = toml::from_str(&contents).unwrap_or_else(|_| Config::default());
+            match toml::from_str::<Config>(&contents) {
+                Ok(config) => Ok(config),
+                Err(e) => {
+                    // Keep the user's file so a typo doesn't cost them their settings
+                    let backup = crate::utils::move_aside_corrupt_file(&config_path)?;
+                    crate::cli::print_stderr_warning(&format!(
+                        "Could not parse {}: {}\nMoved it to {} and continuing with default settings.",
+                        config_path.display(),
+                        e.to_string().trim_end(),
+                        backup.display()
+                    ));
 
-            Ok(config)
+                    let config = Config::default();
+                    config.save().await?;
+                    Ok(config)
+                }
+            }
         } else {
             let config = Config::default();
             config.save().await?;
@@ -144,8 +398,10 @@ impl Config {
     }
 
     pub async fn save(&self) -> Result<()> {
-        let config_path = Self::get_config_path()?;
+        self.save_to(&Self::get_config_path()?).await
+    }
 
+    pub async fn save_to(&self, config_path: &std::path::Path) -> Result<()> {
         // Create config directory if it doesn't exist
         if let Some(parent) = config_path.parent() {
             if !parent.exists() {
@@ -154,22 +410,15 @@ impl Config {
         }
 
         let contents = toml::to_string_pretty(self)?;
-
-        let mut file = fs::File::create(&config_path).await?;
-        file.write_all(contents.as_bytes()).await?;
-        file.flush().await?;
-
-        Ok(())
+        crate::utils::write_atomic(config_path, contents.as_bytes()).await
     }
 
     fn get_config_path() -> Result<PathBuf> {
-        if let Some(config_dir) = dirs::config_dir() {
-            Ok(config_dir.join("terzi").join("config.toml"))
-        } else if let Some(home_dir) = dirs::home_dir() {
-            Ok(home_dir.join(".terzi").join("config.toml"))
-        } else {
-            Ok(PathBuf::from(".terzi/config.toml"))
-        }
+        Ok(Self::profile_config_path(active_profile().as_deref()))
+    }
+
+    pub fn profile_config_path(profile: Option<&str>) -> PathBuf {
+        config_dir().join(profile_file_name("config", "toml", profile))
     }
 
     pub async fn get_value(&self, key: &str) -> Option<String> {
@@ -180,6 +429,12 @@ impl Config {
             "general.max_history_entries" => Some(self.general.max_history_entries.to_string()),
             "general.auto_save_requests" => Some(self.general.auto_save_requests.to_string()),
             "general.check_updates" => Some(self.general.check_updates.to_string()),
+            "general.log_file" => self.general.log_file.clone(),
+            "general.log_mask_secrets" => Some(self.general.log_mask_secrets.to_string()),
+            "general.redact_secrets" => Some(self.general.redact_secrets.to_string()),
+            "general.auto_backup" => Some(self.general.auto_backup.to_string()),
+            "general.max_auto_backups" => Some(self.general.max_auto_backups.to_string()),
+            "general.token_refresh_command" => self.general.token_refresh_command.clone(),
 
             "output.default_format" => Some(self.output.default_format.clone()),
             "output.pretty_print" => Some(self.output.pretty_print.to_string()),
@@ -189,6 +444,7 @@ impl Config {
             "output.syntax_highlighting" => Some(self.output.syntax_highlighting.to_string()),
             "output.color_scheme" => Some(self.output.color_scheme.clone()),
             "output.max_body_length" => self.output.max_body_length.map(|v| v.to_string()),
+            "output.show_progress" => Some(self.output.show_progress.to_string()),
 
             "network.user_agent" => Some(self.network.user_agent.clone()),
             "network.proxy_url" => self.network.proxy_url.clone(),
@@ -198,6 +454,24 @@ impl Config {
             "network.max_redirects" => Some(self.network.max_redirects.to_string()),
             "network.keep_alive" => Some(self.network.keep_alive.to_string()),
             "network.compression" => Some(self.network.compression.to_string()),
+            "network.client_cert_path" => self.network.client_cert_path.clone(),
+            "network.client_key_path" => self.network.client_key_path.clone(),
+            "network.ca_cert_path" => self.network.ca_cert_path.clone(),
+            "network.max_response_size" => self.network.max_response_size.map(|v| v.to_string()),
+            "network.warn_request_size" => self.network.warn_request_size.map(|v| v.to_string()),
+            "network.enable_cache" => Some(self.network.enable_cache.to_string()),
+            "network.default_headers" => {
+                let mut headers: Vec<String> = self
+                    .network
+                    .default_headers
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect();
+                headers.sort();
+                Some(headers.join("; "))
+            }
+            "network.inject_request_id" => Some(self.network.inject_request_id.to_string()),
+            "network.request_id_header" => Some(self.network.request_id_header.clone()),
 
             "ui.theme" => Some(self.ui.theme.clone()),
             "ui.editor" => Some(self.ui.editor.clone()),
@@ -245,15 +519,40 @@ impl Config {
                     .parse()
                     .map_err(|_| anyhow::anyhow!("Invalid boolean value"))?;
             }
+            "general.log_file" => {
+                self.general.log_file = optional_path(value);
+            }
+            "general.log_mask_secrets" => {
+                self.general.log_mask_secrets = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value"))?;
+            }
+            "general.redact_secrets" => {
+                self.general.redact_secrets = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value"))?;
+            }
+            "general.auto_backup" => {
+                self.general.auto_backup = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value"))?;
+            }
+            "general.max_auto_backups" => {
+                self.general.max_auto_backups = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid number value"))?;
+            }
+            "general.token_refresh_command" => {
+                self.general.token_refresh_command = optional_path(value);
+            }
 
             "output.default_format" => {
-                let valid_formats = ["auto", "json", "yaml", "table", "raw"];
-                if valid_formats.contains(&value) {
+                if OUTPUT_FORMATS.contains(&value) {
                     self.output.default_format = value.to_string();
                 } else {
                     return Err(anyhow::anyhow!(
                         "Invalid format. Valid options: {}",
-                        valid_formats.join(", ")
+                        OUTPUT_FORMATS.join(", ")
                     ));
                 }
             }
@@ -304,6 +603,11 @@ impl Config {
                     );
                 }
             }
+            "output.show_progress" => {
+                self.output.show_progress = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value"))?;
+            }
 
             "network.user_agent" => {
                 self.network.user_agent = value.to_string();
@@ -347,6 +651,56 @@ impl Config {
                     .parse()
                     .map_err(|_| anyhow::anyhow!("Invalid boolean value"))?;
             }
+            "network.max_response_size" => {
+                if value == "none" || value.is_empty() {
+                    self.network.max_response_size = None;
+                } else {
+                    self.network.max_response_size = Some(crate::utils::parse_size(value)?);
+                }
+            }
+            "network.warn_request_size" => {
+                if value == "none" || value.is_empty() {
+                    self.network.warn_request_size = None;
+                } else {
+                    self.network.warn_request_size = Some(crate::utils::parse_size(value)?);
+                }
+            }
+            "network.enable_cache" => {
+                self.network.enable_cache = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value"))?;
+            }
+            "network.default_headers" => {
+                // "Name: value" pairs separated by ';', replacing the current set
+                let mut headers = HashMap::new();
+                if !(value.is_empty() || value == "none") {
+                    for header in value.split(';').filter(|h| !h.trim().is_empty()) {
+                        let (name, value) = crate::utils::parse_header(header)?;
+                        headers.insert(name, value);
+                    }
+                }
+                self.network.default_headers = headers;
+            }
+            "network.inject_request_id" => {
+                self.network.inject_request_id = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value"))?;
+            }
+            "network.request_id_header" => {
+                if !crate::utils::is_valid_header_name(value) {
+                    return Err(anyhow::anyhow!("Invalid header name: '{}'", value));
+                }
+                self.network.request_id_header = value.to_string();
+            }
+            "network.client_cert_path" => {
+                self.network.client_cert_path = optional_path(value);
+            }
+            "network.client_key_path" => {
+                self.network.client_key_path = optional_path(value);
+            }
+            "network.ca_cert_path" => {
+                self.network.ca_cert_path = optional_path(value);
+            }
 
             "ui.theme" => {
                 let valid_themes = ["default", "dark", "light", "minimal"];
@@ -401,6 +755,102 @@ impl Config {
         Ok(())
     }
 
+    /// Reverts one key to its default value and returns the new value.
+    pub async fn unset_value(&mut self, key: &str) -> Result<Option<String>> {
+        let defaults = Config::default();
+        match key {
+            "general.default_timeout" => {
+                self.general.default_timeout = defaults.general.default_timeout
+            }
+            "general.follow_redirects" => {
+                self.general.follow_redirects = defaults.general.follow_redirects
+            }
+            "general.save_history" => self.general.save_history = defaults.general.save_history,
+            "general.max_history_entries" => {
+                self.general.max_history_entries = defaults.general.max_history_entries
+            }
+            "general.auto_save_requests" => {
+                self.general.auto_save_requests = defaults.general.auto_save_requests
+            }
+            "general.check_updates" => self.general.check_updates = defaults.general.check_updates,
+            "general.log_file" => self.general.log_file = defaults.general.log_file,
+            "general.log_mask_secrets" => {
+                self.general.log_mask_secrets = defaults.general.log_mask_secrets
+            }
+            "general.redact_secrets" => {
+                self.general.redact_secrets = defaults.general.redact_secrets
+            }
+            "general.auto_backup" => self.general.auto_backup = defaults.general.auto_backup,
+            "general.max_auto_backups" => {
+                self.general.max_auto_backups = defaults.general.max_auto_backups
+            }
+            "general.token_refresh_command" => {
+                self.general.token_refresh_command = defaults.general.token_refresh_command
+            }
+            "output.default_format" => self.output.default_format = defaults.output.default_format,
+            "output.pretty_print" => self.output.pretty_print = defaults.output.pretty_print,
+            "output.show_headers" => self.output.show_headers = defaults.output.show_headers,
+            "output.show_timing" => self.output.show_timing = defaults.output.show_timing,
+            "output.show_size" => self.output.show_size = defaults.output.show_size,
+            "output.syntax_highlighting" => {
+                self.output.syntax_highlighting = defaults.output.syntax_highlighting
+            }
+            "output.color_scheme" => self.output.color_scheme = defaults.output.color_scheme,
+            "output.max_body_length" => {
+                self.output.max_body_length = defaults.output.max_body_length
+            }
+            "output.show_progress" => self.output.show_progress = defaults.output.show_progress,
+            "network.user_agent" => self.network.user_agent = defaults.network.user_agent,
+            "network.proxy_url" => self.network.proxy_url = defaults.network.proxy_url,
+            "network.verify_ssl" => self.network.verify_ssl = defaults.network.verify_ssl,
+            "network.connection_timeout" => {
+                self.network.connection_timeout = defaults.network.connection_timeout
+            }
+            "network.read_timeout" => self.network.read_timeout = defaults.network.read_timeout,
+            "network.max_redirects" => self.network.max_redirects = defaults.network.max_redirects,
+            "network.keep_alive" => self.network.keep_alive = defaults.network.keep_alive,
+            "network.compression" => self.network.compression = defaults.network.compression,
+            "network.client_cert_path" => {
+                self.network.client_cert_path = defaults.network.client_cert_path
+            }
+            "network.client_key_path" => {
+                self.network.client_key_path = defaults.network.client_key_path
+            }
+            "network.ca_cert_path" => self.network.ca_cert_path = defaults.network.ca_cert_path,
+            "network.max_response_size" => {
+                self.network.max_response_size = defaults.network.max_response_size
+            }
+            "network.warn_request_size" => {
+                self.network.warn_request_size = defaults.network.warn_request_size
+            }
+            "network.enable_cache" => self.network.enable_cache = defaults.network.enable_cache,
+            "network.default_headers" => {
+                self.network.default_headers = defaults.network.default_headers
+            }
+            "network.inject_request_id" => {
+                self.network.inject_request_id = defaults.network.inject_request_id
+            }
+            "network.request_id_header" => {
+                self.network.request_id_header = defaults.network.request_id_header
+            }
+            "ui.theme" => self.ui.theme = defaults.ui.theme,
+            "ui.editor" => self.ui.editor = defaults.ui.editor,
+            "ui.confirm_dangerous_operations" => {
+                self.ui.confirm_dangerous_operations = defaults.ui.confirm_dangerous_operations
+            }
+            "ui.show_welcome_message" => {
+                self.ui.show_welcome_message = defaults.ui.show_welcome_message
+            }
+            "ui.auto_complete" => self.ui.auto_complete = defaults.ui.auto_complete,
+            "ui.fuzzy_search" => self.ui.fuzzy_search = defaults.ui.fuzzy_search,
+            "ui.table_style" => self.ui.table_style = defaults.ui.table_style,
+            _ => return Err(anyhow::anyhow!("Unknown configuration key: {}", key)),
+        }
+
+        self.save().await?;
+        Ok(self.get_value(key).await)
+    }
+
     pub async fn reset_to_defaults(&mut self) -> Result<()> {
         *self = Config::default();
         self.save().await?;
@@ -415,6 +865,12 @@ impl Config {
             "general.max_history_entries",
             "general.auto_save_requests",
             "general.check_updates",
+            "general.log_file",
+            "general.log_mask_secrets",
+            "general.redact_secrets",
+            "general.auto_backup",
+            "general.max_auto_backups",
+            "general.token_refresh_command",
             "output.default_format",
             "output.pretty_print",
             "output.show_headers",
@@ -423,6 +879,7 @@ impl Config {
             "output.syntax_highlighting",
             "output.color_scheme",
             "output.max_body_length",
+            "output.show_progress",
             "network.user_agent",
             "network.proxy_url",
             "network.verify_ssl",
@@ -431,6 +888,15 @@ impl Config {
             "network.max_redirects",
             "network.keep_alive",
             "network.compression",
+            "network.client_cert_path",
+            "network.client_key_path",
+            "network.ca_cert_path",
+            "network.max_response_size",
+            "network.warn_request_size",
+            "network.enable_cache",
+            "network.default_headers",
+            "network.inject_request_id",
+            "network.request_id_header",
             "ui.theme",
             "ui.editor",
             "ui.confirm_dangerous_operations",
@@ -464,6 +930,127 @@ impl Config {
         self.auth.stored_tokens.keys().cloned().collect()
     }
 
+    /// Saves the current configuration, tokens included, under `backups/` so a
+    /// reset or import can be undone with `config import`.
+    pub async fn create_auto_backup(&self) -> Result<PathBuf> {
+        const PREFIX: &str = "terzi_config_autobackup";
+        let backup_dir = config_dir().join("backups");
+        fs::create_dir_all(&backup_dir).await?;
+
+        let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f");
+        let backup_file = backup_dir.join(format!("{}_{}.toml", PREFIX, timestamp));
+        crate::utils::write_atomic(&backup_file, self.export_toml(true)?.as_bytes()).await?;
+
+        crate::storage::prune_backups(&backup_dir, PREFIX, self.general.max_auto_backups).await?;
+        Ok(backup_file)
+    }
+
+    /// Serializes the config as TOML. Stored tokens are left out and sensitive default
+    /// headers masked unless asked for.
+    pub fn export_toml(&self, include_secrets: bool) -> Result<String> {
+        let mut config = self.clone();
+        if !include_secrets {
+            config.auth.stored_tokens.clear();
+            for (name, value) in config.network.default_headers.iter_mut() {
+                *value = crate::utils::mask_header_value(name, value);
+            }
+        }
+        Ok(toml::to_string_pretty(&config)?)
+    }
+
+    /// Serializes the whole config as JSON for tooling. Token values, refresh
+    /// tokens, client secrets and sensitive default headers are masked unless asked for.
+    pub fn export_json(&self, include_secrets: bool) -> Result<String> {
+        let mut config = self.clone();
+        if !include_secrets {
+            let mask = |value: &str| crate::utils::mask_sensitive_data(value, &[r".*"]);
+            for token in config.auth.stored_tokens.values_mut() {
+                token.value = mask(&token.value);
+                token.refresh_token = token.refresh_token.as_deref().map(mask);
+                token.client_secret = token.client_secret.as_deref().map(mask);
+            }
+            for (name, value) in config.network.default_headers.iter_mut() {
+                *value = crate::utils::mask_header_value(name, value);
+            }
+        }
+        Ok(serde_json::to_string_pretty(&config)?)
+    }
+
+    /// Parses and validates an exported config. Tokens already stored locally
+    /// are kept unless the file replaces them.
+    pub fn import_toml(&self, contents: &str) -> Result<Config> {
+        let mut config: Config = toml::from_str(contents)
+            .map_err(|e| anyhow::anyhow!("Invalid configuration: {}", e))?;
+        config
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Invalid configuration: {}", e))?;
+
+        for (name, token) in &self.auth.stored_tokens {
+            config
+                .auth
+                .stored_tokens
+                .entry(name.clone())
+                .or_insert_with(|| token.clone());
+        }
+        Ok(config)
+    }
+
+    /// Opens the config in `ui.editor` and returns the edited config if it changed.
+    /// Nothing is saved here; the caller persists the result.
+    pub fn edit_in_editor(&self) -> Result<Option<Config>> {
+        let original = toml::to_string_pretty(self)?;
+        let edited = self
+            .edit_text(&original, "toml")
+            .map_err(|e| anyhow::anyhow!("{}; configuration left unchanged", e))?;
+        if edited == original {
+            return Ok(None);
+        }
+
+        let config: Config =
+            toml::from_str(&edited).map_err(|e| anyhow::anyhow!("Invalid configuration: {}", e))?;
+        config
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Invalid configuration: {}", e))?;
+        Ok(Some(config))
+    }
+
+    /// Opens `initial` in `ui.editor` as a temporary `.{extension}` file and returns
+    /// the saved text.
+    pub fn edit_text(&self, initial: &str, extension: &str) -> Result<String> {
+        use std::io::Write;
+
+        // The text can hold tokens, so the file is created unguessable and owner-only (0600),
+        // and dropping it removes it however this returns
+        let mut temp_file = tempfile::Builder::new()
+            .prefix("terzi-edit-")
+            .suffix(&format!(".{}", extension))
+            .tempfile()?;
+        temp_file.write_all(initial.as_bytes())?;
+        temp_file.flush()?;
+
+        self.run_editor(temp_file.path())?;
+        Ok(std::fs::read_to_string(temp_file.path())?)
+    }
+
+    fn run_editor(&self, path: &std::path::Path) -> Result<()> {
+        // Editors are often configured with arguments, e.g. "code --wait"
+        let mut parts = self.ui.editor.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No editor configured. Set ui.editor or $EDITOR"))?;
+
+        let status = std::process::Command::new(program)
+            .args(parts)
+            .arg(path)
+            .status()
+            .map_err(|e| anyhow::anyhow!("Failed to launch editor '{}': {}", program, e))?;
+
+        if !status.success() {
+            return Err(anyhow::anyhow!("Editor exited with {}", status));
+        }
+        Ok(())
+    }
+
     // Validation helpers
     pub fn validate(&self) -> Result<()> {
         // Validate timeouts
@@ -497,6 +1084,12 @@ impl Config {
             ));
         }
 
+        if self.general.max_auto_backups == 0 {
+            return Err(anyhow::anyhow!(
+                "Max auto backups must be at least 1; set general.auto_backup to false to turn them off"
+            ));
+        }
+
         // Validate proxy URL if set
         if let Some(ref proxy_url) = self.network.proxy_url {
             url::Url::parse(proxy_url)
@@ -0,0 +1,282 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::client::TerziClient;
+use crate::config::{self, Config};
+use crate::storage::Storage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    pub detail: String,
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Runs every check against the files on disk, without the repairs a normal start makes.
+///
+/// Loading the config or data file normally moves a broken one aside, so this has to run
+/// before either is loaded.
+pub async fn run(url: &str) -> Vec<Check> {
+    let profile = config::active_profile();
+    let (config_check, config) = check_config(&Config::profile_config_path(profile.as_deref()));
+    let data_file = config::config_dir().join(Storage::data_file_name(profile.as_deref()));
+
+    vec![
+        config_check,
+        check_data_file(&data_file),
+        check_writable(&config::config_dir()),
+        check_editor(&config),
+        check_proxy(&config).await,
+        check_network(&config, url).await,
+    ]
+}
+
+fn check_config(path: &Path) -> (Check, Config) {
+    const NAME: &str = "Config file";
+    if !path.exists() {
+        let detail = format!("{} not created yet; defaults are used", path.display());
+        return (Check::pass(NAME, detail), Config::default());
+    }
+
+    let parsed = std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|contents| Ok(toml::from_str::<Config>(&contents)?));
+    match parsed {
+        Ok(config) => match config.validate() {
+            Ok(()) => (Check::pass(NAME, path.display().to_string()), config),
+            Err(e) => (
+                Check::fail(
+                    NAME,
+                    format!("{}: {}", path.display(), e),
+                    "Fix the value with 'terzi config set <key> <value>' or 'terzi config unset <key>'",
+                ),
+                config,
+            ),
+        },
+        Err(e) => (
+            Check::fail(
+                NAME,
+                format!(
+                    "{} does not parse: {}",
+                    path.display(),
+                    e.to_string().trim_end()
+                ),
+                "Fix the TOML by hand, or run 'terzi config reset' to start over. Until then every command moves it aside and uses defaults",
+            ),
+            Config::default(),
+        ),
+    }
+}
+
+fn check_data_file(path: &Path) -> Check {
+    const NAME: &str = "Data file";
+    if !path.exists() {
+        return Check::pass(NAME, format!("{} not created yet", path.display()));
+    }
+
+    match Storage::verify_data_file(path) {
+        Ok(count) => Check::pass(
+            NAME,
+            format!("{} ({} saved request(s))", path.display(), count),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{} does not parse: {}", path.display(), e),
+            "Fix the JSON or restore a backup with 'terzi backup restore'. Until then every command moves it aside and starts empty",
+        ),
+    }
+}
+
+fn check_writable(dir: &Path) -> Check {
+    const NAME: &str = "Data directory";
+    let probe = dir.join(format!(".terzi-doctor-{}", uuid::Uuid::new_v4()));
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe));
+
+    match result {
+        Ok(()) => Check::pass(NAME, format!("{} is writable", dir.display())),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{} is not writable: {}", dir.display(), e),
+            "Fix the directory's permissions, or point TERZI_CONFIG_DIR (or --config-dir) somewhere writable",
+        ),
+    }
+}
+
+fn check_editor(config: &Config) -> Check {
+    const NAME: &str = "Editor";
+    let hint = "Set $EDITOR or run 'terzi config set ui.editor <command>'";
+    let Some(program) = config.ui.editor.split_whitespace().next() else {
+        return Check::warn(NAME, "ui.editor is empty", hint);
+    };
+
+    match find_program(program) {
+        Some(path) => Check::pass(NAME, format!("{} ({})", config.ui.editor, path.display())),
+        None => Check::warn(
+            NAME,
+            format!(
+                "'{}' was not found; --edit-body and 'terzi edit' need it",
+                program
+            ),
+            hint,
+        ),
+    }
+}
+
+fn find_program(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| {
+            candidate.is_file() || (cfg!(windows) && candidate.with_extension("exe").is_file())
+        })
+}
+
+async fn check_proxy(config: &Config) -> Check {
+    const NAME: &str = "Proxy";
+    let Some(ref proxy) = config.network.proxy_url else {
+        return Check::pass(NAME, "none configured");
+    };
+
+    let hint = "Check network.proxy_url, or bypass it for one request with --no-proxy";
+    let url = match url::Url::parse(proxy) {
+        Ok(url) => url,
+        Err(e) => return Check::fail(NAME, format!("'{}' is not a valid URL: {}", proxy, e), hint),
+    };
+    let port = url.port_or_known_default().or(match url.scheme() {
+        "socks5" | "socks5h" => Some(1080),
+        _ => None,
+    });
+    let (Some(host), Some(port)) = (url.host_str(), port) else {
+        return Check::fail(NAME, format!("'{}' has no host and port", proxy), hint);
+    };
+
+    let connect_timeout = Duration::from_secs(config.network.connection_timeout);
+    match tokio::time::timeout(
+        connect_timeout,
+        tokio::net::TcpStream::connect((host, port)),
+    )
+    .await
+    {
+        Ok(Ok(_)) => Check::pass(NAME, format!("{}:{} accepts connections", host, port)),
+        Ok(Err(e)) => Check::fail(
+            NAME,
+            format!("{}:{} is unreachable: {}", host, port, e),
+            hint,
+        ),
+        Err(_) => Check::fail(
+            NAME,
+            format!(
+                "{}:{} did not answer within {}s",
+                host,
+                port,
+                connect_timeout.as_secs()
+            ),
+            hint,
+        ),
+    }
+}
+
+async fn check_network(config: &Config, url: &str) -> Check {
+    const NAME: &str = "Network";
+    let hint = "Check your connection, DNS and proxy settings, or try another host with --url";
+    let client = match TerziClient::new(config) {
+        Ok(client) => client,
+        Err(e) => return Check::fail(NAME, crate::utils::format_error_chain(&e), hint),
+    };
+
+    match client.test_connection(url).await {
+        Ok(probe) => Check::pass(
+            NAME,
+            format!(
+                "{} answered HTTP {} in {}",
+                url,
+                probe.status,
+                crate::utils::format_duration(probe.duration)
+            ),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{}: {}", url, crate::utils::format_error_chain(&e)),
+            hint,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_config_reports_parse_and_validation_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+
+        let (check, _) = check_config(&path);
+        assert_eq!(check.outcome, Outcome::Pass);
+
+        std::fs::write(&path, "[general\n").unwrap();
+        let (check, _) = check_config(&path);
+        assert_eq!(check.outcome, Outcome::Fail);
+        assert!(check.detail.contains("does not parse"));
+
+        let mut config = Config::default();
+        config.network.max_redirects = 99;
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+        let (check, _) = check_config(&path);
+        assert_eq!(check.outcome, Outcome::Fail);
+        assert!(check.detail.contains("Max redirects"));
+        // The file is only read, never moved aside
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_check_editor_warns_when_missing() {
+        let mut config = Config::default();
+        config.ui.editor = "terzi-no-such-editor --wait".to_string();
+        assert_eq!(check_editor(&config).outcome, Outcome::Warn);
+    }
+}
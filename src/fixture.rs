@@ -0,0 +1,84 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::client::Response;
+use crate::request::SavedRequest;
+
+// A recorded request and the response it got, for golden-file tests and replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub recorded_at: DateTime<Utc>,
+    pub request: SavedRequest,
+    pub response: Response,
+}
+
+impl Fixture {
+    /// Credentials in request headers are masked, since fixtures tend to get committed.
+    pub fn new(request: &SavedRequest, response: &Response) -> Self {
+        let mut request = request.clone();
+        for (name, value) in request.headers.iter_mut() {
+            *value = crate::utils::mask_header_value(name, value);
+        }
+
+        Self {
+            recorded_at: Utc::now(),
+            request,
+            response: response.clone(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read fixture '{}': {}", path.display(), e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Invalid fixture '{}': {}", path.display(), e))
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        crate::utils::write_atomic(path, contents.as_bytes()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::create_test_response;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_fixture_round_trip_masks_credentials() {
+        let mut request = SavedRequest::new(
+            "user".to_string(),
+            "https://api.example.com/users/1".to_string(),
+            "GET".to_string(),
+        );
+        request.add_header(
+            "Authorization".to_string(),
+            "Bearer secret-token".to_string(),
+        );
+        request.add_header("Accept".to_string(), "application/json".to_string());
+        let mut response = create_test_response();
+        response.status = 201;
+        response.headers =
+            HashMap::from([("content-type".to_string(), "application/json".to_string())]);
+        response.body = r#"{"id": 1}"#.to_string();
+        response.duration = Duration::from_millis(42);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("user.json");
+        Fixture::new(&request, &response).save(&path).await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("secret-token"));
+
+        let fixture = Fixture::load(&path).unwrap();
+        assert_eq!(fixture.request.headers["Accept"], "application/json");
+        assert_eq!(fixture.response.status, 201);
+        assert_eq!(fixture.response.body, r#"{"id": 1}"#);
+        assert_eq!(fixture.response.duration, Duration::from_millis(42));
+    }
+}
@@ -0,0 +1,196 @@
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+use crate::storage::HistoryEntry;
+
+// HTTP Archive 1.2 export of history entries, oldest first
+pub fn build_har(entries: &[HistoryEntry], mask_secrets: bool) -> Value {
+    let mut entries: Vec<&HistoryEntry> = entries
+        .iter()
+        .filter(|entry| entry.method != "WS")
+        .collect();
+    entries.sort_by_key(|entry| entry.timestamp);
+
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": {
+                "name": "terzi",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "entries": entries
+                .iter()
+                .map(|entry| har_entry(entry, mask_secrets))
+                .collect::<Vec<_>>(),
+        }
+    })
+}
+
+fn har_entry(entry: &HistoryEntry, mask_secrets: bool) -> Value {
+    let time = entry.duration_ms.unwrap_or(0);
+    let status = entry.response_status.unwrap_or(0);
+
+    let mut request = json!({
+        "method": entry.method,
+        "url": entry.url,
+        "httpVersion": "HTTP/1.1",
+        "cookies": [],
+        "headers": headers(&entry.request_headers, mask_secrets),
+        "queryString": query_string(&entry.url),
+        "headersSize": entry.request_header_size.map(|size| size as i64).unwrap_or(-1),
+        "bodySize": entry.request_size.map(|size| size as i64).unwrap_or(0),
+    });
+    if let Some(ref body) = entry.request_body {
+        request["postData"] = json!({
+            "mimeType": content_type(&entry.request_headers),
+            "text": if mask_secrets { crate::utils::mask_body(body) } else { body.clone() },
+        });
+    }
+
+    let response_size = entry.response_size.unwrap_or(0);
+    let mut content = json!({
+        "size": response_size,
+        "mimeType": content_type(&entry.response_headers),
+    });
+    if let Some(ref body) = entry.response_body {
+        content["text"] = Value::from(if mask_secrets {
+            crate::utils::mask_body(body)
+        } else {
+            body.clone()
+        });
+        if body.len() < response_size {
+            content["comment"] = Value::from(format!(
+                "Body truncated to {} in history",
+                crate::utils::format_bytes(body.len())
+            ));
+        }
+    }
+
+    let mut har = json!({
+        "startedDateTime": entry.timestamp.to_rfc3339(),
+        "time": time,
+        "request": request,
+        "response": {
+            "status": status,
+            "statusText": http::StatusCode::from_u16(status)
+                .ok()
+                .and_then(|code| code.canonical_reason())
+                .unwrap_or(""),
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": headers(&entry.response_headers, mask_secrets),
+            "content": content,
+            "redirectURL": "",
+            "headersSize": entry.response_header_size.map(|size| size as i64).unwrap_or(-1),
+            "bodySize": entry.response_size.map(|size| size as i64).unwrap_or(-1),
+        },
+        "cache": {},
+        // Only the total duration is recorded, so it is all attributed to waiting
+        "timings": {"send": 0, "wait": time, "receive": 0},
+    });
+    if let Some(ref error) = entry.error_message {
+        har["comment"] = Value::from(error.as_str());
+    }
+    har
+}
+
+fn headers(headers: &HashMap<String, String>, mask_secrets: bool) -> Vec<Value> {
+    let mut headers: Vec<(&String, &String)> = headers.iter().collect();
+    headers.sort();
+    headers
+        .into_iter()
+        .map(|(name, value)| {
+            let value = if mask_secrets {
+                crate::utils::mask_header_value(name, value)
+            } else {
+                value.clone()
+            };
+            json!({"name": name, "value": value})
+        })
+        .collect()
+}
+
+fn content_type(headers: &HashMap<String, String>) -> String {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default()
+}
+
+fn query_string(url: &str) -> Vec<Value> {
+    url::Url::parse(url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| json!({"name": name, "value": value}))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn entry(url: &str, minutes_ago: i64) -> HistoryEntry {
+        HistoryEntry {
+            id: url.to_string(),
+            timestamp: Utc::now() - Duration::minutes(minutes_ago),
+            method: "POST".to_string(),
+            url: url.to_string(),
+            response_status: Some(201),
+            duration_ms: Some(42),
+            response_size: Some(100),
+            request_headers: HashMap::from([(
+                "Authorization".to_string(),
+                "Bearer secret-token".to_string(),
+            )]),
+            request_body: Some(r#"{"password": "hunter2"}"#.to_string()),
+            response_headers: HashMap::from([(
+                "content-type".to_string(),
+                "application/json".to_string(),
+            )]),
+            response_body: Some(r#"{"token": "issued-secret"}"#.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_entries_are_chronological_and_masked() {
+        let history = vec![
+            entry("https://api.example.com/new?page=2", 1),
+            entry("https://api.example.com/old", 5),
+        ];
+        let har = build_har(&history, true);
+        let entries = har["log"]["entries"].as_array().unwrap();
+
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(entries[0]["request"]["url"], "https://api.example.com/old");
+        assert_eq!(
+            entries[1]["request"]["queryString"][0],
+            json!({"name": "page", "value": "2"})
+        );
+
+        let first = &entries[0];
+        assert_eq!(first["response"]["status"], 201);
+        assert_eq!(first["response"]["statusText"], "Created");
+        assert_eq!(first["timings"]["wait"], 42);
+        assert_eq!(first["response"]["content"]["mimeType"], "application/json");
+        // Only 26 of the 100 response bytes were kept
+        assert!(first["response"]["content"]["comment"].is_string());
+
+        let auth = first["request"]["headers"][0]["value"].as_str().unwrap();
+        assert!(!auth.contains("secret-token"));
+        let body = first["request"]["postData"]["text"].as_str().unwrap();
+        assert!(!body.contains("hunter2"));
+        let body = first["response"]["content"]["text"].as_str().unwrap();
+        assert!(!body.contains("issued-secret"));
+
+        let unmasked = build_har(&history, false);
+        assert_eq!(
+            unmasked["log"]["entries"][0]["request"]["headers"][0]["value"],
+            "Bearer secret-token"
+        );
+    }
+}
@@ -1,26 +1,92 @@
 use anyhow::Result;
 use clap::Parser;
 use colored::*;
-use dialoguer::{Confirm, FuzzySelect, Input, MultiSelect, Select, theme::ColorfulTheme};
+use dialoguer::{
+    BasicHistory, Confirm, FuzzySelect, Input, MultiSelect, Select, theme::ColorfulTheme,
+};
 use std::collections::HashMap;
 
-use crate::client::TerziClient;
-use crate::output::ResponseFormatter;
-use crate::request::{RequestBuilder, SavedRequest};
+use crate::client::{Response, TerziClient};
+use crate::config::{Config, OUTPUT_FORMATS};
+use crate::output::{DisplayOptions, ResponseFormatter};
+use crate::request::{
+    RequestBuilder, RequestTemplate, SavedRequest, TemplateVariable, VariableCandidate,
+    VariableType,
+};
 use crate::storage::Storage;
 
+// One REPL line: `[METHOD] URL [flags]`, with a subset of the direct-request flags
+#[derive(Parser, Debug)]
+#[command(name = "request", no_binary_name = true)]
+struct ReplLine {
+    /// HTTP method and URL, or just the URL for a GET
+    #[arg(required = true, num_args = 1..=2, value_name = "[METHOD] URL")]
+    target: Vec<String>,
+
+    /// Request headers (key:value format)
+    #[arg(short = 'H', long = "header", value_name = "HEADER")]
+    headers: Vec<String>,
+
+    /// JSON body
+    #[arg(short, long, conflicts_with_all = ["body", "form_data"])]
+    json: Option<String>,
+
+    /// Raw request body
+    #[arg(short, long, conflicts_with = "form_data")]
+    body: Option<String>,
+
+    /// Form data (key=value pairs)
+    #[arg(short, long = "form")]
+    form_data: Vec<String>,
+
+    /// Authorization header
+    #[arg(short = 'A', long)]
+    auth: Option<String>,
+
+    /// Include response headers in output
+    #[arg(short = 'i', long)]
+    include_headers: bool,
+
+    /// Output format for this response
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+const REPL_HELP: &str = "\
+  [METHOD] URL [flags]   Send a request; URL may be a path under the base URL
+                         Flags: -H, -j, -b, -f, -A, -i, -o (see 'request --help')
+  :base [URL]            Show or set the base URL
+  :help                  Show this help
+  :quit                  Leave the REPL (also 'exit')";
+
 pub struct InteractiveMode {
     client: TerziClient,
     storage: Storage,
     formatter: ResponseFormatter,
+    config: Config,
+    display: DisplayOptions,
+    // Session context: paths are resolved against the base URL and the headers
+    // are added to every request sent until terzi exits
+    base_url: Option<String>,
+    session_headers: HashMap<String, String>,
 }
 
 impl InteractiveMode {
-    pub fn new(client: TerziClient, storage: Storage, formatter: ResponseFormatter) -> Self {
+    pub fn new(
+        client: TerziClient,
+        storage: Storage,
+        formatter: ResponseFormatter,
+        config: Config,
+        display: DisplayOptions,
+    ) -> Self {
         Self {
             client,
             storage,
             formatter,
+            config,
+            display,
+            base_url: None,
+            session_headers: HashMap::new(),
         }
     }
 
@@ -54,8 +120,11 @@ impl InteractiveMode {
         let options = vec![
             "🚀 Create New Request",
             "📋 Load Saved Request",
+            "🧩 Use Template",
             "📚 Browse Request Collection",
             "🔍 Search History",
+            "⌨️  REPL",
+            "🧭 Session (base URL, headers)",
             "⚙️  Settings",
             "🚪 Exit",
         ];
@@ -69,31 +138,253 @@ impl InteractiveMode {
         match selection {
             0 => self.create_new_request().await?,
             1 => self.load_saved_request().await?,
-            2 => self.browse_collection().await?,
-            3 => self.search_history().await?,
-            4 => self.settings_menu().await?,
-            5 => return Ok(false),
+            2 => self.use_template().await?,
+            3 => self.browse_collection().await?,
+            4 => self.search_history().await?,
+            5 => self.run_repl(None).await?,
+            6 => self.session_menu()?,
+            7 => self.settings_menu().await?,
+            8 => return Ok(false),
             _ => unreachable!(),
         }
 
         Ok(true)
     }
 
+    /// Reads request lines until `:quit`, sending each with the same client so
+    /// connections stay open between requests. Up and down recall earlier lines.
+    pub async fn run_repl(&mut self, base_url: Option<String>) -> Result<()> {
+        if let Some(url) = base_url {
+            self.base_url = Some(url.trim_end_matches('/').to_string());
+        }
+        let mut history = BasicHistory::new().max_entries(200).no_duplicates(true);
+
+        println!("{}", "⌨️  Terzi REPL".bright_cyan().bold());
+        println!("{}", REPL_HELP.bright_black());
+        println!();
+
+        loop {
+            let prompt = match self.base_url {
+                Some(ref url) => url.clone(),
+                None => "terzi".to_string(),
+            };
+            let line: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt)
+                .allow_empty(true)
+                .history_with(&mut history)
+                .interact_text()?;
+            let line = line.trim();
+
+            match line {
+                "" => continue,
+                ":quit" | ":q" | "exit" | "quit" => break,
+                ":help" => println!("{}", REPL_HELP),
+                ":base" => match self.base_url {
+                    Some(ref url) => println!("{}", url),
+                    None => self.formatter.display_info("No base URL set"),
+                },
+                _ if line.starts_with(":base ") => {
+                    let url = line[":base ".len()..].trim();
+                    if crate::utils::is_valid_url(url) {
+                        self.base_url = Some(url.trim_end_matches('/').to_string());
+                    } else {
+                        self.formatter
+                            .display_error(&format!("Invalid URL: {}", url));
+                    }
+                }
+                _ => {
+                    if let Err(e) = self.run_repl_line(line).await {
+                        crate::cli::print_request_error(&e);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run_repl_line(&mut self, line: &str) -> Result<()> {
+        let words = shell_words::split(line)
+            .map_err(|e| anyhow::anyhow!("Could not parse the line: {}", e))?;
+        let line = match ReplLine::try_parse_from(words) {
+            Ok(line) => line,
+            Err(e) => {
+                // Covers `request --help` as well as typos
+                let _ = e.print();
+                return Ok(());
+            }
+        };
+
+        let request = self.build_repl_request(&line)?;
+        let mut display = self.display.clone();
+        display.include_headers |= line.include_headers;
+        if let Some(ref output) = line.output {
+            display.output = output.clone();
+        }
+
+        match self.client.execute_request(&request).await {
+            Ok(response) => {
+                self.storage.add_to_history(&request, &response).await?;
+                self.formatter.display_response(&response, &display).await?;
+            }
+            Err(e) => {
+                let error_chain = crate::utils::format_error_chain(&e);
+                self.storage
+                    .add_error_to_history(&request, &error_chain)
+                    .await?;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn build_repl_request(&self, line: &ReplLine) -> Result<SavedRequest> {
+        let (method, target) = match line.target.as_slice() {
+            [method, target] => (method.to_uppercase(), target.as_str()),
+            [target] => ("GET".to_string(), target.as_str()),
+            _ => unreachable!("clap allows one or two values"),
+        };
+        crate::utils::validate_method(&method)?;
+        let url = resolve_url(self.base_url.as_deref(), target)?;
+
+        let mut builder = RequestBuilder::new(&url, &method)?;
+        for header in &line.headers {
+            let (key, value) = crate::utils::parse_header(header)?;
+            builder = builder.header(&key, &value);
+        }
+        if let Some(ref auth) = line.auth {
+            builder = builder.auth(auth)?;
+        }
+        if let Some(ref json) = line.json {
+            builder = builder.json_body(json)?;
+        } else if let Some(ref body) = line.body {
+            builder = builder.raw_body(body);
+        } else if !line.form_data.is_empty() {
+            let mut form = Vec::new();
+            for pair in &line.form_data {
+                let (key, value) = pair.split_once('=').ok_or_else(|| {
+                    anyhow::anyhow!("Invalid form data format: '{}'. Use 'key=value'", pair)
+                })?;
+                form.push((key.to_string(), value.to_string()));
+            }
+            builder = builder.form_body(form)?;
+        }
+        builder = builder.follow_redirects(self.config.general.follow_redirects);
+
+        Ok(self.session_request(&builder.build()))
+    }
+
+    // The request's own headers win over the session's
+    fn session_request(&self, request: &SavedRequest) -> SavedRequest {
+        let mut request = request.clone();
+        request.headers = crate::utils::merge_headers(&self.session_headers, &request.headers);
+        request
+    }
+
+    fn session_menu(&mut self) -> Result<()> {
+        println!("{}", "\n🧭 Session".bright_cyan().bold());
+        println!(
+            "  {}: {}",
+            "Base URL".bright_cyan(),
+            self.base_url.as_deref().unwrap_or("(none)")
+        );
+        if self.session_headers.is_empty() {
+            println!("  {}: (none)", "Headers".bright_cyan());
+        } else {
+            println!("  {}:", "Headers".bright_cyan());
+            let mut headers: Vec<_> = self.session_headers.iter().collect();
+            headers.sort();
+            for (name, value) in headers {
+                println!(
+                    "    {}: {}",
+                    name,
+                    crate::utils::mask_header_value(name, value)
+                );
+            }
+        }
+        println!();
+
+        let options = vec![
+            "Set Base URL",
+            "Clear Base URL",
+            "Add Session Headers",
+            "Clear Session Headers",
+            "Back to Main Menu",
+        ];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Session")
+            .items(&options)
+            .interact()?;
+
+        match selection {
+            0 => {
+                let url: String = Input::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Base URL")
+                    .with_initial_text(
+                        self.base_url
+                            .clone()
+                            .unwrap_or_else(|| "https://".to_string()),
+                    )
+                    .validate_with(|input: &String| -> Result<(), &str> {
+                        if crate::utils::is_valid_url(input) {
+                            Ok(())
+                        } else {
+                            Err("Please enter a valid URL starting with http:// or https://")
+                        }
+                    })
+                    .interact_text()?;
+                let url = url.trim_end_matches('/').to_string();
+                self.formatter
+                    .display_success(&format!("Paths will be sent to {}", url));
+                self.base_url = Some(url);
+            }
+            1 => {
+                self.base_url = None;
+                self.formatter.display_success("Base URL cleared");
+            }
+            2 => {
+                let headers = self.prompt_headers()?;
+                let count = headers.len();
+                self.session_headers = crate::utils::merge_headers(&self.session_headers, &headers);
+                self.formatter.display_success(&format!(
+                    "{} header(s) will be added to every request this session",
+                    count
+                ));
+            }
+            3 => {
+                self.session_headers.clear();
+                self.formatter.display_success("Session headers cleared");
+            }
+            4 => {}
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
     async fn create_new_request(&mut self) -> Result<()> {
         println!("{}", "\n🛠️  Creating New Request".bright_cyan().bold());
 
-        // Get URL
-        let url: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt("Enter the URL")
-            .with_initial_text("https://")
-            .validate_with(|input: &String| -> Result<(), &str> {
-                if input.starts_with("http://") || input.starts_with("https://") {
-                    Ok(())
-                } else {
-                    Err("Please enter a valid URL starting with http:// or https://")
-                }
-            })
-            .interact_text()?;
+        // Get URL; with a session base URL a path is enough
+        let has_base = self.base_url.is_some();
+        let theme = ColorfulTheme::default();
+        let url: String = match self.base_url {
+            Some(ref base) => Input::with_theme(&theme)
+                .with_prompt(format!("Enter a path under {} or a full URL", base)),
+            None => Input::with_theme(&theme)
+                .with_prompt("Enter the URL")
+                .with_initial_text("https://"),
+        }
+        .validate_with(|input: &String| -> Result<(), &str> {
+            let full_url = input.starts_with("http://") || input.starts_with("https://");
+            if full_url || (has_base && !input.trim().is_empty()) {
+                Ok(())
+            } else {
+                Err("Please enter a valid URL starting with http:// or https://")
+            }
+        })
+        .interact_text()?;
+        let url = resolve_url(self.base_url.as_deref(), url.trim())?;
 
         // Get HTTP method
         let methods = vec!["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
@@ -137,7 +428,7 @@ impl InteractiveMode {
         }
 
         // Build the request
-        let request = builder.build();
+        let request = self.session_request(&builder.build());
 
         // Preview request
         self.preview_request(&request);
@@ -158,8 +449,7 @@ impl InteractiveMode {
 
                     // Display response
                     println!();
-                    let cli = crate::Cli::parse(); // Default CLI for formatting
-                    self.formatter.display_response(&response, &cli).await?;
+                    self.show_response(&response).await?;
 
                     // Ask to save request
                     if Confirm::with_theme(&ColorfulTheme::default())
@@ -170,32 +460,70 @@ impl InteractiveMode {
                         self.save_request_interactive(request).await?;
                     }
                 }
-                Err(e) => {
-                    self.formatter
-                        .display_error(&format!("Request failed: {}", e));
-                }
+                Err(e) => crate::cli::print_request_error(&e),
             }
         }
 
         Ok(())
     }
 
-    async fn add_headers(&mut self, mut builder: RequestBuilder) -> Result<RequestBuilder> {
+    async fn show_response(&self, response: &Response) -> Result<()> {
+        self.formatter
+            .display_response(response, &self.display)
+            .await?;
+
+        // Let the user re-render this response without changing their defaults
+        let mut options = self.display.clone();
+        while Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("View this response in another format?")
+            .default(false)
+            .interact()?
+        {
+            let current = OUTPUT_FORMATS
+                .iter()
+                .position(|f| *f == options.output)
+                .unwrap_or(0);
+            let format_index = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Output format")
+                .items(OUTPUT_FORMATS)
+                .default(current)
+                .interact()?;
+
+            options.output = OUTPUT_FORMATS[format_index].to_string();
+            println!();
+            self.formatter.display_response(response, &options).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn add_headers(&mut self, builder: RequestBuilder) -> Result<RequestBuilder> {
+        let headers = self.prompt_headers()?;
+        Ok(builder.headers(headers))
+    }
+
+    fn prompt_headers(&self) -> Result<HashMap<String, String>> {
+        let mut headers = HashMap::new();
+
         loop {
             let header_name: String = Input::with_theme(&ColorfulTheme::default())
                 .with_prompt("Header name (or press Enter to finish)")
                 .allow_empty(true)
+                .validate_with(|input: &String| -> Result<(), &str> {
+                    if input.is_empty() || crate::utils::is_valid_header_name(input) {
+                        Ok(())
+                    } else {
+                        Err("Header names must be ASCII and cannot contain ':' or newlines")
+                    }
+                })
                 .interact_text()?;
 
             if header_name.is_empty() {
                 break;
             }
 
-            let header_value: String = Input::with_theme(&ColorfulTheme::default())
-                .with_prompt(&format!("Value for '{}'", header_name))
-                .interact_text()?;
-
-            builder = builder.header(&header_name, &header_value);
+            let header_value = self.prompt_header_value(&header_name, None)?;
+            headers.insert(header_name, header_value);
 
             if !Confirm::with_theme(&ColorfulTheme::default())
                 .with_prompt("Add another header?")
@@ -206,13 +534,89 @@ impl InteractiveMode {
             }
         }
 
-        Ok(builder)
+        Ok(headers)
+    }
+
+    fn prompt_header_value(&self, name: &str, current: Option<&str>) -> Result<String> {
+        let theme = ColorfulTheme::default();
+        let mut input = Input::with_theme(&theme)
+            .with_prompt(format!("Value for '{}'", name))
+            .validate_with(|input: &String| -> Result<(), &str> {
+                if crate::utils::is_valid_header_value(input) {
+                    Ok(())
+                } else {
+                    Err("Header values cannot contain newlines")
+                }
+            });
+
+        if let Some(current) = current {
+            input = input.with_initial_text(current);
+        }
+
+        Ok(input.interact_text()?)
+    }
+
+    async fn edit_headers(&mut self, request: &mut SavedRequest) -> Result<()> {
+        let header_options = vec!["Add Headers", "Edit Header Value", "Delete Headers", "Done"];
+
+        loop {
+            let mut names: Vec<String> = request.headers.keys().cloned().collect();
+            names.sort();
+
+            let action = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Edit headers")
+                .items(&header_options)
+                .default(0)
+                .interact()?;
+
+            match action {
+                0 => {
+                    let headers = self.prompt_headers()?;
+                    request.headers.extend(headers);
+                }
+                1 | 2 if names.is_empty() => {
+                    self.formatter.display_info("This request has no headers");
+                }
+                1 => {
+                    let selection = Select::with_theme(&ColorfulTheme::default())
+                        .with_prompt("Select a header to edit")
+                        .items(&names)
+                        .interact()?;
+                    let name = &names[selection];
+                    let current = request.headers.get(name).cloned();
+                    let value = self.prompt_header_value(name, current.as_deref())?;
+                    request.headers.insert(name.clone(), value);
+                }
+                2 => {
+                    let items: Vec<String> = names
+                        .iter()
+                        .map(|name| format!("{}: {}", name, request.headers[name]))
+                        .collect();
+                    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+                        .with_prompt("Select headers to delete (space to toggle)")
+                        .items(&items)
+                        .interact()?;
+                    for index in &selected {
+                        request.headers.remove(&names[*index]);
+                    }
+                    if !selected.is_empty() {
+                        self.formatter
+                            .display_success(&format!("Removed {} header(s)", selected.len()));
+                    }
+                }
+                3 => break,
+                _ => unreachable!(),
+            }
+        }
+
+        Ok(())
     }
 
     async fn add_authentication(&mut self, mut builder: RequestBuilder) -> Result<RequestBuilder> {
         let auth_types = vec![
             "Bearer Token",
             "Basic Auth",
+            "Digest Auth",
             "API Key (Header)",
             "API Key (Query)",
         ];
@@ -241,6 +645,16 @@ impl InteractiveMode {
                 builder = builder.auth(&format!("basic:{}:{}", username, password))?;
             }
             2 => {
+                // Digest Auth
+                let username: String = Input::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Username")
+                    .interact_text()?;
+                let password: String = dialoguer::Password::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Password")
+                    .interact()?;
+                builder = builder.auth(&format!("digest:{}:{}", username, password))?;
+            }
+            3 => {
                 // API Key Header
                 let key_name: String = Input::with_theme(&ColorfulTheme::default())
                     .with_prompt("Header name")
@@ -251,7 +665,7 @@ impl InteractiveMode {
                     .interact_text()?;
                 builder = builder.header(&key_name, &key_value);
             }
-            3 => {
+            4 => {
                 // API Key Query
                 let key_name: String = Input::with_theme(&ColorfulTheme::default())
                     .with_prompt("Query parameter name")
@@ -273,7 +687,13 @@ impl InteractiveMode {
     }
 
     async fn add_body(&mut self, mut builder: RequestBuilder) -> Result<RequestBuilder> {
-        let body_types = vec!["JSON", "Form Data", "Raw Text", "File Upload"];
+        let body_types = vec![
+            "JSON",
+            "JSON (open editor)",
+            "Form Data",
+            "Raw Text",
+            "File Upload",
+        ];
 
         let body_type = Select::with_theme(&ColorfulTheme::default())
             .with_prompt("Select body type")
@@ -307,8 +727,35 @@ impl InteractiveMode {
                 }
             }
             1 => {
+                // JSON, written in ui.editor
+                let mut json_body = "{}".to_string();
+                loop {
+                    json_body = self.config.edit_text(&json_body, "json")?;
+                    match serde_json::from_str::<serde_json::Value>(&json_body) {
+                        Ok(_) => {
+                            builder = builder.json_body(json_body.trim_end())?;
+                            self.formatter
+                                .display_success("Valid JSON added to request");
+                            break;
+                        }
+                        Err(e) => {
+                            self.formatter
+                                .display_warning(&format!("Invalid JSON: {}", e));
+                            if !Confirm::with_theme(&ColorfulTheme::default())
+                                .with_prompt("Edit again?")
+                                .default(true)
+                                .interact()?
+                            {
+                                builder = builder.raw_body(json_body.trim_end());
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            2 => {
                 // Form Data
-                let mut form_data = HashMap::new();
+                let mut form_data = Vec::new();
 
                 loop {
                     let key: String = Input::with_theme(&ColorfulTheme::default())
@@ -324,7 +771,7 @@ impl InteractiveMode {
                         .with_prompt(&format!("Value for '{}'", key))
                         .interact_text()?;
 
-                    form_data.insert(key, value);
+                    form_data.push((key, value));
 
                     if !Confirm::with_theme(&ColorfulTheme::default())
                         .with_prompt("Add another field?")
@@ -337,14 +784,14 @@ impl InteractiveMode {
 
                 builder = builder.form_body(form_data)?;
             }
-            2 => {
+            3 => {
                 // Raw Text
                 let raw_body: String = Input::with_theme(&ColorfulTheme::default())
                     .with_prompt("Enter raw body content")
                     .interact_text()?;
                 builder = builder.raw_body(&raw_body);
             }
-            3 => {
+            4 => {
                 // File Upload
                 let file_path: String = Input::with_theme(&ColorfulTheme::default())
                     .with_prompt("Enter file path")
@@ -386,6 +833,23 @@ impl InteractiveMode {
 
         request.name = name.clone();
 
+        let candidates = RequestTemplate::detect_variables(&request);
+        if !candidates.is_empty() {
+            let names: Vec<&str> = candidates.iter().map(|c| c.name.as_str()).collect();
+            if Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "Found values that could be variables ({}). Save as a template instead?",
+                    names.join(", ")
+                ))
+                .default(false)
+                .interact()?
+            {
+                return self
+                    .save_template_interactive(name, request, candidates)
+                    .await;
+            }
+        }
+
         self.storage.save_request(&name, &request).await?;
         self.formatter
             .display_success(&format!("Request saved as '{}'", name));
@@ -393,6 +857,176 @@ impl InteractiveMode {
         Ok(())
     }
 
+    // Placeholders already in the request are always variables; literals are opt-in
+    async fn save_template_interactive(
+        &mut self,
+        name: String,
+        request: SavedRequest,
+        candidates: Vec<VariableCandidate>,
+    ) -> Result<()> {
+        if self.storage.get_template(&name).await?.is_some()
+            && !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Template '{}' already exists. Overwrite it?", name))
+                .default(false)
+                .interact()?
+        {
+            self.formatter.display_info("Template not saved");
+            return Ok(());
+        }
+
+        let (placeholders, literals): (Vec<_>, Vec<_>) =
+            candidates.into_iter().partition(|c| c.value.is_none());
+        let mut chosen = placeholders;
+        if !literals.is_empty() {
+            let items: Vec<String> = literals
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{} = {} → {{{{{}}}}}",
+                        c.location,
+                        c.value.as_deref().unwrap_or_default(),
+                        c.name
+                    )
+                })
+                .collect();
+            let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+                .with_prompt("Values to turn into variables (space to toggle)")
+                .items(&items)
+                .defaults(&vec![true; items.len()])
+                .interact()?;
+            chosen.extend(
+                literals
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, _)| selected.contains(i))
+                    .map(|(_, c)| c),
+            );
+        }
+
+        let mut variables = Vec::new();
+        for mut candidate in chosen {
+            let literal = candidate.value.is_some();
+            if literal {
+                candidate.name = Input::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("Variable name for {}", candidate.location))
+                    .with_initial_text(&candidate.name)
+                    .validate_with(|input: &String| -> Result<(), &str> {
+                        if !input.is_empty()
+                            && input
+                                .chars()
+                                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                        {
+                            Ok(())
+                        } else {
+                            Err("Use letters, digits, '_' or '-'")
+                        }
+                    })
+                    .interact_text()?;
+            }
+            println!("{}", format!("\n{{{{{}}}}}", candidate.name).bright_cyan());
+
+            let default: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Default value (empty for none)")
+                .with_initial_text(candidate.value.as_deref().unwrap_or_default())
+                .allow_empty(true)
+                .interact_text()?;
+            let required = Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Required?")
+                .default(!literal)
+                .interact()?;
+            let inferred = VariableType::infer(&default).to_string();
+            let types: Vec<String> = VariableType::ALL.iter().map(|t| t.to_string()).collect();
+            let type_index = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Type")
+                .items(&types)
+                .default(types.iter().position(|t| *t == inferred).unwrap_or(0))
+                .interact()?;
+
+            let variable = TemplateVariable {
+                name: candidate.name.clone(),
+                description: None,
+                default_value: (!default.is_empty()).then_some(default),
+                required,
+                variable_type: VariableType::ALL[type_index].clone(),
+            };
+            variables.push((candidate, variable));
+        }
+
+        let count = variables.len();
+        let template = RequestTemplate::from_request(name.clone(), &request, variables);
+        self.storage.save_template(template).await?;
+        self.formatter.display_success(&format!(
+            "Template '{}' saved with {} variable(s); send it from 'Use Template'",
+            name, count
+        ));
+
+        Ok(())
+    }
+
+    async fn use_template(&mut self) -> Result<()> {
+        let templates = self.storage.list_templates().await?;
+        if templates.is_empty() {
+            self.formatter.display_info(
+                "No templates yet. Save a request and choose to save it as a template",
+            );
+            return Ok(());
+        }
+
+        let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+        let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select a template")
+            .items(&names)
+            .interact()?;
+        let template = &templates[selection];
+
+        let mut variables: Vec<&TemplateVariable> = template.variables.values().collect();
+        variables.sort_by(|a, b| a.name.cmp(&b.name));
+        let theme = ColorfulTheme::default();
+        let mut values = HashMap::new();
+        for variable in variables {
+            let optional = !variable.required || variable.default_value.is_some();
+            let mut input = Input::<String>::with_theme(&theme)
+                .with_prompt(format!("{} ({})", variable.name, variable.variable_type))
+                .allow_empty(optional);
+            if let Some(ref default) = variable.default_value {
+                input = input.default(default.clone());
+            }
+            let value = input
+                .validate_with(|input: &String| -> Result<(), String> {
+                    if input.is_empty() || variable.variable_type.accepts(input) {
+                        Ok(())
+                    } else {
+                        Err(format!("Expected a {}", variable.variable_type))
+                    }
+                })
+                .interact_text()?;
+            // An optional variable left empty renders as nothing rather than as `{{name}}`
+            values.insert(variable.name.clone(), value);
+        }
+
+        let request = self.session_request(&template.render(None, values)?);
+        self.preview_request(&request);
+
+        if Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Execute this request?")
+            .default(true)
+            .interact()?
+        {
+            println!("{}", "🚀 Executing request...".bright_blue());
+            match self.client.execute_request(&request).await {
+                Ok(response) => {
+                    self.storage.add_to_history(&request, &response).await?;
+
+                    println!();
+                    self.show_response(&response).await?;
+                }
+                Err(e) => crate::cli::print_request_error(&e),
+            }
+        }
+
+        Ok(())
+    }
+
     async fn load_saved_request(&mut self) -> Result<()> {
         let requests = self.storage.list_requests(None).await?;
 
@@ -422,20 +1056,15 @@ impl InteractiveMode {
         {
             println!("{}", "🚀 Executing request...".bright_blue());
 
-            match self.client.execute_request(selected_request).await {
+            let request = self.session_request(selected_request);
+            match self.client.execute_request(&request).await {
                 Ok(response) => {
-                    self.storage
-                        .add_to_history(selected_request, &response)
-                        .await?;
+                    self.storage.add_to_history(&request, &response).await?;
 
                     println!();
-                    let cli = crate::Cli::parse();
-                    self.formatter.display_response(&response, &cli).await?;
-                }
-                Err(e) => {
-                    self.formatter
-                        .display_error(&format!("Request failed: {}", e));
+                    self.show_response(&response).await?;
                 }
+                Err(e) => crate::cli::print_request_error(&e),
             }
         }
 
@@ -493,14 +1122,13 @@ impl InteractiveMode {
 
                 match action {
                     0 => self.preview_request(&requests[selection]),
-                    1 => match self.client.execute_request(&requests[selection]).await {
-                        Ok(response) => {
-                            let cli = crate::Cli::parse();
-                            self.formatter.display_response(&response, &cli).await?;
-                        }
-                        Err(e) => self
-                            .formatter
-                            .display_error(&format!("Request failed: {}", e)),
+                    1 => match self
+                        .client
+                        .execute_request(&self.session_request(&requests[selection]))
+                        .await
+                    {
+                        Ok(response) => self.show_response(&response).await?,
+                        Err(e) => crate::cli::print_request_error(&e),
                     },
                     2 => {
                         let mut request = requests[selection].clone();
@@ -512,6 +1140,16 @@ impl InteractiveMode {
                             .default(false)
                             .interact()?
                         {
+                            if self.config.general.auto_backup {
+                                let path = self
+                                    .storage
+                                    .create_auto_backup(self.config.general.max_auto_backups)
+                                    .await?;
+                                self.formatter.display_info(&format!(
+                                    "Backed up existing data to {}",
+                                    path.display()
+                                ));
+                            }
                             self.storage
                                 .delete_request(&requests[selection].name)
                                 .await?;
@@ -570,23 +1208,19 @@ impl InteractiveMode {
                         .interact()?;
                     request.method = methods[method_index].to_string();
                 }
-                2 => {
-                    // Edit headers - simplified for now
-                    self.formatter
-                        .display_info("Header editing will be implemented in next version");
-                }
+                2 => self.edit_headers(request).await?,
                 3 => {
                     let new_body: String = Input::with_theme(&ColorfulTheme::default())
                         .with_prompt("Enter new body (or leave empty to remove)")
-                        .with_initial_text(request.body.as_deref().unwrap_or(""))
+                        .with_initial_text(request.body_text().unwrap_or(""))
                         .allow_empty(true)
                         .interact_text()?;
 
-                    request.body = if new_body.is_empty() {
+                    request.set_body(if new_body.is_empty() {
                         None
                     } else {
                         Some(new_body)
-                    };
+                    });
                 }
                 4 => {
                     self.storage.save_request(&request.name, request).await?;
@@ -665,18 +1299,69 @@ impl InteractiveMode {
             .interact()?;
 
         match selection {
-            0 => self
-                .formatter
-                .display_info("Settings display not yet implemented"),
-            1 => self
-                .formatter
-                .display_info("Timeout setting not yet implemented"),
-            2 => self
-                .formatter
-                .display_info("Output format setting not yet implemented"),
-            3 => self
-                .formatter
-                .display_info("Reset settings not yet implemented"),
+            0 => {
+                println!("{}", "\n⚙️  Current Settings".bright_cyan().bold());
+                for key in Config::list_all_keys() {
+                    if let Some(value) = self.config.get_value(key).await {
+                        println!("  {}: {}", key.bright_cyan(), value);
+                    }
+                }
+                println!();
+            }
+            1 => {
+                let timeout: String = Input::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Default timeout (seconds)")
+                    .with_initial_text(self.config.general.default_timeout.to_string())
+                    .validate_with(|input: &String| -> Result<(), String> {
+                        let seconds = input
+                            .trim()
+                            .parse::<u64>()
+                            .map_err(|_| "Please enter a whole number of seconds".to_string())?;
+                        crate::utils::validate_timeout(seconds).map_err(|e| e.to_string())
+                    })
+                    .interact_text()?;
+
+                self.config
+                    .set_value("general.default_timeout", timeout.trim())
+                    .await?;
+                self.formatter
+                    .display_success(&format!("Default timeout set to {}s", timeout.trim()));
+            }
+            2 => {
+                let current = OUTPUT_FORMATS
+                    .iter()
+                    .position(|f| *f == self.config.output.default_format)
+                    .unwrap_or(0);
+                let format_index = Select::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Default output format")
+                    .items(OUTPUT_FORMATS)
+                    .default(current)
+                    .interact()?;
+
+                self.config
+                    .set_value("output.default_format", OUTPUT_FORMATS[format_index])
+                    .await?;
+                self.display.output = OUTPUT_FORMATS[format_index].to_string();
+                self.formatter.display_success(&format!(
+                    "Default output format set to {}",
+                    OUTPUT_FORMATS[format_index]
+                ));
+            }
+            3 => {
+                if Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Reset all configuration to defaults?")
+                    .default(false)
+                    .interact()?
+                {
+                    self.config.reset_to_defaults().await?;
+                    self.display = DisplayOptions {
+                        verbose: self.display.verbose,
+                        ..DisplayOptions::from_config(&self.config)
+                    };
+                    self.formatter
+                        .display_success("Configuration reset to defaults");
+                }
+            }
             4 => return Ok(()),
             _ => unreachable!(),
         }
@@ -684,3 +1369,46 @@ impl InteractiveMode {
         Ok(())
     }
 }
+
+// Full URLs are used as given; anything else is a path under the base URL
+fn resolve_url(base_url: Option<&str>, target: &str) -> Result<String> {
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return Ok(target.to_string());
+    }
+    match base_url {
+        Some(base) => Ok(format!(
+            "{}/{}",
+            base.trim_end_matches('/'),
+            target.trim_start_matches('/')
+        )),
+        None => Err(anyhow::anyhow!(
+            "'{}' is not a full URL. Set a base URL with ':base <url>' first",
+            target
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repl_line_parsing() {
+        assert_eq!(
+            resolve_url(Some("https://api.example.com/"), "/users?page=2").unwrap(),
+            "https://api.example.com/users?page=2"
+        );
+        assert_eq!(
+            resolve_url(None, "http://localhost:8080/health").unwrap(),
+            "http://localhost:8080/health"
+        );
+        assert!(resolve_url(None, "/users").is_err());
+
+        let words =
+            shell_words::split(r#"POST /users -j '{"name": "Ada"}' -H "X-Trace: 1""#).unwrap();
+        let line = ReplLine::try_parse_from(words).unwrap();
+        assert_eq!(line.target, vec!["POST", "/users"]);
+        assert_eq!(line.json.as_deref(), Some(r#"{"name": "Ada"}"#));
+        assert_eq!(line.headers, vec!["X-Trace: 1"]);
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::request::{Body, SavedRequest, VARIABLE_PATTERN, validate_request};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+// Headers the HTTP client computes itself; a saved value is ignored or wrong
+const MANAGED_HEADERS: &[&str] = &["content-length", "host", "transfer-encoding"];
+
+/// Checks a saved request without sending it. With `variables` (an environment)
+/// placeholders that it can't fill are errors; without one they are warnings,
+/// since `--env` may still supply them at run time.
+pub fn lint_request(
+    request: &SavedRequest,
+    variables: Option<&HashMap<String, String>>,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let empty = HashMap::new();
+    let filled = fill_known(request, variables.unwrap_or(&empty));
+
+    for name in placeholders(&filled) {
+        findings.push(match variables {
+            Some(_) => Finding::error(format!(
+                "Unresolved variable {{{{{}}}}} is not in the environment",
+                name
+            )),
+            None => Finding::warning(format!(
+                "Template variable {{{{{}}}}} needs a value from --env",
+                name
+            )),
+        });
+    }
+
+    // Leftover placeholders would fail the structural checks for the wrong reason
+    let mut probe = filled.clone();
+    if url::Url::parse(&probe.url).is_err() && has_placeholder(&probe.url) {
+        probe.url = "http://placeholder.invalid/".to_string();
+    }
+    if probe.body_text().is_some_and(has_placeholder) {
+        probe.body = None;
+    }
+    if let Err(e) = validate_request(&probe) {
+        findings.push(Finding::error(e.to_string()));
+    }
+
+    check_headers(&filled, &mut findings);
+
+    match &filled.body {
+        Some(Body::File { file }) if !file.exists() => {
+            findings.push(Finding::error(format!(
+                "Body file not found: {}",
+                file.display()
+            )));
+        }
+        Some(_) if matches!(filled.method.as_str(), "GET" | "HEAD") => {
+            findings.push(Finding::warning(format!(
+                "{} request has a body, which many servers ignore",
+                filled.method
+            )));
+        }
+        _ => {}
+    }
+
+    if let Ok(url) = url::Url::parse(&filled.url)
+        && url.scheme() == "http"
+        && !is_loopback(&url)
+        && let Some(scheme) = header(&filled, "Authorization")
+            .and_then(|value| value.split_whitespace().next())
+            .filter(|scheme| {
+                scheme.eq_ignore_ascii_case("bearer") || scheme.eq_ignore_ascii_case("basic")
+            })
+    {
+        findings.push(Finding::warning(format!(
+            "{} credentials are sent over plain http:// to {}",
+            scheme,
+            url.host_str().unwrap_or_default()
+        )));
+    }
+
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    findings
+}
+
+fn check_headers(request: &SavedRequest, findings: &mut Vec<Finding>) {
+    let mut names: Vec<&String> = request.headers.keys().collect();
+    names.sort();
+
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    for name in names {
+        let value = &request.headers[name];
+        if !crate::utils::is_valid_header_name(name) || name.contains(char::is_whitespace) {
+            findings.push(Finding::error(format!("Invalid header name: '{}'", name)));
+            continue;
+        }
+        if !crate::utils::is_valid_header_value(value) {
+            findings.push(Finding::error(format!(
+                "Header '{}' has a value containing a newline",
+                name
+            )));
+        } else if value.trim().is_empty() {
+            findings.push(Finding::warning(format!("Header '{}' is empty", name)));
+        } else if value.trim() != value {
+            findings.push(Finding::warning(format!(
+                "Header '{}' has leading or trailing whitespace",
+                name
+            )));
+        }
+
+        let lower = name.to_ascii_lowercase();
+        if MANAGED_HEADERS.contains(&lower.as_str()) {
+            findings.push(Finding::warning(format!(
+                "Header '{}' is set by the client and should not be saved",
+                name
+            )));
+        }
+        if lower == "authorization" && value.split_whitespace().count() == 1 {
+            let scheme = value.trim();
+            if scheme.eq_ignore_ascii_case("bearer") || scheme.eq_ignore_ascii_case("basic") {
+                findings.push(Finding::warning(format!(
+                    "Authorization header has a '{}' scheme but no credentials",
+                    scheme
+                )));
+            }
+        }
+        if let Some(previous) = seen.insert(lower, name) {
+            findings.push(Finding::warning(format!(
+                "Headers '{}' and '{}' differ only in case; only one is sent",
+                previous, name
+            )));
+        }
+    }
+}
+
+fn header<'a>(request: &'a SavedRequest, name: &str) -> Option<&'a str> {
+    request
+        .headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn is_loopback(url: &url::Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain == "localhost",
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+fn has_placeholder(text: &str) -> bool {
+    VARIABLE_PATTERN.is_match(text)
+}
+
+// Fills the variables that are known and leaves the rest in place
+fn fill_known(request: &SavedRequest, variables: &HashMap<String, String>) -> SavedRequest {
+    let fill = |text: &str| {
+        VARIABLE_PATTERN
+            .replace_all(text, |captures: &regex::Captures| {
+                variables
+                    .get(&captures[1])
+                    .cloned()
+                    .unwrap_or_else(|| captures[0].to_string())
+            })
+            .into_owned()
+    };
+
+    let mut request = request.clone();
+    request.url = fill(&request.url);
+    for value in request.headers.values_mut() {
+        *value = fill(value);
+    }
+    if let Some(Body::Text(ref body)) = request.body {
+        request.body = Some(Body::Text(fill(body)));
+    }
+    if let Some(ref mut credentials) = request.digest_auth {
+        credentials.username = fill(&credentials.username);
+        credentials.password = fill(&credentials.password);
+    }
+    if let Some(ref mut hmac) = request.hmac {
+        hmac.secret = fill(&hmac.secret);
+    }
+    request
+}
+
+fn placeholders(request: &SavedRequest) -> Vec<String> {
+    let mut texts = vec![request.url.as_str()];
+    texts.extend(request.headers.values().map(String::as_str));
+    texts.extend(request.body_text());
+    if let Some(credentials) = &request.digest_auth {
+        texts.push(&credentials.username);
+        texts.push(&credentials.password);
+    }
+    if let Some(hmac) = &request.hmac {
+        texts.push(&hmac.secret);
+    }
+
+    let mut names: Vec<String> = texts
+        .into_iter()
+        .flat_map(|text| {
+            VARIABLE_PATTERN
+                .captures_iter(text)
+                .map(|c| c[1].to_string())
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(url: &str, method: &str) -> SavedRequest {
+        SavedRequest::new("test".to_string(), url.to_string(), method.to_string())
+    }
+
+    fn messages(findings: &[Finding], severity: Severity) -> Vec<&str> {
+        findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .map(|finding| finding.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_request_has_no_findings() {
+        let mut request = saved("https://api.example.com/users/{{uuid()}}", "POST");
+        request.add_header("Content-Type".to_string(), "application/json".to_string());
+        request.set_body(Some(r#"{"name": "ada"}"#.to_string()));
+        assert!(lint_request(&request, None).is_empty());
+    }
+
+    #[test]
+    fn test_lint_findings() {
+        let mut request = saved("http://api.example.com/users/{{id}}", "GET");
+        request.add_header("authorization".to_string(), "Bearer abc".to_string());
+        request.add_header("Content-Type".to_string(), "application/json".to_string());
+        request.add_header("content-type".to_string(), "application/json".to_string());
+        request.add_header("X-Trace".to_string(), " ".to_string());
+        request.set_body(Some("{not json".to_string()));
+
+        let findings = lint_request(&request, None);
+        let errors = messages(&findings, Severity::Error);
+        let warnings = messages(&findings, Severity::Warning);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Invalid JSON body"));
+        assert!(warnings.contains(&"Template variable {{id}} needs a value from --env"));
+        assert!(warnings.contains(&"Header 'X-Trace' is empty"));
+        assert!(warnings.iter().any(|w| w.contains("differ only in case")));
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.contains("sent over plain http://"))
+        );
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.contains("GET request has a body"))
+        );
+        assert_eq!(findings[0].severity, Severity::Error);
+
+        // With an environment, filled variables are fine and missing ones are errors
+        let mut variables = HashMap::new();
+        variables.insert("id".to_string(), "42".to_string());
+        let mut templated = saved("{{base}}/users/{{id}}", "DELETE");
+        templated.set_body(None);
+        let findings = lint_request(&templated, Some(&variables));
+        assert_eq!(
+            messages(&findings, Severity::Error),
+            vec!["Unresolved variable {{base}} is not in the environment"]
+        );
+    }
+}
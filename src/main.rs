@@ -1,21 +1,37 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use colored::*;
 use std::collections::HashMap;
 
+mod assertions;
+mod auth;
 mod cli;
 mod client;
+mod completions;
 mod config;
+mod doctor;
+mod fixture;
+mod har;
 mod interactive;
+mod lint;
+mod openapi;
 mod output;
+mod postman;
 mod request;
+mod request_log;
+mod runner;
+mod schema;
+mod serve;
 mod storage;
+#[cfg(unix)]
+mod unix_socket;
 mod utils;
+mod websocket;
 
 use client::TerziClient;
 use config::Config;
 use interactive::InteractiveMode;
-use output::ResponseFormatter;
+use output::{DisplayOptions, ResponseFormatter};
 use request::RequestBuilder;
 use storage::Storage;
 use utils::*;
@@ -27,7 +43,15 @@ use utils::*;
     long_about = "Terzi: A modern CLI API client designed for developers who value precision and simplicity. \
                   Build, test, and manage your API requests with ease.",
     version,
-    author = "Sabir Khan <simplysabir@gmail.com>"
+    author = "Sabir Khan <simplysabir@gmail.com>",
+    // At the top level --fail-fast and --keep-going only make sense for --parallel
+    group(
+        clap::ArgGroup::new("parallel_failure")
+            .args(["fail_fast", "keep_going"])
+            .multiple(true)
+            .requires("parallel")
+            .conflicts_with_all(["url", "load"])
+    )
 )]
 struct Cli {
     #[command(subcommand)]
@@ -53,22 +77,98 @@ struct Cli {
     #[arg(short, long)]
     json: Option<String>,
 
-    /// Form data (key=value pairs)
+    /// Form data (key=value pairs); keys and values are percent-encoded
     #[arg(short, long = "form")]
     form_data: Vec<String>,
 
+    /// Form field sent exactly as typed (key=value), for values that are already encoded
+    #[arg(long = "form-raw", value_name = "KEY=VALUE")]
+    form_raw: Vec<String>,
+
+    /// Query parameter (key=value) added to the URL; key and value are percent-encoded
+    #[arg(long = "query", value_name = "KEY=VALUE")]
+    query: Vec<String>,
+
+    /// Query parameter added exactly as typed (key=value), for values that are already encoded
+    #[arg(long = "query-raw", value_name = "KEY=VALUE")]
+    query_raw: Vec<String>,
+
+    /// Send a body byte for byte, from @FILE, stdin (@-) or as given; sets no Content-Type
+    #[arg(long, value_name = "DATA")]
+    data_binary: Option<String>,
+
+    /// Write the body in ui.editor ($EDITOR), starting from --json, --body or {}
+    #[arg(long, conflicts_with_all = ["form_data", "form_raw", "data_binary"])]
+    edit_body: bool,
+
+    /// Send a PATCH with this JSON Merge Patch object (application/merge-patch+json)
+    #[arg(long, value_name = "JSON", conflicts_with = "edit_body")]
+    merge_patch: Option<String>,
+
+    /// Send a PATCH with this JSON Patch array of operations (application/json-patch+json)
+    #[arg(long, value_name = "JSON", conflicts_with = "edit_body")]
+    json_patch: Option<String>,
+
+    /// Stream stdin as the body while sending, without reading it into memory first
+    #[arg(long, conflicts_with_all = ["edit_body", "hmac", "parallel", "watch", "save", "paginate", "unix_socket"])]
+    stream_body: bool,
+
+    /// Send --body without guessing a Content-Type from its contents
+    #[arg(long)]
+    no_guess_content_type: bool,
+
+    /// Don't send an Accept header matching --output
+    #[arg(long)]
+    no_accept: bool,
+
+    /// Percent-encode spaces and unsafe characters in the URL's path and query
+    #[arg(long)]
+    encode_url: bool,
+
     /// Authorization header
     #[arg(short = 'A', long)]
     auth: Option<String>,
 
+    /// Sign the body with an HMAC header, as SECRET:HEADER:ALGO (sha1 or sha256)
+    #[arg(long, value_name = "SECRET:HEADER:ALGO")]
+    hmac: Option<String>,
+
+    /// Use a stored token (see 'terzi auth'), refreshing it if expired
+    #[arg(long, value_name = "NAME", conflicts_with = "auth")]
+    auth_token: Option<String>,
+
+    /// Send the bearer token in this file, read again before every request
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["auth", "auth_token"])]
+    auth_token_file: Option<std::path::PathBuf>,
+
     /// Follow redirects
     #[arg(short = 'L', long)]
     follow_redirects: bool,
 
+    /// Return a 3xx response as-is instead of following it
+    #[arg(long, conflicts_with_all = ["follow_redirects", "max_redirects"])]
+    no_follow: bool,
+
     /// Timeout in seconds
     #[arg(short, long, default_value = "30")]
     timeout: u64,
 
+    /// Seconds allowed to establish the connection (default: network.connection_timeout)
+    #[arg(long, value_name = "SECONDS")]
+    connect_timeout: Option<u64>,
+
+    /// Seconds the response may stall between chunks (default: network.read_timeout)
+    #[arg(long, value_name = "SECONDS")]
+    read_timeout: Option<u64>,
+
+    /// Redirects to follow before giving up (default: network.max_redirects)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(..=50))]
+    max_redirects: Option<u8>,
+
+    /// Abort if the response is larger than this, e.g. 10MB (default: network.max_response_size)
+    #[arg(long, value_name = "SIZE", value_parser = utils::parse_size)]
+    max_response_size: Option<u64>,
+
     /// Save request with a name
     #[arg(long)]
     save: Option<String>,
@@ -77,7 +177,47 @@ struct Cli {
     #[arg(long)]
     load: Option<String>,
 
-    /// Output format (auto, json, yaml, table)
+    /// JSONPath to a cursor in the response (e.g. '$.events[-1].id'), remembered by the
+    /// saved request and sent back as --cursor-param next time
+    #[arg(long, value_name = "JSONPATH", value_parser = utils::parse_json_path_arg, requires = "cursor_param")]
+    cursor_field: Option<String>,
+
+    /// Query parameter that carries the --cursor-field value, e.g. since_id
+    #[arg(long, value_name = "NAME", requires = "cursor_field")]
+    cursor_param: Option<String>,
+
+    /// Re-send the request on an interval such as 5s or 500ms, showing the latest response
+    #[arg(long, value_name = "INTERVAL", value_parser = utils::parse_interval, conflicts_with_all = ["paginate", "paginate_jsonpath"])]
+    watch: Option<std::time::Duration>,
+
+    /// Stop watching once this assertion passes, e.g. '$.status == done'
+    #[arg(long, value_name = "EXPR", requires = "watch")]
+    watch_until: Option<String>,
+
+    /// Read URLs from stdin, one per line, and send up to N requests at a time
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..), conflicts_with_all = ["url", "load", "watch", "save"])]
+    parallel: Option<u16>,
+
+    #[command(flatten)]
+    failure: runner::FailureArgs,
+
+    /// Follow Link rel="next" headers (or --paginate-jsonpath cursors) and merge pages into one array
+    #[arg(long)]
+    paginate: bool,
+
+    /// Maximum number of pages to fetch with --paginate
+    #[arg(long, value_name = "N", default_value = "50", value_parser = clap::value_parser!(u32).range(1..))]
+    max_pages: u32,
+
+    /// JSONPath to the next URL or cursor in the body, e.g. '$.next' (implies --paginate)
+    #[arg(long, value_name = "PATH")]
+    paginate_jsonpath: Option<String>,
+
+    /// Query parameter that carries a --paginate-jsonpath cursor
+    #[arg(long, value_name = "NAME", default_value = "cursor")]
+    paginate_param: String,
+
+    /// Output format (auto, json, yaml, xml, form, table, raw, http)
     #[arg(short, long, default_value = "auto")]
     output: String,
 
@@ -89,13 +229,189 @@ struct Cli {
     #[arg(short, long)]
     verbose: bool,
 
+    /// Print the request as sent, with the headers the client adds, to stderr
+    #[arg(long)]
+    verbose_request: bool,
+
     /// Silent mode (no output formatting)
     #[arg(short = 'S', long)]
     silent: bool,
 
-    /// Pretty print JSON responses
-    #[arg(short, long, default_value = "true")]
+    /// Print only the status line, duration and size, without headers or body
+    #[arg(long, conflicts_with = "silent")]
+    summary_only: bool,
+
+    /// Print only the status line and response headers, without the body
+    #[arg(long, alias = "output-headers-only", conflicts_with_all = ["silent", "summary_only", "grep", "jq"])]
+    headers_only: bool,
+
+    /// Send a GET as HEAD and print only the headers (implies --headers-only)
+    #[arg(short = 'I', long, conflicts_with_all = ["silent", "summary_only", "grep", "jq"])]
+    head: bool,
+
+    /// Print only body lines matching this regex, with the matches highlighted
+    #[arg(long, value_name = "PATTERN", value_parser = regex::Regex::new)]
+    grep: Option<regex::Regex>,
+
+    /// Lines of context to show around each --grep match
+    #[arg(long, value_name = "N", default_value = "0", requires = "grep")]
+    grep_context: usize,
+
+    /// Transform a JSON response body with a jq filter before printing, e.g. '.items | map(.id)'
+    #[arg(long, value_name = "FILTER", value_parser = utils::parse_jq_arg, conflicts_with = "grep")]
+    jq: Option<String>,
+
+    /// Exit with status 1 on a 4xx or 5xx response, without printing the body
+    #[arg(long, conflicts_with = "fail_with_body")]
+    fail: bool,
+
+    /// Like --fail, but print the response first
+    #[arg(long)]
+    fail_with_body: bool,
+
+    /// Print a JSON line with status, duration_ms, size, url and method to stderr
+    #[arg(long)]
+    print_stats_json: bool,
+
+    /// Write the --print-stats-json line to this file instead
+    #[arg(long, value_name = "FILE")]
+    print_stats_json_file: Option<String>,
+
+    /// Pretty print JSON, regardless of output.pretty_print
+    #[arg(short, long, conflicts_with = "compact")]
     pretty: bool,
+
+    /// Indent JSON output by this many spaces
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=16))]
+    json_indent: Option<u8>,
+
+    /// Print JSON minified, regardless of output.pretty_print
+    #[arg(long, conflicts_with = "json_indent")]
+    compact: bool,
+
+    /// Flatten nested JSON into dotted columns in table output
+    #[arg(long)]
+    flatten: bool,
+
+    /// Maximum nesting depth to flatten (implies --flatten)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..))]
+    flatten_depth: Option<u8>,
+
+    /// Table columns as comma-separated JSONPaths, e.g. '$.id,$.name' (implies -o table)
+    #[arg(long, value_name = "PATHS", value_delimiter = ',', value_parser = utils::parse_json_path_arg)]
+    columns: Vec<String>,
+
+    /// Directory for config and saved data (overrides TERZI_CONFIG_DIR)
+    #[arg(long, global = true, value_name = "DIR")]
+    config_dir: Option<std::path::PathBuf>,
+
+    /// Config profile to use (overrides TERZI_PROFILE)
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Ring the terminal bell (or show a desktop notification) when the request completes
+    #[arg(long)]
+    notify: bool,
+
+    /// Skip TLS certificate verification for this run
+    #[arg(short = 'k', long)]
+    insecure: bool,
+
+    /// Client certificate (PEM) for mutual TLS
+    #[arg(long, value_name = "FILE")]
+    cert: Option<String>,
+
+    /// Private key (PKCS#8 PEM) for --cert, if not bundled in the certificate file
+    #[arg(long, value_name = "FILE", requires = "cert")]
+    key: Option<String>,
+
+    /// Extra CA certificate(s) to trust, as a PEM file or bundle
+    #[arg(long, value_name = "FILE")]
+    cacert: Option<String>,
+
+    /// Route this run through a proxy (http://, https://, socks5:// or socks5h://)
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Connect directly, ignoring the configured proxy and proxy environment variables
+    #[arg(long, conflicts_with = "proxy")]
+    no_proxy: bool,
+
+    /// Send the request over this Unix domain socket instead of TCP (http:// URLs only)
+    #[arg(long, value_name = "PATH")]
+    unix_socket: Option<String>,
+
+    /// Connect to ADDR instead of resolving HOST for requests to PORT, as HOST:PORT:ADDR (repeatable)
+    #[arg(long = "resolve", value_name = "HOST:PORT:ADDR", value_parser = utils::parse_resolve)]
+    resolve: Vec<(String, std::net::SocketAddr)>,
+
+    /// Send requests for HOST:PORT to TARGET_HOST:TARGET_PORT, keeping the URL's host (repeatable)
+    #[arg(long = "connect-to", value_name = "HOST:PORT:TARGET_HOST:TARGET_PORT", value_parser = utils::parse_connect_to)]
+    connect_to: Vec<config::ConnectTo>,
+
+    /// Send this Host header instead of the one derived from the URL
+    #[arg(long, value_name = "HOST")]
+    host_header: Option<String>,
+
+    /// Send the request as POST with the real method in X-HTTP-Method-Override
+    #[arg(long)]
+    method_override: bool,
+
+    /// Correlation ID to send in network.request_id_header (X-Request-Id by default)
+    #[arg(long, value_name = "ID")]
+    request_id: Option<String>,
+
+    /// Only connect over IPv4
+    #[arg(short = '4', long, conflicts_with = "ipv6")]
+    ipv4: bool,
+
+    /// Only connect over IPv6
+    #[arg(short = '6', long)]
+    ipv6: bool,
+
+    /// Don't record this request in history (see general.save_history)
+    #[arg(long)]
+    no_history: bool,
+
+    /// Leave out the headers from network.default_headers
+    #[arg(long)]
+    no_default_headers: bool,
+
+    /// Append a JSONL record of each request and response to this file
+    #[arg(long, value_name = "FILE")]
+    log: Option<String>,
+
+    /// Write secrets such as tokens and passwords to the request log unmasked
+    #[arg(long)]
+    log_unmasked: bool,
+
+    /// Store secrets as ${TERZI_...} environment placeholders when saving, and mask them in history
+    #[arg(long)]
+    redact_on_save: bool,
+
+    /// Fill {{name}} placeholders from this environment's variables (see `terzi env`)
+    #[arg(long, value_name = "NAME")]
+    env: Option<String>,
+
+    /// Assert on the response, e.g. 'status == 200' or '$.id exists' (repeatable)
+    #[arg(long = "assert", value_name = "EXPR")]
+    assertions: Vec<String>,
+
+    /// Read more assertions from a YAML or JSON file, checked alongside --assert
+    #[arg(long, value_name = "FILE")]
+    assert_file: Option<std::path::PathBuf>,
+
+    /// Validate the response body against a JSON Schema file
+    #[arg(long, value_name = "FILE")]
+    validate_schema: Option<std::path::PathBuf>,
+
+    /// Infer a JSON Schema from the response body and write it to a file
+    #[arg(long, value_name = "FILE")]
+    save_schema: Option<std::path::PathBuf>,
+
+    /// Write the request and full response (status, headers, body, timing) to a JSON fixture
+    #[arg(long, value_name = "FILE")]
+    save_response: Option<std::path::PathBuf>,
 }
 
 #[derive(Subcommand, Clone)]
@@ -103,6 +419,12 @@ enum Commands {
     /// Make an interactive request with guided prompts
     Interactive,
 
+    /// Send requests typed as `[METHOD] URL [flags]`, with line history
+    Repl {
+        /// Base URL that paths such as `/users` are sent to
+        base_url: Option<String>,
+    },
+
     /// List saved requests
     List {
         /// Filter by pattern
@@ -110,10 +432,28 @@ enum Commands {
         filter: Option<String>,
     },
 
+    /// Search saved requests by name, URL, method, tag or description
+    Search {
+        /// Text to look for; letters of a name may be abbreviated or out of place
+        query: String,
+        /// Maximum number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
     /// Show details of a saved request
     Show {
         /// Name of the saved request
         name: String,
+        /// Print the stored request as-is, without decoration or masking
+        #[arg(long)]
+        raw: bool,
+        /// Format for --raw (json, yaml)
+        #[arg(long, default_value = "json", requires = "raw")]
+        format: String,
+        /// Compare with another saved request: method, URL, headers and body
+        #[arg(long, value_name = "OTHER", conflicts_with = "raw")]
+        diff_with: Option<String>,
     },
 
     /// Delete a saved request
@@ -128,33 +468,366 @@ enum Commands {
         name: String,
     },
 
+    /// Save a copy of a saved request under a new name
+    Duplicate {
+        /// Name of the saved request to copy
+        name: String,
+        /// Name for the copy
+        new_name: String,
+    },
+
+    /// Check that a URL answers, with latency, address and certificate
+    Ping {
+        /// URL to probe with a HEAD request
+        url: String,
+        /// Probe again on an interval such as 1s or 500ms
+        #[arg(long, value_name = "INTERVAL", value_parser = utils::parse_interval)]
+        watch: Option<std::time::Duration>,
+        /// Stop after this many probes
+        #[arg(short = 'c', long, requires = "watch")]
+        count: Option<u32>,
+    },
+
+    /// Serve responses recorded with --save-response, matched by method and path
+    Serve {
+        /// Directory of fixture files
+        #[arg(long, value_name = "DIR", default_value = "fixtures")]
+        fixtures: std::path::PathBuf,
+        /// Port to listen on (localhost only)
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+        /// Wait this long before each response, e.g. 300ms or 2s
+        #[arg(long, value_name = "DURATION", value_parser = utils::parse_interval)]
+        delay: Option<std::time::Duration>,
+    },
+
+    /// Check the config, data file, editor, proxy and network for problems
+    Doctor {
+        /// URL used to check network connectivity
+        #[arg(long, default_value = "https://example.com")]
+        url: String,
+    },
+
+    /// Check a saved request for problems without sending it
+    Lint {
+        /// Name of the saved request to check
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+        /// Check every saved request
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+    },
+
     /// Show request history
     History {
+        #[command(subcommand)]
+        action: Option<HistoryAction>,
+
         /// Number of recent requests to show
         #[arg(short, long, default_value = "10")]
         limit: usize,
     },
 
+    /// Manage stored authentication tokens
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+
+    /// Manage config profiles
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+
     /// Configure terzi settings
     Config {
         #[command(subcommand)]
         action: ConfigAction,
     },
 
+    /// Manage cached responses (see network.enable_cache)
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Run several saved requests in one go, reusing connections
+    Run {
+        /// Names of the saved requests, run in order
+        #[arg(required = true)]
+        names: Vec<String>,
+
+        #[command(flatten)]
+        failure: runner::FailureArgs,
+    },
+
+    /// Run a collection's requests and check their assertions
+    Test {
+        /// Name of the collection to run
+        collection: String,
+
+        #[command(flatten)]
+        failure: runner::FailureArgs,
+    },
+
+    /// Open a WebSocket connection and exchange messages
+    Ws {
+        /// WebSocket URL (ws:// or wss://)
+        url: String,
+
+        /// Handshake headers (key:value format)
+        #[arg(short = 'H', long = "header", value_name = "HEADER")]
+        headers: Vec<String>,
+
+        /// Send a single message, print the reply and disconnect
+        #[arg(long, value_name = "MESSAGE")]
+        send: Option<String>,
+
+        /// Seconds to wait for a reply with --send
+        #[arg(long, default_value = "10")]
+        wait: u64,
+    },
+
+    /// Manage collections of saved requests
+    Collection {
+        #[command(subcommand)]
+        action: CollectionAction,
+    },
+
+    /// Manage environments, the variables that fill {{name}} placeholders
+    Env {
+        #[command(subcommand)]
+        action: EnvAction,
+    },
+
+    /// Work with OpenAPI specs
+    Openapi {
+        #[command(subcommand)]
+        action: OpenapiAction,
+    },
+
+    /// Import requests from a terzi export or a Postman collection
+    Import {
+        /// File to import
+        file: std::path::PathBuf,
+        /// Format (json, yaml, postman)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+    },
+
     /// Export saved requests
     Export {
         /// Output file path
         #[arg(short, long)]
         output: Option<String>,
-        /// Format (json, yaml)
+        /// Format (json, yaml, postman)
         #[arg(short, long, default_value = "json")]
         format: String,
     },
 
+    /// Export recent history as a HAR (HTTP Archive) file
+    ExportHar {
+        /// Number of most recent requests to include
+        #[arg(short, long, default_value = "10")]
+        last: usize,
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Keep secrets such as tokens and passwords unmasked
+        #[arg(long)]
+        no_mask: bool,
+    },
+
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    /// Back up and restore saved requests, collections, environments and history
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+
     /// Show version information
     Version,
 }
 
+#[derive(Subcommand, Clone)]
+enum AuthAction {
+    /// Get a token with the OAuth2 client-credentials grant and store it
+    Oauth2 {
+        /// Name to store the token under
+        #[arg(long, default_value = "default")]
+        name: String,
+        /// Token endpoint URL
+        #[arg(long)]
+        token_url: String,
+        /// OAuth2 client ID
+        #[arg(long)]
+        client_id: String,
+        /// OAuth2 client secret
+        #[arg(long)]
+        client_secret: String,
+        /// Scopes to request (space-separated or repeated)
+        #[arg(long)]
+        scope: Vec<String>,
+    },
+    /// List stored tokens
+    List,
+    /// Delete a stored token
+    Delete {
+        /// Token name
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum CollectionAction {
+    /// Create an empty collection
+    Create {
+        /// Collection name
+        name: String,
+        /// Short description of the collection
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+    /// List collections
+    List,
+    /// Add a copy of a saved request to a collection
+    Add {
+        /// Collection name
+        collection: String,
+        /// Name of the saved request
+        request: String,
+    },
+    /// Show the requests in a collection
+    Show {
+        /// Collection name
+        name: String,
+    },
+    /// Run a collection's requests in order and check their assertions
+    Run {
+        /// Collection name
+        name: String,
+        /// Environment whose variables fill the requests' {{placeholders}}
+        #[arg(short, long, value_name = "NAME")]
+        env: Option<String>,
+
+        #[command(flatten)]
+        failure: runner::FailureArgs,
+    },
+    /// Delete a collection (its saved requests are kept)
+    Delete {
+        /// Collection name
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum EnvAction {
+    /// Create an environment
+    Create {
+        /// Environment name
+        name: String,
+        /// Variables (KEY=VALUE)
+        variables: Vec<String>,
+    },
+    /// List environments
+    List,
+    /// Show an environment's variables
+    Show {
+        /// Environment name
+        name: String,
+        /// Show values that look like secrets unmasked
+        #[arg(long)]
+        no_mask: bool,
+    },
+    /// Add or update variables in an environment
+    Set {
+        /// Environment name
+        name: String,
+        /// Variables (KEY=VALUE)
+        #[arg(required = true)]
+        variables: Vec<String>,
+    },
+    /// Delete an environment
+    Delete {
+        /// Environment name
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum OpenapiAction {
+    /// Create a collection with one saved request per operation in an OpenAPI 3.x spec
+    Import {
+        /// Path to the spec (YAML or JSON)
+        spec: std::path::PathBuf,
+        /// Base URL for the requests (default: the spec's first server URL)
+        #[arg(long)]
+        base_url: Option<String>,
+        /// Collection name (default: the spec's title)
+        #[arg(long)]
+        collection: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum ProfileAction {
+    /// List profiles
+    List,
+    /// Create a profile with default settings
+    Create {
+        /// Profile name
+        name: String,
+    },
+    /// Delete a profile and its saved data
+    Delete {
+        /// Profile name
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum BackupAction {
+    /// Write a backup of all saved data
+    Create,
+    /// List backups, newest first
+    List,
+    /// Replace all saved data with a backup
+    Restore {
+        /// Backup file name (from `backup list`) or path
+        file: String,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum HistoryAction {
+    /// Remove all history, or only older entries with --before
+    Clear {
+        /// Only remove entries recorded before this date (YYYY-MM-DD, UTC, or RFC 3339)
+        #[arg(long, value_name = "DATE")]
+        before: Option<String>,
+    },
+    /// Remove a single entry by ID (a unique prefix is enough)
+    Delete {
+        /// Entry ID, as shown by `terzi history`
+        id: String,
+    },
+    /// Summarize recorded requests: outcomes, timings and bytes transferred
+    Stats,
+}
+
+#[derive(Subcommand, Clone)]
+enum CacheAction {
+    /// Remove every cached response
+    Clear,
+}
+
 #[derive(Subcommand, Clone)]
 enum ConfigAction {
     /// Set a configuration value
@@ -169,14 +842,45 @@ enum ConfigAction {
         /// Configuration key
         key: String,
     },
+    /// Revert a configuration value to its default
+    Unset {
+        /// Configuration key
+        key: String,
+    },
     /// List all configuration
-    List,
+    List {
+        /// Print the whole configuration as JSON
+        #[arg(long)]
+        json: bool,
+        /// With --json, leave stored tokens and other secrets unmasked
+        #[arg(long, requires = "json")]
+        include_secrets: bool,
+    },
+    /// Print the configuration as TOML
+    Export {
+        /// Include stored auth tokens
+        #[arg(long)]
+        include_secrets: bool,
+    },
+    /// Replace the configuration with a TOML file from `config export`
+    Import {
+        /// Path to the TOML file
+        file: std::path::PathBuf,
+    },
+    /// Open the configuration file in your editor
+    Edit,
     /// Reset configuration to defaults
     Reset,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Helper for the completion scripts, kept out of clap so it never shows up in them
+    if std::env::args().nth(1).as_deref() == Some("__complete") {
+        completions::print_candidates(std::env::args().nth(2).as_deref()).await;
+        return Ok(());
+    }
+
     let cli = match Cli::try_parse() {
         Ok(cli) => cli,
         Err(e) => {
@@ -204,24 +908,107 @@ async fn main() -> Result<()> {
         }
     };
 
+    // File checks are built from typed values rather than expressions, so they are run
+    // beside the --assert ones instead of being saved with the request
+    let file_checks = match cli.assert_file {
+        Some(ref path) => assertions::load_file(path)?,
+        None => Vec::new(),
+    };
+
+    if let Some(ref dir) = cli.config_dir {
+        config::set_config_dir(dir.clone());
+    }
+    if let Some(ref profile) = cli.profile {
+        config::set_profile(profile.clone());
+    }
+
+    // Profile management has to work before the selected profile exists
+    if let Some(Commands::Profile { ref action }) = cli.command {
+        return handle_profile_action(action).await;
+    }
+
+    if let Some(Commands::Completions { shell }) = cli.command {
+        completions::generate(shell, Cli::command(), &mut std::io::stdout());
+        return Ok(());
+    }
+
+    // Loading the config or data file moves a broken one aside, so check them first
+    if let Some(Commands::Doctor { ref url }) = cli.command {
+        if !print_doctor_report(&doctor::run(url).await) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Initialize configuration and storage
     let config = Config::load().await?;
     let mut storage = Storage::new().await?;
-    let client = TerziClient::new(&config)?;
+    storage.set_redact_secrets(config.general.redact_secrets || cli.redact_on_save);
+    storage.set_max_history_entries(config.general.max_history_entries);
+    storage.set_save_history(config.general.save_history && !cli.no_history);
+    let client = TerziClient::new(&client_config(&cli, &config))?;
     let formatter = ResponseFormatter::new(&config);
+    let display = display_options(&cli, &config);
+    let request_log = request_log(&cli, &config);
+    let pagination = pagination(&cli)?;
 
     match cli.command {
         Some(Commands::Interactive) => {
-            let mut interactive = InteractiveMode::new(client, storage, formatter);
+            let mut interactive = InteractiveMode::new(client, storage, formatter, config, display);
             interactive.run().await?;
         }
 
+        Some(Commands::Repl { base_url }) => {
+            if let Some(ref url) = base_url
+                && !utils::is_valid_url(url)
+            {
+                return Err(anyhow::anyhow!(
+                    "Invalid base URL: {}. Please provide a valid URL starting with http:// or https://",
+                    url
+                ));
+            }
+            let mut interactive = InteractiveMode::new(client, storage, formatter, config, display);
+            interactive.run_repl(base_url).await?;
+        }
+
         Some(Commands::List { filter }) => {
             let requests = storage.list_requests(filter.as_deref()).await?;
             print_request_list(&requests);
         }
 
-        Some(Commands::Show { name }) => match storage.get_request(&name).await? {
+        Some(Commands::Search { query, limit }) => {
+            let results = storage.search_requests(&query).await?;
+            print_search_results(&query, &results[..results.len().min(limit)]);
+        }
+
+        Some(Commands::Show {
+            name,
+            diff_with: Some(other),
+            ..
+        }) => {
+            match (
+                storage.get_request(&name).await?,
+                storage.get_request(&other).await?,
+            ) {
+                (Some(old), Some(new)) => print_request_diff(&old, &new),
+                (None, _) => cli::print_error(&format!("Request '{}' not found", name)),
+                (_, None) => cli::print_error(&format!("Request '{}' not found", other)),
+            }
+        }
+
+        Some(Commands::Show {
+            name, raw, format, ..
+        }) => match storage.get_request(&name).await? {
+            Some(request) if raw => match format.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&request)?),
+                "yaml" => print!("{}", serde_yaml::to_string(&request)?),
+                other => {
+                    return Err(anyhow::anyhow!(
+                        "Unsupported format '{}'. Use json or yaml",
+                        other
+                    ));
+                }
+            },
             Some(request) => print_request_details(&request),
             None => cli::print_error(&format!("Request '{}' not found", name)),
         },
@@ -231,6 +1018,9 @@ async fn main() -> Result<()> {
                 &format!("Are you sure you want to delete request '{}'?", name),
                 &config,
             ) {
+                if storage.get_request(&name).await?.is_some() {
+                    backup_before(&storage, &config, "deleting").await?;
+                }
                 if storage.delete_request(&name).await? {
                     cli::print_success(&format!("Request '{}' deleted", name));
                 } else {
@@ -243,76 +1033,388 @@ async fn main() -> Result<()> {
 
         Some(Commands::Edit { name }) => match storage.get_request(&name).await? {
             Some(mut request) => {
-                let mut interactive = InteractiveMode::new(client, storage, formatter);
+                let mut interactive =
+                    InteractiveMode::new(client, storage, formatter, config, display);
                 interactive.edit_request(&mut request).await?;
             }
             None => cli::print_error(&format!("Request '{}' not found", name)),
         },
 
-        Some(Commands::History { limit }) => {
+        Some(Commands::Duplicate { name, new_name }) => {
+            storage.duplicate_request(&name, &new_name).await?;
+            cli::print_success(&format!("Request '{}' copied to '{}'", name, new_name));
+        }
+
+        Some(Commands::Ping { url, watch, count }) => {
+            url::Url::parse(&url).map_err(|e| anyhow::anyhow!("Invalid URL '{}': {}", url, e))?;
+            if !ping(&client, &url, watch, count).await {
+                std::process::exit(1);
+            }
+        }
+
+        Some(Commands::Serve {
+            fixtures,
+            port,
+            delay,
+        }) => {
+            serve::FixtureServer::load(&fixtures, delay)?
+                .run(port)
+                .await?;
+        }
+
+        Some(Commands::Lint { name, all }) => {
+            let requests = if all {
+                storage.list_requests(None).await?
+            } else {
+                let name = name.unwrap_or_default();
+                vec![
+                    storage
+                        .get_request(&name)
+                        .await?
+                        .ok_or_else(|| anyhow::anyhow!("Request '{}' not found", name))?,
+                ]
+            };
+            let variables = match &cli.env {
+                Some(env) => Some(load_environment(&storage, env).await?),
+                None => None,
+            };
+            lint_requests(&requests, variables.as_ref());
+        }
+
+        Some(Commands::History {
+            action: None,
+            limit,
+        }) => {
             let history = storage.get_history(limit).await?;
             print_history(&history);
         }
 
+        Some(Commands::History {
+            action: Some(action),
+            ..
+        }) => {
+            handle_history_action(action, &mut storage, &config).await?;
+        }
+
+        Some(Commands::Auth { action }) => {
+            let mut config = config;
+            handle_auth_action(action, &client, &mut config).await?;
+        }
+
+        Some(Commands::Profile { .. } | Commands::Completions { .. } | Commands::Doctor { .. }) => {
+            unreachable!("handled before loading config")
+        }
+
         Some(Commands::Config { action }) => {
             handle_config_action(action, &config).await?;
         }
 
+        Some(Commands::Cache {
+            action: CacheAction::Clear,
+        }) => {
+            let removed = storage::ResponseCache::new().clear().await?;
+            cli::print_success(&format!("Removed {} cached responses", removed));
+        }
+
+        Some(Commands::Run { names, failure }) => {
+            let variables = match cli.env {
+                Some(ref env) => Some(load_environment(&storage, env).await?),
+                None => None,
+            };
+            run_saved_requests(
+                &client,
+                &mut storage,
+                &formatter,
+                &display,
+                &config,
+                request_log.as_ref(),
+                &names,
+                variables.as_ref(),
+                &cli.assertions,
+                &file_checks,
+                cli.auth_token.as_deref(),
+                cli.silent,
+                failure.mode(),
+            )
+            .await?;
+        }
+
+        Some(Commands::Test {
+            collection,
+            failure,
+        }) => {
+            run_collection_tests(
+                &client,
+                &mut storage,
+                request_log.as_ref(),
+                &collection,
+                cli.env.as_deref(),
+                failure.mode(),
+            )
+            .await?;
+        }
+
+        Some(Commands::Ws {
+            url,
+            headers,
+            send,
+            wait,
+        }) => {
+            let headers = headers
+                .iter()
+                .map(|h| utils::parse_header(h))
+                .collect::<Result<Vec<_>>>()?;
+            let session = websocket::WebSocketSession::new(&url, headers)?;
+
+            let summary = match send {
+                Some(message) => {
+                    session
+                        .send_once(&message, std::time::Duration::from_secs(wait))
+                        .await
+                }
+                None => session.run_interactive().await,
+            };
+            storage.add_websocket_to_history(&url, &summary).await?;
+
+            if let Some(ref error) = summary.error {
+                cli::print_error(&format!("WebSocket session failed: {}", error));
+                std::process::exit(1);
+            }
+            websocket::print_summary(&summary);
+        }
+
+        Some(Commands::Collection { action }) => {
+            handle_collection_action(
+                action,
+                &client,
+                &mut storage,
+                &config,
+                request_log.as_ref(),
+                cli.env.as_deref(),
+            )
+            .await?;
+        }
+
+        Some(Commands::Env { action }) => {
+            handle_env_action(action, &mut storage, &config).await?;
+        }
+
+        Some(Commands::Openapi { action }) => {
+            handle_openapi_action(action, &mut storage).await?;
+        }
+
+        Some(Commands::Import { file, format }) => {
+            import_requests(&mut storage, &config, &file, &format).await?;
+        }
+
         Some(Commands::Export { output, format }) => {
             export_requests(&storage, output.as_deref(), &format).await?;
         }
 
+        Some(Commands::ExportHar {
+            last,
+            output,
+            no_mask,
+        }) => {
+            export_har(&storage, last, output.as_deref(), !no_mask).await?;
+        }
+
+        Some(Commands::Backup { action }) => {
+            handle_backup_action(action, &mut storage, &config).await?;
+        }
+
         Some(Commands::Version) => {
             cli::print_version();
         }
 
         None => {
+            if let Some(concurrency) = cli.parallel {
+                return run_parallel(
+                    &cli,
+                    client,
+                    &mut storage,
+                    &config,
+                    request_log.as_ref(),
+                    &file_checks,
+                    concurrency as usize,
+                )
+                .await;
+            }
+
             // Direct request mode
             if let Some(ref url) = cli.url {
-                let mut request = build_request_from_cli(&cli, url, &config)?;
+                let variables = match cli.env {
+                    Some(ref env) => Some(load_environment(&storage, env).await?),
+                    None => None,
+                };
+                let mut request = build_request_from_cli(&cli, url, &config, variables.as_ref())?;
+                warn_large_body(&request, &config.network);
+                let schema = load_schema(&cli)?;
 
                 if let Some(ref name) = cli.save {
                     request.name = name.clone();
                     storage.save_request(name, &request).await?;
                     cli::print_success(&format!("Request saved as '{}'", name));
+                    if config.general.redact_secrets || cli.redact_on_save {
+                        let variables = request.clone().redact_secrets();
+                        if !variables.is_empty() {
+                            cli::print_info(&format!(
+                                "Secrets were replaced with placeholders; set {} before loading it",
+                                variables.join(", ")
+                            ));
+                        }
+                    }
+                }
+
+                // Stored tokens are resolved after saving so they never end up in saved requests
+                if let Some(ref token_name) = cli.auth_token {
+                    let mut config = config.clone();
+                    let value =
+                        auth::authorization_header(&client, &mut config, token_name).await?;
+                    request.add_header("Authorization".to_string(), value);
+                }
+                if cli.watch.is_some() {
+                    return watch(
+                        &cli,
+                        &client,
+                        &mut storage,
+                        &formatter,
+                        &display,
+                        &config.network,
+                        request_log.as_ref(),
+                        &request,
+                    )
+                    .await;
                 }
+                // Likewise for request IDs, which are only good for one send
+                set_request_id(&mut request, &config.network, cli.request_id.as_deref());
 
-                match client.execute_request(&request).await {
+                let result = send(&client, &request, pagination.as_ref()).await;
+                if let Some(ref log) = request_log {
+                    log.record(&request, &result).await?;
+                }
+                if cli.notify {
+                    cli::notify_completion(&completion_summary(&request, &result));
+                }
+
+                match result {
                     Ok(response) => {
                         // Save to history
                         storage.add_to_history(&request, &response).await?;
+                        if let Some(ref name) = cli.save {
+                            advance_cursor(&mut storage, name, &mut request, &response).await?;
+                        }
 
                         // Format and display response
-                        if !cli.silent {
-                            let merged_cli = merge_cli_with_config(&cli, &config);
-                            formatter.display_response(&response, &merged_cli).await?;
+                        if !(cli.silent || (cli.fail && response.status >= 400)) {
+                            formatter.display_response(&response, &display).await?;
+                        }
+                        print_stats_json(&cli, &response).await?;
+                        save_fixture(&cli, &request, &response).await?;
+                        exit_on_http_error(&cli, &response);
+
+                        let assertions_passed =
+                            check_assertions(&request.assertions, &file_checks, &response)?;
+                        if !(check_schema(&cli, schema.as_ref(), &response).await?
+                            && assertions_passed)
+                        {
+                            std::process::exit(1);
                         }
                     }
                     Err(e) => {
                         let error_chain = utils::format_error_chain(&e);
                         storage.add_error_to_history(&request, &error_chain).await?;
-                        cli::print_error(&format!("Request failed: {}", error_chain));
+                        cli::print_request_error(&e);
                         std::process::exit(1);
                     }
                 }
             } else if let Some(ref name) = cli.load {
                 match storage.get_request(name).await? {
-                    Some(request) => match client.execute_request(&request).await {
-                        Ok(response) => {
-                            storage.add_to_history(&request, &response).await?;
+                    Some(mut request) => {
+                        if let Some(ref env) = cli.env {
+                            request = request
+                                .substitute_variables(&load_environment(&storage, env).await?)?;
+                        }
+                        request.assertions.extend(cli.assertions.iter().cloned());
+                        assertions::parse_all(&request.assertions)?;
+                        if let (Some(field), Some(param)) = (&cli.cursor_field, &cli.cursor_param) {
+                            request.cursor = Some(request::Cursor {
+                                field: field.clone(),
+                                param: param.clone(),
+                            });
+                        }
+                        request.apply_cursor();
+                        if let Some(ref hmac) = cli.hmac {
+                            request.hmac = Some(request::HmacSignature::parse(hmac)?);
+                        }
+                        if let Some(ref host) = cli.host_header {
+                            set_host_header(&mut request, host);
+                        }
+                        if cli.method_override {
+                            override_method(&mut request);
+                        }
+                        warn_large_body(&request, &config.network);
+                        let schema = load_schema(&cli)?;
+
+                        if let Some(ref token_name) = cli.auth_token {
+                            let mut config = config.clone();
+                            let value =
+                                auth::authorization_header(&client, &mut config, token_name)
+                                    .await?;
+                            request.add_header("Authorization".to_string(), value);
+                        }
 
-                            if !cli.silent {
-                                let merged_cli = merge_cli_with_config(&cli, &config);
-                                formatter.display_response(&response, &merged_cli).await?;
-                            }
+                        if cli.watch.is_some() {
+                            return watch(
+                                &cli,
+                                &client,
+                                &mut storage,
+                                &formatter,
+                                &display,
+                                &config.network,
+                                request_log.as_ref(),
+                                &request,
+                            )
+                            .await;
                         }
-                        Err(e) => {
-                            let error_chain = utils::format_error_chain(&e);
-                            storage.add_error_to_history(&request, &error_chain).await?;
-                            cli::print_error(&format!("Request failed: {}", error_chain));
-                            std::process::exit(1);
+                        set_request_id(&mut request, &config.network, cli.request_id.as_deref());
+
+                        let result = send(&client, &request, pagination.as_ref()).await;
+                        if let Some(ref log) = request_log {
+                            log.record(&request, &result).await?;
+                        }
+                        if cli.notify {
+                            cli::notify_completion(&completion_summary(&request, &result));
+                        }
+
+                        match result {
+                            Ok(response) => {
+                                storage.add_to_history(&request, &response).await?;
+                                advance_cursor(&mut storage, name, &mut request, &response).await?;
+
+                                if !(cli.silent || (cli.fail && response.status >= 400)) {
+                                    formatter.display_response(&response, &display).await?;
+                                }
+                                print_stats_json(&cli, &response).await?;
+                                save_fixture(&cli, &request, &response).await?;
+                                exit_on_http_error(&cli, &response);
+
+                                let assertions_passed =
+                                    check_assertions(&request.assertions, &file_checks, &response)?;
+                                if !(check_schema(&cli, schema.as_ref(), &response).await?
+                                    && assertions_passed)
+                                {
+                                    std::process::exit(1);
+                                }
+                            }
+                            Err(e) => {
+                                let error_chain = utils::format_error_chain(&e);
+                                storage.add_error_to_history(&request, &error_chain).await?;
+                                cli::print_request_error(&e);
+                                std::process::exit(1);
+                            }
                         }
-                    },
+                    }
                     None => {
                         cli::print_error(&format!("Request '{}' not found", name));
                         std::process::exit(1);
@@ -364,238 +1466,1210 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-fn merge_cli_with_config(cli: &Cli, config: &Config) -> Cli {
-    let mut merged = cli.clone();
+// Polls until interrupted or --watch-until passes; only changes of state go to history
+#[allow(clippy::too_many_arguments)]
+async fn watch(
+    cli: &Cli,
+    client: &TerziClient,
+    storage: &mut Storage,
+    formatter: &ResponseFormatter,
+    display: &DisplayOptions,
+    network: &config::NetworkConfig,
+    request_log: Option<&request_log::RequestLog>,
+    request: &request::SavedRequest,
+) -> Result<()> {
+    use std::io::IsTerminal;
+
+    let interval = cli.watch.unwrap_or(std::time::Duration::from_secs(5));
+    let until = cli
+        .watch_until
+        .as_deref()
+        .map(assertions::Assertion::parse)
+        .transpose()?;
+
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_state: Option<String> = None;
+    let mut changes: Vec<String> = Vec::new();
+    let mut poll = 0;
+
+    loop {
+        ticker.tick().await;
+        poll += 1;
+        // Every poll is its own send, so a generated request ID is fresh each time
+        let mut request = request.clone();
+        set_request_id(&mut request, network, cli.request_id.as_deref());
+        let request = &request;
+        let result = client.execute_request(request).await;
+        if let Some(log) = request_log {
+            log.record(request, &result).await?;
+        }
 
-    // Use config defaults for output settings if CLI uses defaults
-    if merged.output == "auto" {
-        merged.output = config.output.default_format.clone();
-    }
+        let state = match &result {
+            Ok(response) => watch_state(response, until.as_ref()),
+            Err(e) => format!("failed: {}", utils::format_error_chain(e)),
+        };
+        if last_state.as_ref() != Some(&state) {
+            match &result {
+                Ok(response) => storage.add_to_history(request, response).await?,
+                Err(e) => {
+                    storage
+                        .add_error_to_history(request, &utils::format_error_chain(e))
+                        .await?
+                }
+            }
+            changes.push(format!(
+                "{}  {}",
+                chrono::Local::now().format("%H:%M:%S"),
+                state
+            ));
+            last_state = Some(state);
+        }
 
-    if merged.pretty == true && cli.pretty == true {
-        merged.pretty = config.output.pretty_print;
-    }
+        if !cli.silent {
+            if std::io::stdout().is_terminal() {
+                print!("\x1b[2J\x1b[H");
+            }
+            println!(
+                "{}",
+                format!(
+                    "Every {}: {} {}  (poll #{}, Ctrl+C to stop)",
+                    utils::format_duration(interval),
+                    request.method,
+                    request.url,
+                    poll
+                )
+                .bright_black()
+            );
+            println!();
+            match &result {
+                Ok(response) => formatter.display_response(response, display).await?,
+                Err(e) => cli::print_request_error(e),
+            }
+            println!();
+            println!("{}", "Changes:".bright_yellow().bold());
+            for change in &changes[changes.len().saturating_sub(10)..] {
+                println!("  {}", change);
+            }
+        }
 
-    if !merged.include_headers {
-        merged.include_headers = config.output.show_headers;
+        if let (Some(assertion), Ok(response)) = (&until, &result)
+            && assertion.evaluate(response).passed
+        {
+            cli::print_success(&format!("Condition met: {}", assertion.source));
+            return Ok(());
+        }
     }
+}
 
-    merged
+// The status code, plus the field --watch-until looks at when it is not the status
+fn watch_state(response: &client::Response, until: Option<&assertions::Assertion>) -> String {
+    let status = format!("HTTP {}", response.status);
+    match until.map(|assertion| (&assertion.subject, assertion)) {
+        Some((assertions::Subject::JsonPath(path), assertion)) => {
+            format!(
+                "{}, {} = {}",
+                status,
+                path,
+                assertion.evaluate(response).actual
+            )
+        }
+        Some((assertions::Subject::Header(name), assertion)) => {
+            format!(
+                "{}, {} = {}",
+                status,
+                name,
+                assertion.evaluate(response).actual
+            )
+        }
+        _ => status,
+    }
 }
 
-fn build_request_from_cli(cli: &Cli, url: &str, config: &Config) -> Result<request::SavedRequest> {
-    // Validate URL first
-    if !utils::is_valid_url(url) {
-        return Err(anyhow::anyhow!(
-            "Invalid URL: {}. Please provide a valid URL starting with http:// or https://",
-            url
-        ));
+fn completion_summary(
+    request: &request::SavedRequest,
+    result: &Result<client::Response>,
+) -> String {
+    match result {
+        Ok(response) => format!(
+            "{} {} → {} ({})",
+            request.method,
+            request.url,
+            response.status,
+            response.duration_human()
+        ),
+        Err(_) => format!("{} {} → failed", request.method, request.url),
     }
+}
 
-    // Validate method
-    utils::validate_method(&cli.method)?;
+fn check_assertions(
+    sources: &[String],
+    file_checks: &[assertions::Assertion],
+    response: &client::Response,
+) -> Result<bool> {
+    if sources.is_empty() && file_checks.is_empty() {
+        return Ok(true);
+    }
 
-    let mut builder = RequestBuilder::new(url, &cli.method)?;
+    let mut checks = assertions::parse_all(sources)?;
+    checks.extend(file_checks.iter().cloned());
+    let results = assertions::evaluate_all(&checks, response);
+    println!();
+    println!("{}", "Assertions:".bright_yellow().bold());
+    assertions::print_results(&results);
 
-    // Add headers
-    for header in &cli.headers {
-        if let Some((key, value)) = header.split_once(':') {
-            let key = key.trim();
-            let value = value.trim();
-            if key.is_empty() {
-                return Err(anyhow::anyhow!(
-                    "Invalid header format: '{}'. Use 'key:value'",
-                    header
-                ));
-            }
+    Ok(results.iter().all(|r| r.passed))
+}
 
-            // Validate header name and value
-            if !utils::is_valid_header_name(key) {
-                return Err(anyhow::anyhow!(
-                    "Invalid header name: '{}'. Header names must be ASCII and cannot contain ':' or newlines",
-                    key
-                ));
-            }
-            if !utils::is_valid_header_value(value) {
-                return Err(anyhow::anyhow!(
-                    "Invalid header value: '{}'. Header values cannot contain newlines",
-                    value
-                ));
-            }
+fn load_schema(cli: &Cli) -> Result<Option<schema::SchemaValidator>> {
+    cli.validate_schema
+        .as_deref()
+        .map(schema::SchemaValidator::load)
+        .transpose()
+}
 
-            builder = builder.header(key, value);
-        } else {
-            return Err(anyhow::anyhow!(
-                "Invalid header format: '{}'. Use 'key:value'",
-                header
-            ));
-        }
+// Runs --validate-schema and --save-schema once a response arrives
+async fn check_schema(
+    cli: &Cli,
+    validator: Option<&schema::SchemaValidator>,
+    response: &client::Response,
+) -> Result<bool> {
+    if let Some(ref path) = cli.save_schema {
+        schema::save_inferred_schema(path, response).await?;
+        cli::print_success(&format!("Schema written to {}", path.display()));
     }
 
-    // Add auth
-    if let Some(ref auth) = cli.auth {
-        builder = builder.auth(auth)?;
-    }
+    let Some(validator) = validator else {
+        return Ok(true);
+    };
+    let violations = validator.validate(&response.body);
+    schema::print_violations(validator.source(), &violations);
+    Ok(violations.is_empty())
+}
 
-    // Add body (validate only one body type)
-    let mut body_count = 0;
-    if cli.json.is_some() {
-        body_count += 1;
-    }
-    if cli.body.is_some() {
-        body_count += 1;
-    }
-    if !cli.form_data.is_empty() {
-        body_count += 1;
+async fn run_collection_tests(
+    client: &TerziClient,
+    storage: &mut Storage,
+    request_log: Option<&request_log::RequestLog>,
+    name: &str,
+    environment: Option<&str>,
+    mode: runner::FailureMode,
+) -> Result<()> {
+    let Some(collection) = storage.get_collection(name).await? else {
+        cli::print_error(&format!("Collection '{}' not found", name));
+        std::process::exit(1);
+    };
+    // Without an environment, requests are sent as saved, placeholders included
+    let variables = match environment {
+        Some(env) => Some(load_environment(storage, env).await?),
+        None => None,
+    };
+
+    if collection.requests.is_empty() {
+        cli::print_info(&format!("Collection '{}' has no requests", name));
+        return Ok(());
     }
 
-    if body_count > 1 {
-        return Err(anyhow::anyhow!(
-            "Only one body type allowed: --json, --body, or --form"
-        ));
+    print!(
+        "{} {} ({} requests)",
+        "Testing collection".bright_yellow().bold(),
+        collection.name.bright_white().bold(),
+        collection.requests.len()
+    );
+    match environment {
+        Some(env) => println!(" with environment {}", env.bright_cyan()),
+        None => println!(),
     }
+    println!();
 
-    if let Some(ref json) = cli.json {
-        // Validate JSON before adding
-        if !utils::is_valid_json(json) {
-            return Err(anyhow::anyhow!(
-                "Invalid JSON provided: {}. Please check your JSON syntax",
-                json
-            ));
-        }
-        builder = builder.json_body(json)?;
-    } else if let Some(ref body) = cli.body {
-        builder = builder.raw_body(body);
-    } else if !cli.form_data.is_empty() {
-        let mut form = HashMap::new();
-        for pair in &cli.form_data {
-            if let Some((key, value)) = pair.split_once('=') {
-                form.insert(key.to_string(), value.to_string());
-            } else {
-                return Err(anyhow::anyhow!(
-                    "Invalid form data format: '{}'. Use 'key=value'",
-                    pair
-                ));
+    let total = collection.requests.len();
+    let mut summary = runner::RunSummary::new(mode);
+
+    for (index, request) in collection.requests.iter().enumerate() {
+        let prepared = assertions::parse_all(&request.assertions).and_then(|checks| {
+            let request = match variables {
+                Some(ref variables) => request.substitute_variables(variables)?,
+                None => request.clone(),
+            };
+            Ok((checks, request))
+        });
+        let result = match prepared {
+            Err(e) => {
+                println!("{} {}", "✗".bright_red(), request.name.bright_white());
+                runner::RunResult::failed(&request.name, None, None, &e.to_string())
+            }
+            Ok((checks, ref request)) => {
+                let result = client.execute_request(request).await;
+                if let Some(log) = request_log {
+                    log.record(request, &result).await?;
+                }
+                match result {
+                    Ok(response) => {
+                        storage.add_to_history(request, &response).await?;
+                        let (result, results) = judge_response(&request.name, &checks, &response);
+
+                        let mark = if result.is_failure() {
+                            "✗".bright_red()
+                        } else {
+                            "✓".bright_green()
+                        };
+                        println!(
+                            "{} {} {} {}",
+                            mark,
+                            request.name.bright_white(),
+                            response.status.to_string().bold(),
+                            format!("({})", response.duration_human()).bright_black()
+                        );
+                        assertions::print_results(&results);
+                        result
+                    }
+                    Err(e) => {
+                        let error_chain = utils::format_error_chain(&e);
+                        storage.add_error_to_history(request, &error_chain).await?;
+                        println!("{} {}", "✗".bright_red(), request.name.bright_white());
+                        runner::RunResult::failed(&request.name, None, None, &error_chain)
+                    }
+                }
             }
+        };
+
+        if !summary.record(result) {
+            summary.skip(total - index - 1);
+            break;
         }
-        builder = builder.form_body(form)?;
     }
 
-    // Use CLI timeout if not default (30), otherwise use config default
-    let timeout = if cli.timeout == 30 {
-        config.general.default_timeout
-    } else {
-        cli.timeout
-    };
-
-    // Validate timeout
-    utils::validate_timeout(timeout)?;
+    summary.print_report();
+    summary.print();
+    std::process::exit(summary.exit_code());
+}
 
-    builder = builder.timeout(timeout);
+// Only a successful response moves the cursor, so a failed poll is retried from the same place
+async fn advance_cursor(
+    storage: &mut Storage,
+    name: &str,
+    request: &mut request::SavedRequest,
+    response: &client::Response,
+) -> Result<()> {
+    if request.cursor.is_some()
+        && response.is_success()
+        && request.advance_cursor(&response.body).is_some()
+    {
+        storage.save_request_state(name, request).await?;
+    }
+    Ok(())
+}
 
-    // Use CLI follow_redirects if explicitly set, otherwise use config default
-    let follow_redirects = if cli.follow_redirects {
-        cli.follow_redirects
+// Without assertions a request passes when it doesn't return an error status
+fn judge_response(
+    name: &str,
+    checks: &[assertions::Assertion],
+    response: &client::Response,
+) -> (runner::RunResult, Vec<assertions::AssertionResult>) {
+    let results = assertions::evaluate_all(checks, response);
+    let failures = results.iter().filter(|r| !r.passed).count();
+
+    let passed = results.len() - failures;
+
+    let result = if checks.is_empty() && response.status >= 400 {
+        runner::RunResult::failed(
+            name,
+            Some(response.status),
+            Some(response.duration),
+            &format!("status {}", response.status),
+        )
+    } else if failures > 0 {
+        runner::RunResult::failed(
+            name,
+            Some(response.status),
+            Some(response.duration),
+            &format!("{} of {} assertions failed", failures, checks.len()),
+        )
     } else {
-        config.general.follow_redirects
+        runner::RunResult::passed(name, response.status, response.duration)
     };
-    builder = builder.follow_redirects(follow_redirects);
 
-    Ok(builder.build())
+    (result.with_assertions(passed, checks.len()), results)
 }
 
-fn print_request_list(requests: &[request::SavedRequest]) {
-    if requests.is_empty() {
-        cli::print_info(
-            "No saved requests found. Create one with 'terzi --save <name> <url>' or 'terzi interactive'",
+#[allow(clippy::too_many_arguments)]
+async fn run_saved_requests(
+    client: &TerziClient,
+    storage: &mut Storage,
+    formatter: &ResponseFormatter,
+    display: &DisplayOptions,
+    config: &Config,
+    request_log: Option<&request_log::RequestLog>,
+    names: &[String],
+    variables: Option<&HashMap<String, String>>,
+    extra_assertions: &[String],
+    file_checks: &[assertions::Assertion],
+    auth_token: Option<&str>,
+    silent: bool,
+    mode: runner::FailureMode,
+) -> Result<()> {
+    // Every request shares one client, so keep-alive connections are reused between them
+    let total = names.len();
+    let mut summary = runner::RunSummary::new(mode);
+
+    for (index, name) in names.iter().enumerate() {
+        println!(
+            "{} {} {}",
+            "━━━".bright_black(),
+            name.bright_white().bold(),
+            format!("({}/{})", index + 1, total).bright_black()
         );
-        return;
-    }
 
-    let headers = vec!["Name", "Method", "URL", "Created"];
-    let rows: Vec<Vec<String>> = requests
-        .iter()
-        .map(|req| {
-            vec![
-                req.name.clone(),
-                req.method.clone(),
-                req.url.clone(),
-                req.created_at.format("%Y-%m-%d %H:%M").to_string(),
-            ]
-        })
-        .collect();
+        let result = match storage.get_request(name).await? {
+            None => {
+                cli::print_error(&format!("Request '{}' not found", name));
+                runner::RunResult::failed(name, None, None, "request not found")
+            }
+            Some(request) => {
+                // A request that can't be prepared fails on its own instead of ending the run
+                let prepared = async {
+                    let mut request = match variables {
+                        Some(variables) => request.substitute_variables(variables)?,
+                        None => request,
+                    };
+                    request.assertions.extend(extra_assertions.iter().cloned());
+                    let mut checks = assertions::parse_all(&request.assertions)?;
+                    checks.extend(file_checks.iter().cloned());
+                    request.apply_cursor();
+                    set_request_id(&mut request, &config.network, None);
+
+                    if let Some(token_name) = auth_token {
+                        let mut config = config.clone();
+                        let value =
+                            auth::authorization_header(client, &mut config, token_name).await?;
+                        request.add_header("Authorization".to_string(), value);
+                    }
+                    Ok::<_, anyhow::Error>((request, checks))
+                }
+                .await;
+                match prepared {
+                    Err(e) => {
+                        let error_chain = utils::format_error_chain(&e);
+                        cli::print_error(&error_chain);
+                        runner::RunResult::failed(name, None, None, &error_chain)
+                    }
+                    Ok((mut request, checks)) => {
+                        let result = client.execute_request(&request).await;
+                        if let Some(log) = request_log {
+                            log.record(&request, &result).await?;
+                        }
+                        match result {
+                            Ok(response) => {
+                                storage.add_to_history(&request, &response).await?;
+                                advance_cursor(storage, name, &mut request, &response).await?;
+                                if !silent {
+                                    formatter.display_response(&response, display).await?;
+                                }
+
+                                let (result, results) = judge_response(name, &checks, &response);
+                                if !results.is_empty() {
+                                    println!();
+                                    println!("{}", "Assertions:".bright_yellow().bold());
+                                    assertions::print_results(&results);
+                                }
+                                result
+                            }
+                            Err(e) => {
+                                let error_chain = utils::format_error_chain(&e);
+                                storage.add_error_to_history(&request, &error_chain).await?;
+                                cli::print_request_error(&e);
+                                runner::RunResult::failed(name, None, None, &error_chain)
+                            }
+                        }
+                    }
+                }
+            }
+        };
+        println!();
 
-    let table = utils::create_url_priority_table(headers, rows, 2); // URL is column index 2
-    println!("{}", table);
+        if !summary.record(result) {
+            summary.skip(total - index - 1);
+            break;
+        }
+    }
+
+    summary.print();
+    std::process::exit(summary.exit_code());
 }
 
-fn print_request_details(request: &request::SavedRequest) {
-    println!("📋 Request Details: {}", request.name);
-    println!("🔗 URL: {}", request.url);
-    println!("🔧 Method: {}", request.method);
+// Requests run concurrently on one shared client; results print as they complete
+async fn run_parallel(
+    cli: &Cli,
+    client: TerziClient,
+    storage: &mut Storage,
+    config: &Config,
+    request_log: Option<&request_log::RequestLog>,
+    file_checks: &[assertions::Assertion],
+    concurrency: usize,
+) -> Result<()> {
+    use std::io::{BufRead, IsTerminal};
+    use std::sync::Arc;
+
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Err(anyhow::anyhow!(
+            "--parallel reads URLs from stdin, one per line (e.g. cat urls.txt | terzi --parallel 10)"
+        ));
+    }
+    let urls: Vec<String> = stdin
+        .lock()
+        .lines()
+        .collect::<std::io::Result<Vec<_>>>()?
+        .into_iter()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    if urls.is_empty() {
+        cli::print_info("No URLs on stdin");
+        return Ok(());
+    }
 
-    if !request.headers.is_empty() {
-        println!("📤 Headers:");
+    let variables = match cli.env {
+        Some(ref env) => Some(load_environment(storage, env).await?),
+        None => None,
+    };
+    // The stored token is resolved once and shared, so a refresh happens at most once per run
+    let authorization = match cli.auth_token {
+        Some(ref token_name) => {
+            let mut config = config.clone();
+            Some(auth::authorization_header(&client, &mut config, token_name).await?)
+        }
+        None => None,
+    };
+    let mut requests = Vec::with_capacity(urls.len());
+    for url in &urls {
+        let mut request = build_request_from_cli(cli, url, config, variables.as_ref())?;
+        if let Some(ref value) = authorization {
+            request.add_header("Authorization".to_string(), value.clone());
+        }
+        set_request_id(&mut request, &config.network, cli.request_id.as_deref());
+        let mut checks = assertions::parse_all(&request.assertions)?;
+        checks.extend(file_checks.iter().cloned());
+        requests.push((request, checks));
+    }
 
-        // Define sensitive header patterns
-        let sensitive_patterns = &[
-            r"(?i)authorization",
-            r"(?i)api-key",
-            r"(?i)x-api-key",
-            r"(?i)access[_-]?token",
-            r"(?i)bearer",
-            r"(?i)session",
-            r"(?i)cookie",
-            r"(?i)password",
-            r"(?i)secret",
-        ];
+    println!(
+        "{} {} {}, {} at a time",
+        "Requesting".bright_yellow().bold(),
+        urls.len(),
+        if urls.len() == 1 { "URL" } else { "URLs" },
+        concurrency.min(urls.len())
+    );
+    println!();
+
+    let client = Arc::new(client);
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (request, checks) in requests {
+        let client = client.clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = client.execute_request(&request).await;
+            (request, checks, result)
+        });
+    }
 
-        for (key, value) in &request.headers {
-            // Check if this header contains sensitive data
-            let is_sensitive = sensitive_patterns.iter().any(|pattern| {
-                regex::Regex::new(pattern)
-                    .map(|re| re.is_match(key))
-                    .unwrap_or(false)
-            });
-
-            if is_sensitive {
-                let masked_value = utils::mask_sensitive_data(value, &[r".*"]);
-                println!("  {}: {}", key, masked_value);
-            } else {
-                println!("  {}: {}", key, value);
+    let total = tasks.len();
+    let mut summary = runner::RunSummary::new(cli.failure.mode());
+    let mut completed = 0;
+    while let Some(joined) = tasks.join_next().await {
+        completed += 1;
+        let (request, checks, result) = joined?;
+        if let Some(log) = request_log {
+            log.record(&request, &result).await?;
+        }
+        let result = match result {
+            Ok(response) => {
+                storage.add_to_history(&request, &response).await?;
+                let (result, _) = judge_response(&request.url, &checks, &response);
+                let mark = if result.is_failure() {
+                    "✗".bright_red()
+                } else {
+                    "✓".bright_green()
+                };
+                println!(
+                    "{} {} {} {}",
+                    mark,
+                    request.url.bright_white(),
+                    response.status.to_string().bold(),
+                    format!("({})", response.duration_human()).bright_black()
+                );
+                result
+            }
+            Err(e) => {
+                let error_chain = utils::format_error_chain(&e);
+                storage.add_error_to_history(&request, &error_chain).await?;
+                println!(
+                    "{} {} {}",
+                    "✗".bright_red(),
+                    request.url.bright_white(),
+                    e.root_cause().to_string().bright_red()
+                );
+                runner::RunResult::failed(&request.url, None, None, &error_chain)
             }
+        };
+        // Requests still in flight are cancelled along with the ones not started
+        if !summary.record(result) {
+            tasks.abort_all();
+            summary.skip(total - completed);
+            break;
         }
     }
 
-    if let Some(ref body) = request.body {
-        // Mask sensitive data in body (tokens, passwords, etc.)
-        let sensitive_body_patterns = &[
-            r#""password"\s*:\s*"[^"]*""#,
-            r#""token"\s*:\s*"[^"]*""#,
-            r#""secret"\s*:\s*"[^"]*""#,
-            r#""api_key"\s*:\s*"[^"]*""#,
-            r#""access_token"\s*:\s*"[^"]*""#,
-        ];
+    summary.print();
+    std::process::exit(summary.exit_code());
+}
 
-        let masked_body = utils::mask_sensitive_data(body, sensitive_body_patterns);
-        println!("📝 Body: {}", masked_body);
+fn pagination(cli: &Cli) -> Result<Option<client::Pagination>> {
+    if !cli.paginate && cli.paginate_jsonpath.is_none() {
+        return Ok(None);
+    }
+    if let Some(ref path) = cli.paginate_jsonpath {
+        utils::parse_json_path(path)?;
     }
+    Ok(Some(client::Pagination {
+        max_pages: cli.max_pages as usize,
+        cursor_path: cli.paginate_jsonpath.clone(),
+        cursor_param: cli.paginate_param.clone(),
+    }))
+}
 
-    println!("📅 Created: {}", request.created_at);
+async fn send(
+    client: &TerziClient,
+    request: &request::SavedRequest,
+    pagination: Option<&client::Pagination>,
+) -> Result<client::Response> {
+    match pagination {
+        Some(pagination) => client.execute_paginated(request, pagination).await,
+        None => client.execute_request(request).await,
+    }
 }
 
-fn print_history(history: &[storage::HistoryEntry]) {
-    if history.is_empty() {
-        cli::print_info("No request history found. Make some requests first!");
-        return;
+fn display_options(cli: &Cli, config: &Config) -> DisplayOptions {
+    let mut options = DisplayOptions::from_config(config);
+
+    // Explicit CLI flags win over config defaults
+    if cli.output != "auto" {
+        options.output = cli.output.clone();
+    } else if !cli.columns.is_empty() {
+        options.output = "table".to_string();
+    }
+    options.include_headers |= cli.include_headers;
+    options.verbose = cli.verbose;
+    options.summary_only = cli.summary_only;
+    options.headers_only = cli.headers_only || cli.head;
+    options.grep = cli.grep.clone();
+    options.grep_context = cli.grep_context;
+    options.columns = cli.columns.clone();
+    options.jq = cli.jq.clone();
+    // --pretty and --compact override output.pretty_print; without either, config decides
+    if cli.pretty {
+        options.pretty = true;
+    }
+    if let Some(indent) = cli.json_indent {
+        options.pretty = true;
+        options.json_indent = indent as usize;
+    }
+    if cli.compact {
+        options.pretty = false;
+    }
+    if let Some(depth) = cli.flatten_depth {
+        options.flatten_depth = Some(depth as usize);
+    } else if cli.flatten {
+        options.flatten_depth = Some(output::DEFAULT_FLATTEN_DEPTH);
     }
 
-    let headers = vec!["Time", "Method", "URL", "Status", "Duration"];
+    options
+}
+
+fn request_log(cli: &Cli, config: &Config) -> Option<request_log::RequestLog> {
+    let path = cli.log.as_ref().or(config.general.log_file.as_ref())?;
+    Some(request_log::RequestLog::new(
+        path,
+        config.general.log_mask_secrets && !cli.log_unmasked,
+    ))
+}
+
+// Stats go to stderr or a file so stdout keeps only the body
+async fn print_stats_json(cli: &Cli, response: &client::Response) -> Result<()> {
+    let stats = serde_json::json!({
+        "status": response.status,
+        "duration_ms": response.duration.as_millis() as u64,
+        "size": response.size,
+        "url": response.url,
+        "method": response.method,
+    });
+
+    if let Some(ref path) = cli.print_stats_json_file {
+        tokio::fs::write(path, format!("{}\n", stats))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to write stats to {}: {}", path, e))?;
+    } else if cli.print_stats_json {
+        eprintln!("{}", stats);
+    }
+    Ok(())
+}
+
+async fn save_fixture(
+    cli: &Cli,
+    request: &request::SavedRequest,
+    response: &client::Response,
+) -> Result<()> {
+    if let Some(ref path) = cli.save_response {
+        fixture::Fixture::new(request, response).save(path).await?;
+        cli::print_success(&format!("Response saved to {}", path.display()));
+    }
+    Ok(())
+}
+
+// --fail and --fail-with-body turn an HTTP error status into a failing exit code
+fn exit_on_http_error(cli: &Cli, response: &client::Response) {
+    if (cli.fail || cli.fail_with_body) && response.status >= 400 {
+        cli::print_error(&format!(
+            "{} {} returned HTTP {}",
+            response.method, response.url, response.status
+        ));
+        std::process::exit(1);
+    }
+}
+
+// One-off network settings only reach the client, so they are never written back to disk
+fn client_config(cli: &Cli, config: &Config) -> Config {
+    let mut config = config.clone();
+    if cli.silent {
+        config.output.show_progress = false;
+    }
+    if cli.insecure {
+        config.network.verify_ssl = false;
+    }
+    if let Some(ref cert) = cli.cert {
+        config.network.client_cert_path = Some(cert.clone());
+        config.network.client_key_path = cli.key.clone();
+    }
+    if let Some(ref cacert) = cli.cacert {
+        config.network.ca_cert_path = Some(cacert.clone());
+    }
+    if let Some(ref proxy) = cli.proxy {
+        config.network.proxy_url = Some(proxy.clone());
+    }
+    config.output.dump_request = cli.verbose_request;
+    // -o http shows the request as sent, so the client has to keep it
+    config.output.capture_request = if cli.output != "auto" {
+        cli.output == "http"
+    } else {
+        cli.columns.is_empty() && config.output.default_format == "http"
+    };
+    config.auth.token_file = cli.auth_token_file.clone();
+    config.network.bypass_proxy = cli.no_proxy;
+    config.network.unix_socket = cli.unix_socket.clone();
+    config.network.resolve = cli.resolve.clone();
+    config.network.connect_to = cli.connect_to.clone();
+    if cli.no_default_headers {
+        config.network.default_headers.clear();
+    }
+    // Binding to an unspecified local address limits connections to that family
+    if cli.ipv4 {
+        config.network.local_address = Some(std::net::Ipv4Addr::UNSPECIFIED.into());
+    } else if cli.ipv6 {
+        config.network.local_address = Some(std::net::Ipv6Addr::UNSPECIFIED.into());
+    }
+    if let Some(limit) = cli.max_response_size {
+        config.network.max_response_size = Some(limit);
+    }
+    config
+}
+
+fn build_request_from_cli(
+    cli: &Cli,
+    url: &str,
+    config: &Config,
+    variables: Option<&HashMap<String, String>>,
+) -> Result<request::SavedRequest> {
+    // With --env, placeholders are filled before anything is validated
+    let fill = |text: &str| match variables {
+        Some(variables) => request::substitute_variables(text, variables),
+        None => Ok(text.to_string()),
+    };
+    let mut url = fill(url)?;
+    // Opt-in, since a pre-encoded URL may be meant exactly as typed
+    if cli.encode_url {
+        url = utils::normalize_url(&url)?;
+    }
+    // Added after --encode-url so neither kind of parameter is encoded twice
+    let mut query = Vec::new();
+    for pair in &cli.query {
+        let (key, value) = split_pair(&fill(pair)?, "query parameter")?;
+        query.push(format!(
+            "{}={}",
+            urlencoding::encode(&key),
+            urlencoding::encode(&value)
+        ));
+    }
+    for pair in &cli.query_raw {
+        let (key, value) = split_pair(&fill(pair)?, "query parameter")?;
+        query.push(format!("{}={}", key, value));
+    }
+    let url = &utils::append_query(&url, &query);
+
+    // Validate URL first
+    if !utils::is_valid_url(url) {
+        return Err(anyhow::anyhow!(
+            "Invalid URL: {}. Please provide a valid URL starting with http:// or https://",
+            url
+        ));
+    }
+
+    // Validate method
+    utils::validate_method(&cli.method)?;
+
+    // --head only swaps a GET; other methods are sent as asked
+    let method = if cli.head && cli.method.eq_ignore_ascii_case("GET") {
+        "HEAD"
+    } else {
+        &cli.method
+    };
+    let mut builder = RequestBuilder::new(url, method)?;
+
+    // Add headers
+    for header in &cli.headers {
+        let (key, value) = utils::parse_header(&fill(header)?)?;
+        builder = builder.header(&key, &value);
+    }
+
+    // Add auth
+    if let Some(ref auth) = cli.auth {
+        builder = builder.auth(&fill(auth)?)?;
+    }
+    if let Some(ref hmac) = cli.hmac {
+        builder = builder.hmac(&fill(hmac)?)?;
+    }
+
+    // Add body (validate only one body type)
+    let mut body_count = 0;
+    if cli.json.is_some() {
+        body_count += 1;
+    }
+    if cli.body.is_some() {
+        body_count += 1;
+    }
+    if !cli.form_data.is_empty() || !cli.form_raw.is_empty() {
+        body_count += 1;
+    }
+    if cli.data_binary.is_some() {
+        body_count += 1;
+    }
+    if cli.merge_patch.is_some() {
+        body_count += 1;
+    }
+    if cli.json_patch.is_some() {
+        body_count += 1;
+    }
+    if cli.stream_body {
+        body_count += 1;
+    }
+
+    if body_count > 1 {
+        return Err(anyhow::anyhow!(
+            "Only one body type allowed: --json, --body, --form, --data-binary, --merge-patch, --json-patch or --stream-body"
+        ));
+    }
+
+    if cli.edit_body {
+        // A raw --body stays raw; anything else is edited as JSON and has to parse
+        let (initial, extension) = match (&cli.body, &cli.json) {
+            (Some(body), _) => (body.as_str(), "txt"),
+            (None, Some(json)) => (json.as_str(), "json"),
+            (None, None) => ("{}", "json"),
+        };
+        let edited = config
+            .edit_text(initial, extension)
+            .map_err(|e| anyhow::anyhow!("{}; request not sent", e))?;
+        let edited = fill(edited.trim_end())?;
+        if extension == "txt" {
+            builder = builder.raw_body(&edited);
+        } else {
+            serde_json::from_str::<serde_json::Value>(&request::render_dynamic(&edited)?)
+                .map_err(|e| anyhow::anyhow!("Edited body is not valid JSON: {}", e))?;
+            builder = builder.json_body(&edited)?;
+        }
+    } else if let Some(ref json) = cli.json {
+        let json = &fill(json)?;
+        // Validate JSON before adding
+        if !utils::is_valid_json(&request::render_dynamic(json)?) {
+            return Err(anyhow::anyhow!(
+                "Invalid JSON provided: {}. Please check your JSON syntax",
+                json
+            ));
+        }
+        builder = builder.json_body(json)?;
+    } else if let Some(ref body) = cli.body {
+        builder = builder.raw_body(&fill(body)?);
+    } else if let Some(ref data) = cli.data_binary {
+        builder = builder.binary_body(data)?;
+    } else if let Some(ref patch) = cli.merge_patch {
+        builder = builder.merge_patch_body(&fill(patch)?)?;
+    } else if let Some(ref patch) = cli.json_patch {
+        builder = builder.json_patch_body(&fill(patch)?)?;
+    } else if cli.stream_body {
+        if std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+            return Err(anyhow::anyhow!(
+                "--stream-body sends stdin as the body; pipe data in (e.g. cat data.ndjson | terzi --stream-body -m POST <url>)"
+            ));
+        }
+        builder = builder.stream_body();
+    } else if !cli.form_data.is_empty() || !cli.form_raw.is_empty() {
+        let mut form = Vec::new();
+        for pair in &cli.form_data {
+            form.push(split_pair(&fill(pair)?, "form data")?);
+        }
+        let mut raw = Vec::new();
+        for pair in &cli.form_raw {
+            raw.push(split_pair(&fill(pair)?, "form data")?);
+        }
+        builder = builder.form_body(form)?.raw_form_fields(raw);
+    }
+
+    // Use CLI timeout if not default (30), otherwise use config default
+    let timeout = if cli.timeout == 30 {
+        config.general.default_timeout
+    } else {
+        cli.timeout
+    };
+
+    // Validate timeout
+    utils::validate_timeout(timeout)?;
+
+    builder = builder.timeout(timeout);
+
+    if let Some(seconds) = cli.connect_timeout {
+        utils::validate_timeout(seconds)?;
+        builder = builder.connect_timeout(seconds);
+    }
+    if let Some(seconds) = cli.read_timeout {
+        utils::validate_timeout(seconds)?;
+        builder = builder.read_timeout(seconds);
+    }
+    if let Some(max) = cli.max_redirects {
+        builder = builder.max_redirects(max);
+    }
+    if let (Some(field), Some(param)) = (&cli.cursor_field, &cli.cursor_param) {
+        if cli.save.is_none() {
+            return Err(anyhow::anyhow!(
+                "--cursor-field is remembered by a saved request; use it with --save or --load"
+            ));
+        }
+        builder = builder.cursor(field, param);
+    }
+
+    // Use CLI -L/--no-follow if explicitly set, otherwise use config default
+    let follow_redirects = if cli.follow_redirects || cli.no_follow {
+        cli.follow_redirects
+    } else {
+        config.general.follow_redirects
+    };
+    builder = builder.follow_redirects(follow_redirects);
+
+    // Validate assertions up front so a typo doesn't cost a request
+    assertions::parse_all(&cli.assertions)?;
+    let mut request = builder.build();
+    request.assertions = cli.assertions.clone();
+    if let Some(ref host) = cli.host_header {
+        set_host_header(&mut request, &fill(host)?);
+    }
+    if cli.method_override {
+        override_method(&mut request);
+    }
+    // A raw --body gets the Content-Type its contents suggest, unless one was given
+    if cli.body.is_some()
+        && !cli.no_guess_content_type
+        && let Some(request::Body::Text(ref body)) = request.body
+        && !request
+            .headers
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case("content-type"))
+    {
+        let content_type = utils::guess_content_type(body).to_string();
+        request
+            .headers
+            .insert("Content-Type".to_string(), content_type);
+    }
+    // Ask for the representation --output renders, unless an Accept was given
+    let format = if cli.output != "auto" {
+        &cli.output
+    } else if !cli.columns.is_empty() {
+        "table"
+    } else {
+        &config.output.default_format
+    };
+    if !cli.no_accept
+        && let Some(accept) = utils::accept_for_format(format)
+        && !request
+            .headers
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case("accept"))
+    {
+        request
+            .headers
+            .insert("Accept".to_string(), accept.to_string());
+    }
+
+    Ok(request)
+}
+
+// --host-header replaces any Host given with -H or saved on the request
+fn set_host_header(request: &mut request::SavedRequest, host: &str) {
+    request
+        .headers
+        .retain(|name, _| !name.eq_ignore_ascii_case("host"));
+    request.headers.insert("Host".to_string(), host.to_string());
+}
+
+// An explicit --request-id replaces any header already set; otherwise one is generated
+// only when network.inject_request_id is on and the request doesn't carry one
+fn set_request_id(
+    request: &mut request::SavedRequest,
+    network: &config::NetworkConfig,
+    id: Option<&str>,
+) {
+    let header = &network.request_id_header;
+    let present = request
+        .headers
+        .keys()
+        .any(|name| name.eq_ignore_ascii_case(header));
+    let id = match id {
+        Some(id) => id.to_string(),
+        None if network.inject_request_id && !present => utils::generate_request_id(),
+        None => return,
+    };
+    request
+        .headers
+        .retain(|name, _| !name.eq_ignore_ascii_case(header));
+    request.headers.insert(header.clone(), id);
+}
+
+fn split_pair(pair: &str, what: &str) -> Result<(String, String)> {
+    pair.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| anyhow::anyhow!("Invalid {} format: '{}'. Use 'key=value'", what, pair))
+}
+
+// On stderr so the warning never ends up in piped response output
+fn warn_large_body(request: &request::SavedRequest, network: &config::NetworkConfig) {
+    let size = request.body.as_ref().and_then(|body| body.size());
+    if let (Some(limit), Some(size)) = (network.warn_request_size, size)
+        && size as u64 > limit
+    {
+        cli::print_stderr_warning(&format!(
+            "Request body is {}, over the {} set in network.warn_request_size",
+            utils::format_bytes(size),
+            utils::format_bytes(limit as usize)
+        ));
+    }
+}
+
+// For proxies that only pass GET and POST; history keeps the POST that was actually sent
+fn override_method(request: &mut request::SavedRequest) {
+    if request.method.eq_ignore_ascii_case("POST") {
+        return;
+    }
+    request
+        .headers
+        .retain(|name, _| !name.eq_ignore_ascii_case("x-http-method-override"));
+    request.headers.insert(
+        "X-HTTP-Method-Override".to_string(),
+        request.method.to_uppercase(),
+    );
+    request.method = "POST".to_string();
+}
+
+fn print_request_list(requests: &[request::SavedRequest]) {
+    if requests.is_empty() {
+        cli::print_info(
+            "No saved requests found. Create one with 'terzi --save <name> <url>' or 'terzi interactive'",
+        );
+        return;
+    }
+
+    let headers = vec!["Name", "Method", "URL", "Body", "Created"];
+    let rows: Vec<Vec<String>> = requests
+        .iter()
+        .map(|req| {
+            vec![
+                req.name.clone(),
+                req.method.clone(),
+                req.url.clone(),
+                req.body
+                    .as_ref()
+                    .and_then(|body| body.size())
+                    .map_or("-".to_string(), utils::format_bytes),
+                req.created_at.format("%Y-%m-%d %H:%M").to_string(),
+            ]
+        })
+        .collect();
+
+    let table = utils::create_url_priority_table(headers, rows, 2); // URL is column index 2
+    println!("{}", table);
+}
+
+fn print_search_results(query: &str, results: &[storage::SearchResult]) {
+    if results.is_empty() {
+        cli::print_info(&format!("No saved requests match '{}'", query));
+        return;
+    }
+
+    for result in results {
+        let request = &result.request;
+        let highlight = |field: &str, text: &str, plain: ColoredString| {
+            if result.matched_field == field {
+                utils::highlight_match(text, query)
+            } else {
+                plain.to_string()
+            }
+        };
+
+        let mut line = format!(
+            "{}  {}  {}",
+            highlight("name", &request.name, request.name.bold()),
+            highlight("method", &request.method, request.method.bright_cyan()),
+            highlight("url", &request.url, request.url.normal())
+        );
+        match result.matched_field {
+            "tag" => line.push_str(&format!(
+                "  [{}]",
+                request
+                    .tags
+                    .iter()
+                    .map(|tag| utils::highlight_match(tag, query))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            "description" => {
+                if let Some(ref description) = request.description {
+                    line.push_str(&format!(
+                        "  - {}",
+                        utils::highlight_match(description, query)
+                    ));
+                }
+            }
+            _ => {}
+        }
+        println!("{}", line);
+    }
+}
+
+fn print_request_details(request: &request::SavedRequest) {
+    println!("📋 Request Details: {}", request.name);
+    println!("🔗 URL: {}", request.url);
+    println!("🔧 Method: {}", request.method);
+
+    if !request.headers.is_empty() {
+        println!("📤 Headers:");
+
+        for (key, value) in &request.headers {
+            println!("  {}: {}", key, utils::mask_header_value(key, value));
+        }
+    }
+
+    if let Some(ref body) = request.body {
+        // Mask sensitive data in body (tokens, passwords, etc.)
+        match body.as_text() {
+            Some(text) => println!("📝 Body: {}", utils::mask_body(text)),
+            None => println!("📝 Body: {}", body),
+        }
+    }
+
+    println!("📅 Created: {}", request.created_at);
+}
+
+fn print_request_diff(old: &request::SavedRequest, new: &request::SavedRequest) {
+    use request::HeaderChange;
+
+    println!(
+        "{} {} {} {}",
+        "Comparing".bright_yellow().bold(),
+        old.name.bright_white().bold(),
+        "→".bright_black(),
+        new.name.bright_white().bold()
+    );
+
+    let diff = old.diff(new);
+    if diff.is_empty() {
+        cli::print_info("No differences in method, URL, headers or body");
+        return;
+    }
+
+    if let Some((ref from, ref to)) = diff.method {
+        println!("🔧 Method: {} → {}", from.bright_red(), to.bright_green());
+    }
+    if let Some((ref from, ref to)) = diff.url {
+        println!("🔗 URL:");
+        println!("  {} {}", "-".bright_red(), from.bright_red());
+        println!("  {} {}", "+".bright_green(), to.bright_green());
+    }
+    if !diff.headers.is_empty() {
+        println!("📤 Headers:");
+        for change in &diff.headers {
+            match change {
+                HeaderChange::Added(name, value) => {
+                    println!("  {}", format!("+ {}: {}", name, value).bright_green())
+                }
+                HeaderChange::Removed(name, value) => {
+                    println!("  {}", format!("- {}: {}", name, value).bright_red())
+                }
+                HeaderChange::Changed(name, from, to) => println!(
+                    "  {} {}: {} → {}",
+                    "~".bright_yellow(),
+                    name,
+                    from.bright_red(),
+                    to.bright_green()
+                ),
+            }
+        }
+    }
+    if !diff.body.is_empty() {
+        println!("📝 Body:");
+        for line in &diff.body {
+            let line = if line.starts_with('+') {
+                line.bright_green()
+            } else if line.starts_with('-') {
+                line.bright_red()
+            } else if line.starts_with("@@") {
+                line.bright_cyan()
+            } else {
+                line.normal()
+            };
+            println!("  {}", line);
+        }
+    }
+}
+
+fn print_history(history: &[storage::HistoryEntry]) {
+    if history.is_empty() {
+        cli::print_info("No request history found. Make some requests first!");
+        return;
+    }
+
+    let headers = vec![
+        "ID",
+        "Time",
+        "Method",
+        "URL",
+        "Status",
+        "Duration",
+        "Resp Size",
+    ];
     let rows: Vec<Vec<String>> = history
         .iter()
         .map(|entry| {
-            let status_display = match entry.response_status {
-                Some(status) => {
+            let status_display = match (entry.response_status, &entry.summary) {
+                (Some(_), Some(summary)) => format!("🔌 {}", summary),
+                (Some(status), None) => {
                     let color = if status >= 200 && status < 300 {
                         "🟢"
                     } else if status >= 400 {
@@ -605,23 +2679,703 @@ fn print_history(history: &[storage::HistoryEntry]) {
                     };
                     format!("{} {}", color, status)
                 }
-                None => "❌ Error".to_string(),
+                (None, _) => "❌ Error".to_string(),
             };
 
             vec![
+                entry.id.chars().take(HISTORY_ID_LENGTH).collect(),
                 entry.timestamp.format("%H:%M:%S").to_string(),
                 entry.method.clone(),
                 entry.url.clone(),
                 status_display,
                 format!("{}ms", entry.duration_ms.unwrap_or(0)),
+                entry
+                    .response_size
+                    .map_or("-".to_string(), utils::format_bytes),
             ]
         })
         .collect();
 
-    let table = utils::create_url_priority_table(headers, rows, 2); // URL is column index 2
+    let table = utils::create_url_priority_table(headers, rows, 3); // URL is column index 3
     println!("{}", table);
 }
 
+fn print_history_stats(stats: &storage::HistoryStats) {
+    if stats.total_requests == 0 {
+        cli::print_info("No request history found. Make some requests first!");
+        return;
+    }
+
+    let ms = |value: Option<u64>| value.map_or("-".to_string(), |ms| format!("{}ms", ms));
+    let rows = vec![
+        vec!["Requests".to_string(), stats.total_requests.to_string()],
+        vec!["2xx".to_string(), stats.successful_requests.to_string()],
+        vec!["4xx".to_string(), stats.client_errors.to_string()],
+        vec!["5xx".to_string(), stats.server_errors.to_string()],
+        vec!["Failed".to_string(), stats.failed_requests.to_string()],
+        vec![
+            "Duration (min/avg/max)".to_string(),
+            format!(
+                "{} / {} / {}",
+                ms(stats.min_duration_ms),
+                ms(stats.average_duration_ms),
+                ms(stats.max_duration_ms)
+            ),
+        ],
+        vec!["Sent".to_string(), utils::format_bytes(stats.bytes_sent)],
+        vec![
+            "Received".to_string(),
+            utils::format_bytes(stats.bytes_received),
+        ],
+    ];
+    let table = utils::create_simple_responsive_table(vec!["Metric", "Value"], rows);
+    println!("{}", table);
+}
+
+// Enough of an entry's UUID to pass to `history delete`
+const HISTORY_ID_LENGTH: usize = 8;
+
+async fn handle_history_action(
+    action: HistoryAction,
+    storage: &mut Storage,
+    config: &Config,
+) -> Result<()> {
+    match action {
+        HistoryAction::Clear { before: None } => {
+            if cli::confirm_action_with_config(
+                "Are you sure you want to clear all request history?",
+                config,
+            ) {
+                backup_before(storage, config, "clearing history").await?;
+                storage.clear_history().await?;
+                cli::print_success("History cleared");
+            } else {
+                cli::print_info("Clear operation cancelled");
+            }
+        }
+        HistoryAction::Clear {
+            before: Some(before),
+        } => {
+            let cutoff = utils::parse_date(&before)?;
+            if cli::confirm_action_with_config(
+                &format!(
+                    "Are you sure you want to remove history recorded before {}?",
+                    cutoff.format("%Y-%m-%d %H:%M UTC")
+                ),
+                config,
+            ) {
+                backup_before(storage, config, "clearing history").await?;
+                let removed = storage.clear_history_before(cutoff).await?;
+                cli::print_success(&format!("Removed {} history entries", removed));
+            } else {
+                cli::print_info("Clear operation cancelled");
+            }
+        }
+        HistoryAction::Stats => print_history_stats(&storage.get_history_stats().await?),
+        HistoryAction::Delete { id } => {
+            if id.is_empty() {
+                return Err(anyhow::anyhow!("History ID cannot be empty"));
+            }
+            match storage.delete_history_entry(&id).await? {
+                Some(entry) => cli::print_success(&format!(
+                    "Deleted history entry {} ({} {})",
+                    entry.id.chars().take(HISTORY_ID_LENGTH).collect::<String>(),
+                    entry.method,
+                    entry.url
+                )),
+                None => {
+                    cli::print_error(&format!("History entry '{}' not found", id));
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_auth_action(
+    action: AuthAction,
+    client: &TerziClient,
+    config: &mut Config,
+) -> Result<()> {
+    match action {
+        AuthAction::Oauth2 {
+            name,
+            token_url,
+            client_id,
+            client_secret,
+            scope,
+        } => {
+            if !utils::is_valid_url(&token_url) {
+                return Err(anyhow::anyhow!("Invalid token URL: {}", token_url));
+            }
+
+            let credentials = auth::ClientCredentials {
+                token_url,
+                client_id,
+                client_secret,
+                scopes: scope
+                    .iter()
+                    .flat_map(|s| s.split_whitespace())
+                    .map(String::from)
+                    .collect(),
+            };
+            let token = auth::client_credentials_grant(client, &credentials).await?;
+            let expiry = token
+                .expires_at
+                .map(|at| format!(", expires {}", at.format("%Y-%m-%d %H:%M UTC")))
+                .unwrap_or_default();
+            config.save_token(&name, token).await?;
+
+            cli::print_success(&format!("Token '{}' stored{}", name, expiry));
+            cli::print_info(&format!("Use it with: terzi --auth-token {} <url>", name));
+        }
+        AuthAction::List => {
+            let mut names = config.list_tokens();
+            if names.is_empty() {
+                cli::print_info("No stored tokens. Create one with 'terzi auth oauth2'");
+                return Ok(());
+            }
+            names.sort();
+
+            let headers = vec!["Name", "Type", "Scopes", "Expires"];
+            let rows: Vec<Vec<String>> = names
+                .iter()
+                .filter_map(|name| config.get_token(name).map(|token| (name, token)))
+                .map(|(name, token)| {
+                    let expires = match token.expires_at {
+                        Some(_) if token.is_expired() => "expired".bright_red().to_string(),
+                        Some(at) => at.format("%Y-%m-%d %H:%M").to_string(),
+                        None => "never".to_string(),
+                    };
+                    vec![
+                        name.clone(),
+                        token.token_type.clone(),
+                        token.scopes.join(" "),
+                        expires,
+                    ]
+                })
+                .collect();
+
+            println!("{}", utils::create_simple_responsive_table(headers, rows));
+        }
+        AuthAction::Delete { name } => {
+            if config.delete_token(&name).await? {
+                cli::print_success(&format!("Token '{}' deleted", name));
+            } else {
+                cli::print_error(&format!("Token '{}' not found", name));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_collection_action(
+    action: CollectionAction,
+    client: &TerziClient,
+    storage: &mut Storage,
+    config: &Config,
+    request_log: Option<&request_log::RequestLog>,
+    default_env: Option<&str>,
+) -> Result<()> {
+    match action {
+        CollectionAction::Create { name, description } => {
+            if storage.get_collection(&name).await?.is_some() {
+                return Err(anyhow::anyhow!("Collection '{}' already exists", name));
+            }
+            storage.create_collection(&name, description).await?;
+            cli::print_success(&format!("Collection '{}' created", name));
+            cli::print_info(&format!(
+                "Add requests with: terzi collection add '{}' <request>",
+                name
+            ));
+        }
+        CollectionAction::List => {
+            let collections = storage.list_collections().await?;
+            if collections.is_empty() {
+                cli::print_info(
+                    "No collections found. Create one with 'terzi collection create <name>'",
+                );
+                return Ok(());
+            }
+
+            let headers = vec!["Name", "Requests", "Description", "Updated"];
+            let rows: Vec<Vec<String>> = collections
+                .iter()
+                .map(|collection| {
+                    vec![
+                        collection.name.clone(),
+                        collection.requests.len().to_string(),
+                        collection.description.clone().unwrap_or_default(),
+                        collection.updated_at.format("%Y-%m-%d %H:%M").to_string(),
+                    ]
+                })
+                .collect();
+            println!("{}", utils::create_simple_responsive_table(headers, rows));
+        }
+        CollectionAction::Add {
+            collection,
+            request,
+        } => {
+            let Some(saved) = storage.get_request(&request).await? else {
+                return Err(anyhow::anyhow!("Request '{}' not found", request));
+            };
+            storage
+                .add_request_to_collection(&collection, saved)
+                .await?;
+            cli::print_success(&format!(
+                "Added '{}' to collection '{}'",
+                request, collection
+            ));
+        }
+        CollectionAction::Show { name } => {
+            let Some(collection) = storage.get_collection(&name).await? else {
+                return Err(anyhow::anyhow!("Collection '{}' not found", name));
+            };
+
+            println!("{}", collection.name.bright_white().bold());
+            if let Some(ref description) = collection.description {
+                println!("{}", description.bright_black());
+            }
+            println!();
+
+            if collection.requests.is_empty() {
+                cli::print_info(&format!(
+                    "Collection '{}' has no requests. Add one with 'terzi collection add'",
+                    name
+                ));
+                return Ok(());
+            }
+
+            let headers = vec!["#", "Name", "Method", "URL", "Assertions"];
+            let rows: Vec<Vec<String>> = collection
+                .requests
+                .iter()
+                .enumerate()
+                .map(|(i, req)| {
+                    vec![
+                        (i + 1).to_string(),
+                        req.name.clone(),
+                        req.method.clone(),
+                        req.url.clone(),
+                        req.assertions.len().to_string(),
+                    ]
+                })
+                .collect();
+            let table = utils::create_url_priority_table(headers, rows, 3); // URL is column index 3
+            println!("{}", table);
+        }
+        CollectionAction::Run { name, env, failure } => {
+            run_collection_tests(
+                client,
+                storage,
+                request_log,
+                &name,
+                env.as_deref().or(default_env),
+                failure.mode(),
+            )
+            .await?;
+        }
+        CollectionAction::Delete { name } => {
+            if storage.get_collection(&name).await?.is_none() {
+                cli::print_error(&format!("Collection '{}' not found", name));
+            } else if cli::confirm_action_with_config(
+                &format!("Are you sure you want to delete collection '{}'?", name),
+                config,
+            ) {
+                backup_before(storage, config, "deleting").await?;
+                storage.delete_collection(&name).await?;
+                cli::print_success(&format!("Collection '{}' deleted", name));
+            } else {
+                cli::print_info("Delete operation cancelled");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Probes until --count is reached or Ctrl+C; true when at least one probe got an answer
+async fn ping(
+    client: &TerziClient,
+    url: &str,
+    interval: Option<std::time::Duration>,
+    count: Option<u32>,
+) -> bool {
+    let mut ticker = tokio::time::interval(interval.unwrap_or(std::time::Duration::from_secs(1)));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut sent = 0;
+    let mut timings = Vec::new();
+    let mut shown_certificate = false;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+        sent += 1;
+        match client.test_connection(url).await {
+            Ok(probe) => {
+                let address = probe
+                    .remote_addr
+                    .map(|addr| format!(" from {}", addr))
+                    .unwrap_or_default();
+                println!(
+                    "{} {} {}{} in {}",
+                    "✅".green(),
+                    url,
+                    format!("HTTP {}", probe.status).bold(),
+                    address,
+                    utils::format_duration(probe.duration).cyan()
+                );
+                if let Some(ref fingerprint) = probe.certificate_sha256
+                    && !shown_certificate
+                {
+                    println!(
+                        "   {} {}",
+                        "TLS certificate SHA-256:".bright_black(),
+                        fingerprint
+                    );
+                    shown_certificate = true;
+                }
+                timings.push(probe.duration);
+            }
+            Err(e) => println!(
+                "{} {} {}",
+                "❌".red(),
+                url,
+                utils::format_error_chain(&e).red()
+            ),
+        }
+
+        if interval.is_none() || count.is_some_and(|count| sent >= count) {
+            break;
+        }
+    }
+
+    if sent > 1 {
+        println!();
+        print!("{} probes, {} answered", sent, timings.len());
+        if let (Some(min), Some(max)) = (timings.iter().min(), timings.iter().max()) {
+            let average = timings.iter().sum::<std::time::Duration>() / timings.len() as u32;
+            print!(
+                ", min/avg/max {}/{}/{}",
+                utils::format_duration(*min),
+                utils::format_duration(average),
+                utils::format_duration(*max)
+            );
+        }
+        println!();
+    }
+    !timings.is_empty()
+}
+
+// Returns false when any check failed; warnings don't count
+fn print_doctor_report(checks: &[doctor::Check]) -> bool {
+    let mut failures = 0;
+    let mut warnings = 0;
+    for check in checks {
+        let icon = match check.outcome {
+            doctor::Outcome::Pass => "✅".green(),
+            doctor::Outcome::Warn => {
+                warnings += 1;
+                "⚠️".yellow()
+            }
+            doctor::Outcome::Fail => {
+                failures += 1;
+                "❌".red()
+            }
+        };
+        println!(
+            "{} {} {}",
+            icon,
+            format!("{}:", check.name).bold(),
+            check.detail
+        );
+        if let Some(ref hint) = check.hint {
+            println!("   {} {}", "💡".yellow(), hint.bright_black());
+        }
+    }
+
+    println!();
+    println!("{} failed, {} warning(s)", failures, warnings);
+    failures == 0
+}
+
+// Prints the findings per request and exits with 1 when any of them is an error
+fn lint_requests(requests: &[request::SavedRequest], variables: Option<&HashMap<String, String>>) {
+    let mut errors = 0;
+    let mut warnings = 0;
+    for request in requests {
+        let findings = lint::lint_request(request, variables);
+        if findings.is_empty() {
+            println!("{} {}", "✅".green(), request.name.bold());
+            continue;
+        }
+        println!("{} {}", "🔍".cyan(), request.name.bold());
+        for finding in &findings {
+            match finding.severity {
+                lint::Severity::Error => {
+                    errors += 1;
+                    println!("   {} {}", "error:".red().bold(), finding.message);
+                }
+                lint::Severity::Warning => {
+                    warnings += 1;
+                    println!("   {} {}", "warning:".yellow().bold(), finding.message);
+                }
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{} error(s), {} warning(s) in {} request(s)",
+        errors,
+        warnings,
+        requests.len()
+    );
+    if errors > 0 {
+        std::process::exit(1);
+    }
+}
+
+async fn load_environment(storage: &Storage, name: &str) -> Result<HashMap<String, String>> {
+    if let Some(variables) = storage.get_environment(name).await? {
+        return Ok(variables);
+    }
+    let available = storage.list_environments().await?;
+    if available.is_empty() {
+        Err(anyhow::anyhow!(
+            "Environment '{}' not found. Create one with 'terzi env create {} KEY=VALUE'",
+            name,
+            name
+        ))
+    } else {
+        Err(anyhow::anyhow!(
+            "Environment '{}' not found (available: {})",
+            name,
+            available.join(", ")
+        ))
+    }
+}
+
+fn parse_variables(pairs: &[String]) -> Result<HashMap<String, String>> {
+    pairs
+        .iter()
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.to_string()))
+            }
+            _ => Err(anyhow::anyhow!(
+                "Invalid variable format: '{}'. Use 'KEY=VALUE'",
+                pair
+            )),
+        })
+        .collect()
+}
+
+async fn handle_env_action(
+    action: EnvAction,
+    storage: &mut Storage,
+    config: &Config,
+) -> Result<()> {
+    match action {
+        EnvAction::Create { name, variables } => {
+            if storage.get_environment(&name).await?.is_some() {
+                return Err(anyhow::anyhow!("Environment '{}' already exists", name));
+            }
+            let variables = parse_variables(&variables)?;
+            let count = variables.len();
+            storage.save_environment(&name, variables).await?;
+            cli::print_success(&format!(
+                "Environment '{}' created with {} variables",
+                name, count
+            ));
+        }
+        EnvAction::List => {
+            let names = storage.list_environments().await?;
+            if names.is_empty() {
+                cli::print_info(
+                    "No environments found. Create one with 'terzi env create <name> KEY=VALUE'",
+                );
+                return Ok(());
+            }
+
+            let headers = vec!["Name", "Variables"];
+            let mut rows = Vec::new();
+            for name in names {
+                let count = storage
+                    .get_environment(&name)
+                    .await?
+                    .map(|variables| variables.len())
+                    .unwrap_or(0);
+                rows.push(vec![name, count.to_string()]);
+            }
+            println!("{}", utils::create_simple_responsive_table(headers, rows));
+        }
+        EnvAction::Show { name, no_mask } => {
+            let Some(variables) = storage.get_environment(&name).await? else {
+                return Err(anyhow::anyhow!("Environment '{}' not found", name));
+            };
+            if variables.is_empty() {
+                cli::print_info(&format!("Environment '{}' has no variables", name));
+                return Ok(());
+            }
+
+            let mut variables: Vec<(String, String)> = variables.into_iter().collect();
+            variables.sort();
+            let headers = vec!["Key", "Value"];
+            let rows: Vec<Vec<String>> = variables
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = if no_mask {
+                        value
+                    } else {
+                        utils::mask_variable_value(&key, &value)
+                    };
+                    vec![key, value]
+                })
+                .collect();
+            println!("{}", utils::create_simple_responsive_table(headers, rows));
+        }
+        EnvAction::Set { name, variables } => {
+            let Some(mut existing) = storage.get_environment(&name).await? else {
+                return Err(anyhow::anyhow!(
+                    "Environment '{}' not found. Create it with 'terzi env create {}'",
+                    name,
+                    name
+                ));
+            };
+            let variables = parse_variables(&variables)?;
+            let count = variables.len();
+            existing.extend(variables);
+            storage.save_environment(&name, existing).await?;
+            cli::print_success(&format!(
+                "Updated {} variables in environment '{}'",
+                count, name
+            ));
+        }
+        EnvAction::Delete { name } => {
+            if storage.get_environment(&name).await?.is_none() {
+                cli::print_error(&format!("Environment '{}' not found", name));
+            } else if cli::confirm_action_with_config(
+                &format!("Are you sure you want to delete environment '{}'?", name),
+                config,
+            ) {
+                backup_before(storage, config, "deleting").await?;
+                storage.delete_environment(&name).await?;
+                cli::print_success(&format!("Environment '{}' deleted", name));
+            } else {
+                cli::print_info("Delete operation cancelled");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_openapi_action(action: OpenapiAction, storage: &mut Storage) -> Result<()> {
+    match action {
+        OpenapiAction::Import {
+            spec,
+            base_url,
+            collection,
+        } => {
+            let contents = tokio::fs::read_to_string(&spec)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", spec.display(), e))?;
+            let document = openapi::OpenApiSpec::parse(&contents)?;
+
+            let base_url = base_url.or_else(|| document.server_url()).ok_or_else(|| {
+                anyhow::anyhow!("The spec has no absolute server URL; pass --base-url")
+            })?;
+            if !utils::is_valid_url(&base_url) {
+                return Err(anyhow::anyhow!("Invalid base URL: {}", base_url));
+            }
+
+            let name = collection.unwrap_or_else(|| document.title());
+            let collection = document.to_collection(&base_url, &name)?;
+            let count = collection.requests.len();
+            storage.save_collection(collection).await?;
+
+            cli::print_success(&format!(
+                "Imported {} requests into collection '{}'",
+                count, name
+            ));
+            cli::print_info(&format!("Run them with: terzi test '{}'", name));
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_profile_action(action: &ProfileAction) -> Result<()> {
+    match action {
+        ProfileAction::List => {
+            let active = config::active_profile();
+            let mark = |selected: bool| {
+                if selected {
+                    "*".bright_green().to_string()
+                } else {
+                    " ".to_string()
+                }
+            };
+
+            println!("{} default", mark(active.is_none()));
+            for profile in config::list_profiles()? {
+                println!(
+                    "{} {}",
+                    mark(active.as_deref() == Some(profile.as_str())),
+                    profile
+                );
+            }
+        }
+        ProfileAction::Create { name } => {
+            config::validate_profile_name(name)?;
+            if name == "default" {
+                return Err(anyhow::anyhow!("The default profile always exists"));
+            }
+
+            let path = Config::profile_config_path(Some(name));
+            if path.exists() {
+                return Err(anyhow::anyhow!("Profile '{}' already exists", name));
+            }
+            Config::default().save_to(&path).await?;
+
+            cli::print_success(&format!("Profile '{}' created at {}", name, path.display()));
+            cli::print_info(&format!("Use it with: terzi --profile {} <url>", name));
+        }
+        ProfileAction::Delete { name } => {
+            config::validate_profile_name(name)?;
+            if name == "default" {
+                return Err(anyhow::anyhow!("The default profile cannot be deleted"));
+            }
+
+            let path = Config::profile_config_path(Some(name));
+            if !path.exists() {
+                cli::print_error(&format!("Profile '{}' not found", name));
+                std::process::exit(1);
+            }
+
+            if !cli::confirm_action(&format!(
+                "Delete profile '{}' and its saved requests and history?",
+                name
+            )) {
+                cli::print_info("Delete operation cancelled");
+                return Ok(());
+            }
+
+            tokio::fs::remove_file(&path).await?;
+            let data_file = config::config_dir().join(Storage::data_file_name(Some(name)));
+            if data_file.exists() {
+                tokio::fs::remove_file(&data_file).await?;
+            }
+            cli::print_success(&format!("Profile '{}' deleted", name));
+        }
+    }
+    Ok(())
+}
+
 async fn handle_config_action(action: ConfigAction, config: &Config) -> Result<()> {
     match action {
         ConfigAction::Set { key, value } => {
@@ -636,7 +3390,20 @@ async fn handle_config_action(action: ConfigAction, config: &Config) -> Result<(
                 cli::print_error(&format!("Configuration key '{}' not found", key));
             }
         }
-        ConfigAction::List => {
+        ConfigAction::Unset { key } => {
+            let mut config = config.clone();
+            match config.unset_value(&key).await? {
+                Some(value) => cli::print_success(&format!("Reset {} to default: {}", key, value)),
+                None => cli::print_success(&format!("Reset {} to default (not set)", key)),
+            }
+        }
+        ConfigAction::List {
+            json: true,
+            include_secrets,
+        } => {
+            println!("{}", config.export_json(include_secrets)?);
+        }
+        ConfigAction::List { .. } => {
             println!("{}", "🚀 Configuration".bright_cyan().bold());
             println!();
 
@@ -652,11 +3419,35 @@ async fn handle_config_action(action: ConfigAction, config: &Config) -> Result<(
                 cli::print_info("No configuration found. Using defaults.");
             }
         }
+        ConfigAction::Export { include_secrets } => {
+            print!("{}", config.export_toml(include_secrets)?);
+        }
+        ConfigAction::Import { file } => {
+            let contents = tokio::fs::read_to_string(&file)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", file.display(), e))?;
+            let imported = config.import_toml(&contents)?;
+            backup_config_before(config, "importing").await?;
+            imported.save().await?;
+            cli::print_success(&format!("Configuration imported from {}", file.display()));
+        }
+        ConfigAction::Edit => match config.edit_in_editor() {
+            Ok(Some(edited)) => {
+                edited.save().await?;
+                cli::print_success("Configuration updated");
+            }
+            Ok(None) => cli::print_info("No changes made"),
+            Err(e) => {
+                cli::print_error(&e.to_string());
+                std::process::exit(1);
+            }
+        },
         ConfigAction::Reset => {
             if cli::confirm_action_with_config(
                 "Are you sure you want to reset all configuration to defaults?",
                 &config,
             ) {
+                backup_config_before(config, "resetting").await?;
                 let mut config = config.clone();
                 config.reset_to_defaults().await?;
                 cli::print_success("Configuration reset to defaults");
@@ -668,25 +3459,211 @@ async fn handle_config_action(action: ConfigAction, config: &Config) -> Result<(
     Ok(())
 }
 
+async fn handle_backup_action(
+    action: BackupAction,
+    storage: &mut Storage,
+    config: &Config,
+) -> Result<()> {
+    match action {
+        BackupAction::Create => {
+            let path = storage.create_backup().await?;
+            cli::print_success(&format!("Backup written to {}", path.display()));
+        }
+        BackupAction::List => {
+            let backups = storage.list_backups().await?;
+            if backups.is_empty() {
+                cli::print_info("No backups found. Create one with 'terzi backup create'");
+                return Ok(());
+            }
+
+            let headers = vec!["File", "Size", "Created"];
+            let rows: Vec<Vec<String>> = backups
+                .iter()
+                .map(|path| {
+                    let metadata = std::fs::metadata(path).ok();
+                    vec![
+                        path.file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_default(),
+                        metadata
+                            .as_ref()
+                            .map(|m| utils::format_bytes(m.len() as usize))
+                            .unwrap_or_default(),
+                        metadata
+                            .and_then(|m| m.modified().ok())
+                            .map(utils::time_ago)
+                            .unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            // The file name is what `backup restore` takes, so keep it whole
+            println!("{}", utils::create_url_priority_table(headers, rows, 0));
+        }
+        BackupAction::Restore { file } => {
+            let path = storage.backup_path(&file);
+            if !path.is_file() {
+                return Err(anyhow::anyhow!("Backup '{}' not found", path.display()));
+            }
+
+            if cli::confirm_action_with_config(
+                "Restoring replaces all saved requests, collections, environments and history. Continue?",
+                config,
+            ) {
+                backup_before(storage, config, "restoring").await?;
+                storage
+                    .restore_backup(&path)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to restore {}: {}", path.display(), e))?;
+                cli::print_success(&format!("Restored {}", path.display()));
+            } else {
+                cli::print_info("Restore operation cancelled");
+            }
+        }
+    }
+    Ok(())
+}
+
+// Safety net before commands that delete or overwrite saved data (general.auto_backup)
+async fn backup_before(storage: &Storage, config: &Config, action: &str) -> Result<()> {
+    if !config.general.auto_backup {
+        return Ok(());
+    }
+    let path = storage
+        .create_auto_backup(config.general.max_auto_backups)
+        .await?;
+    cli::print_info(&format!(
+        "Backed up existing data to {} before {}",
+        path.display(),
+        action
+    ));
+    Ok(())
+}
+
+async fn backup_config_before(config: &Config, action: &str) -> Result<()> {
+    if !config.general.auto_backup {
+        return Ok(());
+    }
+    let path = config.create_auto_backup().await?;
+    cli::print_info(&format!(
+        "Backed up the configuration to {} before {}; undo with 'terzi config import'",
+        path.display(),
+        action
+    ));
+    Ok(())
+}
+
+async fn import_requests(
+    storage: &mut Storage,
+    config: &Config,
+    file: &std::path::Path,
+    format: &str,
+) -> Result<()> {
+    let contents = tokio::fs::read_to_string(file)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", file.display(), e))?;
+
+    match format {
+        "json" | "yaml" => {
+            let data = if format == "yaml" {
+                let value: serde_json::Value = serde_yaml::from_str(&contents)?;
+                value.to_string()
+            } else {
+                contents
+            };
+            backup_before(storage, config, "importing").await?;
+            storage.import_data(&data, true).await?;
+            cli::print_success(&format!("Imported {}", file.display()));
+        }
+        "postman" => {
+            let import = postman::import_collection(&contents)?;
+            backup_before(storage, config, "importing").await?;
+
+            // Keep existing saved requests and collections; clashing names get a numeric suffix
+            let mut saved = 0;
+            for mut request in import.requests {
+                let base = request.name.clone();
+                let mut suffix = 2;
+                while storage.get_request(&request.name).await?.is_some() {
+                    request.name = format!("{}-{}", base, suffix);
+                    suffix += 1;
+                }
+                storage
+                    .save_request(&request.name.clone(), &request)
+                    .await?;
+                saved += 1;
+            }
+
+            let folders = import.folders.len();
+            let mut folder_requests = 0;
+            for mut folder in import.folders {
+                let base = folder.name.clone();
+                let mut suffix = 2;
+                while storage.get_collection(&folder.name).await?.is_some() {
+                    folder.name = format!("{}-{}", base, suffix);
+                    suffix += 1;
+                }
+                folder_requests += folder.requests.len();
+                storage.save_collection(folder).await?;
+            }
+
+            if !import.variables.is_empty() {
+                storage
+                    .save_environment(&import.name, import.variables.into_iter().collect())
+                    .await?;
+                cli::print_info(&format!(
+                    "Collection variables saved as environment '{}'",
+                    import.name
+                ));
+            }
+
+            for warning in &import.warnings {
+                cli::print_warning(warning);
+            }
+            cli::print_success(&format!(
+                "Imported '{}': {} saved requests, {} requests in {} collections",
+                import.name, saved, folder_requests, folders
+            ));
+        }
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unsupported import format '{}'. Use json, yaml or postman",
+                other
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 async fn export_requests(storage: &Storage, output: Option<&str>, format: &str) -> Result<()> {
     let data = storage.export_data(false).await?.to_string();
 
     let output_content = match format {
         "yaml" => {
             let json_value: serde_json::Value = serde_json::from_str(&data)?;
-            serde_yaml::to_string(&json_value)?
+            utils::json_to_yaml(&json_value)?
+        }
+        "postman" => {
+            let collection = postman::export_collection(
+                "terzi",
+                &storage.list_requests(None).await?,
+                &storage.list_collections().await?,
+            );
+            serde_json::to_string_pretty(&collection)?
         }
         "json" => data.clone(),
         _ => data,
     };
+    // Postman collections are plain JSON files
+    let extension = if format == "postman" { "json" } else { format };
 
     match output {
         Some(file_path) => {
             use tokio::io::AsyncWriteExt;
 
             // Add appropriate extension if not present
-            let final_path = if !file_path.ends_with(&format!(".{}", format)) {
-                format!("{}.{}", file_path, format)
+            let final_path = if !file_path.ends_with(&format!(".{}", extension)) {
+                format!("{}.{}", file_path, extension)
             } else {
                 file_path.to_string()
             };
@@ -703,3 +3680,25 @@ async fn export_requests(storage: &Storage, output: Option<&str>, format: &str)
 
     Ok(())
 }
+
+async fn export_har(
+    storage: &Storage,
+    last: usize,
+    output: Option<&str>,
+    mask_secrets: bool,
+) -> Result<()> {
+    let history = storage.get_history(last).await?;
+    let har = serde_json::to_string_pretty(&har::build_har(&history, mask_secrets))?;
+
+    match output {
+        Some(file_path) => {
+            utils::write_atomic(std::path::Path::new(file_path), har.as_bytes()).await?;
+            cli::print_success(&format!("History exported to {}", file_path));
+        }
+        None => {
+            println!("{}", har);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,421 @@
+use anyhow::{Result, anyhow};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+use crate::request::{RequestCollection, SavedRequest};
+
+// Turns an OpenAPI 3.x document into saved requests, one per operation
+const METHODS: [&str; 7] = ["get", "post", "put", "patch", "delete", "head", "options"];
+
+// Guards against self-referencing schemas when building example bodies
+const MAX_EXAMPLE_DEPTH: usize = 8;
+
+pub struct OpenApiSpec {
+    document: Value,
+}
+
+impl OpenApiSpec {
+    /// Parses a YAML or JSON document.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let document: Value = serde_yaml::from_str(contents)
+            .map_err(|e| anyhow!("Failed to parse OpenAPI document: {}", e))?;
+
+        match document.get("openapi").and_then(Value::as_str) {
+            Some(version) if version.starts_with("3.") => Ok(Self { document }),
+            Some(version) => Err(anyhow!(
+                "Unsupported OpenAPI version {}; only 3.x documents are supported",
+                version
+            )),
+            None if document.get("swagger").is_some() => Err(anyhow!(
+                "Swagger 2.0 documents are not supported; convert the spec to OpenAPI 3.x first"
+            )),
+            None => Err(anyhow!("Not an OpenAPI document: missing 'openapi' field")),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        self.document
+            .pointer("/info/title")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .unwrap_or("openapi")
+            .to_string()
+    }
+
+    /// The first absolute server URL, used when no base URL is given.
+    pub fn server_url(&self) -> Option<String> {
+        self.document
+            .pointer("/servers/0/url")
+            .and_then(Value::as_str)
+            .filter(|url| crate::utils::is_valid_url(url))
+            .map(String::from)
+    }
+
+    pub fn to_collection(&self, base_url: &str, name: &str) -> Result<RequestCollection> {
+        let paths = self
+            .document
+            .get("paths")
+            .and_then(Value::as_object)
+            .ok_or_else(|| anyhow!("OpenAPI document has no paths"))?;
+
+        let mut collection = RequestCollection::new(name.to_string());
+        collection.description = self
+            .document
+            .pointer("/info/description")
+            .and_then(Value::as_str)
+            .map(String::from);
+
+        let base_url = base_url.trim_end_matches('/');
+        let mut names = HashSet::new();
+        for (path, item) in paths {
+            for method in METHODS {
+                let Some(operation) = item.get(method) else {
+                    continue;
+                };
+
+                let mut parameters = self.parameters(item.get("parameters"));
+                parameters.extend(self.parameters(operation.get("parameters")));
+
+                let name = unique_name(&mut names, &operation_name(operation, method, path));
+                let mut request = SavedRequest::new(
+                    name,
+                    format!("{}{}", base_url, request_path(path, &parameters)),
+                    method.to_uppercase(),
+                );
+                request.description = ["summary", "description"]
+                    .iter()
+                    .find_map(|key| operation.get(*key).and_then(Value::as_str))
+                    .map(String::from);
+                request.tags = operation
+                    .get("tags")
+                    .and_then(Value::as_array)
+                    .map(|tags| {
+                        tags.iter()
+                            .filter_map(Value::as_str)
+                            .map(String::from)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                for parameter in parameters.iter().filter(|p| p.location == "header") {
+                    request.add_header(
+                        parameter.name.clone(),
+                        format!("{{{{{}}}}}", parameter.name),
+                    );
+                }
+
+                if let Some((content_type, body)) = self.request_body(operation) {
+                    request.add_header("Content-Type".to_string(), content_type);
+                    request.set_body(Some(body));
+                }
+
+                collection.add_request(request);
+            }
+        }
+
+        Ok(collection)
+    }
+
+    fn parameters(&self, list: Option<&Value>) -> Vec<Parameter> {
+        list.and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(|parameter| self.resolve(parameter))
+            .filter_map(|parameter| {
+                Some(Parameter {
+                    name: parameter.get("name")?.as_str()?.to_string(),
+                    location: parameter.get("in")?.as_str()?.to_string(),
+                    required: parameter
+                        .get("required")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false),
+                })
+            })
+            .filter(|parameter| parameter.location == "path" || parameter.required)
+            .collect()
+    }
+
+    // Picks JSON when offered, and prefers explicit examples over generated ones
+    fn request_body(&self, operation: &Value) -> Option<(String, String)> {
+        let body = self.resolve(operation.get("requestBody")?);
+        let content = body.get("content")?.as_object()?;
+        let (content_type, media) = content
+            .iter()
+            .find(|(content_type, _)| content_type.contains("json"))
+            .or_else(|| content.iter().next())?;
+
+        let example = media
+            .get("example")
+            .cloned()
+            .or_else(|| {
+                media
+                    .get("examples")
+                    .and_then(Value::as_object)
+                    .and_then(|examples| examples.values().next())
+                    .map(|example| self.resolve(example))
+                    .and_then(|example| example.get("value").cloned())
+            })
+            .or_else(|| {
+                media
+                    .get("schema")
+                    .map(|schema| self.example_for(schema, 0))
+            })?;
+
+        let body = match example {
+            Value::String(text) if !content_type.contains("json") => text,
+            other => serde_json::to_string_pretty(&other).ok()?,
+        };
+        Some((content_type.clone(), body))
+    }
+
+    fn example_for(&self, schema: &Value, depth: usize) -> Value {
+        if depth > MAX_EXAMPLE_DEPTH {
+            return Value::Null;
+        }
+        let schema = self.resolve(schema);
+
+        for key in ["example", "default"] {
+            if let Some(value) = schema.get(key) {
+                return value.clone();
+            }
+        }
+        if let Some(first) = schema
+            .get("enum")
+            .and_then(Value::as_array)
+            .and_then(|v| v.first())
+        {
+            return first.clone();
+        }
+        if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in parts {
+                if let Value::Object(fields) = self.example_for(part, depth + 1) {
+                    merged.extend(fields);
+                }
+            }
+            return Value::Object(merged);
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(first) = schema
+                .get(key)
+                .and_then(Value::as_array)
+                .and_then(|v| v.first())
+            {
+                return self.example_for(first, depth + 1);
+            }
+        }
+
+        let kind = match schema.get("type") {
+            Some(Value::String(kind)) => kind.as_str(),
+            // 3.1 allows a list of types; the first non-null one wins
+            Some(Value::Array(kinds)) => kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|kind| *kind != "null")
+                .unwrap_or("null"),
+            _ if schema.get("properties").is_some() => "object",
+            _ => "",
+        };
+
+        match kind {
+            "object" => Value::Object(
+                schema
+                    .get("properties")
+                    .and_then(Value::as_object)
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, property)| (name.clone(), self.example_for(property, depth + 1)))
+                    .collect(),
+            ),
+            "array" => Value::Array(
+                schema
+                    .get("items")
+                    .map(|items| vec![self.example_for(items, depth + 1)])
+                    .unwrap_or_default(),
+            ),
+            "string" => Value::from(match schema.get("format").and_then(Value::as_str) {
+                Some("date-time") => "2024-01-01T00:00:00Z",
+                Some("date") => "2024-01-01",
+                Some("email") => "user@example.com",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("uri") | Some("url") => "https://example.com",
+                _ => "string",
+            }),
+            "integer" => Value::from(0),
+            "number" => Value::from(0.0),
+            "boolean" => Value::from(false),
+            _ => Value::Null,
+        }
+    }
+
+    // Follows a local `#/components/...` reference; external references are left as-is
+    fn resolve<'a>(&'a self, value: &'a Value) -> &'a Value {
+        let mut current = value;
+        for _ in 0..MAX_EXAMPLE_DEPTH {
+            match current
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.strip_prefix('#'))
+                .and_then(|pointer| self.document.pointer(pointer))
+            {
+                Some(target) => current = target,
+                None => break,
+            }
+        }
+        current
+    }
+}
+
+struct Parameter {
+    name: String,
+    location: String,
+    required: bool,
+}
+
+fn operation_name(operation: &Value, method: &str, path: &str) -> String {
+    if let Some(id) = operation.get("operationId").and_then(Value::as_str) {
+        return id.to_string();
+    }
+
+    let slug: Vec<&str> = path
+        .split('/')
+        .map(|segment| segment.trim_matches(|c| c == '{' || c == '}'))
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    if slug.is_empty() {
+        method.to_string()
+    } else {
+        format!("{}-{}", method, slug.join("-"))
+    }
+}
+
+fn unique_name(taken: &mut HashSet<String>, name: &str) -> String {
+    let mut candidate = name.to_string();
+    let mut suffix = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{}-{}", name, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+// `/users/{id}` becomes `/users/{{id}}`, with required query parameters appended as variables
+fn request_path(path: &str, parameters: &[Parameter]) -> String {
+    let mut result = path.replace('{', "{{").replace('}', "}}");
+
+    let query: Vec<String> = parameters
+        .iter()
+        .filter(|parameter| parameter.location == "query")
+        .map(|parameter| format!("{}={{{{{}}}}}", parameter.name, parameter.name))
+        .collect();
+    if !query.is_empty() {
+        result.push('?');
+        result.push_str(&query.join("&"));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.3
+info:
+  title: Pet Store
+servers:
+  - url: https://petstore.example.com/v1
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      parameters:
+        - name: limit
+          in: query
+          required: true
+    post:
+      operationId: createPet
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+  /pets/{petId}:
+    parameters:
+      - $ref: '#/components/parameters/PetId'
+    delete:
+      summary: Remove a pet
+components:
+  parameters:
+    PetId:
+      name: petId
+      in: path
+      required: true
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+          example: Rex
+        tags:
+          type: array
+          items:
+            type: string
+        owner:
+          $ref: '#/components/schemas/Pet'
+"##;
+
+    #[test]
+    fn test_operations_become_requests() {
+        let spec = OpenApiSpec::parse(SPEC).unwrap();
+        assert_eq!(spec.title(), "Pet Store");
+        assert_eq!(
+            spec.server_url().as_deref(),
+            Some("https://petstore.example.com/v1")
+        );
+
+        let collection = spec
+            .to_collection("https://api.test/", "Pet Store")
+            .unwrap();
+        let names: Vec<&str> = collection
+            .requests
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(names, ["listPets", "createPet", "delete-pets-petId"]);
+
+        let list = &collection.requests[0];
+        assert_eq!(list.url, "https://api.test/pets?limit={{limit}}");
+
+        let delete = &collection.requests[2];
+        assert_eq!(delete.method, "DELETE");
+        assert_eq!(delete.url, "https://api.test/pets/{{petId}}");
+        assert_eq!(delete.description.as_deref(), Some("Remove a pet"));
+    }
+
+    #[test]
+    fn test_request_body_is_generated_from_schema() {
+        let spec = OpenApiSpec::parse(SPEC).unwrap();
+        let collection = spec.to_collection("https://api.test", "pets").unwrap();
+        let create = &collection.requests[1];
+
+        assert_eq!(
+            create.headers.get("Content-Type").map(String::as_str),
+            Some("application/json")
+        );
+        let body: Value = serde_json::from_str(create.body_text().unwrap()).unwrap();
+        assert_eq!(body["name"], "Rex");
+        assert_eq!(body["tags"], serde_json::json!(["string"]));
+        // The recursive owner reference stops at the depth limit instead of overflowing
+        assert!(body["owner"].is_object());
+    }
+
+    #[test]
+    fn test_rejects_non_openapi_documents() {
+        assert!(OpenApiSpec::parse("swagger: '2.0'").is_err());
+        assert!(OpenApiSpec::parse("openapi: 2.0.0").is_err());
+        assert!(OpenApiSpec::parse("title: nope").is_err());
+    }
+}
@@ -7,10 +7,53 @@ use syntect::highlighting::{Style, ThemeSet};
 use syntect::parsing::SyntaxSet;
 use syntect::util::{LinesWithEndings, as_24_bit_terminal_escaped};
 
-use crate::Cli;
 use crate::client::Response;
 use crate::config::Config;
 
+// How a response should be rendered, resolved from CLI flags and config
+#[derive(Debug, Clone)]
+pub struct DisplayOptions {
+    pub output: String,
+    pub include_headers: bool,
+    pub verbose: bool,
+    pub pretty: bool,
+    pub json_indent: usize,
+    // Some(depth) renders nested JSON as dotted columns in table output
+    pub flatten_depth: Option<usize>,
+    // Only the status line with timing and size; headers and body are skipped
+    pub summary_only: bool,
+    // The status line and headers table; the body is skipped
+    pub headers_only: bool,
+    // Print only body lines matching this, with `grep_context` lines around each
+    pub grep: Option<regex::Regex>,
+    pub grep_context: usize,
+    // JSONPath expressions that pick the table columns, one per column
+    pub columns: Vec<String>,
+    // Filter applied to a JSON body before it is formatted
+    pub jq: Option<String>,
+}
+
+pub const DEFAULT_FLATTEN_DEPTH: usize = 3;
+
+impl DisplayOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            output: config.output.default_format.clone(),
+            include_headers: config.output.show_headers,
+            verbose: false,
+            pretty: config.output.pretty_print,
+            json_indent: 2,
+            flatten_depth: None,
+            summary_only: false,
+            headers_only: false,
+            grep: None,
+            grep_context: 0,
+            columns: Vec::new(),
+            jq: None,
+        }
+    }
+}
+
 pub struct ResponseFormatter {
     syntax_set: SyntaxSet,
     theme_set: ThemeSet,
@@ -26,51 +69,188 @@ impl ResponseFormatter {
         }
     }
 
-    pub async fn display_response(&self, response: &Response, cli: &Cli) -> Result<()> {
+    pub async fn display_response(
+        &self,
+        response: &Response,
+        options: &DisplayOptions,
+    ) -> Result<()> {
+        if options.output == "http" {
+            return self.print_transcript(response, options);
+        }
+
         // Print status line
         self.print_status_line(response);
+        if options.summary_only {
+            println!(
+                "{}",
+                format!(
+                    "   {} in {}",
+                    response.size_human(),
+                    response.duration_human()
+                )
+                .bright_black()
+            );
+            return Ok(());
+        }
+
+        if options.headers_only {
+            if response.headers.is_empty() {
+                println!("{}", "No response headers".bright_black());
+            }
+            self.print_headers(&response.headers);
+            if options.verbose {
+                self.print_footer(response);
+            }
+            return Ok(());
+        }
 
         // Print headers if requested
-        if cli.include_headers {
+        if options.include_headers {
             self.print_headers(&response.headers);
         }
 
-        // Print body based on format
-        match cli.output.as_str() {
-            "json" => self.print_json_body(&response.body, cli.pretty),
-            "yaml" => self.print_yaml_body(&response.body),
-            "table" => self.print_table_body(&response.body),
-            "raw" => self.print_raw_body(&response.body),
-            _ => self.print_auto_body(response, cli.pretty),
+        if let Some(ref filter) = options.jq {
+            self.print_filtered_body(response, filter, options)?;
+        } else if let Some(ref pattern) = options.grep {
+            self.print_grep_body(&response.body, pattern, options);
+        } else {
+            self.print_body(response, options);
         }
 
         // Print footer with timing info
-        if cli.verbose {
+        if options.verbose {
             self.print_footer(response);
         }
 
         Ok(())
     }
 
+    // HTTPie-style exchange: the request as sent, a blank line, then the response
+    fn print_transcript(&self, response: &Response, options: &DisplayOptions) -> Result<()> {
+        let mut version = "HTTP/1.1";
+        if let Some(ref wire) = response.wire_request {
+            version = wire.version();
+            let mut head = wire.head.iter();
+            if let Some(line) = head.next() {
+                let mut parts = line.splitn(2, ' ');
+                let method = parts.next().unwrap_or_default();
+                let rest = parts.next().unwrap_or_default();
+                println!("{} {}", method.bright_blue().bold(), rest.bright_cyan());
+            }
+            for header in head {
+                self.print_transcript_header(header);
+            }
+            println!();
+            if let Some(ref body) = wire.body {
+                println!("{}", body.trim_end_matches('\n'));
+                println!();
+            }
+        }
+
+        let reason = reqwest::StatusCode::from_u16(response.status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or_default();
+        println!(
+            "{} {}",
+            version.bright_black(),
+            format!("{} {}", response.status, reason)
+                .trim_end()
+                .color(status_color(response.status))
+                .bold()
+        );
+        let mut headers: Vec<_> = response.headers.iter().collect();
+        headers.sort();
+        for (name, value) in headers {
+            self.print_transcript_header(&format!("{}: {}", name, value));
+        }
+        println!();
+
+        if options.summary_only || options.headers_only {
+            return Ok(());
+        }
+        if let Some(ref filter) = options.jq {
+            self.print_filtered_body(response, filter, options)?;
+        } else if let Some(ref pattern) = options.grep {
+            self.print_grep_body(&response.body, pattern, options);
+        } else {
+            self.print_body(response, options);
+        }
+
+        if options.verbose {
+            self.print_footer(response);
+        }
+        Ok(())
+    }
+
+    fn print_transcript_header(&self, header: &str) {
+        match header.split_once(": ") {
+            Some((name, value)) => println!("{}: {}", name.bright_blue(), value),
+            None => println!("{}", header),
+        }
+    }
+
+    // Print body based on format
+    fn print_body(&self, response: &Response, options: &DisplayOptions) {
+        match options.output.as_str() {
+            "json" => self.print_json_body(&response.body, options),
+            "yaml" => self.print_yaml_body(&response.body),
+            "xml" => self.print_xml_body(&response.body),
+            "form" => self.print_form_body(&response.body),
+            "table" => self.print_table_body(&response.body, options),
+            "raw" => self.print_raw_body(&response.body),
+            _ => self.print_auto_body(response, options),
+        }
+    }
+
+    // Each filter output is printed as its own JSON body; with -o raw strings lose their quotes
+    fn print_filtered_body(
+        &self,
+        response: &Response,
+        filter: &str,
+        options: &DisplayOptions,
+    ) -> Result<()> {
+        for value in crate::utils::run_jq(filter, &response.body)? {
+            if let (jaq_json::Val::TStr(text), "raw") = (&value, options.output.as_str()) {
+                println!("{}", String::from_utf8_lossy(text));
+                continue;
+            }
+
+            let mut filtered = response.clone();
+            filtered.body = value.to_string();
+            filtered
+                .headers
+                .insert("content-type".to_string(), "application/json".to_string());
+            self.print_body(&filtered, options);
+        }
+        Ok(())
+    }
+
     fn print_status_line(&self, response: &Response) {
-        let status_color = match response.status {
-            200..=299 => "bright_green",
-            300..=399 => "bright_yellow",
-            400..=499 => "bright_red",
-            500..=599 => "red",
-            _ => "white",
-        };
+        let status_color = status_color(response.status);
 
         println!(
-            "{} {} {} {} {}",
+            "{} {} {} {} {}{}",
             response.status_emoji(),
             response.method.bright_blue().bold(),
             response.url.bright_cyan().underline(),
             response.status.to_string().color(status_color).bold(),
             format!("({})", response.duration_human())
                 .bright_black()
-                .italic()
+                .italic(),
+            if response.from_cache {
+                format!(" {}", "📦 from cache (304 Not Modified)".bright_black())
+            } else {
+                String::new()
+            }
         );
+        if let Some(location) = response.redirect_location() {
+            println!(
+                "   {} {}",
+                "↪ Location:".bright_yellow().bold(),
+                location.bright_cyan().underline()
+            );
+        }
     }
 
     fn print_headers(&self, headers: &HashMap<String, String>) {
@@ -89,7 +269,7 @@ impl ResponseFormatter {
         }
     }
 
-    fn print_json_body(&self, body: &str, pretty: bool) {
+    fn print_json_body(&self, body: &str, options: &DisplayOptions) {
         if body.is_empty() {
             println!("{}", "No response body".bright_black());
             return;
@@ -97,9 +277,8 @@ impl ResponseFormatter {
 
         // Validate JSON first, then format appropriately
         if crate::utils::is_valid_json(body) {
-            let formatted = if pretty {
-                // Use utils prettify_json for better formatting
-                match crate::utils::prettify_json(body) {
+            let formatted = if options.pretty {
+                match crate::utils::prettify_json_with_indent(body, options.json_indent) {
                     Ok(formatted) => formatted,
                     Err(_) => body.to_string(),
                 }
@@ -117,69 +296,48 @@ impl ResponseFormatter {
         }
     }
 
-    fn pretty_format_json(&self, json_str: &str) -> Result<String> {
-        // Simple pretty formatting that preserves field order
-        let mut formatted = String::new();
-        let mut indent_level = 0;
-        let mut in_string = false;
-        let mut escape_next = false;
-        let chars: Vec<char> = json_str.chars().collect();
+    // JSON and XML are re-indented first so matches land on readable lines
+    fn print_grep_body(&self, body: &str, pattern: &regex::Regex, options: &DisplayOptions) {
+        let text = if crate::utils::is_valid_json(body) {
+            crate::utils::prettify_json_with_indent(body, options.json_indent)
+                .unwrap_or_else(|_| body.to_string())
+        } else if body.trim_start().starts_with('<') {
+            crate::utils::prettify_xml(body).unwrap_or_else(|_| body.to_string())
+        } else {
+            body.to_string()
+        };
 
-        for (i, &ch) in chars.iter().enumerate() {
-            if escape_next {
-                formatted.push(ch);
-                escape_next = false;
-                continue;
-            }
+        let lines = grep_lines(&text, pattern, options.grep_context);
+        if lines.is_empty() {
+            println!(
+                "{}",
+                format!("No lines match /{}/", pattern.as_str()).bright_black()
+            );
+            return;
+        }
 
-            match ch {
-                '"' if !escape_next => {
-                    in_string = !in_string;
-                    formatted.push(ch);
-                }
-                '\\' if in_string => {
-                    escape_next = true;
-                    formatted.push(ch);
-                }
-                '{' | '[' if !in_string => {
-                    formatted.push(ch);
-                    indent_level += 1;
-                    if i + 1 < chars.len() && chars[i + 1] != '}' && chars[i + 1] != ']' {
-                        formatted.push('\n');
-                        formatted.push_str(&"  ".repeat(indent_level));
-                    }
-                }
-                '}' | ']' if !in_string => {
-                    if chars.get(i.saturating_sub(1)) != Some(&'{')
-                        && chars.get(i.saturating_sub(1)) != Some(&'[')
-                    {
-                        formatted.push('\n');
-                        indent_level = indent_level.saturating_sub(1);
-                        formatted.push_str(&"  ".repeat(indent_level));
-                    } else {
-                        indent_level = indent_level.saturating_sub(1);
-                    }
-                    formatted.push(ch);
-                }
-                ',' if !in_string => {
-                    formatted.push(ch);
-                    formatted.push('\n');
-                    formatted.push_str(&"  ".repeat(indent_level));
-                }
-                ':' if !in_string => {
-                    formatted.push(ch);
-                    formatted.push(' ');
-                }
-                _ if ch.is_whitespace() && !in_string => {
-                    // Skip existing whitespace outside strings
+        for line in lines {
+            match line {
+                Some((number, line, true)) => {
+                    let highlighted = pattern.replace_all(line, |caps: &regex::Captures| {
+                        caps[0].bright_yellow().bold().to_string()
+                    });
+                    println!(
+                        "{} {}",
+                        format!("{:>5}:", number).bright_black(),
+                        highlighted
+                    );
                 }
-                _ => {
-                    formatted.push(ch);
+                Some((number, line, false)) => {
+                    println!(
+                        "{} {}",
+                        format!("{:>5}-", number).bright_black(),
+                        line.bright_black()
+                    );
                 }
+                None => println!("{}", "   --".bright_black()),
             }
         }
-
-        Ok(formatted)
     }
 
     fn print_yaml_body(&self, body: &str) {
@@ -190,7 +348,7 @@ impl ResponseFormatter {
 
         // Try to parse as JSON first, then convert to YAML
         match serde_json::from_str::<Value>(body) {
-            Ok(json) => match serde_yaml::to_string(&json) {
+            Ok(json) => match crate::utils::json_to_yaml(&json) {
                 Ok(yaml) => self.highlight_and_print(&yaml, "yaml"),
                 Err(_) => self.print_raw_body(body),
             },
@@ -201,19 +359,111 @@ impl ResponseFormatter {
         }
     }
 
-    fn print_table_body(&self, body: &str) {
+    fn print_xml_body(&self, body: &str) {
+        if body.is_empty() {
+            println!("{}", "No response body".bright_black());
+            return;
+        }
+
+        // Re-indent when the document parses, otherwise show it untouched
+        match crate::utils::prettify_xml(body) {
+            Ok(formatted) => self.highlight_and_print(&formatted, "xml"),
+            Err(_) => self.print_raw_body(body),
+        }
+    }
+
+    fn print_form_body(&self, body: &str) {
+        if body.is_empty() {
+            println!("{}", "No response body".bright_black());
+            return;
+        }
+
+        match form_table(body) {
+            Some(table) => println!("{}", table),
+            None => self.print_raw_body(body),
+        }
+    }
+
+    fn print_table_body(&self, body: &str, options: &DisplayOptions) {
         if body.is_empty() {
             println!("{}", "No response body".bright_black());
             return;
         }
 
         match serde_json::from_str::<Value>(body) {
+            Ok(value) if !options.columns.is_empty() && (value.is_array() || value.is_object()) => {
+                // A single object becomes a one-row table
+                let items = match value {
+                    Value::Array(arr) => arr,
+                    other => vec![other],
+                };
+                if items.is_empty() {
+                    println!("{}", "Empty array".bright_black());
+                    return;
+                }
+
+                let headers: Vec<&str> = options.columns.iter().map(|c| column_label(c)).collect();
+                let rows: Vec<Vec<String>> = items
+                    .iter()
+                    .map(|item| {
+                        options
+                            .columns
+                            .iter()
+                            .map(|path| {
+                                crate::utils::json_path(item, path)
+                                    .map(|v| self.value_to_string(v))
+                                    .unwrap_or_default()
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                let table = crate::utils::create_simple_responsive_table(headers, rows);
+                println!("{}", table);
+            }
             Ok(Value::Array(arr)) => {
                 if arr.is_empty() {
                     println!("{}", "Empty array".bright_black());
                     return;
                 }
 
+                if let Some(depth) = options.flatten_depth
+                    && arr.iter().all(Value::is_object)
+                {
+                    let rows: Vec<Vec<(String, Value)>> =
+                        arr.iter().map(|item| flatten_json(item, depth)).collect();
+
+                    // Later rows may carry keys the first one lacks, so take the union in order
+                    let mut headers: Vec<String> = Vec::new();
+                    for row in &rows {
+                        for (key, _) in row {
+                            if !headers.contains(key) {
+                                headers.push(key.clone());
+                            }
+                        }
+                    }
+                    let headers_ref: Vec<&str> = headers.iter().map(|s| s.as_str()).collect();
+
+                    let rows: Vec<Vec<String>> = rows
+                        .iter()
+                        .map(|row| {
+                            headers
+                                .iter()
+                                .map(|h| {
+                                    row.iter()
+                                        .find(|(key, _)| key == h)
+                                        .map(|(_, v)| self.value_to_string(v))
+                                        .unwrap_or_default()
+                                })
+                                .collect()
+                        })
+                        .collect();
+
+                    let table = crate::utils::create_simple_responsive_table(headers_ref, rows);
+                    println!("{}", table);
+                    return;
+                }
+
                 // Get headers from first object
                 if let Some(Value::Object(first_obj)) = arr.first() {
                     let headers: Vec<String> = first_obj.keys().cloned().collect();
@@ -247,12 +497,16 @@ impl ResponseFormatter {
                         "{}",
                         "Cannot create table from non-object array".bright_red()
                     );
-                    self.print_json_body(body, true);
+                    self.print_json_body(body, options);
                 }
             }
             Ok(Value::Object(obj)) => {
                 let headers = vec!["Key", "Value"];
-                let rows: Vec<Vec<String>> = obj
+                let pairs = match options.flatten_depth {
+                    Some(depth) => flatten_json(&Value::Object(obj), depth),
+                    None => obj.into_iter().collect(),
+                };
+                let rows: Vec<Vec<String>> = pairs
                     .iter()
                     .map(|(key, value)| {
                         vec![key.bright_blue().to_string(), self.value_to_string(value)]
@@ -267,7 +521,7 @@ impl ResponseFormatter {
                     "{}",
                     "Cannot create table from this response type".bright_red()
                 );
-                self.print_json_body(body, true);
+                self.print_json_body(body, options);
             }
         }
     }
@@ -280,11 +534,13 @@ impl ResponseFormatter {
         }
     }
 
-    fn print_auto_body(&self, response: &Response, pretty: bool) {
+    fn print_auto_body(&self, response: &Response, options: &DisplayOptions) {
         if response.is_json() {
-            self.print_json_body(&response.body, pretty);
+            self.print_json_body(&response.body, options);
         } else if response.is_xml() {
-            self.highlight_and_print(&response.body, "xml");
+            self.print_xml_body(&response.body);
+        } else if response.is_form_urlencoded() {
+            self.print_form_body(&response.body);
         } else if response.is_html() {
             self.highlight_and_print(&response.body, "html");
         } else {
@@ -333,6 +589,23 @@ impl ResponseFormatter {
             ],
             vec!["Size".bright_blue().to_string(), response.size_human()],
         ];
+        let transfer = response.transfer;
+        if transfer.request_body_bytes > 0 {
+            rows.push(vec![
+                "Request Body".bright_blue().to_string(),
+                crate::utils::format_bytes(transfer.request_body_bytes),
+            ]);
+        }
+        if transfer.sent() + transfer.received() > 0 {
+            rows.push(vec![
+                "Transferred".bright_blue().to_string(),
+                format!(
+                    "{} sent, {} received (headers included)",
+                    crate::utils::format_bytes(transfer.sent()),
+                    crate::utils::format_bytes(transfer.received())
+                ),
+            ]);
+        }
 
         if let Some(content_type) = response.content_type() {
             rows.push(vec![
@@ -340,9 +613,121 @@ impl ResponseFormatter {
                 content_type.clone(),
             ]);
         }
+        if let Some(location) = response.redirect_location() {
+            rows.push(vec!["Location".bright_blue().to_string(), location.clone()]);
+        }
+        if let Some(ref id) = response.request_id {
+            rows.push(vec!["Request ID".bright_blue().to_string(), id.clone()]);
+        }
 
         let table = crate::utils::create_simple_responsive_table(headers, rows);
         println!("{}", table);
+
+        self.print_cookies(response);
+        self.print_caching(&response.headers);
+
+        if !response.redirects.is_empty() {
+            println!();
+            println!("{}", "Redirects:".bright_yellow().bold());
+            for (i, hop) in response.redirects.iter().enumerate() {
+                println!(
+                    "  {}. {} {} {} {}",
+                    i + 1,
+                    hop.status
+                        .to_string()
+                        .color(status_color(hop.status))
+                        .bold(),
+                    hop.url.bright_cyan(),
+                    "→".bright_black(),
+                    hop.location.bright_cyan()
+                );
+            }
+        }
+    }
+
+    // Set-Cookie split into its parts, one row per cookie
+    fn print_cookies(&self, response: &Response) {
+        let mut cookies: Vec<&str> = response.set_cookies.iter().map(String::as_str).collect();
+        if cookies.is_empty()
+            && let Some(cookie) = response.headers.get("set-cookie")
+        {
+            cookies.push(cookie);
+        }
+        if cookies.is_empty() {
+            return;
+        }
+
+        println!();
+        println!("{}", "Cookies Set:".bright_yellow().bold());
+        let rows = cookies
+            .iter()
+            .filter_map(|cookie| parse_set_cookie(cookie))
+            .map(|cookie| {
+                let expires = match (cookie.attribute("max-age"), cookie.attribute("expires")) {
+                    (Some(max_age), _) => format!("in {}s", max_age),
+                    (None, Some(expires)) => expires.to_string(),
+                    (None, None) => "session".bright_black().to_string(),
+                };
+                let flags: Vec<String> = cookie
+                    .attributes
+                    .iter()
+                    .filter(|(name, _)| {
+                        !name.eq_ignore_ascii_case("expires")
+                            && !name.eq_ignore_ascii_case("max-age")
+                    })
+                    .map(|(name, value)| match value {
+                        Some(value) => format!("{}={}", name, value),
+                        None => name.bright_green().to_string(),
+                    })
+                    .collect();
+                vec![
+                    cookie.name.bright_blue().to_string(),
+                    cookie.value.to_string(),
+                    expires,
+                    flags.join(" "),
+                ]
+            })
+            .collect();
+        let table = crate::utils::create_simple_responsive_table(
+            vec!["Name", "Value", "Expires", "Attributes"],
+            rows,
+        );
+        println!("{}", table);
+    }
+
+    fn print_caching(&self, headers: &HashMap<String, String>) {
+        let rows: Vec<Vec<String>> = CACHE_HEADERS
+            .iter()
+            .filter_map(|(name, label)| {
+                let value = headers.get(*name)?;
+                let value = if *name == "cache-control" {
+                    value
+                        .split(',')
+                        .map(|directive| {
+                            let directive = directive.trim();
+                            match directive.to_ascii_lowercase().as_str() {
+                                "no-store" | "no-cache" | "private" => {
+                                    directive.bright_yellow().to_string()
+                                }
+                                _ => directive.to_string(),
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                } else {
+                    value.clone()
+                };
+                Some(vec![label.bright_blue().to_string(), value])
+            })
+            .collect();
+        if rows.is_empty() {
+            return;
+        }
+
+        println!();
+        println!("{}", "Caching:".bright_yellow().bold());
+        let table = crate::utils::create_simple_responsive_table(vec!["Header", "Value"], rows);
+        println!("{}", table);
     }
 
     pub fn display_error(&self, error: &str) {
@@ -361,3 +746,245 @@ impl ResponseFormatter {
         println!("{} {}", "⚠️".yellow(), message);
     }
 }
+
+// Shown together in the verbose footer, in this order
+const CACHE_HEADERS: &[(&str, &str)] = &[
+    ("cache-control", "Cache-Control"),
+    ("etag", "ETag"),
+    ("age", "Age"),
+    ("expires", "Expires"),
+    ("last-modified", "Last-Modified"),
+    ("vary", "Vary"),
+];
+
+#[derive(Debug, PartialEq)]
+struct SetCookie<'a> {
+    name: &'a str,
+    value: &'a str,
+    attributes: Vec<(&'a str, Option<&'a str>)>,
+}
+
+impl SetCookie<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+            .and_then(|(_, value)| *value)
+    }
+}
+
+// `name=value; Path=/; Secure` -> the pair and its attributes, as RFC 6265 lays them out
+fn parse_set_cookie(header: &str) -> Option<SetCookie<'_>> {
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let attributes = parts
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (part, None),
+        })
+        .collect();
+    Some(SetCookie {
+        name: name.trim(),
+        value: value.trim(),
+        attributes,
+    })
+}
+
+fn status_color(status: u16) -> &'static str {
+    match status {
+        200..=299 => "bright_green",
+        300..=399 => "bright_yellow",
+        400..=499 => "bright_red",
+        500..=599 => "red",
+        _ => "white",
+    }
+}
+
+fn form_table(body: &str) -> Option<comfy_table::Table> {
+    let pairs = crate::utils::parse_form_urlencoded(body);
+    if pairs.is_empty() {
+        return None;
+    }
+
+    let headers = vec!["Key", "Value"];
+    let rows: Vec<Vec<String>> = pairs
+        .into_iter()
+        .map(|(key, value)| vec![key, value])
+        .collect();
+
+    Some(crate::utils::create_simple_responsive_table(headers, rows))
+}
+
+// Column header for a --columns expression: `$.user.name` reads as `user.name`
+fn column_label(path: &str) -> &str {
+    path.strip_prefix("$.")
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or(path)
+}
+
+// Dotted key paths for nested objects, stopping after `depth` levels; arrays and
+// anything deeper are left as values so the column count stays bounded
+fn flatten_json(value: &Value, depth: usize) -> Vec<(String, Value)> {
+    fn walk(value: &Value, prefix: &str, depth: usize, out: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(obj) if depth > 0 && !obj.is_empty() => {
+                for (key, child) in obj {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(child, &path, depth - 1, out);
+                }
+            }
+            _ => out.push((prefix.to_string(), value.clone())),
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(value, "", depth, &mut out);
+    out
+}
+
+// grep-style selection: (1-based line number, line, is a match), with None
+// between groups that are not adjacent
+fn grep_lines<'a>(
+    text: &'a str,
+    pattern: &regex::Regex,
+    context: usize,
+) -> Vec<Option<(usize, &'a str, bool)>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let matches: Vec<bool> = lines.iter().map(|line| pattern.is_match(line)).collect();
+
+    let mut selected = Vec::new();
+    let mut last_printed: Option<usize> = None;
+    for (index, _) in matches.iter().enumerate().filter(|(_, matched)| **matched) {
+        let start = index.saturating_sub(context);
+        let end = (index + context).min(lines.len() - 1);
+        let start = match last_printed {
+            Some(last) if last >= start => last + 1,
+            Some(last) => {
+                if start > last + 1 {
+                    selected.push(None);
+                }
+                start
+            }
+            None => start,
+        };
+        for line in start..=end {
+            selected.push(Some((line + 1, lines[line], matches[line])));
+            last_printed = Some(line);
+        }
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grep_lines_with_context() {
+        let text = "a\nmatch 1\nb\nc\nd\ne\nmatch 2\nmatch 3\nf";
+        let pattern = regex::Regex::new("match").unwrap();
+
+        let lines = grep_lines(text, &pattern, 0);
+        assert_eq!(
+            lines,
+            vec![
+                Some((2, "match 1", true)),
+                None,
+                Some((7, "match 2", true)),
+                Some((8, "match 3", true)),
+            ]
+        );
+
+        // Overlapping context is printed once and adjacent groups are not split
+        let lines = grep_lines(text, &pattern, 2);
+        let numbers: Vec<Option<usize>> = lines.iter().map(|l| l.map(|(n, _, _)| n)).collect();
+        assert_eq!(
+            numbers,
+            vec![
+                Some(1),
+                Some(2),
+                Some(3),
+                Some(4),
+                Some(5),
+                Some(6),
+                Some(7),
+                Some(8),
+                Some(9)
+            ]
+        );
+        assert!(grep_lines(text, &regex::Regex::new("zzz").unwrap(), 1).is_empty());
+    }
+
+    #[test]
+    fn test_parse_set_cookie_splits_attributes() {
+        let cookie = parse_set_cookie(
+            "session=abc=123; Expires=Wed, 21 Oct 2026 07:28:00 GMT; Path=/; Secure; HttpOnly",
+        )
+        .unwrap();
+        assert_eq!(cookie.name, "session");
+        assert_eq!(cookie.value, "abc=123");
+        assert_eq!(
+            cookie.attribute("expires"),
+            Some("Wed, 21 Oct 2026 07:28:00 GMT")
+        );
+        assert_eq!(cookie.attribute("path"), Some("/"));
+        assert_eq!(cookie.attributes.last(), Some(&("HttpOnly", None)));
+
+        assert_eq!(parse_set_cookie("not a cookie"), None);
+    }
+
+    #[test]
+    fn test_form_table_lists_decoded_pairs() {
+        let table = form_table("access_token=abc%2F123&token_type=bearer&expires_in=3600")
+            .unwrap()
+            .to_string();
+
+        assert!(table.contains("access_token"));
+        assert!(table.contains("abc/123"));
+        assert!(table.contains("token_type"));
+        assert!(table.contains("expires_in"));
+        assert!(table.contains("3600"));
+        assert!(form_table("").is_none());
+    }
+
+    #[test]
+    fn test_column_label_drops_root_prefix() {
+        assert_eq!(column_label("$.id"), "id");
+        assert_eq!(column_label("$.user.name"), "user.name");
+        assert_eq!(column_label("$['first name']"), "$['first name']");
+        assert_eq!(column_label("$"), "$");
+    }
+
+    #[test]
+    fn test_flatten_json_uses_dotted_keys_up_to_depth() {
+        let value = serde_json::json!({
+            "id": 1,
+            "user": {"name": "Ada", "address": {"city": "London", "geo": {"lat": 51.5}}},
+            "tags": ["a", "b"],
+        });
+
+        let keys: Vec<String> = flatten_json(&value, 3)
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "id",
+                "user.name",
+                "user.address.city",
+                "user.address.geo",
+                "tags"
+            ]
+        );
+
+        let shallow = flatten_json(&value, 1);
+        assert!(shallow.iter().any(|(k, v)| k == "user" && v.is_object()));
+    }
+}
@@ -0,0 +1,641 @@
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+
+use crate::request::{Body, RequestCollection, SavedRequest};
+
+// Postman Collection v2.1 interchange
+const SCHEMA_URL: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+/// Builds a collection with saved requests at the top level and one folder per terzi collection.
+pub fn export_collection(
+    name: &str,
+    requests: &[SavedRequest],
+    collections: &[RequestCollection],
+) -> Value {
+    let mut items: Vec<Value> = requests.iter().map(request_item).collect();
+    items.extend(collections.iter().map(|collection| {
+        let mut folder = json!({
+            "name": collection.name,
+            "item": collection.requests.iter().map(request_item).collect::<Vec<_>>(),
+        });
+        if let Some(ref description) = collection.description {
+            folder["description"] = Value::from(description.as_str());
+        }
+        folder
+    }));
+
+    json!({
+        "info": {
+            "_postman_id": uuid::Uuid::new_v4().to_string(),
+            "name": name,
+            "schema": SCHEMA_URL,
+        },
+        "item": items,
+    })
+}
+
+fn request_item(request: &SavedRequest) -> Value {
+    let mut headers: Vec<(&String, &String)> = request.headers.iter().collect();
+    headers.sort();
+
+    let mut postman_request = json!({
+        "method": request.method.to_uppercase(),
+        "header": headers
+            .iter()
+            .map(|(key, value)| json!({"key": key, "value": value}))
+            .collect::<Vec<_>>(),
+        "url": url(&request.url),
+    });
+
+    match request.body {
+        Some(Body::Text(ref body)) => {
+            let content_type = request
+                .headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+                .map(|(_, value)| value.to_lowercase())
+                .unwrap_or_default();
+            postman_request["body"] = body_for(body, &content_type);
+        }
+        Some(Body::File { ref file }) => {
+            postman_request["body"] = json!({"mode": "file", "file": {"src": file}});
+        }
+        // Postman has no way to embed raw bytes or a piped body in a collection
+        Some(Body::Bytes { .. }) | Some(Body::Stdin) | None => {}
+    }
+
+    if let Some(ref digest) = request.digest_auth {
+        postman_request["auth"] = json!({
+            "type": "digest",
+            "digest": [
+                {"key": "username", "value": digest.username, "type": "string"},
+                {"key": "password", "value": digest.password, "type": "string"},
+            ],
+        });
+    }
+
+    let mut item = json!({"name": request.name, "request": postman_request});
+    if let Some(ref description) = request.description {
+        item["request"]["description"] = Value::from(description.as_str());
+    }
+    item
+}
+
+fn body_for(body: &str, content_type: &str) -> Value {
+    if content_type.contains("application/x-www-form-urlencoded") {
+        let fields: Vec<Value> = body
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                json!({"key": decode(key), "value": decode(value)})
+            })
+            .collect();
+        return json!({"mode": "urlencoded", "urlencoded": fields});
+    }
+
+    let language = if content_type.contains("json") {
+        "json"
+    } else if content_type.contains("xml") {
+        "xml"
+    } else if content_type.contains("html") {
+        "html"
+    } else {
+        "text"
+    };
+    json!({
+        "mode": "raw",
+        "raw": body,
+        "options": {"raw": {"language": language}},
+    })
+}
+
+fn decode(value: &str) -> String {
+    let value = value.replace('+', " ");
+    urlencoding::decode(&value)
+        .map(|decoded| decoded.into_owned())
+        .unwrap_or(value)
+}
+
+// Split by hand rather than with `url::Url`, which would percent-encode `{{variables}}`
+fn url(raw: &str) -> Value {
+    let (protocol, rest) = match raw.split_once("://") {
+        Some((protocol, rest)) => (Some(protocol), rest),
+        None => (None, raw),
+    };
+    let rest = rest.split('#').next().unwrap_or(rest);
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index + 1..]),
+        None => (rest, ""),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => {
+            (host, Some(port))
+        }
+        _ => (authority, None),
+    };
+
+    let mut url = json!({
+        "raw": raw,
+        "host": host.split('.').collect::<Vec<_>>(),
+        "path": path.split('/').filter(|segment| !segment.is_empty()).collect::<Vec<_>>(),
+    });
+    if let Some(protocol) = protocol {
+        url["protocol"] = Value::from(protocol);
+    }
+    if let Some(port) = port {
+        url["port"] = Value::from(port);
+    }
+    if let Some(query) = query.filter(|query| !query.is_empty()) {
+        url["query"] = Value::Array(
+            query
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    json!({"key": key, "value": value})
+                })
+                .collect(),
+        );
+    }
+    url
+}
+
+/// Requests and folders read from a Postman collection.
+#[derive(Debug, Default)]
+pub struct PostmanImport {
+    pub name: String,
+    /// Requests at the top level of the collection
+    pub requests: Vec<SavedRequest>,
+    /// One collection per folder; nested folders are named `Parent / Child`
+    pub folders: Vec<RequestCollection>,
+    /// Collection variables, kept as `{{name}}` in the imported requests
+    pub variables: Vec<(String, String)>,
+    pub warnings: Vec<String>,
+}
+
+pub fn import_collection(contents: &str) -> Result<PostmanImport> {
+    let document: Value = serde_json::from_str(contents)
+        .map_err(|e| anyhow!("Postman collection is not valid JSON: {}", e))?;
+
+    let schema = document
+        .pointer("/info/schema")
+        .and_then(Value::as_str)
+        .unwrap_or("");
+    if !schema.contains("v2.1") && !schema.contains("v2.0") {
+        return Err(anyhow!(
+            "Unsupported Postman collection: expected a v2.0 or v2.1 schema in info.schema"
+        ));
+    }
+
+    let mut import = PostmanImport {
+        name: document
+            .pointer("/info/name")
+            .and_then(Value::as_str)
+            .unwrap_or("postman")
+            .to_string(),
+        ..Default::default()
+    };
+    import.variables = document
+        .get("variable")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|variable| {
+            let key = variable.get("key")?.as_str()?;
+            Some((key.to_string(), text(variable.get("value"))))
+        })
+        .collect();
+
+    let items = document.get("item").and_then(Value::as_array);
+    let auth = document.get("auth");
+    for item in items.into_iter().flatten() {
+        if item.get("item").is_some() {
+            walk_folder(item, None, auth, &mut import);
+        } else if let Some(request) = read_request(item, auth, &mut import.warnings) {
+            import.requests.push(request);
+        }
+    }
+
+    Ok(import)
+}
+
+fn walk_folder(
+    folder: &Value,
+    parent: Option<&str>,
+    inherited_auth: Option<&Value>,
+    import: &mut PostmanImport,
+) {
+    let folder_name = folder
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("folder");
+    let name = match parent {
+        Some(parent) => format!("{} / {}", parent, folder_name),
+        None => folder_name.to_string(),
+    };
+    let auth = folder.get("auth").or(inherited_auth);
+
+    let mut collection = RequestCollection::new(name.clone());
+    collection.description = description(folder.get("description"));
+
+    let items = folder.get("item").and_then(Value::as_array);
+    for item in items.into_iter().flatten() {
+        if item.get("item").is_some() {
+            walk_folder(item, Some(&name), auth, import);
+        } else if let Some(request) = read_request(item, auth, &mut import.warnings) {
+            collection.add_request(request);
+        }
+    }
+
+    import.folders.push(collection);
+}
+
+fn read_request(
+    item: &Value,
+    inherited_auth: Option<&Value>,
+    warnings: &mut Vec<String>,
+) -> Option<SavedRequest> {
+    let name = item
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("request")
+        .to_string();
+    let Some(request) = item.get("request") else {
+        warnings.push(format!("'{}' has no request and was skipped", name));
+        return None;
+    };
+
+    // A request may be just its URL
+    let (method, url) = match request {
+        Value::String(url) => ("GET".to_string(), url.clone()),
+        _ => (
+            request
+                .get("method")
+                .and_then(Value::as_str)
+                .unwrap_or("GET")
+                .to_uppercase(),
+            read_url(request.get("url")),
+        ),
+    };
+    let mut saved = SavedRequest::new(name.clone(), url, method);
+    saved.description = description(request.get("description"));
+
+    let headers = request.get("header").and_then(Value::as_array);
+    for header in headers.into_iter().flatten().filter(|h| !is_disabled(h)) {
+        if let Some(key) = header.get("key").and_then(Value::as_str) {
+            saved.add_header(key.to_string(), text(header.get("value")));
+        }
+    }
+
+    if let Some(body) = request.get("body") {
+        read_body(&mut saved, body, warnings);
+    }
+
+    // Requests without their own auth inherit the nearest folder's or the collection's
+    if let Some(auth) = request.get("auth").or(inherited_auth) {
+        read_auth(&mut saved, auth, warnings);
+    }
+
+    Some(saved)
+}
+
+fn read_url(url: Option<&Value>) -> String {
+    match url {
+        Some(Value::String(raw)) => raw.clone(),
+        Some(url) => match url.get("raw").and_then(Value::as_str) {
+            Some(raw) => raw.to_string(),
+            None => {
+                let joined = |key: &str, separator: &str| {
+                    url.get(key)
+                        .and_then(Value::as_array)
+                        .map(|parts| {
+                            parts
+                                .iter()
+                                .map(|part| text(Some(part)))
+                                .collect::<Vec<_>>()
+                                .join(separator)
+                        })
+                        .unwrap_or_default()
+                };
+                let mut raw = String::new();
+                if let Some(protocol) = url.get("protocol").and_then(Value::as_str) {
+                    raw.push_str(protocol);
+                    raw.push_str("://");
+                }
+                raw.push_str(&joined("host", "."));
+                if let Some(port) = url.get("port").and_then(Value::as_str) {
+                    raw.push(':');
+                    raw.push_str(port);
+                }
+                let path = joined("path", "/");
+                if !path.is_empty() {
+                    raw.push('/');
+                    raw.push_str(&path);
+                }
+                raw
+            }
+        },
+        None => String::new(),
+    }
+}
+
+fn read_body(saved: &mut SavedRequest, body: &Value, warnings: &mut Vec<String>) {
+    let has_content_type = saved
+        .headers
+        .keys()
+        .any(|key| key.eq_ignore_ascii_case("content-type"));
+
+    match body.get("mode").and_then(Value::as_str) {
+        Some("raw") => {
+            let raw = text(body.get("raw"));
+            if raw.is_empty() {
+                return;
+            }
+            let content_type = match body
+                .pointer("/options/raw/language")
+                .and_then(Value::as_str)
+            {
+                Some("json") => Some("application/json"),
+                Some("xml") => Some("application/xml"),
+                Some("html") => Some("text/html"),
+                _ => None,
+            };
+            if let (false, Some(content_type)) = (has_content_type, content_type) {
+                saved.add_header("Content-Type".to_string(), content_type.to_string());
+            }
+            saved.set_body(Some(raw));
+        }
+        Some(mode @ ("urlencoded" | "formdata")) => {
+            let mut pairs = Vec::new();
+            let fields = body.get(mode).and_then(Value::as_array);
+            for field in fields.into_iter().flatten().filter(|f| !is_disabled(f)) {
+                let key = text(field.get("key"));
+                if field.get("type").and_then(Value::as_str) == Some("file") {
+                    warnings.push(format!(
+                        "'{}': file field '{}' is not supported and was dropped",
+                        saved.name, key
+                    ));
+                    continue;
+                }
+                pairs.push(format!(
+                    "{}={}",
+                    urlencoding::encode(&key),
+                    urlencoding::encode(&text(field.get("value")))
+                ));
+            }
+            if mode == "formdata" {
+                warnings.push(format!(
+                    "'{}': multipart form data was converted to a urlencoded form",
+                    saved.name
+                ));
+                saved
+                    .headers
+                    .retain(|key, _| !key.eq_ignore_ascii_case("content-type"));
+            }
+            if mode == "formdata" || !has_content_type {
+                saved.add_header(
+                    "Content-Type".to_string(),
+                    "application/x-www-form-urlencoded".to_string(),
+                );
+            }
+            saved.set_body(Some(pairs.join("&")));
+        }
+        Some(other) => warnings.push(format!(
+            "'{}': body mode '{}' is not supported and was dropped",
+            saved.name, other
+        )),
+        None => {}
+    }
+}
+
+// Maps Postman auth onto terzi auth strings, e.g. `basic:user:pass`
+fn read_auth(saved: &mut SavedRequest, auth: &Value, warnings: &mut Vec<String>) {
+    let auth_type = auth.get("type").and_then(Value::as_str).unwrap_or("noauth");
+    let param = |key: &str| auth_param(auth, auth_type, key);
+
+    let auth_string = match auth_type {
+        "noauth" | "inherit" => return,
+        "bearer" => format!("bearer:{}", param("token")),
+        "basic" => format!("basic:{}:{}", param("username"), param("password")),
+        "digest" => format!("digest:{}:{}", param("username"), param("password")),
+        "apikey" => {
+            let key = param("key");
+            let value = param("value");
+            if param("in") == "query" {
+                let separator = if saved.url.contains('?') { '&' } else { '?' };
+                saved.url = format!("{}{}{}={}", saved.url, separator, key, value);
+                return;
+            }
+            format!("apikey:{}:{}", key, value)
+        }
+        other => {
+            warnings.push(format!(
+                "'{}': {} auth is not supported; add it by hand",
+                saved.name, other
+            ));
+            return;
+        }
+    };
+
+    if let Err(e) = saved.apply_auth(&auth_string) {
+        warnings.push(format!("'{}': {}", saved.name, e));
+    }
+}
+
+// v2.1 stores auth parameters as a key/value list, v2.0 as an object
+fn auth_param(auth: &Value, auth_type: &str, key: &str) -> String {
+    match auth.get(auth_type) {
+        Some(Value::Array(params)) => params
+            .iter()
+            .find(|param| param.get("key").and_then(Value::as_str) == Some(key))
+            .map(|param| text(param.get("value")))
+            .unwrap_or_default(),
+        Some(Value::Object(params)) => text(params.get(key)),
+        _ => String::new(),
+    }
+}
+
+fn description(value: Option<&Value>) -> Option<String> {
+    match value {
+        Some(Value::String(text)) if !text.is_empty() => Some(text.clone()),
+        Some(Value::Object(fields)) => fields
+            .get("content")
+            .and_then(Value::as_str)
+            .map(String::from),
+        _ => None,
+    }
+}
+
+fn is_disabled(value: &Value) -> bool {
+    value
+        .get("disabled")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_is_split_into_parts() {
+        let url = url("https://api.example.com:8443/v1/users/{{id}}?page=2&sort");
+        assert_eq!(url["protocol"], "https");
+        assert_eq!(url["host"], json!(["api", "example", "com"]));
+        assert_eq!(url["port"], "8443");
+        assert_eq!(url["path"], json!(["v1", "users", "{{id}}"]));
+        assert_eq!(
+            url["query"],
+            json!([{"key": "page", "value": "2"}, {"key": "sort", "value": ""}])
+        );
+
+        let templated = super::url("{{base_url}}/health");
+        assert_eq!(templated["host"], json!(["{{base_url}}"]));
+        assert!(templated.get("protocol").is_none());
+    }
+
+    #[test]
+    fn test_body_mode_follows_content_type() {
+        let mut request = SavedRequest::new(
+            "login".to_string(),
+            "https://example.com/login".to_string(),
+            "POST".to_string(),
+        );
+        request.add_header(
+            "Content-Type".to_string(),
+            "application/x-www-form-urlencoded".to_string(),
+        );
+        request.set_body(Some("user=ada&note=hello+world".to_string()));
+
+        let item = request_item(&request);
+        assert_eq!(item["request"]["body"]["mode"], "urlencoded");
+        assert_eq!(
+            item["request"]["body"]["urlencoded"][1],
+            json!({"key": "note", "value": "hello world"})
+        );
+
+        request.add_header("Content-Type".to_string(), "application/json".to_string());
+        request.set_body(Some("{}".to_string()));
+        let item = request_item(&request);
+        assert_eq!(item["request"]["body"]["mode"], "raw");
+        assert_eq!(
+            item["request"]["body"]["options"]["raw"]["language"],
+            "json"
+        );
+    }
+
+    #[test]
+    fn test_collections_become_folders() {
+        let request = SavedRequest::new(
+            "health".to_string(),
+            "https://example.com/health".to_string(),
+            "GET".to_string(),
+        );
+        let mut collection = RequestCollection::new("smoke".to_string());
+        collection.add_request(request.clone());
+
+        let exported = export_collection("terzi", &[request], &[collection]);
+        assert_eq!(exported["info"]["schema"], SCHEMA_URL);
+        assert_eq!(exported["item"][0]["name"], "health");
+        assert_eq!(exported["item"][1]["name"], "smoke");
+        assert_eq!(exported["item"][1]["item"][0]["request"]["method"], "GET");
+    }
+
+    #[test]
+    fn test_import_walks_folders_and_maps_auth() {
+        let collection = json!({
+            "info": {"name": "Shop", "schema": SCHEMA_URL},
+            "auth": {"type": "bearer", "bearer": [{"key": "token", "value": "{{token}}"}]},
+            "variable": [{"key": "base", "value": "https://shop.example.com"}],
+            "item": [
+                {"name": "health", "request": "{{base}}/health"},
+                {
+                    "name": "Orders",
+                    "item": [
+                        {
+                            "name": "create",
+                            "request": {
+                                "method": "post",
+                                "url": {"raw": "{{base}}/orders"},
+                                "header": [
+                                    {"key": "X-Trace", "value": "1"},
+                                    {"key": "X-Off", "value": "1", "disabled": true}
+                                ],
+                                "body": {
+                                    "mode": "raw",
+                                    "raw": "{\"qty\": 1}",
+                                    "options": {"raw": {"language": "json"}}
+                                }
+                            }
+                        },
+                        {
+                            "name": "Admin",
+                            "auth": {"type": "basic", "basic": {"username": "root", "password": "pw"}},
+                            "item": [{
+                                "name": "refund",
+                                "request": {
+                                    "method": "POST",
+                                    "url": "{{base}}/refunds",
+                                    "body": {"mode": "formdata", "formdata": [
+                                        {"key": "id", "value": "7"},
+                                        {"key": "receipt", "type": "file", "src": "a.pdf"}
+                                    ]}
+                                }
+                            }]
+                        }
+                    ]
+                }
+            ]
+        });
+
+        let import = import_collection(&collection.to_string()).unwrap();
+        assert_eq!(import.name, "Shop");
+        assert_eq!(import.variables[0].0, "base");
+
+        let health = &import.requests[0];
+        assert_eq!(health.url, "{{base}}/health");
+        assert_eq!(health.headers["Authorization"], "Bearer {{token}}");
+
+        let names: Vec<&str> = import.folders.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["Orders / Admin", "Orders"]);
+
+        let create = &import.folders[1].requests[0];
+        assert_eq!(create.method, "POST");
+        assert_eq!(create.headers["Content-Type"], "application/json");
+        assert!(!create.headers.contains_key("X-Off"));
+
+        let refund = &import.folders[0].requests[0];
+        assert!(refund.headers["Authorization"].starts_with("Basic "));
+        assert_eq!(refund.body_text(), Some("id=7"));
+        assert_eq!(import.warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_import_round_trips_export() {
+        let mut request = SavedRequest::new(
+            "search".to_string(),
+            "https://example.com/search?q=rust".to_string(),
+            "GET".to_string(),
+        );
+        request.add_header("Accept".to_string(), "application/json".to_string());
+
+        let exported = export_collection("terzi", &[request], &[]);
+        let import = import_collection(&exported.to_string()).unwrap();
+        assert_eq!(import.requests[0].url, "https://example.com/search?q=rust");
+        assert_eq!(import.requests[0].headers["Accept"], "application/json");
+        assert!(import_collection(r#"{"info": {"name": "x"}}"#).is_err());
+    }
+}
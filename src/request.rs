@@ -3,6 +3,8 @@ use base64::Engine;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,13 +14,198 @@ pub struct SavedRequest {
     pub url: String,
     pub method: String,
     pub headers: HashMap<String, String>,
-    pub body: Option<String>,
+    pub body: Option<Body>,
     pub timeout: Option<u64>,
+    #[serde(default)]
+    pub connect_timeout: Option<u64>,
+    #[serde(default)]
+    pub read_timeout: Option<u64>,
+    #[serde(default)]
+    pub max_redirects: Option<u8>,
     pub follow_redirects: Option<bool>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub tags: Vec<String>,
     pub description: Option<String>,
+    #[serde(default)]
+    pub assertions: Vec<String>,
+    #[serde(default)]
+    pub digest_auth: Option<DigestCredentials>,
+    #[serde(default)]
+    pub hmac: Option<HmacSignature>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<Cursor>,
+    /// Values carried from one send to the next, such as the last cursor
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub state: HashMap<String, String>,
+}
+
+// Text bodies serialize as a plain string, so requests saved before binary bodies still load
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Body {
+    Text(String),
+    Bytes {
+        #[serde(with = "base64_bytes")]
+        bytes: Vec<u8>,
+    },
+    // Read at send time, so the file's current contents go out byte for byte
+    File {
+        file: PathBuf,
+    },
+    // Piped from stdin while sending (--stream-body). It can't be replayed, so it
+    // serializes as no body at all
+    Stdin,
+}
+
+impl Body {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Body::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            Body::Text(text) => Some(text.len()),
+            Body::Bytes { bytes } => Some(bytes.len()),
+            Body::File { file } => std::fs::metadata(file).ok().map(|m| m.len() as usize),
+            Body::Stdin => None,
+        }
+    }
+
+    pub async fn read(&self) -> Result<Vec<u8>> {
+        match self {
+            Body::Text(text) => Ok(text.as_bytes().to_vec()),
+            Body::Bytes { bytes } => Ok(bytes.clone()),
+            Body::File { file } => tokio::fs::read(file)
+                .await
+                .map_err(|e| anyhow!("Failed to read body file {}: {}", file.display(), e)),
+            Body::Stdin => {
+                let mut bytes = Vec::new();
+                tokio::io::AsyncReadExt::read_to_end(&mut tokio::io::stdin(), &mut bytes).await?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+impl From<String> for Body {
+    fn from(text: String) -> Self {
+        Body::Text(text)
+    }
+}
+
+impl std::fmt::Display for Body {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Body::Text(text) => write!(f, "{}", text),
+            Body::Bytes { bytes } => write!(
+                f,
+                "<{} of binary data>",
+                crate::utils::format_bytes(bytes.len())
+            ),
+            Body::File { file } => write!(f, "@{}", file.display()),
+            Body::Stdin => write!(f, "<streamed from stdin>"),
+        }
+    }
+}
+
+mod base64_bytes {
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// Digest needs a server challenge first, so credentials are kept instead of a header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+// Incremental polling: `field` is read from each successful response and sent back as the
+// `param` query parameter next time, so only new items are fetched
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub field: String,
+    pub param: String,
+}
+
+// Signs the body as sent, so the header is computed by the client right before sending
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HmacSignature {
+    pub secret: String,
+    pub header: String,
+    pub algorithm: String,
+}
+
+impl HmacSignature {
+    pub const ALGORITHMS: &'static [&'static str] = &["sha1", "sha256"];
+
+    /// Parses `--hmac` as `SECRET:HEADER:ALGO`; the secret itself may contain ':'.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut parts = spec.rsplitn(3, ':');
+        let (Some(algorithm), Some(header), Some(secret)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(anyhow!(
+                "Invalid --hmac '{}'. Use SECRET:HEADER:ALGO, e.g. s3cret:X-Signature:sha256",
+                spec
+            ));
+        };
+
+        let algorithm = algorithm.trim().to_lowercase();
+        if !Self::ALGORITHMS.contains(&algorithm.as_str()) {
+            return Err(anyhow!(
+                "Unsupported HMAC algorithm '{}'. Valid options: {}",
+                algorithm,
+                Self::ALGORITHMS.join(", ")
+            ));
+        }
+        if !crate::utils::is_valid_header_name(header.trim()) {
+            return Err(anyhow!("Invalid HMAC header name: '{}'", header));
+        }
+
+        Ok(Self {
+            secret: secret.to_string(),
+            header: header.trim().to_string(),
+            algorithm,
+        })
+    }
+
+    /// The header value, `<algo>=<hex digest>` as GitHub-style webhooks expect.
+    pub fn sign(&self, body: &[u8]) -> String {
+        use hmac::{Hmac, Mac};
+
+        let digest = match self.algorithm.as_str() {
+            "sha1" => {
+                let mut mac = Hmac::<sha1::Sha1>::new_from_slice(self.secret.as_bytes())
+                    .expect("HMAC accepts keys of any length");
+                mac.update(body);
+                mac.finalize().into_bytes().to_vec()
+            }
+            _ => {
+                let mut mac = Hmac::<sha2::Sha256>::new_from_slice(self.secret.as_bytes())
+                    .expect("HMAC accepts keys of any length");
+                mac.update(body);
+                mac.finalize().into_bytes().to_vec()
+            }
+        };
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{}={}", self.algorithm, hex)
+    }
 }
 
 impl SavedRequest {
@@ -32,11 +219,19 @@ impl SavedRequest {
             headers: HashMap::new(),
             body: None,
             timeout: None,
+            connect_timeout: None,
+            read_timeout: None,
+            max_redirects: None,
             follow_redirects: None,
             created_at: now,
             updated_at: now,
             tags: Vec::new(),
             description: None,
+            assertions: Vec::new(),
+            digest_auth: None,
+            hmac: None,
+            cursor: None,
+            state: HashMap::new(),
         }
     }
 
@@ -45,11 +240,117 @@ impl SavedRequest {
         self.updated_at = Utc::now();
     }
 
+    /// Puts the cursor remembered from the last send into the query string, replacing any
+    /// value already there. Does nothing before the first send.
+    ///
+    /// Only the cursor parameter is touched; the rest of the URL, `{{placeholders}}`
+    /// included, is kept exactly as saved.
+    pub fn apply_cursor(&mut self) {
+        let Some(ref cursor) = self.cursor else {
+            return;
+        };
+        let Some(value) = self.state.get(&cursor.param) else {
+            return;
+        };
+
+        let (base, fragment) = self
+            .url
+            .split_at(self.url.find('#').unwrap_or(self.url.len()));
+        let (path, query) = base.split_once('?').unwrap_or((base, ""));
+        let mut pairs: Vec<String> = query
+            .split('&')
+            .filter(|pair| {
+                url::form_urlencoded::parse(pair.as_bytes())
+                    .next()
+                    .is_some_and(|(name, _)| name != cursor.param.as_str())
+            })
+            .map(String::from)
+            .collect();
+        pairs.push(format!(
+            "{}={}",
+            urlencoding::encode(&cursor.param),
+            urlencoding::encode(value)
+        ));
+        self.url = format!("{}?{}{}", path, pairs.join("&"), fragment);
+    }
+
+    /// Remembers the cursor found in a response body, returning it. A body without one
+    /// (say, no new events) keeps the previous cursor.
+    pub fn advance_cursor(&mut self, body: &str) -> Option<String> {
+        let cursor = self.cursor.as_ref()?;
+        let body: serde_json::Value = serde_json::from_str(body).ok()?;
+        let value = match crate::utils::json_path(&body, &cursor.field)? {
+            serde_json::Value::String(value) if !value.is_empty() => value.clone(),
+            serde_json::Value::Number(value) => value.to_string(),
+            _ => return None,
+        };
+        self.state.insert(cursor.param.clone(), value.clone());
+        Some(value)
+    }
+
     pub fn set_body(&mut self, body: Option<String>) {
-        self.body = body;
+        self.body = body.map(Body::Text);
         self.updated_at = Utc::now();
     }
 
+    /// The body when it is text; binary and file bodies have none.
+    pub fn body_text(&self) -> Option<&str> {
+        self.body.as_ref().and_then(Body::as_text)
+    }
+
+    /// Applies a terzi auth string such as `bearer:TOKEN` or `basic:user:pass`.
+    pub fn apply_auth(&mut self, auth: &str) -> Result<()> {
+        if let Some((auth_type, credentials)) = auth.split_once(':') {
+            match auth_type.to_lowercase().as_str() {
+                "bearer" => {
+                    self.headers.insert(
+                        "Authorization".to_string(),
+                        format!("Bearer {}", credentials),
+                    );
+                }
+                "basic" => {
+                    if let Some((username, password)) = credentials.split_once(':') {
+                        let encoded = base64::prelude::BASE64_STANDARD
+                            .encode(format!("{}:{}", username, password));
+                        self.headers
+                            .insert("Authorization".to_string(), format!("Basic {}", encoded));
+                    } else {
+                        return Err(anyhow!("Basic auth requires username:password format"));
+                    }
+                }
+                "digest" => {
+                    if let Some((username, password)) = credentials.split_once(':') {
+                        self.digest_auth = Some(DigestCredentials {
+                            username: username.to_string(),
+                            password: password.to_string(),
+                        });
+                    } else {
+                        return Err(anyhow!("Digest auth requires username:password format"));
+                    }
+                }
+                "api-key" | "apikey" => {
+                    if let Some((header_name, key_value)) = credentials.split_once(':') {
+                        self.headers
+                            .insert(header_name.to_string(), key_value.to_string());
+                    } else {
+                        // Default to X-API-Key
+                        self.headers
+                            .insert("X-API-Key".to_string(), credentials.to_string());
+                    }
+                }
+                _ => {
+                    return Err(anyhow!("Unsupported auth type: {}", auth_type));
+                }
+            }
+        } else {
+            // Assume it's a bearer token if no type specified
+            self.headers
+                .insert("Authorization".to_string(), format!("Bearer {}", auth));
+        }
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
     pub fn add_tag(&mut self, tag: String) {
         if !self.tags.contains(&tag) {
             self.tags.push(tag);
@@ -61,6 +362,272 @@ impl SavedRequest {
         self.tags.retain(|t| t != tag);
         self.updated_at = Utc::now();
     }
+
+    /// Replaces secret header values and digest passwords with `${TERZI_...}`
+    /// placeholders, returning the environment variables that now supply them.
+    pub fn redact_secrets(&mut self) -> Vec<String> {
+        let mut variables = Vec::new();
+
+        for (name, value) in self.headers.iter_mut() {
+            if crate::utils::is_sensitive_header(name) && env_placeholder(value).is_none() {
+                let variable = secret_variable(name);
+                *value = format!("${{{}}}", variable);
+                variables.push(variable);
+            }
+        }
+        if let Some(ref mut credentials) = self.digest_auth
+            && env_placeholder(&credentials.password).is_none()
+        {
+            let variable = "TERZI_DIGEST_PASSWORD".to_string();
+            credentials.password = format!("${{{}}}", variable);
+            variables.push(variable);
+        }
+        if let Some(ref mut hmac) = self.hmac
+            && env_placeholder(&hmac.secret).is_none()
+        {
+            let variable = "TERZI_HMAC_SECRET".to_string();
+            hmac.secret = format!("${{{}}}", variable);
+            variables.push(variable);
+        }
+
+        variables.sort();
+        variables
+    }
+
+    /// Fills in placeholders left by `redact_secrets` from the environment.
+    pub fn resolve_secrets(&self) -> Result<SavedRequest> {
+        let mut request = self.clone();
+        for value in request.headers.values_mut() {
+            resolve_placeholder(value)?;
+        }
+        if let Some(ref mut credentials) = request.digest_auth {
+            resolve_placeholder(&mut credentials.password)?;
+        }
+        if let Some(ref mut hmac) = request.hmac {
+            resolve_placeholder(&mut hmac.secret)?;
+        }
+        Ok(request)
+    }
+
+    /// Fills `{{name}}` placeholders in the URL, headers, text body and digest
+    /// credentials from `variables`. Any placeholder left over is an error, so a
+    /// missing variable is reported instead of being sent as-is.
+    pub fn substitute_variables(
+        &self,
+        variables: &HashMap<String, String>,
+    ) -> Result<SavedRequest> {
+        let mut request = self.clone();
+        request.url = substitute_variables(&request.url, variables)?;
+        for value in request.headers.values_mut() {
+            *value = substitute_variables(value, variables)?;
+        }
+        if let Some(Body::Text(ref body)) = request.body {
+            request.body = Some(Body::Text(substitute_variables(body, variables)?));
+        }
+        if let Some(ref mut credentials) = request.digest_auth {
+            credentials.username = substitute_variables(&credentials.username, variables)?;
+            credentials.password = substitute_variables(&credentials.password, variables)?;
+        }
+        if let Some(ref mut hmac) = request.hmac {
+            hmac.secret = substitute_variables(&hmac.secret, variables)?;
+        }
+        Ok(request)
+    }
+
+    /// Evaluates template functions such as `{{uuid()}}` in the URL, headers and body.
+    pub fn render_dynamic(&self) -> Result<SavedRequest> {
+        let mut request = self.clone();
+        request.url = render_dynamic(&request.url)?;
+        for value in request.headers.values_mut() {
+            *value = render_dynamic(value)?;
+        }
+        // Binary bodies are sent exactly as given
+        if let Some(Body::Text(ref body)) = request.body {
+            request.body = Some(Body::Text(render_dynamic(body)?));
+        }
+        Ok(request)
+    }
+
+    /// Compares method, URL, headers and body with `other`. Header names match
+    /// case-insensitively, and credentials are masked on both sides.
+    pub fn diff(&self, other: &SavedRequest) -> RequestDiff {
+        let changed = |a: &str, b: &str| (a != b).then(|| (a.to_string(), b.to_string()));
+
+        let find = |headers: &HashMap<String, String>, name: &str| {
+            headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(key, value)| (key.clone(), value.clone()))
+        };
+        let mask = crate::utils::mask_header_value;
+        let mut names: Vec<String> = self
+            .headers
+            .keys()
+            .chain(other.headers.keys())
+            .map(|name| name.to_ascii_lowercase())
+            .collect();
+        names.sort();
+        names.dedup();
+
+        // Values are compared before masking, so a rotated token still shows as changed
+        let headers = names
+            .iter()
+            .filter_map(
+                |name| match (find(&self.headers, name), find(&other.headers, name)) {
+                    (Some((key, old)), Some((_, new))) if old != new => Some(
+                        HeaderChange::Changed(key.clone(), mask(&key, &old), mask(&key, &new)),
+                    ),
+                    (Some((key, old)), None) => {
+                        Some(HeaderChange::Removed(key.clone(), mask(&key, &old)))
+                    }
+                    (None, Some((key, new))) => {
+                        Some(HeaderChange::Added(key.clone(), mask(&key, &new)))
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+
+        let old_body = diffable_body(self.body.as_ref());
+        let new_body = diffable_body(other.body.as_ref());
+        let old_lines: Vec<&str> = old_body.lines().collect();
+        let new_lines: Vec<&str> = new_body.lines().collect();
+        // The first two lines are the ---/+++ file header, which names nothing useful here
+        let body = difflib::unified_diff(&old_lines, &new_lines, "", "", "", "", 3)
+            .into_iter()
+            .skip(2)
+            .map(|line| line.trim_end_matches('\n').to_string())
+            .collect();
+
+        RequestDiff {
+            method: changed(&self.method.to_uppercase(), &other.method.to_uppercase()),
+            url: changed(&self.url, &other.url),
+            headers,
+            body,
+        }
+    }
+}
+
+// JSON is pretty-printed so a one-field change shows up as a one-line change
+fn diffable_body(body: Option<&Body>) -> String {
+    match body {
+        None => String::new(),
+        Some(Body::Text(text)) => {
+            let text = crate::utils::prettify_json(text).unwrap_or_else(|_| text.clone());
+            crate::utils::mask_body(&text)
+        }
+        Some(other) => other.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderChange {
+    Added(String, String),
+    Removed(String, String),
+    /// Name, old value, new value
+    Changed(String, String, String),
+}
+
+// The differences between two saved requests, old value first
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestDiff {
+    pub method: Option<(String, String)>,
+    pub url: Option<(String, String)>,
+    pub headers: Vec<HeaderChange>,
+    /// Unified diff lines of the bodies, empty when they match
+    pub body: Vec<String>,
+}
+
+impl RequestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.method.is_none()
+            && self.url.is_none()
+            && self.headers.is_empty()
+            && self.body.is_empty()
+    }
+}
+
+// `Authorization` -> `TERZI_AUTHORIZATION`, `X-Api-Key` -> `TERZI_X_API_KEY`
+fn secret_variable(header: &str) -> String {
+    let name: String = header
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("TERZI_{}", name)
+}
+
+// Only a whole value of the form `${TERZI_NAME}` counts, so other `${...}` text (shell
+// snippets, JS template strings) is sent as typed
+fn env_placeholder(value: &str) -> Option<&str> {
+    value.strip_prefix("${")?.strip_suffix('}').filter(|name| {
+        name.len() > "TERZI_".len()
+            && name.starts_with("TERZI_")
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+fn resolve_placeholder(value: &mut String) -> Result<()> {
+    if let Some(variable) = env_placeholder(value) {
+        *value = std::env::var(variable).map_err(|_| {
+            anyhow!(
+                "Environment variable {} is not set; it holds a secret redacted from this saved request",
+                variable
+            )
+        })?;
+    }
+    Ok(())
+}
+
+// Checks each operation's shape; whether the paths exist is up to the server
+fn validate_json_patch(patch: &serde_json::Value) -> Result<()> {
+    let operations = patch
+        .as_array()
+        .ok_or_else(|| anyhow!("expected an array of operations"))?;
+
+    for (index, operation) in operations.iter().enumerate() {
+        let field = |name: &str| operation.get(name);
+        let pointer = |name: &str| -> Result<()> {
+            match field(name).and_then(|value| value.as_str()) {
+                Some(path) if path.is_empty() || path.starts_with('/') => Ok(()),
+                Some(path) => Err(anyhow!(
+                    "operation {}: '{}' must be a JSON pointer such as /items/0, not '{}'",
+                    index,
+                    name,
+                    path
+                )),
+                None => Err(anyhow!("operation {}: missing '{}' string", index, name)),
+            }
+        };
+
+        if !operation.is_object() {
+            return Err(anyhow!("operation {}: expected an object", index));
+        }
+        let op = field("op")
+            .and_then(|op| op.as_str())
+            .ok_or_else(|| anyhow!("operation {}: missing 'op' string", index))?;
+        pointer("path")?;
+        match op {
+            "add" | "replace" | "test" if field("value").is_none() => {
+                return Err(anyhow!("operation {}: '{}' needs a 'value'", index, op));
+            }
+            "add" | "replace" | "test" | "remove" => {}
+            "move" | "copy" => pointer("from")?,
+            other => {
+                return Err(anyhow!(
+                    "operation {}: unknown op '{}' (expected add, remove, replace, move, copy or test)",
+                    index,
+                    other
+                ));
+            }
+        }
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone)]
@@ -107,63 +674,56 @@ impl RequestBuilder {
     }
 
     pub fn auth(mut self, auth: &str) -> Result<Self> {
-        if let Some((auth_type, credentials)) = auth.split_once(':') {
-            match auth_type.to_lowercase().as_str() {
-                "bearer" => {
-                    self.request.headers.insert(
-                        "Authorization".to_string(),
-                        format!("Bearer {}", credentials),
-                    );
-                }
-                "basic" => {
-                    if let Some((username, password)) = credentials.split_once(':') {
-                        let encoded = base64::prelude::BASE64_STANDARD
-                            .encode(format!("{}:{}", username, password));
-                        self.request
-                            .headers
-                            .insert("Authorization".to_string(), format!("Basic {}", encoded));
-                    } else {
-                        return Err(anyhow!("Basic auth requires username:password format"));
-                    }
-                }
-                "api-key" | "apikey" => {
-                    if let Some((header_name, key_value)) = credentials.split_once(':') {
-                        self.request
-                            .headers
-                            .insert(header_name.to_string(), key_value.to_string());
-                    } else {
-                        // Default to X-API-Key
-                        self.request
-                            .headers
-                            .insert("X-API-Key".to_string(), credentials.to_string());
-                    }
-                }
-                _ => {
-                    return Err(anyhow!("Unsupported auth type: {}", auth_type));
-                }
-            }
-        } else {
-            // Assume it's a bearer token if no type specified
-            self.request
-                .headers
-                .insert("Authorization".to_string(), format!("Bearer {}", auth));
-        }
+        self.request.apply_auth(auth)?;
         Ok(self)
     }
 
     pub fn json_body(mut self, json: &str) -> Result<Self> {
-        // Validate JSON
-        serde_json::from_str::<serde_json::Value>(json)
+        // Validate JSON, as it will be sent once template functions are filled in
+        serde_json::from_str::<serde_json::Value>(&render_dynamic(json)?)
             .map_err(|e| anyhow!("Invalid JSON: {}", e))?;
 
         self.request
             .headers
             .insert("Content-Type".to_string(), "application/json".to_string());
-        self.request.body = Some(json.to_string());
+        self.request.body = Some(Body::Text(json.to_string()));
         Ok(self)
     }
 
-    pub fn form_body(mut self, form_data: HashMap<String, String>) -> Result<Self> {
+    /// RFC 7386 JSON Merge Patch: a PATCH whose body is an object of fields to change.
+    pub fn merge_patch_body(mut self, json: &str) -> Result<Self> {
+        let patch = serde_json::from_str::<serde_json::Value>(&render_dynamic(json)?)
+            .map_err(|e| anyhow!("Invalid JSON merge patch: {}", e))?;
+        if !patch.is_object() {
+            return Err(anyhow!("Invalid JSON merge patch: expected an object"));
+        }
+
+        self.request.method = "PATCH".to_string();
+        self.request.headers.insert(
+            "Content-Type".to_string(),
+            "application/merge-patch+json".to_string(),
+        );
+        self.request.body = Some(Body::Text(json.to_string()));
+        Ok(self)
+    }
+
+    /// RFC 6902 JSON Patch: a PATCH whose body is an array of operations.
+    pub fn json_patch_body(mut self, json: &str) -> Result<Self> {
+        let patch = serde_json::from_str::<serde_json::Value>(&render_dynamic(json)?)
+            .map_err(|e| anyhow!("Invalid JSON patch: {}", e))?;
+        validate_json_patch(&patch).map_err(|e| anyhow!("Invalid JSON patch: {}", e))?;
+
+        self.request.method = "PATCH".to_string();
+        self.request.headers.insert(
+            "Content-Type".to_string(),
+            "application/json-patch+json".to_string(),
+        );
+        self.request.body = Some(Body::Text(json.to_string()));
+        Ok(self)
+    }
+
+    /// Pairs are encoded in order, so a repeated key such as `tags=a&tags=b` is kept
+    pub fn form_body(mut self, form_data: Vec<(String, String)>) -> Result<Self> {
         let encoded = form_data
             .iter()
             .map(|(k, v)| format!("{}={}", urlencoding::encode(k), urlencoding::encode(v)))
@@ -174,20 +734,98 @@ impl RequestBuilder {
             "Content-Type".to_string(),
             "application/x-www-form-urlencoded".to_string(),
         );
-        self.request.body = Some(encoded);
+        self.request.body = Some(Body::Text(encoded));
         Ok(self)
     }
 
+    /// Adds `key=value` pairs exactly as given, for values that are already encoded.
+    /// They follow any fields from `form_body`.
+    pub fn raw_form_fields(mut self, fields: Vec<(String, String)>) -> Self {
+        if fields.is_empty() {
+            return self;
+        }
+        let mut encoded: Vec<String> = match self.request.body {
+            Some(Body::Text(ref text)) if !text.is_empty() => vec![text.clone()],
+            _ => Vec::new(),
+        };
+        encoded.extend(fields.iter().map(|(k, v)| format!("{}={}", k, v)));
+
+        self.request.headers.insert(
+            "Content-Type".to_string(),
+            "application/x-www-form-urlencoded".to_string(),
+        );
+        self.request.body = Some(Body::Text(encoded.join("&")));
+        self
+    }
+
+    pub fn stream_body(mut self) -> Self {
+        self.request.body = Some(Body::Stdin);
+        self
+    }
+
     pub fn raw_body(mut self, body: &str) -> Self {
-        self.request.body = Some(body.to_string());
+        self.request.body = Some(Body::Text(body.to_string()));
         self
     }
 
+    /// curl-style `--data-binary`: `@path` sends a file as-is, `@-` what is piped to stdin,
+    /// anything else the literal bytes.
+    pub fn binary_body(mut self, data: &str) -> Result<Self> {
+        let body = match data.strip_prefix('@') {
+            // Read up front, so a saved request replays the same bytes
+            Some("-") => {
+                let mut bytes = Vec::new();
+                std::io::Read::read_to_end(&mut std::io::stdin(), &mut bytes)
+                    .map_err(|e| anyhow!("Cannot read body from stdin: {}", e))?;
+                Body::Bytes { bytes }
+            }
+            Some(path) => {
+                // Absolute, so a saved request still finds the file from another directory
+                let file = std::fs::canonicalize(path)
+                    .map_err(|e| anyhow!("Cannot read body file {}: {}", path, e))?;
+                Body::File { file }
+            }
+            None => Body::Bytes {
+                bytes: data.as_bytes().to_vec(),
+            },
+        };
+        self.request.body = Some(body);
+        Ok(self)
+    }
+
     pub fn timeout(mut self, seconds: u64) -> Self {
         self.request.timeout = Some(seconds);
         self
     }
 
+    pub fn connect_timeout(mut self, seconds: u64) -> Self {
+        self.request.connect_timeout = Some(seconds);
+        self
+    }
+
+    pub fn read_timeout(mut self, seconds: u64) -> Self {
+        self.request.read_timeout = Some(seconds);
+        self
+    }
+
+    pub fn cursor(mut self, field: &str, param: &str) -> Self {
+        self.request.cursor = Some(Cursor {
+            field: field.to_string(),
+            param: param.to_string(),
+        });
+        self
+    }
+
+    pub fn max_redirects(mut self, max: u8) -> Self {
+        self.request.max_redirects = Some(max);
+        self
+    }
+
+    pub fn hmac(mut self, spec: &str) -> Result<Self> {
+        self.request.hmac = Some(HmacSignature::parse(spec)?);
+        Ok(self)
+    }
+
     pub fn follow_redirects(mut self, follow: bool) -> Self {
         self.request.follow_redirects = Some(follow);
         self
@@ -287,6 +925,83 @@ pub enum VariableType {
     Json,
 }
 
+impl VariableType {
+    pub const ALL: [VariableType; 6] = [
+        VariableType::String,
+        VariableType::Number,
+        VariableType::Boolean,
+        VariableType::Url,
+        VariableType::Email,
+        VariableType::Json,
+    ];
+
+    // A best guess from an example value, offered first when asking for the type
+    pub fn infer(value: &str) -> Self {
+        if value.parse::<f64>().is_ok() {
+            VariableType::Number
+        } else if value == "true" || value == "false" {
+            VariableType::Boolean
+        } else if value.starts_with("http://") || value.starts_with("https://") {
+            VariableType::Url
+        } else {
+            VariableType::String
+        }
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            VariableType::String => true,
+            VariableType::Number => value.parse::<f64>().is_ok(),
+            VariableType::Boolean => value == "true" || value == "false",
+            VariableType::Url => url::Url::parse(value).is_ok(),
+            VariableType::Email => value
+                .split_once('@')
+                .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.')),
+            VariableType::Json => serde_json::from_str::<serde_json::Value>(value).is_ok(),
+        }
+    }
+}
+
+impl std::fmt::Display for VariableType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            VariableType::String => "string",
+            VariableType::Number => "number",
+            VariableType::Boolean => "boolean",
+            VariableType::Url => "url",
+            VariableType::Email => "email",
+            VariableType::Json => "json",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A value in a request that could become a template variable
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableCandidate {
+    pub name: String,
+    pub location: CandidateLocation,
+    // The literal as it appears in the URL; None for a `{{name}}` already in the request
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CandidateLocation {
+    Placeholder,
+    Query(String),
+    PathSegment(usize),
+}
+
+impl std::fmt::Display for CandidateLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CandidateLocation::Placeholder => write!(f, "placeholder"),
+            CandidateLocation::Query(key) => write!(f, "query '{}'", key),
+            CandidateLocation::PathSegment(index) => write!(f, "path segment {}", index + 1),
+        }
+    }
+}
+
 impl RequestTemplate {
     pub fn new(name: String, base_request: SavedRequest) -> Self {
         Self {
@@ -298,6 +1013,107 @@ impl RequestTemplate {
         }
     }
 
+    /// `{{name}}` placeholders already in the URL, headers or body, then literal values in
+    /// the URL that usually change between calls: query values and ID-like path segments.
+    pub fn detect_variables(request: &SavedRequest) -> Vec<VariableCandidate> {
+        let mut texts = vec![request.url.as_str()];
+        texts.extend(request.headers.values().map(String::as_str));
+        if let Some(Body::Text(ref body)) = request.body {
+            texts.push(body);
+        }
+        let mut names: Vec<String> = texts
+            .iter()
+            .flat_map(|text| crate::utils::extract_template_variables(text))
+            .filter(|name| !name.contains('('))
+            .collect();
+        names.sort();
+        names.dedup();
+
+        let mut candidates: Vec<VariableCandidate> = names
+            .into_iter()
+            .map(|name| VariableCandidate {
+                name,
+                location: CandidateLocation::Placeholder,
+                value: None,
+            })
+            .collect();
+
+        let (_, path, query, _) = split_url(&request.url);
+        let segments: Vec<&str> = path.split('/').collect();
+        for (index, segment) in segments.iter().enumerate() {
+            if !is_id_like(segment) {
+                continue;
+            }
+            let name = match index.checked_sub(1).map(|i| segments[i]) {
+                Some(previous) if !previous.is_empty() && !previous.contains("{{") => {
+                    format!("{}_id", previous.strip_suffix('s').unwrap_or(previous))
+                }
+                _ => "id".to_string(),
+            };
+            push_candidate(
+                &mut candidates,
+                name,
+                CandidateLocation::PathSegment(index),
+                segment,
+            );
+        }
+        for pair in query.unwrap_or_default().split('&') {
+            if let Some((key, value)) = pair.split_once('=')
+                && !key.is_empty()
+                && !value.is_empty()
+                && !value.contains("{{")
+            {
+                push_candidate(
+                    &mut candidates,
+                    key.to_string(),
+                    CandidateLocation::Query(key.to_string()),
+                    value,
+                );
+            }
+        }
+        candidates
+    }
+
+    /// Builds a template from `request`, swapping each chosen literal for its placeholder
+    pub fn from_request(
+        name: String,
+        request: &SavedRequest,
+        variables: Vec<(VariableCandidate, TemplateVariable)>,
+    ) -> Self {
+        let (prefix, path, query, fragment) = split_url(&request.url);
+        let mut segments: Vec<String> = path.split('/').map(str::to_string).collect();
+        let mut pairs: Vec<String> = query
+            .map(|query| query.split('&').map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let mut template = Self::new(name.clone(), request.clone());
+        for (candidate, variable) in variables {
+            let placeholder = format!("{{{{{}}}}}", variable.name);
+            match candidate.location {
+                CandidateLocation::Placeholder => {}
+                CandidateLocation::PathSegment(index) => segments[index] = placeholder,
+                CandidateLocation::Query(ref key) => {
+                    for pair in pairs.iter_mut() {
+                        if pair.split_once('=').is_some_and(|(k, _)| k == key) {
+                            *pair = format!("{}={}", key, placeholder);
+                        }
+                    }
+                }
+            }
+            template.add_variable(variable);
+        }
+
+        let mut url = format!("{}{}", prefix, segments.join("/"));
+        if query.is_some() {
+            url.push('?');
+            url.push_str(&pairs.join("&"));
+        }
+        url.push_str(fragment);
+        template.base_request.url = url;
+        template.base_request.name = name;
+        template
+    }
+
     pub fn add_variable(&mut self, variable: TemplateVariable) {
         self.variables.insert(variable.name.clone(), variable);
     }
@@ -351,8 +1167,8 @@ impl RequestTemplate {
         }
 
         // Replace variables in body
-        if let Some(ref body) = rendered_request.body {
-            rendered_request.body = Some(self.replace_variables(body, &all_variables)?);
+        if let Some(Body::Text(ref body)) = rendered_request.body {
+            rendered_request.body = Some(Body::Text(self.replace_variables(body, &all_variables)?));
         }
 
         Ok(rendered_request)
@@ -366,6 +1182,7 @@ impl RequestTemplate {
             let pattern = format!("{{{{{}}}}}", var_name);
             result = result.replace(&pattern, var_value);
         }
+        result = render_dynamic(&result)?;
 
         // Check for unresolved variables
         if result.contains("{{") && result.contains("}}") {
@@ -379,6 +1196,171 @@ impl RequestTemplate {
     }
 }
 
+// Splits a URL as typed into the part before the path, the path, the query without
+// its `?`, and the fragment with its `#`
+fn split_url(url: &str) -> (&str, &str, Option<&str>, &str) {
+    let authority_start = url.find("://").map_or(0, |i| i + 3);
+    let path_start = url[authority_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |i| authority_start + i);
+    let fragment_start = url[path_start..]
+        .find('#')
+        .map_or(url.len(), |i| path_start + i);
+    let (path, query) = match url[path_start..fragment_start].split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (&url[path_start..fragment_start], None),
+    };
+    (&url[..path_start], path, query, &url[fragment_start..])
+}
+
+// Numbers and UUIDs, the segments that name one resource out of many
+fn is_id_like(segment: &str) -> bool {
+    let numeric = !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit());
+    numeric || Uuid::parse_str(segment).is_ok()
+}
+
+// Names must be unique within a template, so a repeat gets a number
+fn push_candidate(
+    candidates: &mut Vec<VariableCandidate>,
+    name: String,
+    location: CandidateLocation,
+    value: &str,
+) {
+    let taken = |name: &str| candidates.iter().any(|c| c.name == name);
+    let mut unique = name.clone();
+    let mut n = 2;
+    while taken(&unique) {
+        unique = format!("{}_{}", name, n);
+        n += 1;
+    }
+    candidates.push(VariableCandidate {
+        name: unique,
+        location,
+        value: Some(value.to_string()),
+    });
+}
+
+/// A `{{name}}` variable placeholder, with the name as the first capture. Template
+/// functions such as `{{uuid()}}` have parentheses, so they never match.
+pub static VARIABLE_PATTERN: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_.\-]*)\s*\}\}").unwrap());
+
+pub fn substitute_variables(text: &str, variables: &HashMap<String, String>) -> Result<String> {
+    if !text.contains("{{") {
+        return Ok(text.to_string());
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for captures in VARIABLE_PATTERN.captures_iter(text) {
+        let whole = captures.get(0).unwrap();
+        let value = variables
+            .get(&captures[1])
+            .ok_or_else(|| anyhow!("Unresolved variable: {}", whole.as_str()))?;
+        result.push_str(&text[last..whole.start()]);
+        result.push_str(value);
+        last = whole.end();
+    }
+    result.push_str(&text[last..]);
+    Ok(result)
+}
+
+// Template functions: `{{uuid()}}`, `{{randomInt(1,100)}}`, `{{now()}}` and friends.
+// Plain `{{name}}` variables are left for `RequestTemplate` to fill in, and calls to
+// functions terzi doesn't know are left as typed, since bodies often carry their own
+// templating (Handlebars, Mustache).
+const MAX_RANDOM_STRING_LENGTH: usize = 4096;
+
+pub fn render_dynamic(text: &str) -> Result<String> {
+    if !text.contains("{{") {
+        return Ok(text.to_string());
+    }
+
+    let pattern = regex::Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\(([^()]*)\)\s*\}\}").unwrap();
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for captures in pattern.captures_iter(text) {
+        let whole = captures.get(0).unwrap();
+        result.push_str(&text[last..whole.start()]);
+        match template_function(&captures[1], &captures[2])? {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(whole.as_str()),
+        }
+        last = whole.end();
+    }
+    result.push_str(&text[last..]);
+    Ok(result)
+}
+
+fn template_function(name: &str, args: &str) -> Result<Option<String>> {
+    use rand::Rng;
+
+    let args: Vec<&str> = args
+        .split(',')
+        .map(|arg| arg.trim().trim_matches(|c| c == '"' || c == '\''))
+        .filter(|arg| !arg.is_empty())
+        .collect();
+    let number = |index: usize| -> Result<f64> {
+        let arg = args
+            .get(index)
+            .ok_or_else(|| anyhow!("{}() needs {} arguments", name, index + 1))?;
+        arg.parse::<f64>()
+            .map_err(|_| anyhow!("{}() expects a number, got '{}'", name, arg))
+    };
+    let range = || -> Result<(f64, f64)> {
+        let (min, max) = (number(0)?, number(1)?);
+        if min > max {
+            return Err(anyhow!(
+                "{}({}, {}): min is greater than max",
+                name,
+                min,
+                max
+            ));
+        }
+        Ok((min, max))
+    };
+    let mut rng = rand::thread_rng();
+
+    let value = match name {
+        "uuid" => Uuid::new_v4().to_string(),
+        "now" => Utc::now().to_rfc3339(),
+        "timestamp" => Utc::now().timestamp().to_string(),
+        "randomInt" => {
+            let (min, max) = range()?;
+            rng.gen_range(min as i64..=max as i64).to_string()
+        }
+        "randomFloat" => {
+            let (min, max) = range()?;
+            format!("{:.2}", rng.gen_range(min..=max))
+        }
+        "randomBool" => rng.gen_bool(0.5).to_string(),
+        "randomString" => {
+            let length = if args.is_empty() { 8.0 } else { number(0)? };
+            if length < 0.0 || length > MAX_RANDOM_STRING_LENGTH as f64 {
+                return Err(anyhow!(
+                    "randomString() length must be between 0 and {}, got {}",
+                    MAX_RANDOM_STRING_LENGTH,
+                    length
+                ));
+            }
+            random_alphanumeric(&mut rng, length as usize)
+        }
+        "randomEmail" => format!(
+            "{}@example.com",
+            random_alphanumeric(&mut rng, 10).to_lowercase()
+        ),
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+fn random_alphanumeric(rng: &mut impl rand::Rng, length: usize) -> String {
+    (0..length)
+        .map(|_| rng.sample(rand::distributions::Alphanumeric))
+        .map(char::from)
+        .collect()
+}
+
 // Request validation
 pub fn validate_request(request: &SavedRequest) -> Result<()> {
     // Validate URL
@@ -391,13 +1373,15 @@ pub fn validate_request(request: &SavedRequest) -> Result<()> {
     }
 
     // Validate JSON body if content-type is JSON
-    if let Some(ref body) = request.body {
-        if let Some(content_type) = request.headers.get("Content-Type") {
-            if content_type.contains("application/json") {
-                serde_json::from_str::<serde_json::Value>(body)
-                    .map_err(|e| anyhow!("Invalid JSON body: {}", e))?;
-            }
-        }
+    if let Some(body) = request.body_text()
+        && let Some((_, content_type)) = request
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+        && content_type.contains("application/json")
+    {
+        serde_json::from_str::<serde_json::Value>(body)
+            .map_err(|e| anyhow!("Invalid JSON body: {}", e))?;
     }
 
     // Validate timeout
@@ -426,3 +1410,355 @@ pub fn create_authenticated_request(url: &str, method: &str, token: &str) -> Res
         .auth(&format!("bearer:{}", token))?
         .build())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_signature() {
+        let hmac = HmacSignature::parse("key:with:colons:X-Hub-Signature-256:SHA256").unwrap();
+        assert_eq!(hmac.secret, "key:with:colons");
+        assert_eq!(hmac.header, "X-Hub-Signature-256");
+
+        // RFC 4231 test case 2
+        let hmac = HmacSignature::parse("Jefe:X-Signature:sha256").unwrap();
+        assert_eq!(
+            hmac.sign(b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let hmac = HmacSignature::parse("Jefe:X-Signature:sha1").unwrap();
+        assert_eq!(
+            hmac.sign(b"what do ya want for nothing?"),
+            "sha1=effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+
+        assert!(HmacSignature::parse("secret:X-Signature:md5").is_err());
+        assert!(HmacSignature::parse("X-Signature:sha256").is_err());
+    }
+
+    #[test]
+    fn test_render_dynamic_functions() {
+        let rendered = render_dynamic(
+            r#"{"id": "{{uuid()}}", "n": {{ randomInt(5, 5) }}, "user": "{{name}}"}"#,
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+
+        assert!(Uuid::parse_str(value["id"].as_str().unwrap()).is_ok());
+        assert_eq!(value["n"], 5);
+        // Plain variables are not template functions
+        assert_eq!(value["user"], "{{name}}");
+
+        assert!(
+            render_dynamic("{{randomEmail()}}")
+                .unwrap()
+                .ends_with("@example.com")
+        );
+        assert_eq!(render_dynamic("{{randomString(12)}}").unwrap().len(), 12);
+        assert_ne!(
+            render_dynamic("{{uuid()}}").unwrap(),
+            render_dynamic("{{uuid()}}").unwrap()
+        );
+        assert!(render_dynamic("{{randomInt(9,1)}}").is_err());
+        assert!(render_dynamic("{{randomString(100000000)}}").is_err());
+        // Other templating in the body is sent untouched
+        assert_eq!(
+            render_dynamic("{{formatDate(created)}} {{#each items}}").unwrap(),
+            "{{formatDate(created)}} {{#each items}}"
+        );
+    }
+
+    #[test]
+    fn test_resolve_secrets_only_reads_terzi_placeholders() {
+        let mut request = SavedRequest::new(
+            "hook".to_string(),
+            "https://example.com".to_string(),
+            "POST".to_string(),
+        );
+        request.add_header("X-Script".to_string(), "${HOME}".to_string());
+        let resolved = request.resolve_secrets().unwrap();
+        assert_eq!(resolved.headers["X-Script"], "${HOME}");
+
+        request.add_header(
+            "Authorization".to_string(),
+            "${TERZI_TEST_UNSET_SECRET}".to_string(),
+        );
+        let error = request.resolve_secrets().unwrap_err().to_string();
+        assert!(error.contains("TERZI_TEST_UNSET_SECRET"), "{}", error);
+    }
+
+    #[test]
+    fn test_substitute_variables() {
+        let mut request = SavedRequest::new(
+            "orders".to_string(),
+            "{{base}}/orders/{{ id }}".to_string(),
+            "POST".to_string(),
+        );
+        request.add_header("Authorization".to_string(), "Bearer {{token}}".to_string());
+        request.set_body(Some(r#"{"ref": "{{uuid()}}", "id": "{{id}}"}"#.to_string()));
+
+        let variables = HashMap::from([
+            (
+                "base".to_string(),
+                "https://staging.example.com".to_string(),
+            ),
+            ("id".to_string(), "42".to_string()),
+            ("token".to_string(), "abc".to_string()),
+        ]);
+        let rendered = request.substitute_variables(&variables).unwrap();
+        assert_eq!(rendered.url, "https://staging.example.com/orders/42");
+        assert_eq!(rendered.headers["Authorization"], "Bearer abc");
+        // Template functions are left for `render_dynamic`
+        assert_eq!(
+            rendered.body_text(),
+            Some(r#"{"ref": "{{uuid()}}", "id": "42"}"#)
+        );
+
+        let error = request.substitute_variables(&HashMap::new()).unwrap_err();
+        assert_eq!(error.to_string(), "Unresolved variable: {{base}}");
+    }
+
+    #[test]
+    fn test_body_serialization_round_trips() {
+        let text = serde_json::to_value(Body::Text("hi".to_string())).unwrap();
+        assert_eq!(text, serde_json::json!("hi"));
+
+        let bytes = Body::Bytes {
+            bytes: vec![0x89, b'P', b'N', b'G', 0xff],
+        };
+        let encoded = serde_json::to_value(&bytes).unwrap();
+        assert_eq!(encoded, serde_json::json!({"bytes": "iVBOR/8="}));
+        assert_eq!(serde_json::from_value::<Body>(encoded).unwrap(), bytes);
+
+        let file: Body = serde_json::from_str(r#"{"file": "/tmp/upload.bin"}"#).unwrap();
+        assert_eq!(
+            file,
+            Body::File {
+                file: PathBuf::from("/tmp/upload.bin")
+            }
+        );
+
+        // A piped body isn't kept; it comes back as no body
+        assert_eq!(
+            serde_json::to_value(Body::Stdin).unwrap(),
+            serde_json::Value::Null
+        );
+        assert_eq!(serde_json::from_str::<Option<Body>>("null").unwrap(), None);
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let mut request =
+            RequestBuilder::new("https://api.example.com/events?since_id=1&limit=10", "GET")
+                .unwrap()
+                .cursor("$.events[-1].id", "since_id")
+                .build();
+
+        // Nothing remembered yet, so the URL is sent as saved
+        request.apply_cursor();
+        assert_eq!(
+            request.url,
+            "https://api.example.com/events?since_id=1&limit=10"
+        );
+
+        assert_eq!(request.advance_cursor(r#"{"events": []}"#), None);
+        assert_eq!(
+            request.advance_cursor(r#"{"events": [{"id": 4}, {"id": "e-9"}]}"#),
+            Some("e-9".to_string())
+        );
+        request.apply_cursor();
+        assert_eq!(
+            request.url,
+            "https://api.example.com/events?limit=10&since_id=e-9"
+        );
+
+        // The rest of the query is left as saved, placeholders and escapes included
+        request.url = "{{base}}/events?q=a%20b&since_id=1&tag={{tag}}#latest".to_string();
+        request.apply_cursor();
+        assert_eq!(
+            request.url,
+            "{{base}}/events?q=a%20b&tag={{tag}}&since_id=e-9#latest"
+        );
+    }
+
+    #[test]
+    fn test_form_body_keeps_repeated_keys_in_order() {
+        let form = vec![
+            ("tags".to_string(), "a".to_string()),
+            ("name".to_string(), "x y".to_string()),
+            ("tags".to_string(), "b".to_string()),
+        ];
+        let request = RequestBuilder::new("https://api.example.com/items", "POST")
+            .unwrap()
+            .form_body(form)
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            request.body.unwrap().to_string(),
+            "tags=a&name=x%20y&tags=b"
+        );
+
+        let request = RequestBuilder::new("https://api.example.com/items", "POST")
+            .unwrap()
+            .form_body(vec![("q".to_string(), "a&b".to_string())])
+            .unwrap()
+            .raw_form_fields(vec![("sig".to_string(), "abc%3D".to_string())])
+            .build();
+        assert_eq!(request.body.unwrap().to_string(), "q=a%26b&sig=abc%3D");
+        assert_eq!(
+            request.headers["Content-Type"],
+            "application/x-www-form-urlencoded"
+        );
+    }
+
+    #[test]
+    fn test_template_from_detected_variables() {
+        let request = RequestBuilder::new(
+            "https://api.example.com/users/42/orders?status=open&limit=10#top",
+            "GET",
+        )
+        .unwrap()
+        .header("Authorization", "Bearer {{token}}")
+        .build();
+
+        let candidates = RequestTemplate::detect_variables(&request);
+        let names: Vec<&str> = candidates.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["token", "user_id", "status", "limit"]);
+        assert_eq!(candidates[1].value.as_deref(), Some("42"));
+        assert_eq!(candidates[1].location, CandidateLocation::PathSegment(2));
+
+        // Parameterize everything but the limit
+        let variables = candidates
+            .into_iter()
+            .filter(|c| c.name != "limit")
+            .map(|c| {
+                let variable = TemplateVariable {
+                    name: c.name.clone(),
+                    description: None,
+                    default_value: c.value.clone(),
+                    required: c.value.is_none(),
+                    variable_type: VariableType::infer(c.value.as_deref().unwrap_or("")),
+                };
+                (c, variable)
+            })
+            .collect();
+        let template = RequestTemplate::from_request("orders".to_string(), &request, variables);
+        assert_eq!(
+            template.base_request.url,
+            "https://api.example.com/users/{{user_id}}/orders?status={{status}}&limit=10#top"
+        );
+
+        assert!(template.render(None, HashMap::new()).is_err());
+        let rendered = template
+            .render(
+                None,
+                HashMap::from([
+                    ("token".to_string(), "abc".to_string()),
+                    ("user_id".to_string(), "7".to_string()),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(
+            rendered.url,
+            "https://api.example.com/users/7/orders?status=open&limit=10#top"
+        );
+        assert_eq!(rendered.headers["Authorization"], "Bearer abc");
+    }
+
+    #[test]
+    fn test_diff_compares_fields_and_masks_secrets() {
+        let mut old = SavedRequest::new(
+            "old".to_string(),
+            "https://api.example.com/users".to_string(),
+            "get".to_string(),
+        );
+        old.add_header("Accept".to_string(), "application/json".to_string());
+        old.add_header("Authorization".to_string(), "Bearer old-secret".to_string());
+        old.add_header("X-Old".to_string(), "1".to_string());
+        old.body = Some(Body::Text(r#"{"name":"Ada","role":"admin"}"#.to_string()));
+
+        let mut new = old.clone();
+        new.method = "POST".to_string();
+        new.headers.remove("X-Old");
+        new.headers.remove("Authorization");
+        new.add_header("authorization".to_string(), "Bearer new-secret".to_string());
+        new.add_header("X-New".to_string(), "2".to_string());
+        new.body = Some(Body::Text(r#"{"name":"Ada","role":"viewer"}"#.to_string()));
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.method, Some(("GET".to_string(), "POST".to_string())));
+        assert_eq!(diff.url, None);
+        assert_eq!(diff.headers.len(), 3);
+        assert!(
+            matches!(diff.headers[0], HeaderChange::Changed(ref name, ..) if name == "Authorization")
+        );
+        assert_eq!(
+            diff.headers[1],
+            HeaderChange::Added("X-New".to_string(), "2".to_string())
+        );
+        assert_eq!(
+            diff.headers[2],
+            HeaderChange::Removed("X-Old".to_string(), "1".to_string())
+        );
+        assert!(!format!("{:?}", diff).contains("secret"));
+        assert!(
+            diff.body.contains(&"-  \"role\": \"admin\"".to_string()),
+            "{:?}",
+            diff.body
+        );
+        assert!(
+            diff.body.contains(&"+  \"role\": \"viewer\"".to_string()),
+            "{:?}",
+            diff.body
+        );
+        assert!(old.diff(&old.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_patch_bodies() {
+        let request = RequestBuilder::new("https://api.example.com/users/1", "GET")
+            .unwrap()
+            .merge_patch_body(r#"{"name": "Ada", "nickname": null}"#)
+            .unwrap()
+            .build();
+        assert_eq!(request.method, "PATCH");
+        assert_eq!(
+            request.headers["Content-Type"],
+            "application/merge-patch+json"
+        );
+        let builder = RequestBuilder::new("https://api.example.com/users/1", "PATCH").unwrap();
+        assert!(builder.clone().merge_patch_body("[1]").is_err());
+
+        let request = builder
+            .clone()
+            .json_patch_body(
+                r#"[{"op": "replace", "path": "/name", "value": "Ada"},
+                    {"op": "remove", "path": "/tags/0"},
+                    {"op": "move", "from": "/a", "path": ""}]"#,
+            )
+            .unwrap()
+            .build();
+        assert_eq!(
+            request.headers["Content-Type"],
+            "application/json-patch+json"
+        );
+
+        for (patch, message) in [
+            (r#"{"op": "add"}"#, "array"),
+            (r#"[{"path": "/a"}]"#, "missing 'op'"),
+            (r#"[{"op": "add", "path": "/a"}]"#, "needs a 'value'"),
+            (r#"[{"op": "remove", "path": "a"}]"#, "JSON pointer"),
+            (r#"[{"op": "copy", "path": "/a"}]"#, "missing 'from'"),
+            (r#"[{"op": "merge", "path": "/a"}]"#, "unknown op"),
+        ] {
+            let error = builder
+                .clone()
+                .json_patch_body(patch)
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains(message), "{}: {}", patch, error);
+        }
+    }
+}
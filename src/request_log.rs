@@ -0,0 +1,163 @@
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+
+use crate::client::Response;
+use crate::request::SavedRequest;
+
+// Append-only JSONL audit log with one complete record per request
+pub struct RequestLog {
+    path: PathBuf,
+    mask_secrets: bool,
+}
+
+impl RequestLog {
+    pub fn new(path: impl Into<PathBuf>, mask_secrets: bool) -> Self {
+        Self {
+            path: path.into(),
+            mask_secrets,
+        }
+    }
+
+    pub async fn record(&self, request: &SavedRequest, result: &Result<Response>) -> Result<()> {
+        let mut line = serde_json::to_string(&self.entry(request, result))?;
+        line.push('\n');
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to open request log {}: {}", self.path.display(), e)
+            })?;
+        // A single write per record keeps lines whole when several runs share a log
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    fn entry(&self, request: &SavedRequest, result: &Result<Response>) -> Value {
+        let mut entry = json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "method": request.method,
+            "url": request.url,
+            "request_headers": self.headers(&request.headers),
+            "request_body": request.body.as_ref().map(|body| match body.as_text() {
+                Some(text) => self.body(text),
+                None => body.to_string(),
+            }),
+        });
+
+        match result {
+            Ok(response) => {
+                entry["status"] = json!(response.status);
+                entry["duration_ms"] = json!(response.duration.as_millis() as u64);
+                entry["response_size"] = json!(response.size);
+                entry["response_headers"] = json!(self.headers(&response.headers));
+                entry["response_body"] = json!(self.body(&response.body));
+            }
+            Err(e) => {
+                entry["error"] = json!(crate::utils::format_error_chain(e));
+            }
+        }
+        entry
+    }
+
+    fn headers(&self, headers: &HashMap<String, String>) -> HashMap<String, String> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.mask_secrets {
+                    crate::utils::mask_header_value(name, value)
+                } else {
+                    value.clone()
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+
+    fn body(&self, body: &str) -> String {
+        if self.mask_secrets {
+            crate::utils::mask_body(body)
+        } else {
+            body.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn request() -> SavedRequest {
+        let mut request = SavedRequest::new(
+            "login".to_string(),
+            "https://api.example.com/login".to_string(),
+            "POST".to_string(),
+        );
+        request.add_header(
+            "Authorization".to_string(),
+            "Bearer secret-token".to_string(),
+        );
+        request.set_body(Some(r#"{"password": "hunter2"}"#.to_string()));
+        request
+    }
+
+    #[tokio::test]
+    async fn test_records_are_appended_and_masked() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("logs").join("requests.jsonl");
+        let log = RequestLog::new(&path, true);
+
+        let response = Response {
+            status: 200,
+            headers: HashMap::new(),
+            body: "{}".to_string(),
+            duration: Duration::from_millis(12),
+            size: 2,
+            url: "https://api.example.com/login".to_string(),
+            method: "POST".to_string(),
+            from_cache: false,
+            request_id: None,
+            wire_request: None,
+            transfer: Default::default(),
+            set_cookies: Vec::new(),
+            redirects: Vec::new(),
+        };
+        log.record(&request(), &Ok(response)).await.unwrap();
+        log.record(&request(), &Err(anyhow::anyhow!("connection refused")))
+            .await
+            .unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["status"], 200);
+        assert_eq!(lines[0]["duration_ms"], 12);
+        assert_eq!(lines[1]["error"], "connection refused");
+        assert!(!contents.contains("secret-token"));
+        assert!(!contents.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_masking_can_be_disabled() {
+        let log = RequestLog::new("unused.jsonl", false);
+        let entry = log.entry(&request(), &Err(anyhow::anyhow!("timeout")));
+        assert_eq!(
+            entry["request_headers"]["Authorization"],
+            "Bearer secret-token"
+        );
+    }
+}
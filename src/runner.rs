@@ -0,0 +1,240 @@
+use clap::Args;
+use colored::*;
+use std::time::Duration;
+
+// Failure handling shared by every command that executes several requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureMode {
+    FailFast,
+    KeepGoing,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct FailureArgs {
+    /// Stop at the first failed request
+    #[arg(long, conflicts_with = "keep_going")]
+    pub fail_fast: bool,
+
+    /// Run every request and report failures at the end (default)
+    #[arg(long)]
+    pub keep_going: bool,
+}
+
+impl FailureArgs {
+    pub fn mode(&self) -> FailureMode {
+        if self.fail_fast {
+            FailureMode::FailFast
+        } else {
+            FailureMode::KeepGoing
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub name: String,
+    pub status: Option<u16>,
+    pub duration: Option<Duration>,
+    pub failure: Option<String>,
+    /// Passed and total assertion counts, when the request had any
+    pub assertions: Option<(usize, usize)>,
+}
+
+impl RunResult {
+    pub fn passed(name: &str, status: u16, duration: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            status: Some(status),
+            duration: Some(duration),
+            failure: None,
+            assertions: None,
+        }
+    }
+
+    pub fn failed(
+        name: &str,
+        status: Option<u16>,
+        duration: Option<Duration>,
+        reason: &str,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            duration,
+            failure: Some(reason.to_string()),
+            assertions: None,
+        }
+    }
+
+    pub fn with_assertions(mut self, passed: usize, total: usize) -> Self {
+        if total > 0 {
+            self.assertions = Some((passed, total));
+        }
+        self
+    }
+
+    pub fn is_failure(&self) -> bool {
+        self.failure.is_some()
+    }
+}
+
+// Aggregates results across a bulk run and decides whether to keep going
+#[derive(Debug)]
+pub struct RunSummary {
+    mode: FailureMode,
+    results: Vec<RunResult>,
+    skipped: usize,
+}
+
+impl RunSummary {
+    pub fn new(mode: FailureMode) -> Self {
+        Self {
+            mode,
+            results: Vec::new(),
+            skipped: 0,
+        }
+    }
+
+    /// Records a result and returns whether the run should continue.
+    pub fn record(&mut self, result: RunResult) -> bool {
+        let failed = result.is_failure();
+        self.results.push(result);
+        !(failed && self.mode == FailureMode::FailFast)
+    }
+
+    pub fn skip(&mut self, count: usize) {
+        self.skipped += count;
+    }
+
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| !r.is_failure()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|r| r.is_failure()).count()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        if self.failed() > 0 { 1 } else { 0 }
+    }
+
+    /// Prints one row per request with its status, duration and assertion results.
+    pub fn print_report(&self) {
+        println!();
+        println!("{}", "Test Report:".bright_yellow().bold());
+
+        let headers = vec!["Request", "Status", "Duration", "Assertions", "Result"];
+        let rows: Vec<Vec<String>> = self
+            .results
+            .iter()
+            .map(|result| {
+                vec![
+                    result.name.clone(),
+                    result
+                        .status
+                        .map(|status| status.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    result
+                        .duration
+                        .map(|duration| format!("{}ms", duration.as_millis()))
+                        .unwrap_or_else(|| "-".to_string()),
+                    result
+                        .assertions
+                        .map(|(passed, total)| format!("{}/{}", passed, total))
+                        .unwrap_or_else(|| "-".to_string()),
+                    if result.is_failure() {
+                        "FAIL".bright_red().to_string()
+                    } else {
+                        "PASS".bright_green().to_string()
+                    },
+                ]
+            })
+            .collect();
+        println!(
+            "{}",
+            crate::utils::create_simple_responsive_table(headers, rows)
+        );
+    }
+
+    pub fn print(&self) {
+        println!();
+        println!("{}", "Run Summary:".bright_yellow().bold());
+
+        for result in self.results.iter().filter(|r| r.is_failure()) {
+            println!(
+                "  {} {}: {}",
+                "✗".bright_red(),
+                result.name,
+                result.failure.as_deref().unwrap_or("")
+            );
+        }
+
+        let mut line = format!(
+            "  {} passed, {} failed",
+            self.passed().to_string().bright_green(),
+            self.failed().to_string().bright_red()
+        );
+        if self.skipped() > 0 {
+            line.push_str(&format!(
+                ", {} skipped (--fail-fast)",
+                self.skipped().to_string().bright_black()
+            ));
+        }
+        println!("{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mixed_results() -> Vec<RunResult> {
+        vec![
+            RunResult::passed("first", 200, Duration::from_millis(10)),
+            RunResult::failed("second", Some(500), None, "status 500"),
+            RunResult::passed("third", 201, Duration::from_millis(10)),
+        ]
+    }
+
+    fn run(mode: FailureMode) -> RunSummary {
+        let results = mixed_results();
+        let total = results.len();
+        let mut summary = RunSummary::new(mode);
+        for (i, result) in results.into_iter().enumerate() {
+            if !summary.record(result) {
+                summary.skip(total - i - 1);
+                break;
+            }
+        }
+        summary
+    }
+
+    #[test]
+    fn test_fail_fast_stops_at_first_failure() {
+        let summary = run(FailureMode::FailFast);
+        assert_eq!(summary.passed(), 1);
+        assert_eq!(summary.failed(), 1);
+        assert_eq!(summary.skipped(), 1);
+        assert_eq!(summary.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_keep_going_runs_everything() {
+        let summary = run(FailureMode::KeepGoing);
+        assert_eq!(summary.passed(), 2);
+        assert_eq!(summary.failed(), 1);
+        assert_eq!(summary.skipped(), 0);
+        assert_eq!(summary.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_all_passing_exits_zero() {
+        let mut summary = RunSummary::new(FailureMode::FailFast);
+        assert!(summary.record(RunResult::passed("ok", 200, Duration::ZERO)));
+        assert_eq!(summary.exit_code(), 0);
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::{Result, anyhow};
+use colored::*;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+use crate::client::Response;
+
+// JSON Schema validation of response bodies, for contract checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    pub instance_path: String,
+    pub message: String,
+}
+
+pub struct SchemaValidator {
+    source: String,
+    validator: jsonschema::Validator,
+}
+
+impl SchemaValidator {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read schema '{}': {}", path.display(), e))?;
+        let schema: Value = serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Schema '{}' is not valid JSON: {}", path.display(), e))?;
+
+        let mut validator = Self::from_value(&schema)
+            .map_err(|e| anyhow!("Invalid schema '{}': {}", path.display(), e))?;
+        validator.source = path.display().to_string();
+        Ok(validator)
+    }
+
+    pub fn from_value(schema: &Value) -> Result<Self> {
+        let validator = jsonschema::validator_for(schema).map_err(|e| anyhow!("{}", e))?;
+        Ok(Self {
+            source: "schema".to_string(),
+            validator,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn validate(&self, body: &str) -> Vec<SchemaViolation> {
+        let instance: Value = match serde_json::from_str(body) {
+            Ok(instance) => instance,
+            Err(e) => {
+                return vec![SchemaViolation {
+                    instance_path: String::new(),
+                    message: format!("response body is not valid JSON: {}", e),
+                }];
+            }
+        };
+
+        self.validator
+            .iter_errors(&instance)
+            .map(|error| SchemaViolation {
+                instance_path: error.instance_path().to_string(),
+                message: error.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Builds a schema describing `value`; every object key seen is marked required.
+pub fn infer_schema(value: &Value) -> Value {
+    let mut schema = infer_type(value);
+    if let Value::Object(ref mut map) = schema {
+        map.insert(
+            "$schema".to_string(),
+            Value::from("https://json-schema.org/draft/2020-12/schema"),
+        );
+    }
+    schema
+}
+
+fn infer_type(value: &Value) -> Value {
+    let mut schema = Map::new();
+    match value {
+        Value::Null => {
+            schema.insert("type".to_string(), Value::from("null"));
+        }
+        Value::Bool(_) => {
+            schema.insert("type".to_string(), Value::from("boolean"));
+        }
+        Value::Number(n) => {
+            let kind = if n.is_f64() { "number" } else { "integer" };
+            schema.insert("type".to_string(), Value::from(kind));
+        }
+        Value::String(_) => {
+            schema.insert("type".to_string(), Value::from("string"));
+        }
+        Value::Array(items) => {
+            schema.insert("type".to_string(), Value::from("array"));
+            // The first element stands in for the rest
+            if let Some(first) = items.first() {
+                schema.insert("items".to_string(), infer_type(first));
+            }
+        }
+        Value::Object(fields) => {
+            schema.insert("type".to_string(), Value::from("object"));
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|(key, value)| (key.clone(), infer_type(value)))
+                .collect();
+            schema.insert("properties".to_string(), Value::Object(properties));
+            schema.insert(
+                "required".to_string(),
+                Value::Array(fields.keys().cloned().map(Value::from).collect()),
+            );
+        }
+    }
+    Value::Object(schema)
+}
+
+pub async fn save_inferred_schema(path: &Path, response: &Response) -> Result<()> {
+    let body: Value = serde_json::from_str(&response.body)
+        .map_err(|_| anyhow!("Cannot infer a schema: the response body is not JSON"))?;
+    let contents = serde_json::to_string_pretty(&infer_schema(&body))?;
+    crate::utils::write_atomic(path, contents.as_bytes()).await
+}
+
+pub fn print_violations(source: &str, violations: &[SchemaViolation]) {
+    println!();
+    if violations.is_empty() {
+        println!(
+            "{} {}",
+            "✓".bright_green(),
+            format!("Response matches {}", source).bright_white()
+        );
+        return;
+    }
+
+    println!(
+        "{} {}",
+        "Schema violations:".bright_red().bold(),
+        format!("({} against {})", violations.len(), source).bright_black()
+    );
+    for violation in violations {
+        let path = if violation.instance_path.is_empty() {
+            "/"
+        } else {
+            &violation.instance_path
+        };
+        println!(
+            "  {} {} {}",
+            "✗".bright_red(),
+            path.bright_blue(),
+            violation.message
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_violations_report_instance_path() {
+        let validator = SchemaValidator::from_value(&json!({
+            "type": "object",
+            "properties": {"items": {"type": "array", "items": {"type": "integer"}}},
+            "required": ["items"]
+        }))
+        .unwrap();
+
+        assert!(validator.validate(r#"{"items": [1, 2]}"#).is_empty());
+
+        let violations = validator.validate(r#"{"items": [1, "two"]}"#);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].instance_path, "/items/1");
+
+        assert_eq!(validator.validate("not json").len(), 1);
+    }
+
+    #[test]
+    fn test_inferred_schema_accepts_its_sample() {
+        let sample = json!({"id": 7, "price": 1.5, "tags": ["a"], "owner": null});
+        let schema = infer_schema(&sample);
+
+        assert_eq!(schema["properties"]["id"]["type"], "integer");
+        assert_eq!(schema["properties"]["price"]["type"], "number");
+        assert_eq!(schema["properties"]["tags"]["items"]["type"], "string");
+
+        let validator = SchemaValidator::from_value(&schema).unwrap();
+        assert!(validator.validate(&sample.to_string()).is_empty());
+        assert!(!validator.validate(r#"{"id": "7"}"#).is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::{Result, anyhow};
+use colored::*;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, StatusCode};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::client::Response;
+use crate::fixture::Fixture;
+
+// Headers describing the original transfer, which no longer match the replayed body
+const SKIPPED_HEADERS: &[&str] = &[
+    "content-length",
+    "transfer-encoding",
+    "content-encoding",
+    "connection",
+    "keep-alive",
+];
+
+struct Route {
+    method: String,
+    path: String,
+    source: String,
+    response: Response,
+}
+
+// Stub server replaying recorded fixtures, matched by method and path
+pub struct FixtureServer {
+    routes: Vec<Route>,
+    delay: Option<Duration>,
+}
+
+impl FixtureServer {
+    /// Loads every `*.json` fixture in `dir`. Other JSON files are skipped with a
+    /// warning, and when two fixtures share a method and path the first by name wins.
+    pub fn load(dir: &Path, delay: Option<Duration>) -> Result<Self> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|e| anyhow!("Failed to read fixtures from '{}': {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut server = Self {
+            routes: Vec::new(),
+            delay,
+        };
+        for path in paths {
+            let fixture = match Fixture::load(&path) {
+                Ok(fixture) => fixture,
+                Err(e) => {
+                    crate::cli::print_warning(&format!("Skipping {}", e));
+                    continue;
+                }
+            };
+            let source = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if let Err(e) = server.add(fixture, source) {
+                crate::cli::print_warning(&e.to_string());
+            }
+        }
+        Ok(server)
+    }
+
+    fn add(&mut self, fixture: Fixture, source: String) -> Result<()> {
+        let url = url::Url::parse(&fixture.request.url)
+            .map_err(|e| anyhow!("Skipping {}: invalid URL: {}", source, e))?;
+        let method = fixture.request.method.to_uppercase();
+        let path = url.path().to_string();
+
+        if let Some(existing) = self.find(&method, &path) {
+            return Err(anyhow!(
+                "Skipping {}: {} {} is already served from {}",
+                source,
+                method,
+                path,
+                existing.source
+            ));
+        }
+        self.routes.push(Route {
+            method,
+            path,
+            source,
+            response: fixture.response,
+        });
+        Ok(())
+    }
+
+    fn find(&self, method: &str, path: &str) -> Option<&Route> {
+        self.routes
+            .iter()
+            .find(|route| route.method.eq_ignore_ascii_case(method) && route.path == path)
+    }
+
+    pub async fn run(self, port: u16) -> Result<()> {
+        if self.routes.is_empty() {
+            return Err(anyhow!("No fixtures to serve"));
+        }
+
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        let bound = hyper::Server::try_bind(&addr)
+            .map_err(|e| anyhow!("Failed to listen on {}: {}", addr, e))?;
+
+        println!(
+            "{} {}",
+            "Serving fixtures on".bright_yellow().bold(),
+            format!("http://{}", addr).bright_cyan().underline()
+        );
+        for route in &self.routes {
+            println!(
+                "  {} {} {} {}",
+                route.method.bright_blue().bold(),
+                route.path.bright_white(),
+                route.response.status.to_string().bright_black(),
+                format!("({})", route.source).bright_black()
+            );
+        }
+        println!("{}", "Press Ctrl+C to stop".bright_black());
+
+        let server = Arc::new(self);
+        let make_service = make_service_fn(move |_| {
+            let server = server.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.respond(request).await) }
+                }))
+            }
+        });
+
+        bound
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                tokio::signal::ctrl_c().await.ok();
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn respond(&self, request: Request<Body>) -> hyper::Response<Body> {
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+
+        let method = request.method().as_str();
+        let path = request.uri().path();
+        let Some(route) = self.find(method, path) else {
+            println!(
+                "{} {} {}",
+                method.bright_blue(),
+                path,
+                "404 (no fixture)".bright_red()
+            );
+            return hyper::Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header("content-type", "text/plain")
+                .body(Body::from(format!("No fixture for {} {}\n", method, path)))
+                .unwrap_or_default();
+        };
+
+        println!(
+            "{} {} {} {}",
+            method.bright_blue(),
+            path,
+            route.response.status.to_string().bright_green(),
+            format!("({})", route.source).bright_black()
+        );
+        fixture_response(&route.response)
+    }
+}
+
+fn fixture_response(response: &Response) -> hyper::Response<Body> {
+    let mut builder = hyper::Response::builder()
+        .status(StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR));
+    for (name, value) in &response.headers {
+        if !SKIPPED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            builder = builder.header(name, value);
+        }
+    }
+    builder
+        .body(Body::from(response.body.clone()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::SavedRequest;
+    use crate::utils::test_utils::create_test_response;
+    use std::collections::HashMap;
+
+    fn fixture(method: &str, url: &str, status: u16) -> Fixture {
+        let request = SavedRequest::new("fixture".to_string(), url.to_string(), method.to_string());
+        let mut response = create_test_response();
+        response.status = status;
+        response.headers = HashMap::from([
+            ("content-type".to_string(), "application/json".to_string()),
+            ("content-length".to_string(), "999".to_string()),
+        ]);
+        response.body = r#"{"id": 1}"#.to_string();
+        response.url = url.to_string();
+        response.method = method.to_string();
+        Fixture::new(&request, &response)
+    }
+
+    #[test]
+    fn test_routes_match_method_and_path() {
+        let mut server = FixtureServer {
+            routes: Vec::new(),
+            delay: None,
+        };
+        server
+            .add(
+                fixture("GET", "https://api.example.com/users/1?x=1", 200),
+                "get.json".to_string(),
+            )
+            .unwrap();
+        server
+            .add(
+                fixture("DELETE", "https://api.example.com/users/1", 204),
+                "delete.json".to_string(),
+            )
+            .unwrap();
+        assert!(
+            server
+                .add(
+                    fixture("get", "http://other.example.com/users/1", 500),
+                    "dup.json".to_string(),
+                )
+                .is_err()
+        );
+
+        assert_eq!(server.find("GET", "/users/1").unwrap().source, "get.json");
+        assert_eq!(
+            server.find("DELETE", "/users/1").unwrap().response.status,
+            204
+        );
+        assert!(server.find("POST", "/users/1").is_none());
+        assert!(server.find("GET", "/users").is_none());
+    }
+
+    #[test]
+    fn test_replayed_response_drops_transfer_headers() {
+        let response = fixture_response(&fixture("GET", "https://api.example.com/", 201).response);
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["content-type"], "application/json");
+        assert!(!response.headers().contains_key("content-length"));
+    }
+}
@@ -1,15 +1,26 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use crate::client::Response;
-use crate::request::{RequestCollection, SavedRequest};
+use crate::request::{RequestCollection, RequestTemplate, SavedRequest};
+use crate::websocket::SessionSummary;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Bodies kept in history are capped so data.json stays small, each one and all together
+const HISTORY_BODY_LIMIT: usize = 64 * 1024;
+const HISTORY_TOTAL_BODY_LIMIT: usize = 4 * 1024 * 1024;
+
+// Matches the `general.max_history_entries` default
+const DEFAULT_MAX_HISTORY_ENTRIES: usize = 1000;
+
+// Backups taken automatically are named apart so pruning never touches manual ones
+const AUTO_BACKUP_PREFIX: &str = "terzi_autobackup";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub id: String,
     pub timestamp: DateTime<Utc>,
@@ -19,16 +30,35 @@ pub struct HistoryEntry {
     pub duration_ms: Option<u64>,
     pub request_size: Option<usize>,
     pub response_size: Option<usize>,
+    /// Request line and headers as sent, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_header_size: Option<usize>,
+    /// Status line and headers as received, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_header_size: Option<usize>,
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub request_headers: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub response_headers: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_body: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StorageData {
     requests: HashMap<String, SavedRequest>,
     collections: HashMap<String, RequestCollection>,
-    history: Vec<HistoryEntry>,
+    // Oldest first, so trimming pops from the front
+    history: VecDeque<HistoryEntry>,
     environments: HashMap<String, HashMap<String, String>>,
     settings: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    templates: HashMap<String, RequestTemplate>,
 }
 
 impl Default for StorageData {
@@ -36,9 +66,10 @@ impl Default for StorageData {
         Self {
             requests: HashMap::new(),
             collections: HashMap::new(),
-            history: Vec::new(),
+            history: VecDeque::new(),
             environments: HashMap::new(),
             settings: HashMap::new(),
+            templates: HashMap::new(),
         }
     }
 }
@@ -46,6 +77,9 @@ impl Default for StorageData {
 pub struct Storage {
     data_dir: PathBuf,
     data: StorageData,
+    redact_secrets: bool,
+    max_history_entries: usize,
+    save_history: bool,
 }
 
 impl Storage {
@@ -60,6 +94,9 @@ impl Storage {
         let mut storage = Self {
             data_dir,
             data: StorageData::default(),
+            redact_secrets: false,
+            max_history_entries: DEFAULT_MAX_HISTORY_ENTRIES,
+            save_history: true,
         };
 
         // Load existing data
@@ -68,18 +105,48 @@ impl Storage {
         Ok(storage)
     }
 
+    /// Keeps secrets out of data.json: saved requests get `${TERZI_...}`
+    /// placeholders and history entries are masked.
+    pub fn set_redact_secrets(&mut self, enabled: bool) {
+        self.redact_secrets = enabled;
+    }
+
+    /// Caps history at `general.max_history_entries`, dropping the oldest entries first.
+    pub fn set_max_history_entries(&mut self, limit: usize) {
+        self.max_history_entries = limit.max(1);
+    }
+
+    /// When disabled (`general.save_history = false` or `--no-history`),
+    /// nothing is added to history.
+    pub fn set_save_history(&mut self, enabled: bool) {
+        self.save_history = enabled;
+    }
+
     fn get_data_directory() -> Result<PathBuf> {
-        if let Some(config_dir) = dirs::config_dir() {
-            Ok(config_dir.join("terzi"))
-        } else if let Some(home_dir) = dirs::home_dir() {
-            Ok(home_dir.join(".terzi"))
-        } else {
-            Ok(PathBuf::from(".terzi"))
+        Ok(crate::config::config_dir())
+    }
+
+    fn data_file(&self) -> PathBuf {
+        let profile = crate::config::active_profile();
+        self.data_dir.join(Self::data_file_name(profile.as_deref()))
+    }
+
+    pub fn data_file_name(profile: Option<&str>) -> String {
+        crate::config::profile_file_name("data", "json", profile)
+    }
+
+    /// Parses a data file without loading it, returning how many requests it saves.
+    pub fn verify_data_file(path: &std::path::Path) -> Result<usize> {
+        let contents = std::fs::read_to_string(path)?;
+        if contents.is_empty() {
+            return Ok(0);
         }
+        let data: StorageData = serde_json::from_str(&contents)?;
+        Ok(data.requests.len())
     }
 
     async fn load(&mut self) -> Result<()> {
-        let data_file = self.data_dir.join("data.json");
+        let data_file = self.data_file();
 
         if data_file.exists() {
             let mut file = fs::File::open(&data_file).await?;
@@ -87,8 +154,19 @@ impl Storage {
             file.read_to_string(&mut contents).await?;
 
             if !contents.is_empty() {
-                self.data =
-                    serde_json::from_str(&contents).unwrap_or_else(|_| StorageData::default());
+                match serde_json::from_str(&contents) {
+                    Ok(data) => self.data = data,
+                    Err(e) => {
+                        // Never overwrite saved requests we failed to read
+                        let backup = crate::utils::move_aside_corrupt_file(&data_file)?;
+                        crate::cli::print_stderr_warning(&format!(
+                            "Could not parse {}: {}\nMoved it to {}; saved requests and history start empty until it is fixed and restored.",
+                            data_file.display(),
+                            e,
+                            backup.display()
+                        ));
+                    }
+                }
             }
         }
 
@@ -96,21 +174,31 @@ impl Storage {
     }
 
     async fn save(&self) -> Result<()> {
-        let data_file = self.data_dir.join("data.json");
+        let data_file = self.data_file();
         let contents = serde_json::to_string_pretty(&self.data)?;
-
-        let mut file = fs::File::create(&data_file).await?;
-        file.write_all(contents.as_bytes()).await?;
-        file.flush().await?;
-
-        Ok(())
+        crate::utils::write_atomic(&data_file, contents.as_bytes()).await
     }
 
     // Request management
+    /// Stores a request's cursor and state after a send, leaving everything else as saved.
+    pub async fn save_request_state(&mut self, name: &str, request: &SavedRequest) -> Result<()> {
+        let saved = self
+            .data
+            .requests
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Request '{}' not found", name))?;
+        saved.cursor = request.cursor.clone();
+        saved.state = request.state.clone();
+        self.save().await
+    }
+
     pub async fn save_request(&mut self, name: &str, request: &SavedRequest) -> Result<()> {
         let mut request = request.clone();
         request.name = name.to_string();
         request.updated_at = Utc::now();
+        if self.redact_secrets {
+            request.redact_secrets();
+        }
 
         self.data.requests.insert(name.to_string(), request);
         self.save().await?;
@@ -143,6 +231,27 @@ impl Storage {
         Ok(requests)
     }
 
+    /// Saves a copy of `source` as `name`, with a fresh ID and timestamps.
+    pub async fn duplicate_request(&mut self, source: &str, name: &str) -> Result<SavedRequest> {
+        if self.data.requests.contains_key(name) {
+            return Err(anyhow::anyhow!("Request '{}' already exists", name));
+        }
+        let Some(original) = self.data.requests.get(source) else {
+            return Err(anyhow::anyhow!("Request '{}' not found", source));
+        };
+
+        let now = Utc::now();
+        let mut copy = original.clone();
+        copy.id = uuid::Uuid::new_v4().to_string();
+        copy.name = name.to_string();
+        copy.created_at = now;
+        copy.updated_at = now;
+
+        self.data.requests.insert(name.to_string(), copy.clone());
+        self.save().await?;
+        Ok(copy)
+    }
+
     pub async fn delete_request(&mut self, name: &str) -> Result<bool> {
         let removed = self.data.requests.remove(name).is_some();
         if removed {
@@ -151,52 +260,64 @@ impl Storage {
         Ok(removed)
     }
 
-    pub async fn search_requests(&self, query: &str) -> Result<Vec<SavedRequest>> {
+    pub async fn search_requests(&self, query: &str) -> Result<Vec<SearchResult>> {
         let query_lower = query.to_lowercase();
         let mut results = Vec::new();
 
         for request in self.data.requests.values() {
-            let mut score = 0;
+            // (points, field) for each field that matched
+            let mut matches: Vec<(u32, &'static str)> = Vec::new();
 
             // Exact name match gets highest score
             if request.name.to_lowercase() == query_lower {
-                score += 100;
+                matches.push((100, "name"));
             } else if request.name.to_lowercase().contains(&query_lower) {
-                score += 50;
+                matches.push((50, "name"));
+            } else if let Some(score) = crate::utils::fuzzy_match(query, &request.name) {
+                // Letters in order, e.g. a typo or abbreviation of the name
+                matches.push(((score * 80.0).ceil() as u32, "name"));
             }
 
             // URL matches
             if request.url.to_lowercase().contains(&query_lower) {
-                score += 30;
+                matches.push((30, "url"));
             }
 
             // Method matches
             if request.method.to_lowercase().contains(&query_lower) {
-                score += 20;
+                matches.push((20, "method"));
             }
 
             // Tag matches
             for tag in &request.tags {
                 if tag.to_lowercase().contains(&query_lower) {
-                    score += 25;
+                    matches.push((25, "tag"));
                 }
             }
 
             // Description matches
             if let Some(ref desc) = request.description {
                 if desc.to_lowercase().contains(&query_lower) {
-                    score += 15;
+                    matches.push((15, "description"));
                 }
             }
 
-            if score > 0 {
-                results.push((score, request.clone()));
+            if let Some(&(_, field)) = matches.iter().max_by_key(|(points, _)| *points) {
+                results.push(SearchResult {
+                    score: matches.iter().map(|(points, _)| points).sum(),
+                    matched_field: field,
+                    request: request.clone(),
+                });
             }
         }
 
-        // Sort by score (highest first)
-        results.sort_by(|a, b| b.0.cmp(&a.0));
-        Ok(results.into_iter().map(|(_, req)| req).collect())
+        // Sort by score (highest first), then by name for a stable order
+        results.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.request.name.cmp(&b.request.name))
+        });
+        Ok(results)
     }
 
     // Collection management
@@ -214,11 +335,27 @@ impl Storage {
         Ok(())
     }
 
+    /// Stores a collection, replacing any existing one with the same name.
+    pub async fn save_collection(&mut self, mut collection: RequestCollection) -> Result<()> {
+        if self.redact_secrets {
+            for request in collection.requests.iter_mut() {
+                request.redact_secrets();
+            }
+        }
+        self.data
+            .collections
+            .insert(collection.name.clone(), collection);
+        self.save().await
+    }
+
     pub async fn add_request_to_collection(
         &mut self,
         collection_name: &str,
-        request: SavedRequest,
+        mut request: SavedRequest,
     ) -> Result<()> {
+        if self.redact_secrets {
+            request.redact_secrets();
+        }
         if let Some(collection) = self.data.collections.get_mut(collection_name) {
             collection.add_request(request);
             self.save().await?;
@@ -256,6 +393,9 @@ impl Storage {
         request: &SavedRequest,
         response: &Response,
     ) -> Result<()> {
+        if !self.save_history {
+            return Ok(());
+        }
         let entry = HistoryEntry {
             id: uuid::Uuid::new_v4().to_string(),
             timestamp: Utc::now(),
@@ -263,18 +403,23 @@ impl Storage {
             url: request.url.clone(),
             response_status: Some(response.status),
             duration_ms: Some(response.duration.as_millis() as u64),
-            request_size: request.body.as_ref().map(|b| b.len()),
-            response_size: Some(response.size),
+            // What actually crossed the wire, including a digest challenge round trip
+            request_size: request
+                .body
+                .as_ref()
+                .map(|_| response.transfer.request_body_bytes),
+            response_size: Some(response.transfer.response_body_bytes),
+            request_header_size: Some(response.transfer.request_header_bytes),
+            response_header_size: Some(response.transfer.response_header_bytes),
             error_message: None,
+            summary: None,
+            request_headers: self.history_headers(&request.headers),
+            request_body: request.body_text().map(|body| self.history_body(body)),
+            response_headers: self.history_headers(&response.headers),
+            response_body: Some(self.history_body(&response.body)),
         };
 
-        self.data.history.push(entry);
-
-        // Keep only last 1000 entries to prevent infinite growth
-        if self.data.history.len() > 1000 {
-            self.data.history.remove(0);
-        }
-
+        self.push_history(entry);
         self.save().await?;
         Ok(())
     }
@@ -284,6 +429,9 @@ impl Storage {
         request: &SavedRequest,
         error: &str,
     ) -> Result<()> {
+        if !self.save_history {
+            return Ok(());
+        }
         let entry = HistoryEntry {
             id: uuid::Uuid::new_v4().to_string(),
             timestamp: Utc::now(),
@@ -291,23 +439,80 @@ impl Storage {
             url: request.url.clone(),
             response_status: None,
             duration_ms: None,
-            request_size: request.body.as_ref().map(|b| b.len()),
+            request_size: request.body.as_ref().and_then(|b| b.size()),
             response_size: None,
             error_message: Some(error.to_string()),
+            request_headers: self.history_headers(&request.headers),
+            request_body: request.body_text().map(|body| self.history_body(body)),
+            ..Default::default()
         };
 
-        self.data.history.push(entry);
+        self.push_history(entry);
+        self.save().await?;
+        Ok(())
+    }
 
-        if self.data.history.len() > 1000 {
-            self.data.history.remove(0);
+    pub async fn add_websocket_to_history(
+        &mut self,
+        url: &str,
+        session: &SessionSummary,
+    ) -> Result<()> {
+        if !self.save_history {
+            return Ok(());
         }
+        let entry = HistoryEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            method: "WS".to_string(),
+            url: url.to_string(),
+            response_status: session.connected.then_some(101),
+            duration_ms: Some(session.duration.as_millis() as u64),
+            request_size: Some(session.bytes_sent),
+            response_size: Some(session.bytes_received),
+            error_message: session.error.clone(),
+            summary: Some(format!(
+                "{} sent, {} received",
+                session.messages_sent, session.messages_received
+            )),
+            ..Default::default()
+        };
 
+        self.push_history(entry);
         self.save().await?;
         Ok(())
     }
 
+    fn push_history(&mut self, entry: HistoryEntry) {
+        self.data.history.push_back(entry);
+        self.trim_history();
+        self.trim_history_bodies();
+    }
+
+    // The oldest entries lose their bodies first; their status, timing and sizes stay
+    fn trim_history_bodies(&mut self) {
+        let body_size = |entry: &HistoryEntry| {
+            entry.request_body.as_ref().map_or(0, String::len)
+                + entry.response_body.as_ref().map_or(0, String::len)
+        };
+        let mut total: usize = self.data.history.iter().map(body_size).sum();
+        for entry in self.data.history.iter_mut() {
+            if total <= HISTORY_TOTAL_BODY_LIMIT {
+                break;
+            }
+            total -= body_size(entry);
+            entry.request_body = None;
+            entry.response_body = None;
+        }
+    }
+
+    fn trim_history(&mut self) {
+        while self.data.history.len() > self.max_history_entries {
+            self.data.history.pop_front();
+        }
+    }
+
     pub async fn get_history(&self, limit: usize) -> Result<Vec<HistoryEntry>> {
-        let mut history = self.data.history.clone();
+        let mut history: Vec<HistoryEntry> = self.data.history.iter().cloned().collect();
         history.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
 
         if history.len() > limit {
@@ -323,6 +528,44 @@ impl Storage {
         Ok(())
     }
 
+    /// Removes entries recorded before `cutoff`, returning how many were removed.
+    pub async fn clear_history_before(&mut self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let before = self.data.history.len();
+        self.data.history.retain(|entry| entry.timestamp >= cutoff);
+        let removed = before - self.data.history.len();
+        if removed > 0 {
+            self.save().await?;
+        }
+        Ok(removed)
+    }
+
+    /// Removes the entry whose ID starts with `id`. A prefix matching several
+    /// entries is an error rather than a guess.
+    pub async fn delete_history_entry(&mut self, id: &str) -> Result<Option<HistoryEntry>> {
+        let matches: Vec<usize> = self
+            .data
+            .history
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.id.starts_with(id))
+            .map(|(index, _)| index)
+            .collect();
+
+        match matches.as_slice() {
+            [] => Ok(None),
+            [index] => {
+                let entry = self.data.history.remove(*index);
+                self.save().await?;
+                Ok(entry)
+            }
+            _ => Err(anyhow::anyhow!(
+                "History ID '{}' matches {} entries; use more characters",
+                id,
+                matches.len()
+            )),
+        }
+    }
+
     pub async fn get_history_stats(&self) -> Result<HistoryStats> {
         let mut stats = HistoryStats::default();
 
@@ -340,6 +583,11 @@ impl Storage {
                 stats.failed_requests += 1;
             }
 
+            stats.bytes_sent +=
+                entry.request_header_size.unwrap_or(0) + entry.request_size.unwrap_or(0);
+            stats.bytes_received +=
+                entry.response_header_size.unwrap_or(0) + entry.response_size.unwrap_or(0);
+
             if let Some(duration) = entry.duration_ms {
                 stats.total_duration_ms += duration;
                 stats.min_duration_ms = stats
@@ -387,6 +635,26 @@ impl Storage {
         Ok(removed)
     }
 
+    // Template management
+    pub async fn save_template(&mut self, mut template: RequestTemplate) -> Result<()> {
+        if self.redact_secrets {
+            template.base_request.redact_secrets();
+        }
+        self.data.templates.insert(template.name.clone(), template);
+        self.save().await?;
+        Ok(())
+    }
+
+    pub async fn get_template(&self, name: &str) -> Result<Option<RequestTemplate>> {
+        Ok(self.data.templates.get(name).cloned())
+    }
+
+    pub async fn list_templates(&self) -> Result<Vec<RequestTemplate>> {
+        let mut templates: Vec<RequestTemplate> = self.data.templates.values().cloned().collect();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
     // Settings management
     pub async fn set_setting(&mut self, key: &str, value: &str) -> Result<()> {
         self.data
@@ -424,16 +692,19 @@ impl Storage {
             self.data.collections.extend(imported_data.collections);
             self.data.environments.extend(imported_data.environments);
             self.data.settings.extend(imported_data.settings);
+            self.data.templates.extend(imported_data.templates);
 
             // Merge history but maintain chronological order
             self.data.history.extend(imported_data.history);
             self.data
                 .history
+                .make_contiguous()
                 .sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
         } else {
             // Replace existing data
             self.data = imported_data;
         }
+        self.trim_history();
 
         self.save().await?;
         Ok(())
@@ -441,13 +712,26 @@ impl Storage {
 
     // Backup functionality
     pub async fn create_backup(&self) -> Result<PathBuf> {
+        self.write_backup("terzi_backup").await
+    }
+
+    /// Writes a backup taken before a destructive command, keeping only the
+    /// newest `keep` automatic backups. Manual backups are never pruned.
+    pub async fn create_auto_backup(&self, keep: usize) -> Result<PathBuf> {
+        let backup_file = self.write_backup(AUTO_BACKUP_PREFIX).await?;
+        prune_backups(&self.data_dir.join("backups"), AUTO_BACKUP_PREFIX, keep).await?;
+        Ok(backup_file)
+    }
+
+    async fn write_backup(&self, prefix: &str) -> Result<PathBuf> {
         let backup_dir = self.data_dir.join("backups");
         if !backup_dir.exists() {
             fs::create_dir_all(&backup_dir).await?;
         }
 
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-        let backup_file = backup_dir.join(format!("terzi_backup_{}.json", timestamp));
+        // Milliseconds keep an automatic backup from overwriting one taken the same second
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%3f");
+        let backup_file = backup_dir.join(format!("{}_{}.json", prefix, timestamp));
 
         let backup_data = self.export_data(true).await?;
 
@@ -467,6 +751,16 @@ impl Storage {
         Ok(())
     }
 
+    /// Resolves a backup given as a path, or as a file name in the backups directory.
+    pub fn backup_path(&self, name: &str) -> PathBuf {
+        let path = PathBuf::from(name);
+        if path.exists() || path.components().count() > 1 {
+            path
+        } else {
+            self.data_dir.join("backups").join(name)
+        }
+    }
+
     pub async fn list_backups(&self) -> Result<Vec<PathBuf>> {
         let backup_dir = self.data_dir.join("backups");
         if !backup_dir.exists() {
@@ -496,6 +790,132 @@ impl Storage {
 
         Ok(backups)
     }
+
+    // Credentials in headers are never written to history; bodies only with redact_secrets
+    fn history_headers(&self, headers: &HashMap<String, String>) -> HashMap<String, String> {
+        headers
+            .iter()
+            .map(|(name, value)| (name.clone(), crate::utils::mask_header_value(name, value)))
+            .collect()
+    }
+
+    fn history_body(&self, body: &str) -> String {
+        let body = capped_body(body);
+        if self.redact_secrets {
+            crate::utils::mask_body(&body)
+        } else {
+            body
+        }
+    }
+}
+
+// Responses kept for conditional requests, one file per request under `cache/`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub stored_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    pub fn new() -> Self {
+        Self {
+            dir: crate::config::config_dir().join("cache"),
+        }
+    }
+
+    /// Method, URL and headers identify a cached response; header order does not matter.
+    pub fn key(request: &SavedRequest) -> String {
+        use sha2::{Digest, Sha256};
+
+        let mut headers: Vec<(String, &String)> = request
+            .headers
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value))
+            .collect();
+        headers.sort();
+
+        let mut hasher = Sha256::new();
+        hasher.update(request.method.to_uppercase());
+        hasher.update("\n");
+        hasher.update(&request.url);
+        for (name, value) in headers {
+            hasher.update(format!("\n{}: {}", name, value));
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// A missing or unreadable entry is simply a cache miss.
+    pub async fn get(&self, key: &str) -> Option<CachedResponse> {
+        let contents = fs::read_to_string(self.entry_path(key)).await.ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Only responses a server can revalidate, i.e. with an ETag or Last-Modified, are kept.
+    pub async fn put(&self, key: &str, response: &Response) -> Result<bool> {
+        let etag = response.headers.get("etag").cloned();
+        let last_modified = response.headers.get("last-modified").cloned();
+        if etag.is_none() && last_modified.is_none() {
+            return Ok(false);
+        }
+
+        let entry = CachedResponse {
+            status: response.status,
+            headers: response.headers.clone(),
+            body: response.body.clone(),
+            etag,
+            last_modified,
+            stored_at: Utc::now(),
+        };
+        fs::create_dir_all(&self.dir).await?;
+        crate::utils::write_atomic(
+            &self.entry_path(key),
+            serde_json::to_string(&entry)?.as_bytes(),
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Removes every cached response, returning how many there were.
+    pub async fn clear(&self) -> Result<usize> {
+        let mut removed = 0;
+        let mut entries = match fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.path().extension().is_some_and(|ext| ext == "json") {
+                fs::remove_file(entry.path()).await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub request: SavedRequest,
+    pub score: u32,
+    /// The field that contributed most to the score
+    pub matched_field: &'static str,
 }
 
 #[derive(Debug, Default)]
@@ -509,4 +929,37 @@ pub struct HistoryStats {
     pub average_duration_ms: Option<u64>,
     pub min_duration_ms: Option<u64>,
     pub max_duration_ms: Option<u64>,
+    /// Headers and bodies; entries recorded before header sizes were kept count bodies only
+    pub bytes_sent: usize,
+    pub bytes_received: usize,
+}
+
+/// Deletes all but the newest `keep` files in `dir` whose names start with `prefix`.
+pub async fn prune_backups(dir: &std::path::Path, prefix: &str, keep: usize) -> Result<()> {
+    let mut backups = Vec::new();
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with(&format!("{}_", prefix)) {
+            backups.push(name);
+        }
+    }
+
+    // Names carry the timestamp, so they sort chronologically
+    backups.sort_by(|a, b| b.cmp(a));
+    for old in backups.iter().skip(keep.max(1)) {
+        fs::remove_file(dir.join(old)).await?;
+    }
+    Ok(())
+}
+
+fn capped_body(body: &str) -> String {
+    if body.len() <= HISTORY_BODY_LIMIT {
+        return body.to_string();
+    }
+    let mut end = HISTORY_BODY_LIMIT;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    body[..end].to_string()
 }
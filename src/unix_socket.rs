@@ -0,0 +1,122 @@
+use anyhow::Result;
+use hyper::client::connect::{Connected, Connection};
+use hyper::service::Service;
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::UnixStream;
+
+// reqwest only dials TCP, so --unix-socket requests go through hyper with this
+// connector. The URL's host only ends up in the Host header.
+pub struct UnixSocketClient {
+    client: hyper::Client<UnixConnector>,
+}
+
+impl UnixSocketClient {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            client: hyper::Client::builder().build(UnixConnector(path.into())),
+        }
+    }
+
+    /// Sends a built reqwest request and hands back the response as a reqwest one,
+    /// so status handling and body reading stay the same as over TCP.
+    pub async fn send(
+        &self,
+        request: reqwest::Request,
+        user_agent: &str,
+    ) -> Result<reqwest::Response> {
+        if request.url().scheme() != "http" {
+            return Err(anyhow::anyhow!(
+                "--unix-socket only supports http:// URLs, got {}",
+                request.url()
+            ));
+        }
+
+        let mut builder = hyper::Request::builder()
+            .method(request.method().clone())
+            .uri(request.url().as_str());
+        for (name, value) in request.headers() {
+            builder = builder.header(name, value);
+        }
+        // The client's default User-Agent is only added by reqwest when it sends
+        if !request.headers().contains_key(reqwest::header::USER_AGENT) {
+            builder = builder.header(reqwest::header::USER_AGENT, user_agent);
+        }
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| hyper::Body::from(bytes.to_vec()))
+            .unwrap_or_else(hyper::Body::empty);
+
+        let response = self.client.request(builder.body(body)?).await?;
+        Ok(reqwest::Response::from(response))
+    }
+}
+
+#[derive(Clone)]
+struct UnixConnector(PathBuf);
+
+impl Service<hyper::Uri> for UnixConnector {
+    type Response = UnixConnection;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<UnixConnection>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: hyper::Uri) -> Self::Future {
+        let path = self.0.clone();
+        Box::pin(async move {
+            UnixStream::connect(&path)
+                .await
+                .map(UnixConnection)
+                .map_err(|e| {
+                    io::Error::new(
+                        e.kind(),
+                        format!("Failed to connect to Unix socket {}: {}", path.display(), e),
+                    )
+                })
+        })
+    }
+}
+
+struct UnixConnection(UnixStream);
+
+impl Connection for UnixConnection {
+    fn connected(&self) -> Connected {
+        Connected::new()
+    }
+}
+
+impl AsyncRead for UnixConnection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for UnixConnection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_shutdown(cx)
+    }
+}
@@ -4,7 +4,13 @@ use url::Url;
 
 // URL utilities
 pub fn normalize_url(url: &str) -> Result<String> {
-    let mut parsed = Url::parse(url)?;
+    let encoded = encode_url_parts(url);
+    let mut parsed =
+        Url::parse(&encoded).map_err(|e| anyhow::anyhow!("Invalid URL '{}': {}", url, e))?;
+    // Serializing would escape the braces of placeholders that are filled in later
+    if encoded.contains("{{") {
+        return Ok(encoded);
+    }
 
     // Remove default ports
     if let Some(port) = parsed.port() {
@@ -27,6 +33,75 @@ pub fn normalize_url(url: &str) -> Result<String> {
     Ok(parsed.to_string())
 }
 
+// Adds `key=value` pairs, already encoded as they should be sent, to the end of the
+// URL's query and before any fragment
+pub fn append_query(url: &str, pairs: &[String]) -> String {
+    if pairs.is_empty() {
+        return url.to_string();
+    }
+    let (base, fragment) = url.split_at(url.find('#').unwrap_or(url.len()));
+    let separator = match base.find('?') {
+        None => "?",
+        Some(i) if i + 1 == base.len() || base.ends_with('&') => "",
+        Some(_) => "&",
+    };
+    format!("{}{}{}{}", base, separator, pairs.join("&"), fragment)
+}
+
+// Percent-encodes the path and query of a URL as typed. Existing `%XX` escapes and
+// `{{...}}` placeholders are kept, and the fragment is left alone.
+fn encode_url_parts(url: &str) -> String {
+    let authority_start = url.find("://").map_or(0, |i| i + 3);
+    let path_start = url[authority_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |i| authority_start + i);
+    let fragment_start = url[path_start..]
+        .find('#')
+        .map_or(url.len(), |i| path_start + i);
+    let query_start = url[path_start..fragment_start]
+        .find('?')
+        .map_or(fragment_start, |i| path_start + i);
+
+    let mut result = String::with_capacity(url.len());
+    result.push_str(&url[..path_start]);
+    percent_encode_into(&mut result, &url[path_start..query_start], "/:@");
+    if query_start < fragment_start {
+        result.push('?');
+        percent_encode_into(&mut result, &url[query_start + 1..fragment_start], "/:@?");
+    }
+    result.push_str(&url[fragment_start..]);
+    result
+}
+
+fn percent_encode_into(out: &mut String, text: &str, allowed: &str) {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"{{")
+            && let Some(end) = text[i..].find("}}")
+        {
+            out.push_str(&text[i..i + end + 2]);
+            i += end + 2;
+            continue;
+        }
+
+        let byte = bytes[i];
+        let escaped = byte == b'%'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+            && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit);
+        if escaped
+            || byte.is_ascii_alphanumeric()
+            || b"-._~!$&'()*+,;=".contains(&byte)
+            || allowed.as_bytes().contains(&byte)
+        {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+        i += 1;
+    }
+}
+
 pub fn extract_domain(url: &str) -> Result<String> {
     let parsed = Url::parse(url)?;
     Ok(parsed.host_str().unwrap_or("").to_string())
@@ -51,6 +126,50 @@ pub fn format_duration(duration: Duration) -> String {
     }
 }
 
+// Intervals such as `500ms`, `5s`, `2m` or `1h`; a bare number is seconds
+pub fn parse_interval(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid interval '{}'. Use e.g. 500ms, 5s or 2m", input))?;
+    let seconds = match unit.trim() {
+        "ms" => amount / 1000.0,
+        "" | "s" => amount,
+        "m" => amount * 60.0,
+        "h" => amount * 3600.0,
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unknown interval unit '{}'. Use ms, s, m or h",
+                other
+            ));
+        }
+    };
+    if seconds <= 0.0 {
+        return Err(anyhow::anyhow!("Interval must be greater than zero"));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+// `2024-01-31` (midnight UTC) or a full RFC 3339 timestamp
+pub fn parse_date(input: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    let input = input.trim();
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(input) {
+        return Ok(datetime.with_timezone(&chrono::Utc));
+    }
+    chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Invalid date '{}'. Use YYYY-MM-DD or an RFC 3339 timestamp",
+                input
+            )
+        })
+}
+
 pub fn format_timestamp(timestamp: SystemTime) -> String {
     let datetime: chrono::DateTime<chrono::Utc> = timestamp.into();
     datetime.format("%Y-%m-%d %H:%M:%S UTC").to_string()
@@ -110,10 +229,95 @@ pub fn format_bytes(bytes: usize) -> String {
     }
 }
 
+// Sizes such as `512`, `64KB` or `10MB`, in the same 1024-based units as `format_bytes`
+pub fn parse_size(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid size '{}'. Use e.g. 512KB or 10MB", input))?;
+    let multiplier = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1u64,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unknown size unit '{}'. Use B, KB, MB or GB",
+                other
+            ));
+        }
+    };
+    Ok((amount * multiplier as f64) as u64)
+}
+
+// `HOST:PORT:ADDR` as in curl's --resolve; ADDR may be a bracketed IPv6 address
+pub fn parse_resolve(input: &str) -> Result<(String, std::net::SocketAddr)> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid --resolve '{}'. Use HOST:PORT:ADDR, e.g. api.example.com:443:10.0.0.5",
+            input
+        )
+    };
+    let mut parts = input.splitn(3, ':');
+    let (Some(host), Some(port), Some(addr)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    let port: u16 = port.parse().map_err(|_| invalid())?;
+    let addr = addr.trim_start_matches('[').trim_end_matches(']');
+    let ip: std::net::IpAddr = addr
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid IP address '{}' in --resolve '{}'", addr, input))?;
+    Ok((host.to_lowercase(), std::net::SocketAddr::new(ip, port)))
+}
+
+pub fn parse_connect_to(input: &str) -> Result<crate::config::ConnectTo> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid --connect-to '{}'. Use HOST:PORT:TARGET_HOST:TARGET_PORT, e.g. api.example.com:443:backend-2.internal:8443",
+            input
+        )
+    };
+    let mut parts = input.splitn(3, ':');
+    let (Some(host), Some(port), Some(target)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    // The target host may be a bracketed IPv6 address, so its port is after the last colon
+    let (target_host, target_port) = target.rsplit_once(':').ok_or_else(invalid)?;
+    let target_host = target_host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() || target_host.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(crate::config::ConnectTo {
+        host: host.to_lowercase(),
+        port: port.parse().map_err(|_| invalid())?,
+        target_host: target_host.to_lowercase(),
+        target_port: target_port.parse().map_err(|_| invalid())?,
+    })
+}
+
 // JSON utilities
 pub fn prettify_json(json: &str) -> Result<String> {
+    prettify_json_with_indent(json, 2)
+}
+
+pub fn prettify_json_with_indent(json: &str, indent: usize) -> Result<String> {
+    use serde::Serialize;
+
     let value: serde_json::Value = serde_json::from_str(json)?;
-    Ok(serde_json::to_string_pretty(&value)?)
+    let indent = " ".repeat(indent);
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut output = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8(output)?)
 }
 
 pub fn minify_json(json: &str) -> Result<String> {
@@ -125,6 +329,237 @@ pub fn is_valid_json(json: &str) -> bool {
     serde_json::from_str::<serde_json::Value>(json).is_ok()
 }
 
+// Numbers keep their exact text in serde_json (arbitrary_precision), which other serializers
+// don't understand, so JSON is converted to YAML values explicitly. Integers too big for
+// 64 bits become strings rather than lose digits.
+pub fn json_to_yaml(json: &serde_json::Value) -> Result<String> {
+    fn convert(value: &serde_json::Value) -> serde_yaml::Value {
+        use serde_json::Value;
+
+        match value {
+            Value::Null => serde_yaml::Value::Null,
+            Value::Bool(b) => serde_yaml::Value::Bool(*b),
+            Value::Number(n) => {
+                let text = n.to_string();
+                if let Some(i) = n.as_i64() {
+                    i.into()
+                } else if let Some(u) = n.as_u64() {
+                    u.into()
+                } else if text.contains(['.', 'e', 'E'])
+                    && let Some(f) = n.as_f64()
+                {
+                    f.into()
+                } else {
+                    serde_yaml::Value::String(text)
+                }
+            }
+            Value::String(s) => serde_yaml::Value::String(s.clone()),
+            Value::Array(items) => serde_yaml::Value::Sequence(items.iter().map(convert).collect()),
+            Value::Object(obj) => serde_yaml::Value::Mapping(
+                obj.iter()
+                    .map(|(k, v)| (serde_yaml::Value::String(k.clone()), convert(v)))
+                    .collect(),
+            ),
+        }
+    }
+
+    Ok(serde_yaml::to_string(&convert(json))?)
+}
+
+// JSONPath utilities (supports $, .key, ['key'], [index] and negative indexes)
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonPathSegment {
+    Key(String),
+    Index(i64),
+}
+
+pub fn parse_json_path(path: &str) -> Result<Vec<JsonPathSegment>> {
+    let path = path.trim();
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| anyhow::anyhow!("JSONPath must start with '$': {}", path))?;
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                    end += 1;
+                }
+                if end == start {
+                    return Err(anyhow::anyhow!("Empty key in JSONPath: {}", path));
+                }
+                segments.push(JsonPathSegment::Key(chars[start..end].iter().collect()));
+                i = end;
+            }
+            '[' => {
+                let close = chars[i..]
+                    .iter()
+                    .position(|&c| c == ']')
+                    .map(|p| p + i)
+                    .ok_or_else(|| anyhow::anyhow!("Unclosed '[' in JSONPath: {}", path))?;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let inner = inner.trim();
+
+                if let Some(key) = inner
+                    .strip_prefix('\'')
+                    .and_then(|k| k.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|k| k.strip_suffix('"')))
+                {
+                    segments.push(JsonPathSegment::Key(key.to_string()));
+                } else {
+                    let index = inner
+                        .parse::<i64>()
+                        .map_err(|_| anyhow::anyhow!("Invalid index '{}' in JSONPath", inner))?;
+                    segments.push(JsonPathSegment::Index(index));
+                }
+                i = close + 1;
+            }
+            c => {
+                return Err(anyhow::anyhow!("Unexpected '{}' in JSONPath: {}", c, path));
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+pub fn json_path<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    let segments = parse_json_path(path).ok()?;
+    let mut current = value;
+
+    for segment in &segments {
+        current = match segment {
+            JsonPathSegment::Key(key) => current.get(key)?,
+            JsonPathSegment::Index(index) => {
+                let items = current.as_array()?;
+                let position = if *index < 0 {
+                    items.len().checked_sub(index.unsigned_abs() as usize)?
+                } else {
+                    *index as usize
+                };
+                items.get(position)?
+            }
+        };
+    }
+
+    Some(current)
+}
+
+// Clap value parser for arguments that take a JSONPath, so typos fail before the request
+pub fn parse_json_path_arg(input: &str) -> Result<String> {
+    let path = input.trim();
+    parse_json_path(path)?;
+    Ok(path.to_string())
+}
+
+// jq filters for --jq, compiled with jaq's standard library
+pub type JqFilter = jaq_core::Filter<jaq_core::data::JustLut<jaq_json::Val>>;
+
+pub fn compile_jq(code: &str) -> Result<JqFilter> {
+    use jaq_core::load::{Arena, Error, File, Loader};
+
+    let defs = jaq_core::defs()
+        .chain(jaq_std::defs())
+        .chain(jaq_json::defs());
+    let funs = jaq_core::funs()
+        .chain(jaq_std::funs())
+        .chain(jaq_json::funs());
+    let arena = Arena::default();
+    let modules = Loader::new(defs)
+        .load(&arena, File { code, path: () })
+        .map_err(|errors| {
+            let messages: Vec<String> = errors
+                .into_iter()
+                .flat_map(|(_, error)| match error {
+                    Error::Io(errors) => errors.into_iter().map(|(_, e)| e).collect(),
+                    Error::Lex(errors) => errors
+                        .into_iter()
+                        .map(|(expect, _)| format!("expected {}", expect.as_str()))
+                        .collect(),
+                    Error::Parse(errors) => errors
+                        .into_iter()
+                        .map(|(expect, found)| match found.trim() {
+                            "" => format!("expected {}", expect.as_str()),
+                            found => format!("expected {} before '{}'", expect.as_str(), found),
+                        })
+                        .collect::<Vec<_>>(),
+                })
+                .collect();
+            anyhow::anyhow!("Invalid jq expression: {}", messages.join("; "))
+        })?;
+    jaq_core::Compiler::default()
+        .with_funs(funs)
+        .compile(modules)
+        .map_err(|errors| {
+            let names: Vec<&str> = errors
+                .iter()
+                .flat_map(|(_, undefined)| undefined.iter().map(|(name, _)| *name))
+                .collect();
+            anyhow::anyhow!("Invalid jq expression: undefined {}", names.join(", "))
+        })
+}
+
+// Clap value parser for --jq, so a broken filter fails before the request
+pub fn parse_jq_arg(input: &str) -> Result<String> {
+    compile_jq(input)?;
+    Ok(input.to_string())
+}
+
+// Every output of the filter for a JSON body; outputs are produced lazily, so
+// `first(range(1e12))` stops after one value
+pub fn run_jq(code: &str, body: &str) -> Result<Vec<jaq_json::Val>> {
+    let filter = compile_jq(code)?;
+    let input = jaq_json::read::parse_single(body.as_bytes())
+        .map_err(|e| anyhow::anyhow!("--jq needs a JSON response body: {}", e))?;
+    let ctx = jaq_core::Ctx::<jaq_core::data::JustLut<jaq_json::Val>>::new(
+        &filter.lut,
+        jaq_core::Vars::new([]),
+    );
+    filter
+        .id
+        .run((ctx, input))
+        .map(jaq_core::unwrap_valr)
+        .map(|value| value.map_err(|e| anyhow::anyhow!("jq: error: {}", e)))
+        .collect()
+}
+
+// XML utilities
+pub fn prettify_xml(xml: &str) -> Result<String> {
+    use quick_xml::Reader;
+    use quick_xml::Writer;
+    use quick_xml::events::Event;
+
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    let mut depth: usize = 0;
+
+    loop {
+        match reader.read_event()? {
+            Event::Eof => break,
+            event => {
+                match &event {
+                    Event::Start(_) => depth += 1,
+                    Event::End(_) => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                writer.write_event(event)?;
+            }
+        }
+    }
+
+    if depth != 0 {
+        return Err(anyhow::anyhow!("Unclosed XML element"));
+    }
+
+    Ok(String::from_utf8(writer.into_inner())?)
+}
+
 // HTTP utilities
 pub fn parse_content_type(
     content_type: &str,
@@ -144,6 +579,12 @@ pub fn parse_content_type(
     (media_type, parameters)
 }
 
+pub fn parse_form_urlencoded(body: &str) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(body.trim().as_bytes())
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect()
+}
+
 pub fn guess_content_type(body: &str) -> &'static str {
     let trimmed = body.trim();
 
@@ -164,6 +605,17 @@ pub fn guess_content_type(body: &str) -> &'static str {
     "text/plain"
 }
 
+// The Accept header asking a server for what `--output` will render
+pub fn accept_for_format(format: &str) -> Option<&'static str> {
+    match format {
+        "json" | "table" => Some("application/json"),
+        "yaml" => Some("application/yaml, application/x-yaml;q=0.9, text/yaml;q=0.8"),
+        "xml" => Some("application/xml, text/xml;q=0.9"),
+        "form" => Some("application/x-www-form-urlencoded"),
+        _ => None,
+    }
+}
+
 // File utilities
 pub fn get_file_extension(filename: &str) -> Option<&str> {
     std::path::Path::new(filename)
@@ -175,6 +627,51 @@ pub fn read_file_to_string(path: &std::path::Path) -> Result<String> {
     std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("Failed to read file: {}", e))
 }
 
+// Writes via a temp file in the same directory and renames it into place, so an
+// interrupted write never leaves a truncated file behind
+pub async fn write_atomic(path: &std::path::Path, contents: &[u8]) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow::anyhow!("Invalid file path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp-{}", file_name, uuid::Uuid::new_v4()));
+
+    let result = async {
+        let mut file = tokio::fs::File::create(&temp_path).await?;
+        file.write_all(contents).await?;
+        file.sync_all().await?;
+        tokio::fs::rename(&temp_path, path).await?;
+        Ok(())
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+    }
+    result
+}
+
+// Renames a file that failed to parse so it isn't overwritten, and returns the new path
+pub fn move_aside_corrupt_file(path: &std::path::Path) -> Result<std::path::PathBuf> {
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let backup = path.with_file_name(format!("{}.corrupt-{}", file_name, timestamp));
+
+    std::fs::rename(path, &backup).map_err(|e| {
+        anyhow::anyhow!(
+            "{} could not be parsed and could not be backed up: {}",
+            path.display(),
+            e
+        )
+    })?;
+    Ok(backup)
+}
+
 // Environment variable utilities
 pub fn get_env_or_default(key: &str, default: &str) -> String {
     std::env::var(key).unwrap_or_else(|_| default.to_string())
@@ -226,6 +723,41 @@ pub fn is_valid_header_value(value: &str) -> bool {
     !value.chars().any(|c| c == '\n' || c == '\r')
 }
 
+// Parses a `key:value` header argument as given to `-H`
+pub fn parse_header(header: &str) -> Result<(String, String)> {
+    let Some((key, value)) = header.split_once(':') else {
+        return Err(anyhow::anyhow!(
+            "Invalid header format: '{}'. Use 'key:value'",
+            header
+        ));
+    };
+
+    let key = key.trim();
+    let value = value.trim();
+    if key.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Invalid header format: '{}'. Use 'key:value'",
+            header
+        ));
+    }
+
+    // Validate header name and value
+    if !is_valid_header_name(key) {
+        return Err(anyhow::anyhow!(
+            "Invalid header name: '{}'. Header names must be ASCII and cannot contain ':' or newlines",
+            key
+        ));
+    }
+    if !is_valid_header_value(value) {
+        return Err(anyhow::anyhow!(
+            "Invalid header value: '{}'. Header values cannot contain newlines",
+            value
+        ));
+    }
+
+    Ok((key.to_string(), value.to_string()))
+}
+
 // Security utilities
 pub fn mask_sensitive_data(text: &str, patterns: &[&str]) -> String {
     let mut result = text.to_string();
@@ -252,17 +784,73 @@ pub fn mask_sensitive_data(text: &str, patterns: &[&str]) -> String {
     result
 }
 
+const SENSITIVE_HEADER_PATTERNS: &[&str] = &[
+    r"(?i)authorization",
+    r"(?i)api-key",
+    r"(?i)x-api-key",
+    r"(?i)access[_-]?token",
+    r"(?i)bearer",
+    r"(?i)session",
+    r"(?i)cookie",
+    r"(?i)password",
+    r"(?i)secret",
+];
+
+const SENSITIVE_BODY_PATTERNS: &[&str] = &[
+    r#""password"\s*:\s*"[^"]*""#,
+    r#""token"\s*:\s*"[^"]*""#,
+    r#""secret"\s*:\s*"[^"]*""#,
+    r#""api_key"\s*:\s*"[^"]*""#,
+    r#""access_token"\s*:\s*"[^"]*""#,
+];
+
+pub fn is_sensitive_header(name: &str) -> bool {
+    SENSITIVE_HEADER_PATTERNS.iter().any(|pattern| {
+        regex::Regex::new(pattern)
+            .map(|re| re.is_match(name))
+            .unwrap_or(false)
+    })
+}
+
+/// Masks the value when the header name looks like it carries a credential.
+pub fn mask_header_value(name: &str, value: &str) -> String {
+    if is_sensitive_header(name) {
+        mask_sensitive_data(value, &[r".*"])
+    } else {
+        value.to_string()
+    }
+}
+
+/// Masks an environment variable whose name suggests a credential, such as
+/// `token`, `api_key` or `password`.
+pub fn mask_variable_value(name: &str, value: &str) -> String {
+    let lower = name.to_ascii_lowercase();
+    if is_sensitive_header(name) || lower.contains("token") || lower.contains("key") {
+        mask_sensitive_data(value, &[r".*"])
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn mask_body(body: &str) -> String {
+    mask_sensitive_data(body, SENSITIVE_BODY_PATTERNS)
+}
+
 pub fn generate_request_id() -> String {
     uuid::Uuid::new_v4().simple().to_string()
 }
 
 // Collection utilities
+/// Header names compare case-insensitively, so `user-agent` overrides `User-Agent`
 pub fn merge_headers(
     base: &std::collections::HashMap<String, String>,
     override_headers: &std::collections::HashMap<String, String>,
 ) -> std::collections::HashMap<String, String> {
     let mut result = base.clone();
-    result.extend(override_headers.clone());
+    for (name, value) in override_headers {
+        result.retain(|existing, _| !existing.eq_ignore_ascii_case(name));
+        result.insert(name.clone(), value.clone());
+    }
     result
 }
 
@@ -320,6 +908,49 @@ pub fn format_error_chain(error: &anyhow::Error) -> String {
     messages.join(" → ")
 }
 
+/// A likely fix for common network failures, or None when there is nothing useful to add.
+pub fn diagnose_error(error: &reqwest::Error) -> Option<String> {
+    // reqwest only exposes broad kinds, so the underlying causes tell DNS, refusal and TLS apart
+    let mut causes = Vec::new();
+    let mut refused = false;
+    let mut source = std::error::Error::source(error);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            refused |= io.kind() == std::io::ErrorKind::ConnectionRefused;
+        }
+        causes.push(err.to_string().to_lowercase());
+        source = err.source();
+    }
+    let mentions = |needles: &[&str]| {
+        causes
+            .iter()
+            .any(|cause| needles.iter().any(|needle| cause.contains(needle)))
+    };
+
+    let hint = if error.is_timeout() {
+        "The server took too long to answer; increase the timeout with -t/--timeout"
+    } else if error.is_redirect() {
+        "Too many redirects; check for a redirect loop or raise --max-redirects or network.max_redirects"
+    } else if mentions(&[
+        "dns error",
+        "failed to lookup address",
+        "name or service not known",
+    ]) {
+        "The host name could not be resolved; check the hostname in the URL and your DNS or VPN"
+    } else if refused || mentions(&["connection refused"]) {
+        "Nothing accepted the connection; is the server running, and is the port correct?"
+    } else if mentions(&["certificate", "tls", "ssl", "handshake"]) {
+        "The TLS handshake failed; check the server certificate, add its CA with --cacert, or use -k to skip verification"
+    } else if error.is_connect() {
+        "Could not connect; check the URL, your network and any proxy settings (--proxy, --no-proxy)"
+    } else if error.is_builder() {
+        "The request could not be built; check the URL and headers"
+    } else {
+        return None;
+    };
+    Some(hint.to_string())
+}
+
 // Performance utilities
 pub struct Timer {
     start: std::time::Instant,
@@ -448,12 +1079,13 @@ impl ColorScheme {
 }
 
 // Progress utilities
+// Counts bytes, for transfers
 pub fn create_progress_bar(len: u64) -> indicatif::ProgressBar {
     let pb = indicatif::ProgressBar::new(len);
     pb.set_style(
         indicatif::ProgressStyle::default_bar()
             .template(
-                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos:>7}/{len:7} {msg}",
+                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes:>9}/{total_bytes:9} {msg}",
             )
             .unwrap()
             .progress_chars("█▉▊▋▌▍▎▏  "),
@@ -473,11 +1105,59 @@ pub fn fuzzy_match(pattern: &str, text: &str) -> Option<f64> {
     if text.contains(&pattern) {
         // Simple scoring: longer matches score higher
         Some(pattern.len() as f64 / text.len() as f64)
+    } else if subsequence_positions(&pattern, &text).is_some() {
+        // Letters in order with gaps, e.g. "usrlst" in "users-list", tolerates typos and abbreviations
+        Some(pattern.len() as f64 / text.len() as f64 / 2.0)
     } else {
         None
     }
 }
 
+// Char indices in `text` where the characters of `pattern` appear in order
+fn subsequence_positions(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let mut positions = Vec::new();
+    let mut text_chars = text.chars().enumerate();
+    for wanted in pattern.chars() {
+        let (index, _) = text_chars.find(|(_, c)| *c == wanted)?;
+        positions.push(index);
+    }
+    Some(positions)
+}
+
+/// Highlights where `pattern` matches `text`: the substring if there is one,
+/// otherwise the individual letters of a fuzzy match.
+pub fn highlight_match(text: &str, pattern: &str) -> String {
+    use colored::Colorize;
+
+    let lower_text = text.to_lowercase();
+    let lower_pattern = pattern.to_lowercase();
+    if pattern.is_empty() || lower_text.chars().count() != text.chars().count() {
+        return text.to_string();
+    }
+
+    let marked: Vec<usize> = match lower_text.find(&lower_pattern) {
+        Some(start) => {
+            let first = lower_text[..start].chars().count();
+            (first..first + lower_pattern.chars().count()).collect()
+        }
+        None => match subsequence_positions(&lower_pattern, &lower_text) {
+            Some(positions) => positions,
+            None => return text.to_string(),
+        },
+    };
+
+    text.chars()
+        .enumerate()
+        .map(|(index, c)| {
+            if marked.contains(&index) {
+                c.to_string().bright_yellow().bold().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
 pub fn fuzzy_sort<T>(items: &mut [(f64, T)]) {
     items.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
 }
@@ -667,6 +1347,12 @@ pub mod test_utils {
             size: 26,
             url: "https://api.example.com/test".to_string(),
             method: "GET".to_string(),
+            from_cache: false,
+            request_id: None,
+            wire_request: None,
+            transfer: Default::default(),
+            set_cookies: Vec::new(),
+            redirects: Vec::new(),
         }
     }
 }
@@ -675,6 +1361,56 @@ pub mod test_utils {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_jq_filters() {
+        let run = |filter: &str, body: &str| -> Vec<String> {
+            run_jq(filter, body)
+                .unwrap()
+                .iter()
+                .map(|v| v.to_string())
+                .collect()
+        };
+        let body = r#"{"items": [{"id": 1, "name": "b"}, {"id": 2, "name": "a"}], "next": null}"#;
+        assert_eq!(run(".items | map(.id)", body), vec!["[1,2]"]);
+        assert_eq!(run(".items[].name", body), vec![r#""b""#, r#""a""#]);
+        assert_eq!(run(".next // \"none\"", body), vec![r#""none""#]);
+        assert_eq!(
+            run(".items | sort_by(.name) | map(.name) | join(\",\")", body),
+            vec![r#""a,b""#]
+        );
+        assert_eq!(run(".items as $x | $x | length", body), vec!["2"]);
+        // Lazy: neither of these builds the whole range
+        assert_eq!(run("first(range(1e16; 1e17))", "null").len(), 1);
+        assert_eq!(run("[limit(2; range(1e12))]", "null"), vec!["[0,1]"]);
+
+        let error = parse_jq_arg(".items | map(").unwrap_err().to_string();
+        assert!(error.contains("Invalid jq expression"), "{}", error);
+        assert!(parse_jq_arg("nosuchfn").is_err());
+        assert!(run_jq(".items.name", body).is_err());
+        assert!(run_jq(".", "not json").is_err());
+    }
+
+    #[test]
+    fn test_append_query() {
+        let pairs = vec!["q=a%20b".to_string(), "page=2".to_string()];
+        assert_eq!(
+            append_query("https://example.com/search", &pairs),
+            "https://example.com/search?q=a%20b&page=2"
+        );
+        assert_eq!(
+            append_query("https://example.com/search?lang=en#top", &pairs),
+            "https://example.com/search?lang=en&q=a%20b&page=2#top"
+        );
+        assert_eq!(
+            append_query("https://example.com/search?", &pairs),
+            "https://example.com/search?q=a%20b&page=2"
+        );
+        assert_eq!(
+            append_query("https://example.com/", &[]),
+            "https://example.com/"
+        );
+    }
+
     #[test]
     fn test_normalize_url() {
         assert_eq!(
@@ -685,6 +1421,148 @@ mod tests {
             normalize_url("http://example.com:80/path").unwrap(),
             "http://example.com/path"
         );
+        assert_eq!(
+            normalize_url("https://api.example.com/search?q=hello world").unwrap(),
+            "https://api.example.com/search?q=hello%20world"
+        );
+        assert_eq!(
+            normalize_url("https://example.com/files/my doc|v2.txt").unwrap(),
+            "https://example.com/files/my%20doc%7Cv2.txt"
+        );
+        assert_eq!(
+            normalize_url("https://example.com/café?city=Zürich&tag=a^b").unwrap(),
+            "https://example.com/caf%C3%A9?city=Z%C3%BCrich&tag=a%5Eb"
+        );
+        // Existing escapes are kept; a stray % is escaped itself
+        assert_eq!(
+            normalize_url("https://example.com/a%20b?q=100%&r=%2F").unwrap(),
+            "https://example.com/a%20b?q=100%25&r=%2F"
+        );
+        assert_eq!(
+            normalize_url("https://example.com/users/{{id}}?q=a b#top").unwrap(),
+            "https://example.com/users/{{id}}?q=a%20b#top"
+        );
+    }
+
+    #[test]
+    fn test_diagnose_error() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        // Port 9 (discard) is closed on any machine running the tests
+        let error = runtime
+            .block_on(reqwest::get("http://127.0.0.1:9/"))
+            .unwrap_err();
+        assert!(
+            diagnose_error(&error)
+                .unwrap()
+                .contains("is the server running")
+        );
+
+        let error = runtime.block_on(reqwest::get("http://[::1")).unwrap_err();
+        assert!(
+            diagnose_error(&error)
+                .unwrap()
+                .contains("could not be built")
+        );
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("64KB").unwrap(), 64 * 1024);
+        assert_eq!(parse_size("1.5mb").unwrap(), 1024 * 1024 * 3 / 2);
+        assert_eq!(parse_size("2 GB").unwrap(), 2 * 1024 * 1024 * 1024);
+        assert!(parse_size("10 parsecs").is_err());
+        assert!(parse_size("big").is_err());
+    }
+
+    #[test]
+    fn test_parse_resolve() {
+        let (host, addr) = parse_resolve("API.example.com:443:10.0.0.5").unwrap();
+        assert_eq!(host, "api.example.com");
+        assert_eq!(addr.to_string(), "10.0.0.5:443");
+        let (_, addr) = parse_resolve("example.com:8080:[::1]").unwrap();
+        assert_eq!(addr.to_string(), "[::1]:8080");
+        assert!(parse_resolve("example.com:10.0.0.5").is_err());
+        assert!(parse_resolve("example.com:443:not-an-ip").is_err());
+    }
+
+    #[test]
+    fn test_parse_connect_to() {
+        let entry = parse_connect_to("API.example.com:443:backend-2.internal:8443").unwrap();
+        assert_eq!(entry.host, "api.example.com");
+        assert_eq!(entry.port, 443);
+        assert_eq!(entry.target_host, "backend-2.internal");
+        assert_eq!(entry.target_port, 8443);
+        let entry = parse_connect_to("example.com:80:[::1]:8080").unwrap();
+        assert_eq!(entry.target_host, "::1");
+        assert_eq!(entry.target_port, 8080);
+        assert!(parse_connect_to("example.com:443:backend").is_err());
+        assert!(parse_connect_to("example.com:https:backend:443").is_err());
+        assert!(parse_connect_to(":443:backend:443").is_err());
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("5s").unwrap(), Duration::from_secs(5));
+        assert_eq!(parse_interval("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_interval("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_interval("10").unwrap(), Duration::from_secs(10));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("5 days").is_err());
+        assert!(parse_interval("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date("2024-01-31").unwrap().to_rfc3339(),
+            "2024-01-31T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_date("2024-01-31T12:00:00+02:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2024-01-31T10:00:00+00:00"
+        );
+        assert!(parse_date("31/01/2024").is_err());
+    }
+
+    #[test]
+    fn test_prettify_json_with_indent() {
+        let json = r#"{"a":[1]}"#;
+        assert_eq!(prettify_json(json).unwrap(), "{\n  \"a\": [\n    1\n  ]\n}");
+        assert_eq!(
+            prettify_json_with_indent(json, 4).unwrap(),
+            "{\n    \"a\": [\n        1\n    ]\n}"
+        );
+        assert_eq!(
+            prettify_json_with_indent(json, 0).unwrap(),
+            "{\n\"a\": [\n1\n]\n}"
+        );
+    }
+
+    #[test]
+    fn test_prettify_json_keeps_key_order() {
+        let json = r#"{"zebra":1,"apple":{"mango":2,"banana":3}}"#;
+        assert_eq!(minify_json(json).unwrap(), json);
+        let pretty = prettify_json(json).unwrap();
+        assert!(pretty.find("zebra").unwrap() < pretty.find("apple").unwrap());
+        assert!(pretty.find("mango").unwrap() < pretty.find("banana").unwrap());
+    }
+
+    #[test]
+    fn test_merge_headers() {
+        let base = std::collections::HashMap::from([
+            ("User-Agent".to_string(), "team/1.0".to_string()),
+            ("X-Trace".to_string(), "on".to_string()),
+        ]);
+        let overrides =
+            std::collections::HashMap::from([("user-agent".to_string(), "me".to_string())]);
+
+        let merged = merge_headers(&base, &overrides);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged["user-agent"], "me");
+        assert_eq!(merged["X-Trace"], "on");
     }
 
     #[test]
@@ -701,6 +1579,30 @@ mod tests {
         assert_eq!(format_bytes(1_500_000), "1.4 MB");
     }
 
+    #[test]
+    fn test_large_numbers_survive_formatting() {
+        let json = r#"{"id":12345678901234567890123,"big":18446744073709551615,"price":1.10}"#;
+        let pretty = prettify_json(json).unwrap();
+        assert!(
+            pretty.contains("\"id\": 12345678901234567890123"),
+            "{}",
+            pretty
+        );
+        assert!(
+            pretty.contains("\"big\": 18446744073709551615"),
+            "{}",
+            pretty
+        );
+        assert!(pretty.contains("\"price\": 1.10"), "{}", pretty);
+        assert_eq!(minify_json(json).unwrap(), json);
+
+        let yaml = json_to_yaml(&serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            yaml,
+            "id: '12345678901234567890123'\nbig: 18446744073709551615\nprice: 1.1\n"
+        );
+    }
+
     #[test]
     fn test_is_valid_json() {
         assert!(is_valid_json(r#"{"test": true}"#));
@@ -708,6 +1610,55 @@ mod tests {
         assert!(!is_valid_json("not json"));
     }
 
+    #[test]
+    fn test_json_path() {
+        let value: serde_json::Value = serde_json::from_str(
+            r#"{"data": {"items": [{"id": 1}, {"id": 2}], "first name": "Ada"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            json_path(&value, "$.data.items[0].id"),
+            Some(&serde_json::json!(1))
+        );
+        assert_eq!(
+            json_path(&value, "$.data.items[-1].id"),
+            Some(&serde_json::json!(2))
+        );
+        assert_eq!(
+            json_path(&value, "$.data['first name']"),
+            Some(&serde_json::json!("Ada"))
+        );
+        assert_eq!(json_path(&value, "$"), Some(&value));
+        assert!(json_path(&value, "$.data.items[5]").is_none());
+        assert!(json_path(&value, "$.missing").is_none());
+        assert!(parse_json_path("data.items").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_atomic_replaces_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("data.json");
+        std::fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"new").await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_prettify_xml() {
+        let pretty =
+            prettify_xml(r#"<feed><entry id="1"><title>Hi</title></entry></feed>"#).unwrap();
+        assert_eq!(
+            pretty,
+            "<feed>\n  <entry id=\"1\">\n    <title>Hi</title>\n  </entry>\n</feed>"
+        );
+        assert!(prettify_xml("<feed><entry></feed>").is_err());
+        assert!(prettify_xml("<feed>").is_err());
+    }
+
     #[test]
     fn test_guess_content_type() {
         assert_eq!(guess_content_type(r#"{"test": true}"#), "application/json");
@@ -719,6 +1670,18 @@ mod tests {
         assert_eq!(guess_content_type("plain text"), "text/plain");
     }
 
+    #[test]
+    fn test_accept_for_format() {
+        assert_eq!(accept_for_format("json"), Some("application/json"));
+        assert!(
+            accept_for_format("yaml")
+                .unwrap()
+                .starts_with("application/yaml")
+        );
+        assert_eq!(accept_for_format("auto"), None);
+        assert_eq!(accept_for_format("raw"), None);
+    }
+
     #[test]
     fn test_extract_template_variables() {
         let text = "Hello {{name}}, your {{item}} is ready!";
@@ -731,5 +1694,9 @@ mod tests {
         assert!(fuzzy_match("test", "testing").is_some());
         assert!(fuzzy_match("xyz", "testing").is_none());
         assert_eq!(fuzzy_match("", "anything"), Some(1.0));
+        // Letters in order still match, but below a substring match
+        let fuzzy = fuzzy_match("usrlst", "users-list").unwrap();
+        assert!(fuzzy < fuzzy_match("users", "users-list").unwrap());
+        assert!(fuzzy_match("tsl", "list").is_none());
     }
 }
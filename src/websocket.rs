@@ -0,0 +1,212 @@
+use anyhow::{Result, anyhow};
+use chrono::Local;
+use colored::*;
+use futures::{SinkExt, StreamExt};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+
+// Counters for a finished session, recorded in history
+#[derive(Debug, Clone, Default)]
+pub struct SessionSummary {
+    pub connected: bool,
+    pub messages_sent: usize,
+    pub messages_received: usize,
+    pub bytes_sent: usize,
+    pub bytes_received: usize,
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+pub struct WebSocketSession {
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl WebSocketSession {
+    pub fn new(url: &str, headers: Vec<(String, String)>) -> Result<Self> {
+        let parsed = url::Url::parse(url).map_err(|_| anyhow!("Invalid URL: {}", url))?;
+        if !matches!(parsed.scheme(), "ws" | "wss") {
+            return Err(anyhow!(
+                "Invalid WebSocket URL: {}. Please provide a URL starting with ws:// or wss://",
+                url
+            ));
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            headers,
+        })
+    }
+
+    /// Sends a single message and prints replies until the first data frame or the timeout.
+    pub async fn send_once(&self, message: &str, wait: Duration) -> SessionSummary {
+        let start = Instant::now();
+        let mut summary = SessionSummary::default();
+
+        let result = async {
+            let (mut stream, _) = tokio_tungstenite::connect_async(self.handshake()?).await?;
+            summary.connected = true;
+            print_connected(&self.url);
+
+            stream.send(Message::text(message)).await?;
+            record_sent(&mut summary, message);
+
+            let deadline = tokio::time::sleep(wait);
+            tokio::pin!(deadline);
+            loop {
+                tokio::select! {
+                    frame = stream.next() => match frame {
+                        Some(frame) => {
+                            let frame = frame?;
+                            let is_data = matches!(frame, Message::Text(_) | Message::Binary(_));
+                            if !record_received(&mut summary, &frame) || is_data {
+                                break;
+                            }
+                        }
+                        None => break,
+                    },
+                    _ = &mut deadline => {
+                        print_notice(&format!("No reply within {}s", wait.as_secs()));
+                        break;
+                    }
+                }
+            }
+
+            stream.close(None).await.ok();
+            Ok::<(), anyhow::Error>(())
+        }
+        .await;
+
+        finish(summary, start, result)
+    }
+
+    /// Sends each line typed on stdin as a text frame until EOF or Ctrl-C.
+    pub async fn run_interactive(&self) -> SessionSummary {
+        let start = Instant::now();
+        let mut summary = SessionSummary::default();
+
+        let result = async {
+            let (mut stream, _) = tokio_tungstenite::connect_async(self.handshake()?).await?;
+            summary.connected = true;
+            print_connected(&self.url);
+            print_notice("Type a message and press Enter to send. Ctrl-D or Ctrl-C to close.");
+
+            let mut lines = BufReader::new(tokio::io::stdin()).lines();
+            loop {
+                tokio::select! {
+                    line = lines.next_line() => match line? {
+                        Some(line) if line.is_empty() => continue,
+                        Some(line) => {
+                            stream.send(Message::text(line.clone())).await?;
+                            record_sent(&mut summary, &line);
+                        }
+                        None => break,
+                    },
+                    frame = stream.next() => match frame {
+                        Some(frame) => {
+                            if !record_received(&mut summary, &frame?) {
+                                return Ok(());
+                            }
+                        }
+                        None => return Ok(()),
+                    },
+                    _ = tokio::signal::ctrl_c() => break,
+                }
+            }
+
+            stream.close(None).await.ok();
+            Ok::<(), anyhow::Error>(())
+        }
+        .await;
+
+        finish(summary, start, result)
+    }
+
+    fn handshake(&self) -> Result<tokio_tungstenite::tungstenite::handshake::client::Request> {
+        let mut request = self.url.as_str().into_client_request()?;
+        for (key, value) in &self.headers {
+            request.headers_mut().insert(
+                HeaderName::from_bytes(key.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+        Ok(request)
+    }
+}
+
+fn finish(mut summary: SessionSummary, start: Instant, result: Result<()>) -> SessionSummary {
+    summary.duration = start.elapsed();
+    if let Err(e) = result {
+        summary.error = Some(crate::utils::format_error_chain(&e));
+    }
+    summary
+}
+
+fn timestamp() -> ColoredString {
+    format!("[{}]", Local::now().format("%H:%M:%S%.3f")).bright_black()
+}
+
+fn print_connected(url: &str) {
+    println!(
+        "{} {} {}",
+        timestamp(),
+        "🔌 Connected to".bright_green(),
+        url.bright_cyan().underline()
+    );
+}
+
+fn print_notice(message: &str) {
+    println!("{} {}", timestamp(), message.bright_black().italic());
+}
+
+fn record_sent(summary: &mut SessionSummary, message: &str) {
+    summary.messages_sent += 1;
+    summary.bytes_sent += message.len();
+    println!("{} {} {}", timestamp(), "→".bright_blue().bold(), message);
+}
+
+/// Prints an incoming frame and returns whether the connection is still open.
+fn record_received(summary: &mut SessionSummary, frame: &Message) -> bool {
+    match frame {
+        Message::Text(text) => {
+            summary.messages_received += 1;
+            summary.bytes_received += text.len();
+            println!("{} {} {}", timestamp(), "←".bright_green().bold(), text);
+        }
+        Message::Binary(data) => {
+            summary.messages_received += 1;
+            summary.bytes_received += data.len();
+            println!(
+                "{} {} {}",
+                timestamp(),
+                "←".bright_green().bold(),
+                format!("<binary {}>", crate::utils::format_bytes(data.len())).bright_magenta()
+            );
+        }
+        Message::Close(frame) => {
+            let detail = frame
+                .as_ref()
+                .map(|f| format!(" ({} {})", u16::from(f.code), f.reason))
+                .unwrap_or_default();
+            print_notice(&format!("Connection closed by server{}", detail));
+            return false;
+        }
+        // Pings are answered automatically
+        Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => {}
+    }
+    true
+}
+
+pub fn print_summary(summary: &SessionSummary) {
+    println!();
+    println!(
+        "{} {} sent, {} received in {}",
+        "Session:".bright_yellow().bold(),
+        summary.messages_sent.to_string().bright_blue(),
+        summary.messages_received.to_string().bright_green(),
+        crate::utils::format_duration(summary.duration)
+    );
+}
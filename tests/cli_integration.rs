@@ -5,6 +5,15 @@ use tempfile::TempDir;
 
 const HTTPBIN_URL: &str = "https://httpbin.org";
 
+// A local URL with nothing listening: the port was just handed out and freed again
+fn closed_port_url() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    format!(
+        "http://127.0.0.1:{}/",
+        listener.local_addr().unwrap().port()
+    )
+}
+
 #[test]
 fn test_cli_help() {
     let mut cmd = Command::cargo_bin("terzi").unwrap();
@@ -302,3 +311,4782 @@ fn test_invalid_json() {
         .failure()
         .stderr(predicate::str::contains("Invalid JSON").or(predicate::str::contains("error")));
 }
+
+#[tokio::test]
+async fn test_notify_rings_bell_on_completion() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("done"))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/slow", server.uri());
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["--notify", &url])
+            .env_remove("DBUS_SESSION_BUS_ADDRESS")
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stderr.contains(&0x07));
+}
+
+#[tokio::test]
+async fn test_timeout_covers_slow_responses() {
+    use std::time::Duration;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/stall"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("late")
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/stall", server.uri());
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-t", "1", "--connect-timeout", "2", &url])
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("timed out after 1s"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_invalid_connect_timeout_is_rejected() {
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["--connect-timeout", "0", "http://localhost:1/"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Timeout"));
+}
+
+#[tokio::test]
+async fn test_proxy_flag_routes_request() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // The mock server stands in for the proxy; the target host does not exist
+    let proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("via proxy"))
+        .mount(&proxy)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let proxy_url = proxy.uri();
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args([
+                "--proxy",
+                &proxy_url,
+                "http://terzi-proxy-test.invalid/ping",
+            ])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("via proxy"));
+}
+
+#[test]
+fn test_proxy_and_no_proxy_conflict() {
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["--proxy", "socks5://127.0.0.1:1080", "--no-proxy"])
+        .arg("https://example.com")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[tokio::test]
+async fn test_request_log_appends_masked_records() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let log_path = dir.join("audit.jsonl");
+    let log = log_path.clone();
+    tokio::task::spawn_blocking(move || {
+        for _ in 0..2 {
+            Command::cargo_bin("terzi")
+                .unwrap()
+                .args(["--log", log.to_str().unwrap()])
+                .args(["-H", "Authorization:Bearer secret-token", &url])
+                .env("TERZI_CONFIG_DIR", &dir)
+                .assert()
+                .success();
+        }
+    })
+    .await
+    .unwrap();
+
+    let contents = std::fs::read_to_string(log_path).unwrap();
+    let records: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["status"], 200);
+    assert_eq!(records[0]["response_body"], "ok");
+    assert!(!contents.contains("secret-token"));
+}
+
+#[tokio::test]
+async fn test_redact_on_save_uses_env_placeholders() {
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(header("Authorization", "Bearer secret-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let (with_env, without_env) = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["--redact-on-save", "--save", "api"])
+            .args(["-H", "Authorization:Bearer secret-token", &url])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("TERZI_AUTHORIZATION"));
+
+        let load = |token: Option<&str>| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.args(["--load", "api"])
+                .env("TERZI_CONFIG_DIR", &dir)
+                .env_remove("TERZI_AUTHORIZATION");
+            if let Some(token) = token {
+                cmd.env("TERZI_AUTHORIZATION", token);
+            }
+            cmd.output().unwrap()
+        };
+        (load(Some("Bearer secret-token")), load(None))
+    })
+    .await
+    .unwrap();
+
+    let data = std::fs::read_to_string(config_dir.path().join("data.json")).unwrap();
+    assert!(!data.contains("secret-token"));
+    assert!(data.contains("${TERZI_AUTHORIZATION}"));
+
+    assert!(with_env.status.success());
+    assert!(String::from_utf8_lossy(&with_env.stdout).contains("ok"));
+    assert!(!without_env.status.success());
+    assert!(String::from_utf8_lossy(&without_env.stderr).contains("TERZI_AUTHORIZATION"));
+}
+
+#[tokio::test]
+async fn test_insecure_flag_warns() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-k", &url])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("verification is disabled"));
+    // The flag is a one-off and must not end up in the saved config
+    let config = std::fs::read_to_string(config_dir.path().join("config.toml")).unwrap_or_default();
+    assert!(!config.contains("verify_ssl = false"));
+}
+
+#[test]
+fn test_unreadable_client_certificate_is_reported() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing.pem");
+    let garbage = dir.path().join("garbage.pem");
+    std::fs::write(&garbage, "not a certificate").unwrap();
+
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["--cert", missing.to_str().unwrap(), "http://localhost:1/"])
+        .env("TERZI_CONFIG_DIR", dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Failed to read client certificate",
+    ));
+
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["--cert", garbage.to_str().unwrap(), "http://localhost:1/"])
+        .env("TERZI_CONFIG_DIR", dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid client certificate"));
+}
+
+#[test]
+fn test_ca_bundle_without_certificates_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let bundle = dir.path().join("ca.pem");
+    std::fs::write(&bundle, "no certificates here\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["--cacert", bundle.to_str().unwrap(), "http://localhost:1/"])
+        .env("TERZI_CONFIG_DIR", dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No PEM certificates found"));
+}
+
+#[tokio::test]
+async fn test_json_indent_and_compact() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(r#"{"outer": {"inner": 1}}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let (indented, compact) = tokio::task::spawn_blocking(move || {
+        let terzi = |flags: &[&str]| {
+            let output = Command::cargo_bin("terzi")
+                .unwrap()
+                .args(flags)
+                .arg(&url)
+                .env("TERZI_CONFIG_DIR", &dir)
+                .output()
+                .unwrap();
+            // Drop the syntax highlighting escapes
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            regex::Regex::new("\x1b\\[[0-9;]*m")
+                .unwrap()
+                .replace_all(&stdout, "")
+                .to_string()
+        };
+        (terzi(&["--json-indent", "4"]), terzi(&["--compact"]))
+    })
+    .await
+    .unwrap();
+
+    assert!(indented.contains("\n        \"inner\": 1"), "{}", indented);
+    assert!(compact.contains(r#"{"outer":{"inner":1}}"#), "{}", compact);
+}
+
+#[tokio::test]
+async fn test_pretty_print_config_and_flags() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(r#"{"outer": {"inner": 1}}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let (from_config, forced_pretty) = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["config", "set", "output.pretty_print", "false"])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+
+        let terzi = |flags: &[&str]| {
+            let output = Command::cargo_bin("terzi")
+                .unwrap()
+                .args(flags)
+                .arg(&url)
+                .env("TERZI_CONFIG_DIR", &dir)
+                .output()
+                .unwrap();
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            regex::Regex::new("\x1b\\[[0-9;]*m")
+                .unwrap()
+                .replace_all(&stdout, "")
+                .to_string()
+        };
+        (terzi(&[]), terzi(&["--pretty"]))
+    })
+    .await
+    .unwrap();
+
+    assert!(
+        from_config.contains(r#"{"outer":{"inner":1}}"#),
+        "{}",
+        from_config
+    );
+    assert!(
+        forced_pretty.contains("\n    \"inner\": 1"),
+        "{}",
+        forced_pretty
+    );
+}
+
+#[tokio::test]
+async fn test_table_flatten_shows_nested_columns() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            r#"[{"id": 1, "user": {"address": {"city": "London", "geo": {"lat": 51}}}},
+                {"id": 2, "extra": true}]"#,
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let (placeholder, flattened, shallow) = tokio::task::spawn_blocking(move || {
+        let terzi = |flags: &[&str]| {
+            let output = Command::cargo_bin("terzi")
+                .unwrap()
+                .args(["-o", "table"])
+                .args(flags)
+                .arg(&url)
+                .env("TERZI_CONFIG_DIR", &dir)
+                .env("COLUMNS", "200")
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).to_string()
+        };
+        (
+            terzi(&[]),
+            terzi(&["--flatten"]),
+            terzi(&["--flatten-depth", "1"]),
+        )
+    })
+    .await
+    .unwrap();
+
+    assert!(placeholder.contains("{Object}"), "{}", placeholder);
+    assert!(flattened.contains("user.address.city"), "{}", flattened);
+    assert!(flattened.contains("London"), "{}", flattened);
+    assert!(flattened.contains("user.address.geo"), "{}", flattened);
+    assert!(flattened.contains("extra"), "{}", flattened);
+    assert!(shallow.contains("user"), "{}", shallow);
+    assert!(!shallow.contains("user.address"), "{}", shallow);
+}
+
+#[tokio::test]
+async fn test_table_columns_select_json_paths() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            r#"[{"id": 1, "name": "Ada", "secret": "s1", "user": {"city": "London"}},
+                {"id": 2, "name": "Grace", "secret": "s2"}]"#,
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let (table, invalid) = tokio::task::spawn_blocking(move || {
+        let table = Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-o", "table", "--columns", "$.id,$.name,$.user.city", &url])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .env("COLUMNS", "200")
+            .output()
+            .unwrap();
+        let invalid = Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["--columns", "id,$.name", &url])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .output()
+            .unwrap();
+        (table, invalid)
+    })
+    .await
+    .unwrap();
+
+    let stdout = String::from_utf8_lossy(&table.stdout);
+    assert!(stdout.contains("user.city"), "{}", stdout);
+    assert!(stdout.contains("Grace"), "{}", stdout);
+    assert!(stdout.contains("London"), "{}", stdout);
+    assert!(!stdout.contains("secret"), "{}", stdout);
+    assert!(!stdout.contains("s1"), "{}", stdout);
+
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("must start with '$'"));
+}
+
+#[tokio::test]
+async fn test_jq_filters_json_body() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            r#"{"items": [{"id": 7, "name": "Ada"}, {"id": 9, "name": "Grace"}]}"#,
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let (ids, names, invalid) = tokio::task::spawn_blocking(move || {
+        let run = |args: &[&str]| {
+            Command::cargo_bin("terzi")
+                .unwrap()
+                .args(args)
+                .arg(&url)
+                .env("TERZI_CONFIG_DIR", &dir)
+                .output()
+                .unwrap()
+        };
+        (
+            run(&["-o", "raw", "--jq", ".items | map(.id)"]),
+            run(&["-o", "raw", "--jq", ".items[].name"]),
+            run(&["--jq", ".items | map("]),
+        )
+    })
+    .await
+    .unwrap();
+
+    let stdout = String::from_utf8_lossy(&ids.stdout);
+    assert!(ids.status.success(), "{}", stdout);
+    assert!(stdout.contains("[7,9]"), "{}", stdout);
+    assert!(!stdout.contains("Ada"), "{}", stdout);
+
+    let stdout = String::from_utf8_lossy(&names.stdout);
+    assert!(stdout.contains("\nAda\nGrace\n"), "{}", stdout);
+
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("Invalid jq expression"));
+}
+
+#[tokio::test]
+async fn test_paginate_follows_link_header_and_cursor() {
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"[{"id": 3}]"#, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Link", r#"</items?page=2>; rel="next""#)
+                .set_body_raw(r#"[{"id": 1}, {"id": 2}]"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/events"))
+        .and(query_param("cursor", "abc"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            r#"{"data": ["b"], "next_cursor": null}"#,
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            r#"{"data": ["a"], "next_cursor": "abc"}"#,
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let uri = server.uri();
+    let (linked, cursor, limited) = tokio::task::spawn_blocking(move || {
+        let terzi = |flags: &[&str], url: String| {
+            let output = Command::cargo_bin("terzi")
+                .unwrap()
+                .args(["-o", "raw"])
+                .args(flags)
+                .arg(url)
+                .env("TERZI_CONFIG_DIR", &dir)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            String::from_utf8_lossy(&output.stdout).to_string()
+        };
+        (
+            terzi(&["--paginate"], format!("{}/items", uri)),
+            terzi(
+                &["--paginate-jsonpath", "$.next_cursor"],
+                format!("{}/events", uri),
+            ),
+            terzi(
+                &["--paginate", "--max-pages", "1"],
+                format!("{}/items", uri),
+            ),
+        )
+    })
+    .await
+    .unwrap();
+
+    assert!(
+        linked.contains(r#"[{"id":1},{"id":2},{"id":3}]"#),
+        "{}",
+        linked
+    );
+    assert!(cursor.contains(r#"["a","b"]"#), "{}", cursor);
+    assert!(limited.contains(r#"[{"id":1},{"id":2}]"#), "{}", limited);
+}
+
+#[tokio::test]
+async fn test_watch_until_condition_records_transitions() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(r#"{"status": "running"}"#, "application/json"),
+        )
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(r#"{"status": "done"}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let (stdout, har) = tokio::task::spawn_blocking(move || {
+        let output = Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["--watch", "50ms", "--watch-until", "$.status==done"])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let har_path = dir.join("history.har");
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["export-har", "-o", har_path.to_str().unwrap()])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        (
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            std::fs::read_to_string(har_path).unwrap(),
+        )
+    })
+    .await
+    .unwrap();
+
+    assert!(stdout.contains("Condition met"), "{}", stdout);
+    assert!(stdout.contains(r#"$.status = running"#), "{}", stdout);
+    assert!(stdout.contains(r#"$.status = done"#), "{}", stdout);
+
+    // Three polls, but only the two transitions are kept in history
+    let har: serde_json::Value = serde_json::from_str(&har).unwrap();
+    assert_eq!(har["log"]["entries"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_template_functions_render_per_send() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let saved = tokio::task::spawn_blocking(move || {
+        let body = r#"{"id": "{{uuid()}}", "score": {{randomInt(1,100)}}}"#;
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-m", "POST", "-j", body, "--save", "create"])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["--load", "create"])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+
+        let output = Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["show", "create", "--raw"])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    })
+    .await
+    .unwrap();
+
+    // The saved request keeps the template; each send gets its own values
+    assert!(saved.contains("{{uuid()}}"), "{}", saved);
+    let bodies: Vec<serde_json::Value> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect();
+    assert_eq!(bodies.len(), 2);
+    assert_ne!(bodies[0]["id"], bodies[1]["id"]);
+    let score = bodies[0]["score"].as_i64().unwrap();
+    assert!((1..=100).contains(&score));
+}
+
+#[tokio::test]
+async fn test_data_binary_sends_exact_bytes() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let image = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0xff];
+    let piped = image.clone();
+    let upload = dir.join("image.png");
+    std::fs::write(&upload, &image).unwrap();
+    let url = server.uri();
+    tokio::task::spawn_blocking(move || {
+        let data = format!("@{}", upload.display());
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-m", "POST", "-H", "Content-Type: image/png"])
+            .args(["--data-binary", data.as_str(), "--save", "upload"])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["--load", "upload"])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-m", "POST", "--data-binary", "{{uuid()}} a+b"])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        let mut piped_upload = Command::cargo_bin("terzi").unwrap();
+        piped_upload
+            .args(["-m", "POST", "--data-binary", "@-"])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir);
+        assert_cmd::Command::from_std(piped_upload)
+            .write_stdin(piped)
+            .assert()
+            .success();
+    })
+    .await
+    .unwrap();
+
+    let content_type = |request: &wiremock::Request| {
+        request
+            .headers
+            .iter()
+            .find(|(name, _)| name.as_str() == "content-type")
+            .map(|(_, values)| values.last().as_str().to_string())
+    };
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 4);
+    for request in &requests[..2] {
+        assert_eq!(request.body, image);
+        assert_eq!(content_type(request).as_deref(), Some("image/png"));
+    }
+    // Literal data is not templated or given a guessed content type
+    assert_eq!(requests[2].body, b"{{uuid()}} a+b");
+    assert!(content_type(&requests[2]).is_none());
+    // `@-` sends stdin, not a file called "-"
+    assert_eq!(requests[3].body, image);
+}
+
+#[tokio::test]
+async fn test_cache_revalidates_with_etag() {
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(header("if-none-match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304).insert_header("ETag", "\"v1\""))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_raw(r#"{"report": "ready"}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    let (first, second, cleared) = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["config", "set", "network.enable_cache", "true"])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+
+        let terzi = |args: &[&str]| {
+            let output = Command::cargo_bin("terzi")
+                .unwrap()
+                .args(args)
+                .env("TERZI_CONFIG_DIR", &dir)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            String::from_utf8_lossy(&output.stdout).to_string()
+        };
+        (terzi(&[&url]), terzi(&[&url]), terzi(&["cache", "clear"]))
+    })
+    .await
+    .unwrap();
+
+    assert!(
+        first.contains("ready") && !first.contains("from cache"),
+        "{}",
+        first
+    );
+    assert!(second.contains("ready"), "{}", second);
+    assert!(second.contains("from cache"), "{}", second);
+    assert!(
+        cleared.contains("Removed 1 cached responses"),
+        "{}",
+        cleared
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_max_response_size_aborts_large_responses() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(4096)))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["--max-response-size", "1KB", "-o", "raw"])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Response exceeded max size of 1.0 KB (1024 bytes)",
+            ));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["config", "set", "network.max_response_size", "8KB"])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-o", "raw"])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("xxxx"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_large_request_body_warns_and_sizes_are_formatted() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(3072)))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    tokio::task::spawn_blocking(move || {
+        let body = "y".repeat(2048);
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["config", "set", "network.warn_request_size", "1KB"])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-m", "POST", "--body", &body, "-o", "raw"])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(
+                "Request body is 2.0 KB, over the 1.0 KB set in network.warn_request_size",
+            ))
+            .stdout(predicate::str::contains("warn_request_size").not());
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .arg("history")
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("3.0 KB"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["config", "set", "network.warn_request_size", "none"])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-m", "POST", "--body", &body, "-o", "raw"])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("warn_request_size").not());
+    })
+    .await
+    .unwrap();
+}
+
+#[test]
+fn test_request_failure_suggests_fix() {
+    let config_dir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.arg(closed_port_url())
+        .env("TERZI_CONFIG_DIR", config_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Request failed"))
+        .stderr(predicate::str::contains("Hint:"))
+        .stderr(predicate::str::contains("is the server running"));
+}
+
+#[tokio::test]
+async fn test_collection_commands() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/broken"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let base = server.uri();
+    tokio::task::spawn_blocking(move || {
+        let config_dir = TempDir::new().unwrap();
+        let terzi = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.args(args).env("TERZI_CONFIG_DIR", config_dir.path());
+            cmd.assert()
+        };
+
+        terzi(&["--save", "health", &format!("{}/health", base)]).success();
+        terzi(&["--save", "broken", &format!("{}/broken", base)]).success();
+
+        terzi(&["collection", "create", "smoke", "-d", "Quick checks"]).success();
+        terzi(&["collection", "create", "smoke"])
+            .failure()
+            .stderr(predicate::str::contains("already exists"));
+        terzi(&["collection", "add", "smoke", "health"]).success();
+        terzi(&["collection", "add", "smoke", "broken"]).success();
+        terzi(&["collection", "add", "smoke", "missing"])
+            .failure()
+            .stderr(predicate::str::contains("Request 'missing' not found"));
+
+        terzi(&["collection", "list"])
+            .success()
+            .stdout(predicate::str::contains("smoke"))
+            .stdout(predicate::str::contains("Quick checks"));
+        terzi(&["collection", "show", "smoke"])
+            .success()
+            .stdout(predicate::str::contains("health"))
+            .stdout(predicate::str::contains("broken"));
+
+        terzi(&["collection", "run", "smoke"])
+            .failure()
+            .stdout(predicate::str::contains("1 passed, 1 failed"));
+
+        let mut delete = Command::cargo_bin("terzi").unwrap();
+        delete
+            .args(["collection", "delete", "smoke"])
+            .env("TERZI_CONFIG_DIR", config_dir.path());
+        assert_cmd::Command::from_std(delete)
+            .write_stdin("y\n")
+            .assert()
+            .success();
+        terzi(&["collection", "show", "smoke"])
+            .failure()
+            .stderr(predicate::str::contains("Collection 'smoke' not found"));
+        terzi(&["show", "health"]).success();
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_collection_run_with_environment() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/health"))
+        .and(header("authorization", "Bearer staging-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok": true}"#))
+        .mount(&server)
+        .await;
+
+    let dir = TempDir::new().unwrap();
+    let request = serde_json::json!({
+        "id": "health",
+        "name": "health",
+        "url": "{{base}}/{{version}}/health",
+        "method": "GET",
+        "headers": {"Authorization": "Bearer {{token}}"},
+        "body": null,
+        "timeout": 5,
+        "follow_redirects": false,
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+        "tags": [],
+        "description": null,
+        "assertions": ["status == 200", "$.ok == true"],
+    });
+    let data = serde_json::json!({
+        "requests": {},
+        "collections": {
+            "smoke": {
+                "name": "smoke",
+                "description": null,
+                "requests": [request],
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-01-01T00:00:00Z",
+            }
+        },
+        "history": [],
+        "environments": {
+            "staging": {"base": server.uri(), "version": "v2", "token": "staging-token"},
+        },
+        "settings": {},
+    });
+    std::fs::write(dir.path().join("data.json"), data.to_string()).unwrap();
+
+    let config_dir = dir.path().to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let run = |args: &[&str]| {
+            Command::cargo_bin("terzi")
+                .unwrap()
+                .env("TERZI_CONFIG_DIR", &config_dir)
+                .args(["collection", "run", "smoke"])
+                .args(args)
+                .assert()
+        };
+
+        run(&["--env", "staging"])
+            .success()
+            .stdout(predicate::str::contains("with environment staging"))
+            .stdout(predicate::str::contains("Test Report"))
+            .stdout(predicate::str::contains("2/2"))
+            .stdout(predicate::str::contains("1 passed, 0 failed"));
+
+        // Without an environment the placeholders are sent as saved
+        run(&[])
+            .failure()
+            .stdout(predicate::str::contains("relative URL without a base"));
+
+        run(&["--env", "prod"])
+            .failure()
+            .stderr(predicate::str::contains(
+                "Environment 'prod' not found (available: staging)",
+            ));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_env_commands_and_direct_substitution() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/users"))
+        .and(header("authorization", "Bearer secret-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("env-ok"))
+        .mount(&server)
+        .await;
+
+    let base = server.uri();
+    tokio::task::spawn_blocking(move || {
+        let config_dir = TempDir::new().unwrap();
+        let terzi = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.args(args).env("TERZI_CONFIG_DIR", config_dir.path());
+            cmd.assert()
+        };
+
+        terzi(&["env", "create", "staging", &format!("base={}", base)]).success();
+        terzi(&["env", "create", "staging"])
+            .failure()
+            .stderr(predicate::str::contains("already exists"));
+        terzi(&[
+            "env",
+            "set",
+            "staging",
+            "version=v2",
+            "auth_token=secret-token",
+        ])
+        .success();
+        terzi(&["env", "set", "staging", "oops"])
+            .failure()
+            .stderr(predicate::str::contains("Use 'KEY=VALUE'"));
+
+        terzi(&["env", "list"])
+            .success()
+            .stdout(predicate::str::contains("staging"));
+        terzi(&["env", "show", "staging"])
+            .success()
+            .stdout(predicate::str::contains("version"))
+            .stdout(predicate::str::contains("secret-token").not());
+        terzi(&["env", "show", "staging", "--no-mask"])
+            .success()
+            .stdout(predicate::str::contains("secret-token"));
+
+        terzi(&[
+            "--env",
+            "staging",
+            "-H",
+            "Authorization: Bearer {{auth_token}}",
+            "{{base}}/{{version}}/users",
+        ])
+        .success()
+        .stdout(predicate::str::contains("env-ok"));
+
+        terzi(&["--env", "staging", "{{base}}/{{missing}}"])
+            .failure()
+            .stderr(predicate::str::contains("Unresolved variable: {{missing}}"));
+        terzi(&["--env", "prod", "{{base}}/v2/users"])
+            .failure()
+            .stderr(predicate::str::contains("Environment 'prod' not found"));
+
+        let mut delete = Command::cargo_bin("terzi").unwrap();
+        delete
+            .args(["env", "delete", "staging"])
+            .env("TERZI_CONFIG_DIR", config_dir.path());
+        assert_cmd::Command::from_std(delete)
+            .write_stdin("y\n")
+            .assert()
+            .success();
+        terzi(&["env", "show", "staging"]).failure();
+    })
+    .await
+    .unwrap();
+}
+
+#[test]
+fn test_backup_create_list_restore() {
+    let config_dir = TempDir::new().unwrap();
+    let terzi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.args(args).env("TERZI_CONFIG_DIR", config_dir.path());
+        cmd
+    };
+
+    terzi(&["--save", "kept", "https://api.example.com/kept"])
+        .output()
+        .unwrap();
+    terzi(&["backup", "create"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Backup written to"));
+    terzi(&["--save", "later", "https://api.example.com/later"])
+        .output()
+        .unwrap();
+
+    let backups: Vec<_> = std::fs::read_dir(config_dir.path().join("backups"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(backups.len(), 1);
+    terzi(&["backup", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(backups[0].as_str()))
+        .stdout(predicate::str::contains("ago"));
+
+    terzi(&["backup", "restore", "missing.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+
+    // Declining the prompt leaves the data alone
+    assert_cmd::Command::from_std(terzi(&["backup", "restore", &backups[0]]))
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cancelled"));
+    terzi(&["show", "later"]).assert().success();
+
+    assert_cmd::Command::from_std(terzi(&["backup", "restore", &backups[0]]))
+        .write_stdin("y\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("before restoring"));
+    terzi(&["show", "kept"]).assert().success();
+    terzi(&["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("later").not());
+
+    // The data replaced by the restore was itself backed up
+    assert_eq!(
+        std::fs::read_dir(config_dir.path().join("backups"))
+            .unwrap()
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn test_auto_backup_before_destructive_commands() {
+    let config_dir = TempDir::new().unwrap();
+    let terzi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.args(args).env("TERZI_CONFIG_DIR", config_dir.path());
+        cmd
+    };
+    let backups = |prefix: &str| {
+        std::fs::read_dir(config_dir.path().join("backups"))
+            .map(|entries| {
+                entries
+                    .filter(|entry| {
+                        entry
+                            .as_ref()
+                            .unwrap()
+                            .file_name()
+                            .to_string_lossy()
+                            .starts_with(prefix)
+                    })
+                    .count()
+            })
+            .unwrap_or(0)
+    };
+
+    terzi(&["config", "set", "ui.confirm_dangerous_operations", "false"])
+        .assert()
+        .success();
+    terzi(&["config", "set", "general.max_auto_backups", "2"])
+        .assert()
+        .success();
+    terzi(&["backup", "create"]).assert().success();
+    for name in ["one", "two", "three"] {
+        terzi(&["--save", name, "https://api.example.com/"])
+            .output()
+            .unwrap();
+    }
+    for name in ["one", "two", "three"] {
+        terzi(&["delete", name])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Backed up existing data to"));
+    }
+    // Only the newest automatic backups are kept; the manual one survives
+    assert_eq!(backups("terzi_autobackup_"), 2);
+    assert_eq!(backups("terzi_backup_"), 1);
+
+    terzi(&["config", "reset"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Backed up the configuration to"));
+    assert_eq!(backups("terzi_config_autobackup_"), 1);
+
+    terzi(&["config", "set", "ui.confirm_dangerous_operations", "false"])
+        .assert()
+        .success();
+    terzi(&["config", "set", "general.auto_backup", "false"])
+        .assert()
+        .success();
+    terzi(&["--save", "four", "https://api.example.com/"])
+        .output()
+        .unwrap();
+    terzi(&["delete", "four"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Backed up").not());
+    assert_eq!(backups("terzi_autobackup_"), 2);
+}
+
+#[tokio::test]
+async fn test_history_honors_max_history_entries() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let base = server.uri();
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let terzi = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.args(args).env("TERZI_CONFIG_DIR", &dir);
+            cmd.assert().success();
+        };
+
+        terzi(&["config", "set", "general.max_history_entries", "3"]);
+        for i in 1..=5 {
+            terzi(&[&format!("{}/item/{}", base, i)]);
+        }
+    })
+    .await
+    .unwrap();
+
+    let data = std::fs::read_to_string(config_dir.path().join("data.json")).unwrap();
+    let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+    let urls: Vec<&str> = data["history"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["url"].as_str().unwrap())
+        .collect();
+    // The oldest entries are dropped first
+    assert_eq!(urls.len(), 3);
+    assert!(urls[0].ends_with("/item/3"));
+    assert!(urls[2].ends_with("/item/5"));
+}
+
+#[tokio::test]
+async fn test_history_masks_credentials_and_caps_bodies() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("fresh"))
+        .mount(&server)
+        .await;
+
+    // 70 entries with 60 KB bodies each are over the 4 MB kept for all bodies together
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let history: Vec<serde_json::Value> = (0..70)
+        .map(|i| {
+            serde_json::json!({
+                "id": i.to_string(),
+                "timestamp": format!("2024-01-01T00:{:02}:00Z", i % 60),
+                "method": "GET",
+                "url": format!("https://api.example.com/{}", i),
+                "response_status": 200,
+                "response_body": "x".repeat(60 * 1024),
+            })
+        })
+        .collect();
+    let data = serde_json::json!({
+        "requests": {},
+        "collections": {},
+        "history": history,
+        "environments": {},
+        "settings": {},
+    });
+    std::fs::write(dir.join("data.json"), data.to_string()).unwrap();
+
+    let url = server.uri();
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &dir)
+            .args(["-H", "Authorization: Bearer plain-secret", &url])
+            .assert()
+            .success();
+    })
+    .await
+    .unwrap();
+
+    let data = std::fs::read_to_string(config_dir.path().join("data.json")).unwrap();
+    assert!(!data.contains("plain-secret"));
+    let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+    let history = data["history"].as_array().unwrap();
+    assert_eq!(history.len(), 71);
+    assert!(history[0]["response_body"].is_null());
+    assert_eq!(history[70]["response_body"], "fresh");
+    let kept: usize = history
+        .iter()
+        .filter_map(|entry| entry["response_body"].as_str())
+        .map(str::len)
+        .sum();
+    assert!(kept <= 4 * 1024 * 1024, "{}", kept);
+}
+
+#[tokio::test]
+async fn test_history_records_transfer_sizes() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0xffu8; 10]))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/upload", server.uri());
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &dir)
+            .args(["-m", "POST", "-b", "hello", &url])
+            .assert()
+            .success();
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &dir)
+            .args(["history", "stats"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Sent"))
+            .stdout(predicate::str::contains("Received"));
+    })
+    .await
+    .unwrap();
+
+    let data = std::fs::read_to_string(config_dir.path().join("data.json")).unwrap();
+    let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+    let entry = &data["history"][0];
+    assert_eq!(entry["request_size"], 5);
+    // Raw bytes, not the length of the lossily decoded text
+    assert_eq!(entry["response_size"], 10);
+    assert!(entry["request_header_size"].as_u64().unwrap() > "POST /upload HTTP/1.1".len() as u64);
+    assert!(entry["response_header_size"].as_u64().unwrap() > "HTTP/1.1 200 OK".len() as u64);
+}
+
+#[tokio::test]
+async fn test_cursor_is_remembered_between_sends() {
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/events"))
+        .and(query_param("since_id", "5"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(r#"{"events":[{"id":7},{"id":9}]}"#),
+        )
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"events":[{"id":5}]}"#))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/events?limit=10", server.uri());
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let terzi = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.args(args).env("TERZI_CONFIG_DIR", &dir);
+            cmd.assert().success();
+        };
+
+        terzi(&[
+            "--save",
+            "poll",
+            "--cursor-field",
+            "$.events[-1].id",
+            "--cursor-param",
+            "since_id",
+            &url,
+        ]);
+        terzi(&["--load", "poll"]);
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &dir)
+            .args(["--cursor-field", "$.id", "--cursor-param", "since", &url])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--save or --load"));
+    })
+    .await
+    .unwrap();
+
+    let data = std::fs::read_to_string(config_dir.path().join("data.json")).unwrap();
+    let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+    let saved = &data["requests"]["poll"];
+    assert_eq!(saved["state"]["since_id"], "9");
+    // The saved URL keeps its template; the cursor is only added when sending
+    assert!(saved["url"].as_str().unwrap().ends_with("/events?limit=10"));
+}
+
+#[test]
+fn test_history_clear_and_delete() {
+    let config_dir = TempDir::new().unwrap();
+    let entry = |id: &str, url: &str, timestamp: &str| {
+        serde_json::json!({
+            "id": id,
+            "timestamp": timestamp,
+            "method": "GET",
+            "url": url,
+            "response_status": 200,
+            "duration_ms": 5,
+            "request_size": null,
+            "response_size": 2,
+            "error_message": null,
+        })
+    };
+    let data = serde_json::json!({
+        "requests": {},
+        "collections": {},
+        "history": [
+            entry("aaaa1111-0000", "https://api.example.com/old", "2023-06-01T00:00:00Z"),
+            entry("aaaa2222-0000", "https://api.example.com/older", "2023-01-01T00:00:00Z"),
+            entry("bbbb1111-0000", "https://api.example.com/new", "2030-01-01T00:00:00Z"),
+        ],
+        "environments": {},
+        "settings": {},
+    });
+    std::fs::write(config_dir.path().join("data.json"), data.to_string()).unwrap();
+
+    let terzi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.args(args).env("TERZI_CONFIG_DIR", config_dir.path());
+        cmd
+    };
+    let history_urls = || {
+        let data = std::fs::read_to_string(config_dir.path().join("data.json")).unwrap();
+        let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+        data["history"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["url"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    terzi(&["history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("bbbb1111"));
+
+    terzi(&["history", "delete", "aaaa"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("matches 2 entries"));
+    terzi(&["history", "delete", "zzzz"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+    terzi(&["history", "delete", "aaaa2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/older"));
+    assert_eq!(history_urls().len(), 2);
+
+    terzi(&["history", "clear", "--before", "yesterday"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid date"));
+    assert_cmd::Command::from_std(terzi(&["history", "clear", "--before", "2024-01-01"]))
+        .write_stdin("y\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 1 history entries"));
+    assert_eq!(history_urls(), vec!["https://api.example.com/new"]);
+
+    // Declining keeps everything
+    assert_cmd::Command::from_std(terzi(&["history", "clear"]))
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cancelled"));
+    assert_eq!(history_urls().len(), 1);
+
+    terzi(&["config", "set", "ui.confirm_dangerous_operations", "false"])
+        .assert()
+        .success();
+    terzi(&["history", "clear"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("History cleared"));
+    assert!(history_urls().is_empty());
+}
+
+#[tokio::test]
+async fn test_no_history_and_save_history_setting() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let base = server.uri();
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let history_len = move || {
+        let data = std::fs::read_to_string(dir.join("data.json")).unwrap_or_default();
+        serde_json::from_str::<serde_json::Value>(&data)
+            .ok()
+            .and_then(|data| data["history"].as_array().map(|history| history.len()))
+            .unwrap_or(0)
+    };
+    let dir = config_dir.path().to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let terzi = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.args(args).env("TERZI_CONFIG_DIR", &dir);
+            cmd.assert()
+        };
+
+        terzi(&[&format!("{}/recorded", base)]).success();
+        assert_eq!(history_len(), 1);
+
+        terzi(&["--no-history", &format!("{}/secret", base)]).success();
+        // Errors are skipped too
+        terzi(&["--no-history", &closed_port_url()]).failure();
+        assert_eq!(history_len(), 1);
+
+        terzi(&["config", "set", "general.save_history", "false"]).success();
+        terzi(&[&format!("{}/unrecorded", base)]).success();
+        assert_eq!(history_len(), 1);
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_duplicate_request() {
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args([
+                "--save",
+                "original",
+                "-m",
+                "POST",
+                "-H",
+                "X-Trace: 1",
+                "http://127.0.0.1:9/items",
+            ])
+            .output()
+            .unwrap();
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["duplicate", "original", "copy"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("copied to 'copy'"));
+
+        let data: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(config_path.join("data.json")).unwrap())
+                .unwrap();
+        let original = &data["requests"]["original"];
+        let copy = &data["requests"]["copy"];
+        assert_eq!(copy["name"], "copy");
+        assert_eq!(copy["method"], "POST");
+        assert_eq!(copy["url"], original["url"]);
+        assert_eq!(copy["headers"], original["headers"]);
+        assert_ne!(copy["id"], original["id"]);
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["duplicate", "original", "copy"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("already exists"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["duplicate", "missing", "other"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("not found"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_default_headers_are_merged() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/team"))
+        .and(header("x-team", "payments"))
+        .and(header("user-agent", "mine/1.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("with defaults"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/team"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("without defaults"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/team", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args([
+                "config",
+                "set",
+                "network.default_headers",
+                "X-Team: payments; User-Agent: team/1.0",
+            ])
+            .assert()
+            .success();
+
+        // The request's own User-Agent beats the default one
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--save", "team", "-H", "user-agent: mine/1.0", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("with defaults"));
+
+        // Defaults are added when sending, so they are not saved but reach --load too
+        let data = std::fs::read_to_string(config_path.join("data.json")).unwrap();
+        let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+        assert!(data["requests"]["team"]["headers"].get("X-Team").is_none());
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--load", "team"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("with defaults"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--no-default-headers", "-H", "user-agent: mine/1.0", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("without defaults"));
+    })
+    .await
+    .unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_unix_socket_request() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let socket_dir = TempDir::new().unwrap();
+    let socket_path = socket_dir.path().join("daemon.sock");
+    let listener = tokio::net::UnixListener::bind(&socket_path).unwrap();
+
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        let body = r#"[{"Id":"abc123"}]"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8(request).unwrap()
+    });
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let socket = socket_path.to_str().unwrap().to_string();
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--unix-socket", &socket, "http://localhost/containers/json"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("abc123"));
+    })
+    .await
+    .unwrap();
+
+    let request = server.await.unwrap();
+    assert!(request.starts_with("GET /containers/json HTTP/1.1"));
+    assert!(request.to_lowercase().contains("host: localhost"));
+}
+
+#[tokio::test]
+async fn test_resolve_pins_host_to_address() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    let port = server.address().port();
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .and(header(
+            "host",
+            format!("api.terzi-test.invalid:{}", port).as_str(),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_string("green"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("http://api.terzi-test.invalid:{}/health", port);
+    let resolve = format!("api.terzi-test.invalid:{}:127.0.0.1", port);
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--resolve", &resolve, "--ipv4", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("green"));
+
+        // The pinned address is IPv4, so forcing IPv6 leaves nothing to connect to
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--resolve", &resolve, "--ipv6", &url])
+            .assert()
+            .failure();
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--resolve", "api.terzi-test.invalid:127.0.0.1", &url])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("HOST:PORT:ADDR"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_resolve_leaves_other_ports_of_the_host_alone() {
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // As with --connect-to, a request that wrongly follows the pin finds nothing listening
+    let listener = std::net::TcpListener::bind("127.0.0.2:0").unwrap();
+    let target = MockServer::builder().listener(listener).start().await;
+    Mock::given(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("pinned"))
+        .mount(&target)
+        .await;
+    let other = MockServer::start().await;
+    Mock::given(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("other port"))
+        .mount(&other)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let resolve = format!("localhost:{}:127.0.0.2", target.address().port());
+    let pinned_url = format!("http://localhost:{}/health", target.address().port());
+    let other_url = format!("http://localhost:{}/health", other.address().port());
+
+    tokio::task::spawn_blocking(move || {
+        for (url, expected) in [(pinned_url, "pinned"), (other_url, "other port")] {
+            Command::cargo_bin("terzi")
+                .unwrap()
+                .env("TERZI_CONFIG_DIR", &config_path)
+                .args(["--resolve", &resolve, &url])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains(expected));
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_connect_to_and_host_header() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    let port = server.address().port();
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .and(header("host", "api.terzi-test.invalid"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("backend-2"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .and(header("host", "canary.example.com"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("canary"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let connect_to = format!("api.terzi-test.invalid:80:127.0.0.1:{}", port);
+    let direct = format!("http://127.0.0.1:{}/health", port);
+
+    tokio::task::spawn_blocking(move || {
+        // Port 80 in the URL, but the connection goes to the mock server's port
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args([
+                "--connect-to",
+                &connect_to,
+                "http://api.terzi-test.invalid/health",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("backend-2"));
+
+        // --host-header wins over -H Host
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["-H", "Host: ignored.example.com"])
+            .args(["--host-header", "canary.example.com", &direct])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("canary"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--connect-to", "api.terzi-test.invalid:80:backend", &direct])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "HOST:PORT:TARGET_HOST:TARGET_PORT",
+            ));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_connect_to_leaves_other_ports_of_the_host_alone() {
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // The target listens on another loopback address, so a request that wrongly follows
+    // the pin ends up where nothing is listening
+    let listener = std::net::TcpListener::bind("127.0.0.2:0").unwrap();
+    let target = MockServer::builder().listener(listener).start().await;
+    Mock::given(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("pinned"))
+        .mount(&target)
+        .await;
+    let other = MockServer::start().await;
+    Mock::given(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("other port"))
+        .mount(&other)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let connect_to = format!("localhost:80:127.0.0.2:{}", target.address().port());
+    let other_url = format!("http://localhost:{}/health", other.address().port());
+
+    tokio::task::spawn_blocking(move || {
+        for (url, expected) in [
+            ("http://localhost/health", "pinned"),
+            (other_url.as_str(), "other port"),
+        ] {
+            Command::cargo_bin("terzi")
+                .unwrap()
+                .env("TERZI_CONFIG_DIR", &config_path)
+                .args(["--connect-to", &connect_to, url])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains(expected));
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_method_override_sends_post() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/users/1"))
+        .and(header("x-http-method-override", "DELETE"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("deleted"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/users/1", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["-m", "delete", "--method-override", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("deleted"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .arg("history")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("POST"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_fail_on_http_error_status() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("no such widget"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/ok"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("fine"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let missing = format!("{}/missing", server.uri());
+    let ok = format!("{}/ok", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        // Without --fail a 404 still exits 0
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .arg(&missing)
+            .assert()
+            .success();
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--fail", &missing])
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains("no such widget").not())
+            .stderr(predicate::str::contains("HTTP 404"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--fail-with-body", &missing])
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains("no such widget"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--fail", &ok])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("fine"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_print_stats_json() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/stats"))
+        .respond_with(ResponseTemplate::new(201).set_body_string("created"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/stats", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        let output = Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--print-stats-json", &url])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("duration_ms"));
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stats: serde_json::Value =
+            serde_json::from_str(stderr.lines().last().unwrap()).unwrap();
+        assert_eq!(stats["status"], 201);
+        assert_eq!(stats["size"], 7);
+        assert_eq!(stats["method"], "GET");
+        assert!(stats["url"].as_str().unwrap().ends_with("/stats"));
+        assert!(stats["duration_ms"].is_u64());
+
+        let stats_file = config_path.join("stats.json");
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args([
+                "--print-stats-json-file",
+                stats_file.to_str().unwrap(),
+                &url,
+            ])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("duration_ms").not());
+        let stats: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&stats_file).unwrap()).unwrap();
+        assert_eq!(stats["status"], 201);
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_hmac_signed_body() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    // HMAC-SHA256 of the body with key "Jefe" (RFC 4231 test case 2)
+    Mock::given(method("POST"))
+        .and(path("/webhook"))
+        .and(header(
+            "x-signature",
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_string("signature ok"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/webhook", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args([
+                "-m",
+                "POST",
+                "--body",
+                "what do ya want for nothing?",
+                "--hmac",
+                "Jefe:X-Signature:sha256",
+                &url,
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("signature ok"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--hmac", "Jefe:X-Signature:md5", &url])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Unsupported HMAC algorithm"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_summary_only_skips_body() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/latency"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"payload":"large body"}"#))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/latency", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--summary-only", "-i", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("200"))
+            .stdout(predicate::str::contains("24 B"))
+            .stdout(predicate::str::contains("large body").not())
+            .stdout(predicate::str::contains("Headers:").not());
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_repeated_form_keys_are_all_sent() {
+    use wiremock::matchers::{body_string, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string("tags=a&tags=b&name=terzi"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = server.uri();
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args([
+                "-m",
+                "POST",
+                "-f",
+                "tags=a",
+                "-f",
+                "tags=b",
+                "-f",
+                "name=terzi",
+                &url,
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("201"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_query_and_form_raw_skip_encoding() {
+    use wiremock::matchers::{body_string, method, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(query_param("q", "a b&c"))
+        .and(query_param("sig", "abc="))
+        .respond_with(ResponseTemplate::new(200).set_body_string("query ok"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string("note=100%25&sig=abc%3D"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("form ok"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = server.uri();
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args([
+                "--query",
+                "q=a b&c",
+                "--query-raw",
+                "sig=abc%3D",
+                "-o",
+                "raw",
+                &url,
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("query ok"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args([
+                "-m",
+                "POST",
+                "-f",
+                "note=100%",
+                "--form-raw",
+                "sig=abc%3D",
+                "-o",
+                "raw",
+                &url,
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("form ok"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_assert_file_reports_and_fails() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(r#"{"ok":true,"version":3}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let passing = config_path.join("checks.yaml");
+    std::fs::write(
+        &passing,
+        "status: 200\nheaders:\n  content-type: { contains: json }\nchecks:\n  - { jsonpath: $.ok, equals: true }\n",
+    )
+    .unwrap();
+    let failing = config_path.join("failing.json");
+    std::fs::write(&failing, r#"[{"jsonpath": "$.version", "gt": 5}]"#).unwrap();
+    let url = server.uri();
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .arg("--assert-file")
+            .arg(&passing)
+            .arg(&url)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                r#"header.content-type contains "json""#,
+            ))
+            .stdout(predicate::str::contains("$.ok == true"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .arg("--assert-file")
+            .arg(&failing)
+            .args(["--assert", "status == 200", &url])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("status == 200"))
+            .stdout(predicate::str::contains("$.version > 5"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_head_prints_only_headers() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/resource"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-served-by", "head"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/resource"))
+        .and(header("accept", "application/json"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("x-served-by", "get")
+                .set_body_string(r#"{"payload":"large body"}"#),
+        )
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/resource", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["-I", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Headers:"))
+            .stdout(predicate::str::contains("head"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--headers-only", "-H", "Accept:application/json", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("x-served-by"))
+            .stdout(predicate::str::contains("get"))
+            .stdout(predicate::str::contains("large body").not());
+    })
+    .await
+    .unwrap();
+}
+
+#[test]
+fn test_config_list_json() {
+    let config_dir = TempDir::new().unwrap();
+    Command::cargo_bin("terzi")
+        .unwrap()
+        .env("TERZI_CONFIG_DIR", config_dir.path())
+        .args(["config", "set", "general.default_timeout", "45"])
+        .assert()
+        .success();
+
+    let config_path = config_dir.path().join("config.toml");
+    let mut config: toml::Value =
+        toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["auth"]["stored_tokens"] = toml::from_str(
+        r#"
+[github]
+token_type = "bearer"
+value = "ghp_supersecretvalue"
+scopes = []
+"#,
+    )
+    .unwrap();
+    std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+    let output = Command::cargo_bin("terzi")
+        .unwrap()
+        .env("TERZI_CONFIG_DIR", config_dir.path())
+        .args(["config", "list", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let config: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(config["general"]["default_timeout"], 45);
+    assert_eq!(config["ui"]["table_style"], "rounded");
+    let token = config["auth"]["stored_tokens"]["github"]["value"]
+        .as_str()
+        .unwrap();
+    assert!(!token.contains("supersecret"));
+
+    Command::cargo_bin("terzi")
+        .unwrap()
+        .env("TERZI_CONFIG_DIR", config_dir.path())
+        .args(["config", "list", "--json", "--include-secrets"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ghp_supersecretvalue"));
+}
+
+#[tokio::test]
+async fn test_raw_body_content_type_is_guessed() {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/items"))
+        .and(header("content-type", "application/json"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("got json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/items"))
+        .and(header("content-type", "text/csv"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("got csv"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/items"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("no content type"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/items", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        let run = |extra: &[&str]| {
+            let mut args = vec!["-m", "POST", "--body", r#"{"a":1}"#];
+            args.extend_from_slice(extra);
+            args.push(&url);
+            let output = Command::cargo_bin("terzi")
+                .unwrap()
+                .env("TERZI_CONFIG_DIR", &config_path)
+                .args(&args)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        };
+
+        assert!(run(&[]).contains("got json"));
+        // An explicit header is left alone
+        assert!(run(&["-H", "content-type: text/csv"]).contains("got csv"));
+        assert!(run(&["--no-guess-content-type"]).contains("no content type"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_grep_filters_body_lines() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/users"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            r#"{"users":[{"name":"Ada","email":"ada@example.com"},{"name":"Grace","email":"grace@example.com"}]}"#,
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/users", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .env("NO_COLOR", "1")
+            .args(["--grep", "email", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("ada@example.com"))
+            .stdout(predicate::str::contains("grace@example.com"))
+            .stdout(predicate::str::contains("Grace").not());
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .env("NO_COLOR", "1")
+            .args(["--grep", "grace@", "--grep-context", "1", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\"Grace\""))
+            .stdout(predicate::str::contains("ada@example.com").not());
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--grep", "(unclosed", &url])
+            .assert()
+            .failure();
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_lint_saved_requests() {
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        let terzi = || {
+            let mut command = Command::cargo_bin("terzi").unwrap();
+            command.env("TERZI_CONFIG_DIR", &config_path);
+            command
+        };
+        terzi()
+            .args(["--save", "good", "-m", "POST", "-j", r#"{"a": 1}"#])
+            .arg("http://127.0.0.1:9/items")
+            .output()
+            .unwrap();
+        terzi()
+            .args(["--save", "bad", "-H", "Authorization: Bearer abc"])
+            .args(["-H", "Content-Type: application/json", "-b", "{broken"])
+            .arg("http://api.example.invalid/users/{{id}}")
+            .output()
+            .unwrap();
+
+        terzi()
+            .args(["lint", "good"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("0 error(s), 0 warning(s)"));
+
+        terzi()
+            .args(["lint", "--all"])
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains("Invalid JSON body"))
+            .stdout(predicate::str::contains("{{id}} needs a value from --env"))
+            .stdout(predicate::str::contains("sent over plain http://"))
+            .stdout(predicate::str::contains("in 2 request(s)"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_verbose_request_dumps_wire_request() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/items"))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/items?draft=1", server.uri());
+    let host = server.uri().trim_start_matches("http://").to_string();
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--verbose-request", "-m", "POST", "-H", "X-Trace: 7"])
+            .args(["-b", "hello", &url])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("> POST /items?draft=1 HTTP/1.1"))
+            .stderr(predicate::str::contains(format!("> host: {}", host)))
+            .stderr(predicate::str::contains("> x-trace: 7"))
+            .stderr(predicate::str::contains("> user-agent: "))
+            .stderr(predicate::str::contains("> content-length: 5"))
+            .stderr(predicate::str::contains(">\nhello\n"))
+            .get_output()
+            .clone()
+    })
+    .await
+    .unwrap();
+
+    // Every header the server received shows up in the dump, and nothing else
+    let dump = String::from_utf8(output.stderr).unwrap();
+    let mut dumped: Vec<String> = dump
+        .lines()
+        .skip(1)
+        .take_while(|line| *line != ">")
+        .map(|line| line.trim_start_matches("> ").to_string())
+        .collect();
+    let received = &server.received_requests().await.unwrap()[0];
+    let mut sent: Vec<String> = received
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value.last().as_str()))
+        .collect();
+    sent.sort();
+    dumped.sort();
+    assert_eq!(dumped, sent);
+}
+
+#[tokio::test]
+async fn test_http_output_shows_request_and_response() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/items"))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .insert_header("x-item", "42")
+                .set_body_string("created"),
+        )
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/items", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["-o", "http", "-m", "POST", "-b", "hello", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("POST /items HTTP/1.1"))
+            .stdout(predicate::str::contains("user-agent: terzi/"))
+            .stdout(predicate::str::contains("content-length: 5"))
+            .stdout(predicate::str::contains("\nhello\n"))
+            .stdout(predicate::str::contains("HTTP/1.1 201 Created"))
+            .stdout(predicate::str::contains("x-item: 42"))
+            .stdout(predicate::str::contains("created"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_redirect_limits_and_chain() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/old"))
+        .respond_with(ResponseTemplate::new(301).insert_header("Location", "/moved"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/moved"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/new"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/new"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("here"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/old", server.uri());
+    let base = server.uri();
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["-v", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Redirects:"))
+            .stdout(predicate::str::contains(format!("{}/old", base)))
+            .stdout(predicate::str::contains(format!("{}/moved", base)))
+            .stdout(predicate::str::contains(format!("{}/new", base)));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--max-redirects", "1", &url])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("stopped after 1 redirects"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--no-follow", &url])
+            .assert()
+            .stdout(predicate::str::contains("301"))
+            .stdout(predicate::str::contains("Location:"))
+            .stdout(predicate::str::contains("/moved"))
+            .stdout(predicate::str::contains("here").not());
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_body_sends_stdin_chunked() {
+    use wiremock::matchers::{body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/ingest"))
+        .and(header("transfer-encoding", "chunked"))
+        .and(body_string("{\"n\":1}\n{\"n\":2}\n"))
+        .respond_with(ResponseTemplate::new(202))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/ingest", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        let mut cmd = std::process::Command::cargo_bin("terzi").unwrap();
+        cmd.env("TERZI_CONFIG_DIR", &config_path).args([
+            "--stream-body",
+            "--verbose-request",
+            "-m",
+            "POST",
+            &url,
+        ]);
+        assert_cmd::Command::from_std(cmd)
+            .write_stdin("{\"n\":1}\n{\"n\":2}\n")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("> transfer-encoding: chunked"))
+            .stdout(predicate::str::contains("202"));
+    })
+    .await
+    .unwrap();
+}
+
+#[test]
+fn test_stream_body_rejects_paginate_and_unix_socket() {
+    for extra in [&["--paginate"][..], &["--unix-socket", "/tmp/terzi.sock"]] {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.args(["--stream-body", "http://localhost/items"])
+            .args(extra)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
+
+#[tokio::test]
+async fn test_doctor_reports_checks_without_repairing() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = server.uri();
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["doctor", "--url", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Config file:"))
+            .stdout(predicate::str::contains("is writable"))
+            .stdout(predicate::str::contains("answered HTTP 200"))
+            .stdout(predicate::str::contains("0 failed"));
+
+        let data_file = config_path.join("data.json");
+        std::fs::write(&data_file, "{not json").unwrap();
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["doctor", "--url", &url])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("data.json does not parse"))
+            .stdout(predicate::str::contains("1 failed"));
+        assert_eq!(std::fs::read_to_string(&data_file).unwrap(), "{not json");
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_verbose_groups_cookies_and_cache_headers() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("Set-Cookie", "session=abc; Path=/; HttpOnly")
+                .append_header("Set-Cookie", "theme=dark; Max-Age=3600")
+                .insert_header("Cache-Control", "private, max-age=60")
+                .insert_header("ETag", "\"v1\""),
+        )
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = server.uri();
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["-v", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Cookies Set:"))
+            .stdout(predicate::str::contains("session"))
+            .stdout(predicate::str::contains("theme"))
+            .stdout(predicate::str::contains("in 3600s"))
+            .stdout(predicate::str::contains("Caching:"))
+            .stdout(predicate::str::contains("max-age=60"))
+            .stdout(predicate::str::contains("\"v1\""));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_ping_reports_reachability() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/health", server.uri());
+    let address = server.address().to_string();
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["ping", &url, "--watch", "10ms", "--count", "2"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("HTTP 204"))
+            .stdout(predicate::str::contains(format!("from {}", address)))
+            .stdout(predicate::str::contains("2 probes, 2 answered"));
+
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["ping", &closed_port_url()])
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains("❌"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_output_format_sets_accept_header() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = server.uri();
+
+    tokio::task::spawn_blocking(move || {
+        for args in [
+            vec!["-o", "json"],
+            vec!["-o", "yaml", "-H", "Accept: text/csv"],
+            vec!["-o", "json", "--no-accept"],
+        ] {
+            Command::cargo_bin("terzi")
+                .unwrap()
+                .env("TERZI_CONFIG_DIR", &config_path)
+                .args(args)
+                .arg(&url)
+                .assert()
+                .success();
+        }
+    })
+    .await
+    .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let accept: Vec<Option<String>> = requests
+        .iter()
+        .map(|request| {
+            request
+                .headers
+                .get(&"accept".into())
+                .map(|values| values.iter().map(|v| v.to_string()).collect())
+        })
+        .collect();
+    assert_eq!(accept[0].as_deref(), Some("application/json"));
+    assert_eq!(accept[1].as_deref(), Some("text/csv"));
+    // Without a header of its own the client falls back to */*
+    assert_eq!(accept[2].as_deref(), Some("*/*"));
+}
+
+#[tokio::test]
+async fn test_token_file_and_refresh_command() {
+    use wiremock::matchers::{header, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(path("/file"))
+        .and(header("authorization", "Bearer from-file"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("file ok"))
+        .mount(&server)
+        .await;
+    Mock::given(path("/stored"))
+        .and(header("authorization", "Bearer from-command"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("command ok"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let token_file = config_dir.path().join("token");
+    std::fs::write(&token_file, "from-file\n").unwrap();
+    let uri = server.uri();
+
+    tokio::task::spawn_blocking(move || {
+        let terzi = || {
+            let mut command = Command::cargo_bin("terzi").unwrap();
+            command.env("TERZI_CONFIG_DIR", &config_path);
+            command
+        };
+        terzi()
+            .arg("--auth-token-file")
+            .arg(&token_file)
+            .arg(format!("{}/file", uri))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("file ok"));
+
+        terzi()
+            .args(["config", "set", "general.token_refresh_command"])
+            .arg("echo from-command")
+            .assert()
+            .success();
+        let config_file = config_path.join("config.toml");
+        let mut config: toml::Value =
+            toml::from_str(&std::fs::read_to_string(&config_file).unwrap()).unwrap();
+        config["auth"]["stored_tokens"] = toml::from_str(
+            r#"
+[ci]
+token_type = "Bearer"
+value = "stale"
+expires_at = "2000-01-01T00:00:00Z"
+scopes = []
+"#,
+        )
+        .unwrap();
+        std::fs::write(&config_file, toml::to_string(&config).unwrap()).unwrap();
+
+        terzi()
+            .args(["--auth-token", "ci"])
+            .arg(format!("{}/stored", uri))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("command ok"));
+        let saved = std::fs::read_to_string(&config_file).unwrap();
+        assert!(saved.contains("from-command"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_encode_url_escapes_path_and_query() {
+    use wiremock::matchers::{path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(path("/my%20files/caf%C3%A9"))
+        .and(query_param("q", "hello world|x"))
+        .and(query_param("done", "100%"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("found"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let config_path = config_dir.path().to_path_buf();
+    let url = format!("{}/my files/café?q=hello world|x&done=100%25", server.uri());
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_path)
+            .args(["--encode-url", "--save", "search", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("found"));
+
+        let data: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(config_path.join("data.json")).unwrap())
+                .unwrap();
+        let saved = data["requests"]["search"]["url"].as_str().unwrap();
+        assert!(saved.ends_with("/my%20files/caf%C3%A9?q=hello%20world%7Cx&done=100%25"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_schema_validation_and_inference() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/user"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(r#"{"id": 1, "name": "ada"}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/drifted"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"{"id": "1"}"#, "application/json"))
+        .mount(&server)
+        .await;
+
+    let dir = TempDir::new().unwrap();
+    let base = server.uri();
+    let schema_path = dir.path().join("user.schema.json");
+    let config_dir = dir.path().to_path_buf();
+    let (saved, valid, drifted) = tokio::task::spawn_blocking(move || {
+        let terzi = |args: &[&str]| {
+            Command::cargo_bin("terzi")
+                .unwrap()
+                .args(args)
+                .env("TERZI_CONFIG_DIR", &config_dir)
+                .output()
+                .unwrap()
+        };
+        let schema = schema_path.to_str().unwrap();
+        (
+            terzi(&["--save-schema", schema, &format!("{}/user", base)]),
+            terzi(&["--validate-schema", schema, &format!("{}/user", base)]),
+            terzi(&["--validate-schema", schema, &format!("{}/drifted", base)]),
+        )
+    })
+    .await
+    .unwrap();
+
+    assert!(saved.status.success());
+    assert!(valid.status.success());
+    assert!(String::from_utf8_lossy(&valid.stdout).contains("Response matches"));
+
+    assert!(!drifted.status.success());
+    let stdout = String::from_utf8_lossy(&drifted.stdout);
+    assert!(stdout.contains("Schema violations"));
+    assert!(stdout.contains("/id"));
+}
+
+#[tokio::test]
+async fn test_save_response_writes_fixture() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/users"))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .insert_header("x-request-id", "abc123")
+                .set_body_raw(r#"{"id": 7}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let dir = TempDir::new().unwrap();
+    let fixture_path = dir.path().join("created.json");
+    let config_dir = dir.path().to_path_buf();
+    let url = format!("{}/users", server.uri());
+    let fixture = fixture_path.clone();
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_dir)
+            .args(["-m", "POST", "-j", r#"{"name": "Ada"}"#])
+            .args(["-H", "Authorization: Bearer secret-token"])
+            .arg("--save-response")
+            .arg(&fixture)
+            .arg(&url)
+            .assert()
+            .success();
+    })
+    .await
+    .unwrap();
+
+    let contents = std::fs::read_to_string(&fixture_path).unwrap();
+    assert!(!contents.contains("secret-token"));
+    let fixture: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    assert_eq!(fixture["request"]["method"], "POST");
+    assert_eq!(fixture["request"]["body"], r#"{"name": "Ada"}"#);
+    assert_eq!(fixture["response"]["status"], 201);
+    assert_eq!(fixture["response"]["headers"]["x-request-id"], "abc123");
+    assert_eq!(fixture["response"]["body"], r#"{"id": 7}"#);
+    assert!(fixture["response"]["duration"].is_object());
+}
+
+#[test]
+fn test_serve_replays_fixtures() {
+    let dir = TempDir::new().unwrap();
+    let fixtures = dir.path().join("fixtures");
+    std::fs::create_dir(&fixtures).unwrap();
+    std::fs::write(
+        fixtures.join("user.json"),
+        r#"{
+            "recorded_at": "2026-01-01T00:00:00Z",
+            "request": {
+                "id": "1", "name": "user", "url": "https://api.example.com/users/1",
+                "method": "GET", "headers": {}, "body": null, "timeout": null,
+                "follow_redirects": null, "created_at": "2026-01-01T00:00:00Z",
+                "updated_at": "2026-01-01T00:00:00Z", "tags": [], "description": null
+            },
+            "response": {
+                "status": 201, "headers": {"content-type": "application/json", "x-fixture": "yes"},
+                "body": "{\"id\": 1}", "duration": {"secs": 0, "nanos": 5000000}, "size": 9,
+                "url": "https://api.example.com/users/1", "method": "GET"
+            }
+        }"#,
+    )
+    .unwrap();
+    std::fs::write(fixtures.join("schema.json"), r#"{"type": "object"}"#).unwrap();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut server = Command::cargo_bin("terzi")
+        .unwrap()
+        .env("TERZI_CONFIG_DIR", dir.path())
+        .args(["serve", "--fixtures"])
+        .arg(&fixtures)
+        .args(["--port", &port.to_string(), "--delay", "10ms"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let started = std::time::Instant::now();
+    while std::net::TcpStream::connect(("127.0.0.1", port)).is_err() {
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    let terzi = |path: &str| {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", dir.path())
+            .args([
+                "-i",
+                "-o",
+                "raw",
+                &format!("http://127.0.0.1:{}{}", port, path),
+            ])
+            .output()
+            .unwrap()
+    };
+    let found = terzi("/users/1");
+    let missing = terzi("/users/2");
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    let stdout = String::from_utf8_lossy(&found.stdout);
+    assert!(stdout.contains("201"), "{}", stdout);
+    assert!(stdout.contains("x-fixture"), "{}", stdout);
+    assert!(stdout.contains(r#"{"id": 1}"#), "{}", stdout);
+    let stdout = String::from_utf8_lossy(&missing.stdout);
+    assert!(stdout.contains("404"), "{}", stdout);
+    assert!(stdout.contains("No fixture for GET /users/2"), "{}", stdout);
+}
+
+#[tokio::test]
+async fn test_assert_sets_exit_code() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/users/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": 1, "name": "Ada"}"#))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/users/1", server.uri());
+    let (passing, failing) = tokio::task::spawn_blocking(move || {
+        let run = |asserts: &[&str]| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            for assertion in asserts {
+                cmd.args(["--assert", assertion]);
+            }
+            cmd.arg(&url).output().unwrap()
+        };
+        (
+            run(&["status == 200", "$.name == 'Ada'"]),
+            run(&["status == 200", "$.id > 5"]),
+        )
+    })
+    .await
+    .unwrap();
+
+    assert!(passing.status.success());
+    assert!(!failing.status.success());
+    assert!(String::from_utf8_lossy(&failing.stdout).contains("$.id > 5"));
+}
+
+#[test]
+fn test_invalid_assertion_is_rejected() {
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["--assert", "status ~ 200", "http://127.0.0.1:9/"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown assertion operator"));
+}
+
+#[tokio::test]
+async fn test_run_executes_saved_requests_in_order() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/one"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("first body"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/two"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("second body"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let base = server.uri();
+    let (passing, failing, unprepared) = tokio::task::spawn_blocking(move || {
+        let terzi = |args: &[&str]| {
+            Command::cargo_bin("terzi")
+                .unwrap()
+                .args(args)
+                .env("TERZI_CONFIG_DIR", &dir)
+                .output()
+                .unwrap()
+        };
+        terzi(&["--save", "one", &format!("{}/one", base)]);
+        terzi(&["--save", "two", &format!("{}/two", base)]);
+        terzi(&["--save", "three", &format!("{}/one?id={{{{id}}}}", base)]);
+        terzi(&["env", "create", "staging"]);
+        (
+            terzi(&["run", "one", "one"]),
+            terzi(&["run", "one", "two", "missing"]),
+            terzi(&["--env", "staging", "run", "three", "one"]),
+        )
+    })
+    .await
+    .unwrap();
+
+    assert!(passing.status.success());
+    let stdout = String::from_utf8_lossy(&passing.stdout);
+    assert_eq!(stdout.matches("first body").count(), 2);
+    assert!(stdout.contains("2 passed"));
+
+    assert!(!failing.status.success());
+    let stdout = String::from_utf8_lossy(&failing.stdout);
+    assert!(stdout.find("first body").unwrap() < stdout.find("second body").unwrap());
+    assert!(stdout.contains("two: status 503"));
+    assert!(stdout.contains("missing: request not found"));
+
+    // A request whose variables can't be filled fails alone; the run goes on
+    assert_eq!(unprepared.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&unprepared.stdout);
+    assert!(stdout.contains("first body"));
+    assert!(stdout.contains("1 passed, 1 failed"));
+    assert!(stdout.contains("three: "));
+}
+
+#[test]
+fn test_openapi_import_creates_collection() {
+    let dir = TempDir::new().unwrap();
+    let spec = dir.path().join("spec.yaml");
+    std::fs::write(
+        &spec,
+        r#"
+openapi: 3.0.0
+info:
+  title: Inventory
+paths:
+  /items/{id}:
+    get:
+      operationId: getItem
+      parameters:
+        - name: id
+          in: path
+          required: true
+"#,
+    )
+    .unwrap();
+
+    // Without servers in the spec a base URL is required
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["openapi", "import", spec.to_str().unwrap()])
+        .env("TERZI_CONFIG_DIR", dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--base-url"));
+
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["openapi", "import", spec.to_str().unwrap()])
+        .args(["--base-url", "https://inventory.example.com"])
+        .env("TERZI_CONFIG_DIR", dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Imported 1 requests"));
+
+    let data = std::fs::read_to_string(dir.path().join("data.json")).unwrap();
+    let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+    let request = &data["collections"]["Inventory"]["requests"][0];
+    assert_eq!(request["name"], "getItem");
+    assert_eq!(request["url"], "https://inventory.example.com/items/{{id}}");
+}
+
+#[test]
+fn test_export_postman_collection() {
+    let dir = TempDir::new().unwrap();
+    let spec = dir.path().join("spec.yaml");
+    std::fs::write(
+        &spec,
+        "openapi: 3.0.0\ninfo:\n  title: Inventory\npaths:\n  /items:\n    get:\n      operationId: listItems\n",
+    )
+    .unwrap();
+    Command::cargo_bin("terzi")
+        .unwrap()
+        .args(["openapi", "import", spec.to_str().unwrap()])
+        .args(["--base-url", "https://inventory.example.com"])
+        .env("TERZI_CONFIG_DIR", dir.path())
+        .assert()
+        .success();
+
+    let output = dir.path().join("team");
+    Command::cargo_bin("terzi")
+        .unwrap()
+        .args([
+            "export",
+            "--format",
+            "postman",
+            "-o",
+            output.to_str().unwrap(),
+        ])
+        .env("TERZI_CONFIG_DIR", dir.path())
+        .assert()
+        .success();
+
+    let exported = std::fs::read_to_string(dir.path().join("team.json")).unwrap();
+    let exported: serde_json::Value = serde_json::from_str(&exported).unwrap();
+    let folder = &exported["item"][0];
+    assert_eq!(folder["name"], "Inventory");
+    assert_eq!(folder["item"][0]["name"], "listItems");
+    assert_eq!(
+        folder["item"][0]["request"]["url"]["host"],
+        serde_json::json!(["inventory", "example", "com"])
+    );
+}
+
+#[tokio::test]
+async fn test_export_har_from_history() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(201).set_body_raw(r#"{"id": 7}"#, "application/json"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = format!("{}/items?page=2", server.uri());
+    let har = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["-m", "POST", "-H", "Authorization:Bearer secret-token"])
+            .args(["-j", r#"{"name": "widget"}"#])
+            .arg(&url)
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+
+        let har_path = dir.join("session.har");
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args([
+                "export-har",
+                "--last",
+                "5",
+                "-o",
+                har_path.to_str().unwrap(),
+            ])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        std::fs::read_to_string(har_path).unwrap()
+    })
+    .await
+    .unwrap();
+
+    let har: serde_json::Value = serde_json::from_str(&har).unwrap();
+    let entry = &har["log"]["entries"][0];
+    assert_eq!(har["log"]["version"], "1.2");
+    assert_eq!(entry["request"]["method"], "POST");
+    assert_eq!(entry["request"]["queryString"][0]["value"], "2");
+    assert_eq!(entry["response"]["status"], 201);
+    assert_eq!(entry["response"]["content"]["text"], r#"{"id": 7}"#);
+    assert!(
+        entry["request"]["postData"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("widget")
+    );
+    assert!(!har.to_string().contains("secret-token"));
+}
+
+#[test]
+fn test_completions_script() {
+    let dir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["completions", "bash"])
+        .env("TERZI_CONFIG_DIR", dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("_terzi()"))
+        .stdout(predicate::str::contains("--load"));
+
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["completions", "tcsh"]).assert().failure();
+}
+
+#[test]
+fn test_completion_candidates() {
+    let dir = TempDir::new().unwrap();
+    // The request itself fails, but it is saved before being sent
+    Command::cargo_bin("terzi")
+        .unwrap()
+        .args(["--save", "health", "--method", "HEAD", "http://127.0.0.1:9"])
+        .env("TERZI_CONFIG_DIR", dir.path())
+        .assert();
+
+    Command::cargo_bin("terzi")
+        .unwrap()
+        .args(["__complete", "requests"])
+        .env("TERZI_CONFIG_DIR", dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("health"));
+    Command::cargo_bin("terzi")
+        .unwrap()
+        .args(["__complete", "config-keys"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("network.proxy_url\n"));
+
+    // The helper itself is never offered as a subcommand
+    Command::cargo_bin("terzi")
+        .unwrap()
+        .args(["completions", "zsh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(":_terzi_saved_requests'"))
+        .stdout(predicate::str::contains("__complete requests"))
+        .stdout(predicate::str::contains("'__complete:").not());
+}
+
+#[test]
+fn test_config_unset_reverts_single_key() {
+    let dir = TempDir::new().unwrap();
+    let terzi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.args(args).env("TERZI_CONFIG_DIR", dir.path());
+        cmd
+    };
+
+    terzi(&["config", "set", "network.proxy_url", "http://bad.proxy:1"])
+        .assert()
+        .success();
+    terzi(&["config", "set", "general.default_timeout", "90"])
+        .assert()
+        .success();
+    terzi(&["config", "unset", "general.default_timeout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("30"));
+    terzi(&["config", "unset", "network.proxy_url"])
+        .assert()
+        .success();
+
+    let config = std::fs::read_to_string(dir.path().join("config.toml")).unwrap();
+    assert!(!config.contains("bad.proxy"));
+    assert!(config.contains("default_timeout = 30"));
+
+    terzi(&["config", "unset", "general.nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown configuration key"));
+}
+
+#[test]
+fn test_config_export_and_import() {
+    let source = TempDir::new().unwrap();
+    let target = TempDir::new().unwrap();
+    let terzi = |dir: &TempDir, args: &[&str]| {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.args(args).env("TERZI_CONFIG_DIR", dir.path());
+        cmd
+    };
+    let export = |dir: &TempDir, args: &[&str]| {
+        let output = terzi(dir, &[&["config", "export"], args].concat())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // Seed a stored token straight into the config file
+    let mut config: toml::Value = toml::from_str(&export(&source, &[])).unwrap();
+    config["auth"]["stored_tokens"]
+        .as_table_mut()
+        .unwrap()
+        .insert(
+            "ci".to_string(),
+            toml::Value::try_from(serde_json::json!({
+                "token_type": "Bearer",
+                "value": "super-secret-token",
+                "scopes": []
+            }))
+            .unwrap(),
+        );
+    config["network"]["user_agent"] = toml::Value::from("shared-agent/1.0");
+    std::fs::write(
+        source.path().join("config.toml"),
+        toml::to_string(&config).unwrap(),
+    )
+    .unwrap();
+
+    let exported = export(&source, &[]);
+    assert!(exported.contains("shared-agent/1.0"));
+    assert!(!exported.contains("super-secret-token"));
+    assert!(export(&source, &["--include-secrets"]).contains("super-secret-token"));
+
+    let file = target.path().join("shared.toml");
+    std::fs::write(&file, &exported).unwrap();
+    terzi(&target, &["config", "import", file.to_str().unwrap()])
+        .assert()
+        .success();
+    terzi(&target, &["config", "get", "network.user_agent"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("shared-agent/1.0"));
+
+    std::fs::write(
+        &file,
+        exported.replace("default_timeout = 30", "default_timeout = 0"),
+    )
+    .unwrap();
+    terzi(&target, &["config", "import", file.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid configuration"));
+}
+
+#[test]
+fn test_show_raw_prints_stored_request() {
+    let dir = TempDir::new().unwrap();
+    let terzi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.args(args).env("TERZI_CONFIG_DIR", dir.path());
+        cmd
+    };
+    // The request itself fails, but it is saved before being sent
+    terzi(&["--save", "me", "-H", "Authorization:Bearer secret-token"])
+        .arg("http://127.0.0.1:9/me")
+        .assert();
+
+    let output = terzi(&["show", "me", "--raw"]).output().unwrap();
+    assert!(output.status.success());
+    let request: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(request["url"], "http://127.0.0.1:9/me");
+    assert_eq!(request["headers"]["Authorization"], "Bearer secret-token");
+
+    terzi(&["show", "me", "--raw", "--format", "yaml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("url: http://127.0.0.1:9/me"));
+    terzi(&["show", "me", "--format", "yaml"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_show_diff_with_compares_saved_requests() {
+    let dir = TempDir::new().unwrap();
+    let terzi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.args(args).env("TERZI_CONFIG_DIR", dir.path());
+        cmd
+    };
+    // The requests themselves fail, but they are saved before being sent
+    terzi(&["--save", "v1", "-H", "Authorization:Bearer first-token"])
+        .args(["-j", r#"{"plan":"free"}"#, "http://127.0.0.1:9/users"])
+        .assert();
+    terzi(&[
+        "--save",
+        "v2",
+        "-m",
+        "PUT",
+        "-H",
+        "Authorization:Bearer second-token",
+    ])
+    .args([
+        "-H",
+        "X-Trace:1",
+        "-j",
+        r#"{"plan":"pro"}"#,
+        "http://127.0.0.1:9/users",
+    ])
+    .assert();
+
+    let output = terzi(&["show", "v1", "--diff-with", "v2"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("GET → PUT"), "{}", stdout);
+    assert!(stdout.contains("+ X-Trace: 1"), "{}", stdout);
+    assert!(stdout.contains("~ Authorization"), "{}", stdout);
+    assert!(stdout.contains(r#"-  "plan": "free""#), "{}", stdout);
+    assert!(stdout.contains(r#"+  "plan": "pro""#), "{}", stdout);
+    assert!(!stdout.contains("URL"), "{}", stdout);
+    assert!(!stdout.contains("first-token"), "{}", stdout);
+
+    terzi(&["show", "v1", "--diff-with", "v1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No differences"));
+}
+
+#[test]
+fn test_search_ranks_and_tolerates_typos() {
+    let dir = TempDir::new().unwrap();
+    let terzi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.args(args).env("TERZI_CONFIG_DIR", dir.path());
+        cmd
+    };
+    // The requests themselves fail, but they are saved before being sent
+    for (name, url) in [
+        ("users-list", "http://127.0.0.1:9/users"),
+        ("orders-get", "http://127.0.0.1:9/orders/users"),
+    ] {
+        terzi(&["--save", name, url]).assert();
+    }
+
+    let output = terzi(&["search", "users"]).output().unwrap();
+    let stdout = regex::Regex::new("\x1b\\[[0-9;]*m")
+        .unwrap()
+        .replace_all(&String::from_utf8_lossy(&output.stdout), "")
+        .to_string();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", stdout);
+    // A name match outranks a URL-only match
+    assert!(lines[0].starts_with("users-list"));
+    assert!(lines[1].starts_with("orders-get"));
+
+    terzi(&["search", "usrlst"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("users-list"))
+        .stdout(predicate::str::contains("orders-get").not());
+    terzi(&["search", "zzz"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No saved requests match"));
+}
+
+#[test]
+fn test_import_postman_collection() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("shop.postman_collection.json");
+    let collection = serde_json::json!({
+        "info": {
+            "name": "Shop",
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        },
+        "item": [
+            {"name": "health", "request": {"method": "GET", "url": "{{base}}/health"}},
+            {"name": "Orders", "item": [
+                {"name": "list", "request": {"method": "GET", "url": {"raw": "{{base}}/orders"}}}
+            ]},
+            {"name": "Orders", "item": [
+                {"name": "create", "request": {"method": "POST", "url": {"raw": "{{base}}/orders"}}}
+            ]}
+        ]
+    });
+    std::fs::write(&file, collection.to_string()).unwrap();
+
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["import", file.to_str().unwrap(), "--format", "postman"])
+        .env("TERZI_CONFIG_DIR", dir.path());
+    cmd.assert().success().stdout(predicate::str::contains(
+        "1 saved requests, 2 requests in 2 collections",
+    ));
+
+    let data = std::fs::read_to_string(dir.path().join("data.json")).unwrap();
+    let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+    assert_eq!(data["requests"]["health"]["url"], "{{base}}/health");
+    assert_eq!(data["collections"]["Orders"]["requests"][0]["name"], "list");
+    // A folder name that is already taken gets a suffix instead of replacing the collection
+    assert_eq!(
+        data["collections"]["Orders-2"]["requests"][0]["name"],
+        "create"
+    );
+}
+
+#[tokio::test]
+async fn test_collection_test_reports_failures() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok": true}"#))
+        .mount(&server)
+        .await;
+
+    let saved = |name: &str, route: &str, assertions: &[&str]| {
+        serde_json::json!({
+            "id": name,
+            "name": name,
+            "url": format!("{}{}", server.uri(), route),
+            "method": "GET",
+            "headers": {},
+            "body": null,
+            "timeout": 5,
+            "follow_redirects": false,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "tags": [],
+            "description": null,
+            "assertions": assertions,
+        })
+    };
+    let write_suite = |dir: &std::path::Path, requests: Vec<serde_json::Value>| {
+        let data = serde_json::json!({
+            "requests": {},
+            "collections": {
+                "smoke": {
+                    "name": "smoke",
+                    "description": null,
+                    "requests": requests,
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "updated_at": "2024-01-01T00:00:00Z",
+                }
+            },
+            "history": [],
+            "environments": {},
+            "settings": {},
+        });
+        std::fs::write(dir.join("data.json"), data.to_string()).unwrap();
+    };
+
+    let passing_dir = TempDir::new().unwrap();
+    write_suite(
+        passing_dir.path(),
+        vec![saved(
+            "health",
+            "/health",
+            &["status == 200", "$.ok == true"],
+        )],
+    );
+    let failing_dir = TempDir::new().unwrap();
+    write_suite(
+        failing_dir.path(),
+        vec![
+            saved("health", "/health", &["status == 200"]),
+            saved("missing", "/missing", &["status == 200"]),
+        ],
+    );
+
+    let run = |dir: std::path::PathBuf| {
+        tokio::task::spawn_blocking(move || {
+            Command::cargo_bin("terzi")
+                .unwrap()
+                .env("TERZI_CONFIG_DIR", dir)
+                .args(["test", "smoke"])
+                .output()
+                .unwrap()
+        })
+    };
+    let passing = run(passing_dir.path().to_path_buf()).await.unwrap();
+    let failing = run(failing_dir.path().to_path_buf()).await.unwrap();
+
+    assert!(passing.status.success());
+    assert!(String::from_utf8_lossy(&passing.stdout).contains("1 passed, 0 failed"));
+    assert!(!failing.status.success());
+    assert!(String::from_utf8_lossy(&failing.stdout).contains("1 passed, 1 failed"));
+}
+
+#[test]
+fn test_collection_test_missing_collection() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.env("TERZI_CONFIG_DIR", temp_dir.path());
+    cmd.args(["test", "does-not-exist"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+}
+
+#[tokio::test]
+async fn test_websocket_send_prints_reply() {
+    use futures::{SinkExt, StreamExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+        while let Some(Ok(message)) = ws.next().await {
+            if message.is_text() {
+                let reply = format!("echo: {}", message.into_text().unwrap());
+                ws.send(tokio_tungstenite::tungstenite::Message::text(reply))
+                    .await
+                    .unwrap();
+            }
+        }
+    });
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_path_buf();
+    let url = format!("ws://{}", addr);
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", config_home)
+            .args(["ws", &url, "--send", "hello", "-H", "X-Test: 1"])
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("echo: hello"));
+    assert!(stdout.contains("1 sent, 1 received"));
+}
+
+#[test]
+fn test_websocket_rejects_http_url() {
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args(["ws", "https://example.com"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("ws://"));
+}
+
+#[tokio::test]
+async fn test_oauth2_token_is_stored_and_injected() {
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains("grant_type=client_credentials"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"access_token": "tok-123", "token_type": "bearer", "expires_in": 3600}"#,
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/me"))
+        .and(header("Authorization", "Bearer tok-123"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("authorized"))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().to_path_buf();
+    let base = server.uri();
+    let (grant, request) = tokio::task::spawn_blocking(move || {
+        let grant = Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_home)
+            .args([
+                "auth",
+                "oauth2",
+                "--name",
+                "ci",
+                "--token-url",
+                &format!("{}/token", base),
+                "--client-id",
+                "terzi",
+                "--client-secret",
+                "s3cret",
+                "--scope",
+                "read",
+            ])
+            .output()
+            .unwrap();
+        let request = Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &config_home)
+            .args(["--auth-token", "ci", "--assert", "status == 200"])
+            .arg(format!("{}/me", base))
+            .output()
+            .unwrap();
+        (grant, request)
+    })
+    .await
+    .unwrap();
+
+    assert!(grant.status.success());
+    assert!(String::from_utf8_lossy(&grant.stdout).contains("Token 'ci' stored"));
+    assert!(request.status.success());
+}
+
+#[tokio::test]
+async fn test_digest_auth_retries_after_challenge() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/digest"))
+        .respond_with(|request: &Request| {
+            let authorization = request
+                .headers
+                .iter()
+                .find(|(name, _)| name.as_str().eq_ignore_ascii_case("authorization"))
+                .map(|(_, values)| {
+                    values
+                        .iter()
+                        .map(|v| v.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            if authorization.starts_with(r#"Digest username="user""#)
+                && authorization.contains(r#"nonce="abc123""#)
+                && authorization.contains("nc=00000001")
+            {
+                ResponseTemplate::new(200).set_body_string("welcome")
+            } else {
+                // Challenges that can't be answered are skipped in favour of the usable one
+                ResponseTemplate::new(401)
+                    .append_header(
+                        "WWW-Authenticate",
+                        r#"Digest realm="test", algorithm=SHA-512-256, nonce="other""#,
+                    )
+                    .append_header(
+                        "WWW-Authenticate",
+                        r#"Digest realm="test", qop="auth-int", nonce="other""#,
+                    )
+                    .append_header(
+                        "WWW-Authenticate",
+                        r#"Digest realm="test", qop="auth", nonce="abc123", opaque="xyz""#,
+                    )
+            }
+        })
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/digest", server.uri());
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args([
+                "-A",
+                "digest:user:passwd",
+                "--assert",
+                "status == 200",
+                &url,
+            ])
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("welcome"));
+}
+
+#[test]
+fn test_authentication_digest() {
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.args([
+        "-A",
+        "digest:user:passwd",
+        "--assert",
+        "status == 200",
+        &format!("{}/digest-auth/auth/user/passwd", HTTPBIN_URL),
+    ]);
+
+    let output = cmd.output().unwrap();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Only fail if httpbin was reachable
+        if !stderr.contains("Request failed") {
+            panic!(
+                "Digest auth failed: {}",
+                String::from_utf8_lossy(&output.stdout)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_config_dir_isolates_config_and_data() {
+    let env_dir = TempDir::new().unwrap();
+    let flag_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.env("TERZI_CONFIG_DIR", env_dir.path());
+    cmd.args(["config", "set", "general.default_timeout", "42"]);
+    cmd.assert().success();
+    assert!(env_dir.path().join("config.toml").exists());
+
+    // The flag wins over the environment variable
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.env("TERZI_CONFIG_DIR", env_dir.path());
+    cmd.args(["config", "get", "general.default_timeout", "--config-dir"]);
+    cmd.arg(flag_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("= 30"));
+    assert!(flag_dir.path().join("config.toml").exists());
+
+    let config = std::fs::read_to_string(env_dir.path().join("config.toml")).unwrap();
+    assert!(config.contains("default_timeout = 42"));
+}
+
+#[test]
+fn test_profiles_keep_separate_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let terzi = || {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.env("TERZI_CONFIG_DIR", temp_dir.path())
+            .env_remove("TERZI_PROFILE");
+        cmd
+    };
+
+    terzi()
+        .args(["profile", "create", "work"])
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("config.work.toml").exists());
+
+    terzi()
+        .args([
+            "--profile",
+            "work",
+            "config",
+            "set",
+            "general.default_timeout",
+            "5",
+        ])
+        .assert()
+        .success();
+    terzi()
+        .env("TERZI_PROFILE", "work")
+        .args(["config", "get", "general.default_timeout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("= 5"));
+    terzi()
+        .args(["config", "get", "general.default_timeout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("= 30"));
+
+    terzi()
+        .args(["profile", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("work"));
+    terzi()
+        .args(["--profile", "missing", "list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not exist"));
+
+    let mut delete = terzi();
+    delete.args(["profile", "delete", "work"]);
+    assert_cmd::Command::from_std(delete)
+        .write_stdin("y\n")
+        .assert()
+        .success();
+    assert!(!temp_dir.path().join("config.work.toml").exists());
+    assert!(temp_dir.path().join("config.toml").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_config_edit_validates_before_saving() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let editor = |name: &str, script: &str| {
+        let path = temp_dir.path().join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    };
+    let set_timeout = editor(
+        "set-timeout.sh",
+        r#"sed -i 's/^default_timeout = .*/default_timeout = 45/' "$1""#,
+    );
+    let zero_timeout = editor(
+        "zero-timeout.sh",
+        r#"sed -i 's/^default_timeout = .*/default_timeout = 0/' "$1""#,
+    );
+    let failing = editor("fail.sh", "exit 3");
+    // Notes the file it was given, then fails, so cleanup on the error path is checked too
+    let record = editor(
+        "record.sh",
+        &format!(
+            r#"stat -c %a "$1" > "{0}/mode"; echo "$1" > "{0}/path"; exit 3"#,
+            temp_dir.path().display()
+        ),
+    );
+
+    let edit = |editor: &std::path::Path| {
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.env("TERZI_CONFIG_DIR", temp_dir.path())
+            .args(["config", "set", "ui.editor", editor.to_str().unwrap()])
+            .assert()
+            .success();
+        let mut cmd = Command::cargo_bin("terzi").unwrap();
+        cmd.env("TERZI_CONFIG_DIR", temp_dir.path())
+            .args(["config", "edit"]);
+        cmd.assert()
+    };
+    let timeout = || {
+        let config = std::fs::read_to_string(temp_dir.path().join("config.toml")).unwrap();
+        config
+            .lines()
+            .find(|l| l.starts_with("default_timeout"))
+            .unwrap()
+            .to_string()
+    };
+
+    edit(&set_timeout).success();
+    assert_eq!(timeout(), "default_timeout = 45");
+
+    edit(&zero_timeout)
+        .failure()
+        .stderr(predicate::str::contains("Default timeout must be between"));
+    assert_eq!(timeout(), "default_timeout = 45");
+
+    edit(&failing)
+        .failure()
+        .stderr(predicate::str::contains("configuration left unchanged"));
+    assert_eq!(timeout(), "default_timeout = 45");
+
+    // The config holds tokens, so the file handed to the editor is owner-only
+    edit(&record).failure();
+    let read = |name: &str| {
+        std::fs::read_to_string(temp_dir.path().join(name))
+            .unwrap()
+            .trim()
+            .to_string()
+    };
+    assert_eq!(read("mode"), "600");
+    assert!(!std::path::Path::new(&read("path")).exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_edit_body_opens_editor() {
+    use std::os::unix::fs::PermissionsExt;
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/users"))
+        .and(header("content-type", "application/json"))
+        .and(body_json(
+            serde_json::json!({"name": "Ada", "seeded": true}),
+        ))
+        .respond_with(ResponseTemplate::new(201).set_body_string("created"))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let editor = |name: &str, script: &str| {
+        let path = temp_dir.path().join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    };
+    // Appends a field to whatever object the buffer starts with
+    let add_name = editor("add-name.sh", r#"sed -i 's/}$/, "name": "Ada"}/' "$1""#);
+    let broken = editor("broken.sh", r#"printf '{"name": ' > "$1""#);
+
+    let url = format!("{}/users", server.uri());
+    tokio::task::spawn_blocking(move || {
+        // ui.editor defaults to $EDITOR when the config is first written, so each run gets its own
+        let terzi = |editor: &std::path::Path| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.env("TERZI_CONFIG_DIR", editor.with_extension("config"))
+                .env("EDITOR", editor)
+                .args([
+                    "-m",
+                    "POST",
+                    "--edit-body",
+                    "-j",
+                    r#"{"seeded": true}"#,
+                    &url,
+                ]);
+            cmd
+        };
+
+        terzi(&add_name)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("created"));
+        terzi(&broken)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Edited body is not valid JSON"));
+    })
+    .await
+    .unwrap();
+}
+
+#[test]
+fn test_corrupt_files_are_backed_up_not_discarded() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("config.toml"),
+        "[general]\ndefault_timeout = = 30\n",
+    )
+    .unwrap();
+    std::fs::write(temp_dir.path().join("data.json"), r#"{"requests": {"#).unwrap();
+
+    let mut cmd = Command::cargo_bin("terzi").unwrap();
+    cmd.env("TERZI_CONFIG_DIR", temp_dir.path());
+    cmd.arg("list");
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("config.toml"))
+        .stderr(predicate::str::contains("line 2"))
+        .stderr(predicate::str::contains("data.json"));
+
+    let backups: Vec<String> = std::fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.contains(".corrupt-"))
+        .collect();
+    assert_eq!(
+        backups.len(),
+        2,
+        "expected both files backed up: {:?}",
+        backups
+    );
+}
+
+#[tokio::test]
+async fn test_parallel_reads_urls_from_stdin() {
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(path("/ok"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(path("/broken"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let uri = server.uri();
+    let output = tokio::task::spawn_blocking(move || {
+        let mut parallel = Command::cargo_bin("terzi").unwrap();
+        parallel
+            .args(["--parallel", "2"])
+            .env("TERZI_CONFIG_DIR", &dir);
+        assert_cmd::Command::from_std(parallel)
+            .write_stdin(format!(
+                "{uri}/ok\n\n# skipped\n{uri}/broken\n{uri}/ok?page=2\n"
+            ))
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "{}", stdout);
+    assert!(stdout.contains("/ok?page=2"), "{}", stdout);
+    assert!(stdout.contains("503"), "{}", stdout);
+    assert!(stdout.contains("2 passed, 1 failed"), "{}", stdout);
+    assert!(!stdout.contains("skipped"), "{}", stdout);
+}
+
+#[tokio::test]
+async fn test_bulk_runs_stop_at_the_first_failure_with_fail_fast() {
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(path("/ok"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(path("/broken"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let uri = server.uri();
+    tokio::task::spawn_blocking(move || {
+        let terzi = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.args(args).env("TERZI_CONFIG_DIR", &dir);
+            cmd
+        };
+        for (name, route) in [("first", "ok"), ("second", "broken"), ("third", "ok")] {
+            terzi(&["--save", name, &format!("{}/{}", uri, route)])
+                .assert()
+                .code(predicate::in_iter([0, 1]));
+        }
+
+        terzi(&["run", "first", "second", "third"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("2 passed, 1 failed"));
+        terzi(&["run", "first", "second", "third", "--fail-fast"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("1 passed, 1 failed, 1 skipped"));
+
+        // Every URL fails, so whichever finishes first stops the rest
+        let mut parallel = std::process::Command::cargo_bin("terzi").unwrap();
+        parallel
+            .args(["--parallel", "1", "--fail-fast"])
+            .env("TERZI_CONFIG_DIR", &dir);
+        assert_cmd::Command::from_std(parallel)
+            .write_stdin(format!("{uri}/broken\n").repeat(4))
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("0 passed, 1 failed, 3 skipped"));
+        let mut parallel = std::process::Command::cargo_bin("terzi").unwrap();
+        parallel
+            .args(["--parallel", "1", "--keep-going"])
+            .env("TERZI_CONFIG_DIR", &dir);
+        assert_cmd::Command::from_std(parallel)
+            .write_stdin(format!("{uri}/broken\n").repeat(4))
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("0 passed, 4 failed"));
+        // Outside --parallel the top-level flags are rejected rather than ignored
+        terzi(&["--keep-going", &format!("{}/ok", uri)])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+        terzi(&["--fail-fast"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--parallel"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_parallel_sends_auth_token() {
+    use wiremock::matchers::{header, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(path("/items"))
+        .and(header("authorization", "Bearer parallel-token"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let uri = server.uri();
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .env("TERZI_CONFIG_DIR", &dir)
+            .args(["config", "set", "general.save_history", "true"])
+            .assert()
+            .success();
+        let config_file = dir.join("config.toml");
+        let mut config: toml::Value =
+            toml::from_str(&std::fs::read_to_string(&config_file).unwrap()).unwrap();
+        config["auth"]["stored_tokens"] = toml::from_str(
+            r#"
+[ci]
+token_type = "Bearer"
+value = "parallel-token"
+scopes = []
+"#,
+        )
+        .unwrap();
+        std::fs::write(&config_file, toml::to_string(&config).unwrap()).unwrap();
+
+        let mut parallel = std::process::Command::cargo_bin("terzi").unwrap();
+        parallel
+            .args(["--parallel", "2", "--auth-token", "ci"])
+            .env("TERZI_CONFIG_DIR", &dir);
+        assert_cmd::Command::from_std(parallel)
+            .write_stdin(format!("{uri}/items?page=1\n{uri}/items?page=2\n"))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("2 passed"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_patch_helpers_set_method_and_content_type() {
+    use wiremock::matchers::{body_json, header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("PATCH"))
+        .and(header("content-type", "application/merge-patch+json"))
+        .and(body_json(serde_json::json!({"name": "Ada"})))
+        .respond_with(ResponseTemplate::new(200).set_body_string("merged"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(header("content-type", "application/json-patch+json"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("patched"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = format!("{}/users/1", server.uri());
+    tokio::task::spawn_blocking(move || {
+        let terzi = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.args(args).arg(&url).env("TERZI_CONFIG_DIR", &dir);
+            cmd
+        };
+        terzi(&["--merge-patch", r#"{"name": "Ada"}"#])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("merged"));
+        terzi(&["--json-patch", r#"[{"op": "remove", "path": "/nickname"}]"#])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("patched"));
+        terzi(&["--json-patch", r#"[{"op": "replace", "path": "/name"}]"#])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("needs a 'value'"));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_watch_sends_a_fresh_request_id_per_poll() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["config", "set", "network.inject_request_id", "true"])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+        Command::cargo_bin("terzi")
+            .unwrap()
+            .args(["--watch", "20ms", "--watch-until", "status == 200", &url])
+            .env("TERZI_CONFIG_DIR", &dir)
+            .assert()
+            .success();
+    })
+    .await
+    .unwrap();
+
+    let ids: std::collections::HashSet<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter_map(|request| request.headers.get(&"x-request-id".into()))
+        .map(|value| value.as_str().to_string())
+        .collect();
+    assert_eq!(ids.len(), 3, "{:?}", ids);
+}
+
+#[tokio::test]
+async fn test_request_id_is_injected_and_recorded() {
+    use wiremock::matchers::{header, header_exists, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(header("x-trace-id", "debug-42"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("explicit"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(header_exists("x-trace-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("generated"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("none"))
+        .mount(&server)
+        .await;
+
+    let config_dir = TempDir::new().unwrap();
+    let dir = config_dir.path().to_path_buf();
+    let url = server.uri();
+    tokio::task::spawn_blocking(move || {
+        let terzi = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("terzi").unwrap();
+            cmd.args(args).env("TERZI_CONFIG_DIR", &dir);
+            cmd
+        };
+        terzi(&["--request-id", "debug-42", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("none"));
+
+        terzi(&["config", "set", "network.request_id_header", "X-Trace-Id"])
+            .assert()
+            .success();
+        terzi(&["--request-id", "debug-42", "-v", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("explicit"))
+            .stdout(predicate::str::contains("Request ID"));
+        terzi(&[&url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("none"));
+
+        terzi(&["config", "set", "network.inject_request_id", "true"])
+            .assert()
+            .success();
+        terzi(&["--save", "traced", &url])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("generated"));
+
+        let data = std::fs::read_to_string(dir.join("data.json")).unwrap();
+        let data: serde_json::Value = serde_json::from_str(&data).unwrap();
+        let history = data["history"].as_array().unwrap();
+        let last = history
+            .iter()
+            .find(|entry| entry["request_headers"]["X-Trace-Id"].is_string());
+        assert!(last.is_some(), "{}", data);
+        assert!(
+            data["requests"]["traced"]["headers"]
+                .get("X-Trace-Id")
+                .is_none()
+        );
+    })
+    .await
+    .unwrap();
+}